@@ -0,0 +1,489 @@
+//! Implement Alpine's versioning scheme, used by `apk`.
+//!
+//! Versions look like `1.2.3a_alpha2_p1-r4`: dot-separated numbers, an
+//! optional letter, any number of suffixes (each with an optional number),
+//! and an optional package revision `-rN`.
+//!
+//! Suffixes sort in a fixed order: `_alpha < _beta < _pre < _rc` are
+//! pre-releases and sort before the version without suffix, while
+//! `_cvs < _svn < _git < _hg < _p` sort after it.
+//!
+//! Comparison follows apk-tools' token-based algorithm. Components after the
+//! first one that start with zeros sort before those that don't, and the more
+//! zeros the older, so `1.01 < 1.1`, `1.001 < 1.01` and even `1.00 < 1.0`.
+//! What follows the zeros is then compared as a number, `0` for a component
+//! of zeros only, so `1.0.1 < 1.01 < 1.05 < 1.010`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{InvalidVersion, Version};
+
+/// A version suffix, e.g. `_alpha` or `_p`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suffix {
+    Alpha,
+    Beta,
+    Pre,
+    Rc,
+    Cvs,
+    Svn,
+    Git,
+    Hg,
+    P,
+}
+
+const SUFFIXES: &[(&str, Suffix)] = &[
+    ("alpha", Suffix::Alpha),
+    ("beta", Suffix::Beta),
+    ("pre", Suffix::Pre),
+    ("rc", Suffix::Rc),
+    ("cvs", Suffix::Cvs),
+    ("svn", Suffix::Svn),
+    ("git", Suffix::Git),
+    ("hg", Suffix::Hg),
+    ("p", Suffix::P),
+];
+
+impl Suffix {
+    /// Whether this suffix sorts before the version without it.
+    pub fn is_prerelease(&self) -> bool {
+        *self <= Suffix::Rc
+    }
+
+    /// The value apk-tools gives this suffix: negative for pre-releases.
+    fn value(&self) -> i64 {
+        *self as i64 - Suffix::Cvs as i64
+    }
+}
+
+impl fmt::Display for Suffix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let &(name, _) = SUFFIXES.iter().find(|&&(_, s)| s == *self).unwrap();
+        f.write_str(name)
+    }
+}
+
+/// An Alpine package version.
+#[derive(Clone, Debug)]
+pub struct ApkVersion {
+    numbers: String,
+    letter: Option<char>,
+    suffixes: Vec<(Suffix, Option<u64>)>,
+    revision: Option<u64>,
+}
+
+/// The kinds of tokens, in the order of the grammar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum TokenKind {
+    DigitOrZero,
+    Digit,
+    Letter,
+    Suffix,
+    SuffixNumber,
+    Revision,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token {
+    kind: TokenKind,
+    value: i64,
+}
+
+/// apk-tools refuses numbers of 18 digits or more.
+const MAX_DIGITS: usize = 17;
+
+/// Simple cursor over a version string.
+struct Parser<'a> {
+    string: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.string.as_bytes().get(self.pos).cloned()
+    }
+
+    fn take_while<F: Fn(u8) -> bool>(&mut self, pred: F) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.pos += 1;
+        }
+        &self.string[start .. self.pos]
+    }
+
+    fn digits(&mut self) -> &'a str {
+        self.take_while(|c| c.is_ascii_digit())
+    }
+
+    fn number(&mut self) -> Result<u64, InvalidVersion> {
+        let digits = self.digits();
+        if digits.is_empty() {
+            return Err(match self.peek() {
+                Some(b'.') | None => InvalidVersion::EmptyField,
+                Some(_) => InvalidVersion::InvalidCharacter,
+            });
+        }
+        if digits.len() > MAX_DIGITS {
+            return Err(InvalidVersion::NumberTooLarge);
+        }
+        Ok(digits.parse().unwrap())
+    }
+}
+
+impl ApkVersion {
+    /// Parse and validate a version, e.g. `1.2.3_alpha2_p1-r4`.
+    pub fn parse(string: &str) -> Result<ApkVersion, InvalidVersion> {
+        let mut parser = Parser { string, pos: 0 };
+
+        // Numbers
+        parser.number()?;
+        while parser.peek() == Some(b'.') {
+            parser.pos += 1;
+            parser.number()?;
+        }
+        let numbers = &string[.. parser.pos];
+
+        // Letter
+        let mut letter = None;
+        if let Some(c) = parser.peek().filter(|c| c.is_ascii_lowercase()) {
+            parser.pos += 1;
+            letter = Some(c as char);
+        }
+
+        // Suffixes
+        let mut suffixes = Vec::new();
+        while parser.peek() == Some(b'_') {
+            parser.pos += 1;
+            let name = parser.take_while(|c| c.is_ascii_lowercase());
+            let suffix = SUFFIXES.iter()
+                .find(|&&(s, _)| s == name)
+                .map(|&(_, suffix)| suffix)
+                .ok_or(InvalidVersion::InvalidCharacter)?;
+            let number = match parser.peek() {
+                Some(c) if c.is_ascii_digit() => Some(parser.number()?),
+                _ => None,
+            };
+            suffixes.push((suffix, number));
+        }
+
+        // Revision
+        let mut revision = None;
+        if string[parser.pos ..].starts_with("-r") {
+            parser.pos += 2;
+            revision = Some(parser.number()?);
+        }
+
+        if parser.pos != string.len() {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+        Ok(ApkVersion {
+            numbers: numbers.to_owned(),
+            letter,
+            suffixes,
+            revision,
+        })
+    }
+
+    /// The dot-separated numbers, e.g. `1.2.3`.
+    pub fn numbers(&self) -> &str {
+        &self.numbers
+    }
+
+    /// The letter following the numbers, e.g. `a` in `1.2a`.
+    pub fn letter(&self) -> Option<char> {
+        self.letter
+    }
+
+    /// The suffixes and their numbers, e.g. `[(Alpha, Some(2)), (P, None)]`
+    /// for `1.0_alpha2_p`.
+    pub fn suffixes(&self) -> &[(Suffix, Option<u64>)] {
+        &self.suffixes
+    }
+
+    /// The package revision, e.g. `4` for `1.0-r4`.
+    pub fn revision(&self) -> Option<u64> {
+        self.revision
+    }
+
+    /// Whether this is a pre-release, e.g. has an `_alpha` or `_rc` suffix.
+    pub fn is_prerelease(&self) -> bool {
+        self.suffixes.iter().any(|&(suffix, _)| suffix.is_prerelease())
+    }
+
+    /// Split the version into the tokens that apk-tools compares.
+    fn tokens(&self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let count = self.numbers.split('.').count();
+        for (i, number) in self.numbers.split('.').enumerate() {
+            if i == 0 {
+                tokens.push(Token { kind: TokenKind::Digit, value: number.parse().unwrap() });
+                continue;
+            }
+            // Leading zeros make a token of their own, sorting before any
+            // number, followed by the rest of the number (`0` if there is
+            // none, unless this is the last component)
+            let zeros = number.bytes().take_while(|&c| c == b'0').count();
+            if zeros == 0 {
+                tokens.push(Token { kind: TokenKind::DigitOrZero, value: number.parse().unwrap() });
+            } else {
+                tokens.push(Token { kind: TokenKind::DigitOrZero, value: -(zeros as i64) });
+                if zeros < number.len() {
+                    let rest = number[zeros ..].parse().unwrap();
+                    tokens.push(Token { kind: TokenKind::Digit, value: rest });
+                } else if i + 1 < count {
+                    tokens.push(Token { kind: TokenKind::Digit, value: 0 });
+                }
+            }
+        }
+        if let Some(letter) = self.letter {
+            tokens.push(Token { kind: TokenKind::Letter, value: letter as i64 });
+        }
+        for &(suffix, number) in &self.suffixes {
+            tokens.push(Token { kind: TokenKind::Suffix, value: suffix.value() });
+            let number = number.unwrap_or(0) as i64;
+            tokens.push(Token { kind: TokenKind::SuffixNumber, value: number });
+        }
+        if let Some(revision) = self.revision {
+            tokens.push(Token { kind: TokenKind::Revision, value: revision as i64 });
+        }
+        tokens
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ApkVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<ApkVersion, InvalidVersion> {
+        ApkVersion::parse(string)
+    }
+}
+
+impl fmt::Display for ApkVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.numbers)?;
+        if let Some(letter) = self.letter {
+            write!(f, "{}", letter)?;
+        }
+        for &(suffix, number) in &self.suffixes {
+            write!(f, "_{}", suffix)?;
+            if let Some(number) = number {
+                write!(f, "{}", number)?;
+            }
+        }
+        if let Some(revision) = self.revision {
+            write!(f, "-r{}", revision)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<ApkVersion> for ApkVersion {
+    fn eq(&self, other: &ApkVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ApkVersion {}
+
+impl PartialOrd<ApkVersion> for ApkVersion {
+    fn partial_cmp(&self, other: &ApkVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ApkVersion {
+    fn cmp(&self, other: &ApkVersion) -> Ordering {
+        let (a, b) = (self.tokens(), other.tokens());
+        for i in 0 .. {
+            let (x, y) = (a.get(i), b.get(i));
+            match (x, y) {
+                (None, None) => return Ordering::Equal,
+                (Some(x), Some(y)) if x.kind == y.kind => {
+                    if x.value != y.value {
+                        return x.value.cmp(&y.value);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            // The versions continue differently: a pre-release suffix is
+            // older than anything, otherwise the version that continues with
+            // a token that comes earlier in the grammar is newer, and the end
+            // is oldest
+            let is_pre = |t: Option<&Token>| {
+                t.is_some_and(|t| t.kind == TokenKind::Suffix && t.value < 0)
+            };
+            if is_pre(x) {
+                return Ordering::Less;
+            }
+            if is_pre(y) {
+                return Ordering::Greater;
+            }
+            let rank = |t: Option<&Token>| (t.is_none(), t.map(|t| t.kind));
+            return rank(y).cmp(&rank(x));
+        }
+        unreachable!()
+    }
+}
+
+impl From<ApkVersion> for Version {
+    /// Encode the version so that `Version`'s order matches apk's.
+    ///
+    /// Each token after the first number is written as a distinct non-digit
+    /// prefix followed by digits, so Debian's algorithm compares them one by
+    /// one. The prefixes sort in the order of the grammar, reversed: `-` for
+    /// a dotted number (`-a` and tildes for leading zeros, `-b` otherwise),
+    /// `+` for the digits following leading zeros, `x` and the letter, `s`
+    /// for a suffix (`~` for a pre-release suffix), then `r` for the
+    /// revision. For example, `1.02a_rc1-r3` becomes `1-a~0+2xa0~d1r3`.
+    fn from(apk: ApkVersion) -> Version {
+        let mut version = String::new();
+        for (i, token) in apk.tokens().iter().enumerate() {
+            let value = token.value;
+            match token.kind {
+                TokenKind::Digit if i == 0 => version.push_str(&value.to_string()),
+                TokenKind::DigitOrZero if value < 0 => {
+                    version.push_str("-a");
+                    for _ in 0 .. -value {
+                        version.push('~');
+                    }
+                    version.push('0');
+                }
+                TokenKind::DigitOrZero => version.push_str(&format!("-b{}", value)),
+                TokenKind::Digit => version.push_str(&format!("+{}", value)),
+                TokenKind::Letter => version.push_str(&format!("x{}0", value as u8 as char)),
+                TokenKind::Suffix => {
+                    let (prefix, index) = if value < 0 {
+                        ('~', value - Suffix::Alpha.value())
+                    } else {
+                        ('s', value)
+                    };
+                    version.push(prefix);
+                    version.push((b'a' + index as u8) as char);
+                }
+                TokenKind::SuffixNumber => version.push_str(&value.to_string()),
+                TokenKind::Revision => version.push_str(&format!("r{}", value)),
+            }
+        }
+        Version(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InvalidVersion, Version};
+    use super::{ApkVersion, Suffix};
+
+    fn parse(s: &str) -> ApkVersion {
+        ApkVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let version = parse("1.2.3a_alpha2_p-r4");
+        assert_eq!(version.numbers(), "1.2.3");
+        assert_eq!(version.letter(), Some('a'));
+        assert_eq!(version.suffixes(), &[(Suffix::Alpha, Some(2)), (Suffix::P, None)]);
+        assert_eq!(version.revision(), Some(4));
+        assert!(version.is_prerelease());
+        assert_eq!(version.to_string(), "1.2.3a_alpha2_p-r4");
+        assert!(!parse("1.0_p1").is_prerelease());
+
+        for &(invalid, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            ("1.", InvalidVersion::EmptyField),
+            ("1..2", InvalidVersion::EmptyField),
+            (".1", InvalidVersion::EmptyField),
+            ("1.0-r", InvalidVersion::EmptyField),
+            ("1.0_foo", InvalidVersion::InvalidCharacter),
+            ("1.0_", InvalidVersion::InvalidCharacter),
+            ("1.0ab", InvalidVersion::InvalidCharacter),
+            ("1.0A", InvalidVersion::InvalidCharacter),
+            ("1.0-1", InvalidVersion::InvalidCharacter),
+            ("1.0-r1_p1", InvalidVersion::InvalidCharacter),
+            ("1.0_p1a", InvalidVersion::InvalidCharacter),
+            ("v1.0", InvalidVersion::InvalidCharacter),
+            ("123456789012345678", InvalidVersion::NumberTooLarge),
+        ] {
+            assert_eq!(&ApkVersion::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    /// Versions in increasing order, as sorted by `apk version -t`.
+    const ORDERED: &[&str] = &[
+        "0.1.0_alpha",
+        "0.1.0",
+        "1",
+        "1.00",
+        "1.001",
+        "1.0_alpha",
+        "1.0_alpha1",
+        "1.0_alpha2_p1",
+        "1.0_alpha3",
+        "1.0_beta",
+        "1.0_pre2",
+        "1.0_rc1",
+        "1.0_rc1-r1",
+        "1.0_rc2",
+        "1.0",
+        "1.0-r1",
+        "1.0-r2",
+        "1.0_cvs",
+        "1.0_svn",
+        "1.0_git20230101",
+        "1.0_hg",
+        "1.0_p",
+        "1.0_p1",
+        "1.0_p1-r1",
+        "1.0_p2",
+        "1.0a",
+        "1.0a-r1",
+        "1.0b_rc1",
+        "1.0b",
+        "1.0.0",
+        "1.0.1",
+        "1.01",
+        "1.01.1",
+        "1.05",
+        "1.010",
+        "1.1",
+        "1.2.3_alpha2_p1-r4",
+        "1.2.3",
+        "1.10",
+        "2.34",
+        "10",
+    ];
+
+    #[test]
+    fn test_ordering() {
+        for pair in ORDERED.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(parse("1.0_alpha"), parse("1.0_alpha0"));
+        assert_eq!(parse("01.2"), parse("1.2"));
+        assert!(parse("1.0") < parse("1.0-r0"));
+        assert!(parse("1.0.0") < parse("1.010"));
+        assert!(parse("1.0.1") < parse("1.05"));
+        assert!(parse("1.0_rc1") < parse("1.0"));
+        assert!(parse("1.0.0_rc1") < parse("1.0.0"));
+    }
+
+    #[test]
+    fn test_to_version() {
+        let version = Version::from(parse("1.02a_rc1-r3"));
+        assert_eq!(&*version, "1-a~0+2xa0~d1r3");
+
+        let versions: Vec<Version> = ORDERED.iter()
+            .map(|s| Version::from(parse(s)))
+            .collect();
+        for (i, a) in versions.iter().enumerate() {
+            for (j, b) in versions.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{} {}", ORDERED[i], ORDERED[j]);
+            }
+        }
+        assert_eq!(Version::from(parse("1.0_alpha")), Version::from(parse("1.0_alpha0")));
+    }
+}
@@ -0,0 +1,601 @@
+//! Implement Arch Linux's versioning scheme, used by pacman.
+//!
+//! Versions are written `[epoch:]pkgver[-pkgrel]`, where `pkgrel` is a number
+//! that can have a minor part (`1.1`). They are compared with
+//! [`vercmp`](fn.vercmp.html), which is derived from RPM's algorithm but
+//! differs in a few ways:
+//!
+//! * there is no tilde, every non-alphanumeric character is a separator;
+//! * a letter directly following the version sorts it earlier, so
+//!   `1.0a < 1.0`, while a dotted letter sorts it later, `1.0 < 1.0.a`;
+//! * the number of separators matters, `2_a < 2___a`.
+//!
+//! Because of the last point, `vercmp` is not a total order when versions
+//! have repeated or trailing separators (`1. < 1.2 < 1..a < 1.`). Such
+//! versions can't be converted to `Version`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{InvalidVersion, Version};
+
+/// Compare alphanumeric segments the way pacman does (its `rpmvercmp`).
+fn compare_segments(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (mut one, mut two) = (0, 0);
+    while one < a.len() && two < b.len() {
+        let (start1, start2) = (one, two);
+        while one < a.len() && !a[one].is_ascii_alphanumeric() {
+            one += 1;
+        }
+        while two < b.len() && !b[two].is_ascii_alphanumeric() {
+            two += 1;
+        }
+
+        // If we ran to the end of either, we are finished with the loop
+        if one == a.len() || two == b.len() {
+            break;
+        }
+
+        // If the separator lengths were different, we are also finished
+        if one - start1 != two - start2 {
+            return (one - start1).cmp(&(two - start2));
+        }
+
+        // Grab a segment of the same kind from both strings
+        let numeric = a[one].is_ascii_digit();
+        let kind = |c: &u8| if numeric {
+            c.is_ascii_digit()
+        } else {
+            c.is_ascii_alphabetic()
+        };
+        let end1 = one + a[one ..].iter().take_while(|c| kind(c)).count();
+        let end2 = two + b[two ..].iter().take_while(|c| kind(c)).count();
+        if end2 == two {
+            // Numeric segments are newer than alphabetic ones
+            return if numeric { Ordering::Greater } else { Ordering::Less };
+        }
+
+        let ordering = if numeric {
+            let strip = |s: &[u8]| s.iter().position(|&c| c != b'0').unwrap_or(s.len());
+            let seg1 = &a[one + strip(&a[one .. end1]) .. end1];
+            let seg2 = &b[two + strip(&b[two .. end2]) .. end2];
+            seg1.len().cmp(&seg2.len()).then_with(|| seg1.cmp(seg2))
+        } else {
+            a[one .. end1].cmp(&b[two .. end2])
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        one = end1;
+        two = end2;
+    }
+
+    // All the segments compared equal, but the separators may differ
+    if one == a.len() && two == b.len() {
+        return Ordering::Equal;
+    }
+    // A remaining alphabetic segment never beats the end of the string, but
+    // anything else does
+    let alpha1 = a.get(one).is_some_and(|c| c.is_ascii_alphabetic());
+    let alpha2 = b.get(two).is_some_and(|c| c.is_ascii_alphabetic());
+    if (one == a.len() && !alpha2) || alpha1 {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// Split `[epoch:]version[-release]` the way pacman does.
+fn split_evr(evr: &str) -> (&str, &str, Option<&str>) {
+    let digits = evr.bytes().take_while(|c| c.is_ascii_digit()).count();
+    let (epoch, rest) = if evr[digits ..].starts_with(':') {
+        (&evr[.. digits], &evr[digits + 1 ..])
+    } else {
+        ("0", evr)
+    };
+    let epoch = if epoch.is_empty() { "0" } else { epoch };
+    match rest.rfind('-') {
+        Some(hyphen) => (epoch, &rest[.. hyphen], Some(&rest[hyphen + 1 ..])),
+        None => (epoch, rest, None),
+    }
+}
+
+/// Compare two version strings the way `vercmp` (`alpm_pkg_vercmp`) does.
+///
+/// The releases are only compared if both versions have one, so `1.0` and
+/// `1.0-2` are equal.
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (epoch1, version1, release1) = split_evr(a);
+    let (epoch2, version2, release2) = split_evr(b);
+    let ordering = compare_segments(epoch1, epoch2)
+        .then_with(|| compare_segments(version1, version2));
+    match (release1, release2) {
+        (Some(release1), Some(release2)) => {
+            ordering.then_with(|| compare_segments(release1, release2))
+        }
+        _ => ordering,
+    }
+}
+
+/// An Arch Linux package version, `[epoch:]pkgver[-pkgrel]`.
+///
+/// Versions without an epoch compare like epoch `0`. Contrary to `vercmp`, a
+/// missing `pkgrel` sorts before any `pkgrel`, so that this is a total order
+/// (for versions without repeated or trailing separators).
+#[derive(Clone, Debug)]
+pub struct PacmanVersion {
+    epoch: Option<u64>,
+    pkgver: String,
+    pkgrel: Option<String>,
+}
+
+/// The version can't be converted between pacman's scheme and `Version`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConversionError {
+    /// `Version` doesn't support epochs.
+    HasEpoch,
+    /// The version has leading, trailing, or consecutive separators.
+    Separators,
+    /// The `Version` was not converted from a pacman version.
+    InvalidEncoding,
+}
+
+impl PacmanVersion {
+    /// Parse a version of the form `[epoch:]pkgver[-pkgrel]`.
+    pub fn parse(string: &str) -> Result<PacmanVersion, InvalidVersion> {
+        let (epoch, rest) = match string.find(':') {
+            Some(colon) => {
+                let epoch = &string[.. colon];
+                if epoch.is_empty() {
+                    return Err(InvalidVersion::EmptyField);
+                }
+                if !epoch.bytes().all(|c| c.is_ascii_digit()) {
+                    return Err(InvalidVersion::InvalidCharacter);
+                }
+                let epoch = epoch.parse()
+                    .map_err(|_| InvalidVersion::NumberTooLarge)?;
+                (Some(epoch), &string[colon + 1 ..])
+            }
+            None => (None, string),
+        };
+        let (pkgver, pkgrel) = match rest.find('-') {
+            Some(hyphen) => (&rest[.. hyphen], Some(&rest[hyphen + 1 ..])),
+            None => (rest, None),
+        };
+        if pkgver.is_empty() {
+            return Err(InvalidVersion::EmptyField);
+        }
+        if !pkgver.bytes().all(|c| c.is_ascii_graphic() && c != b'/' && c != b':') {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+        if let Some(pkgrel) = pkgrel {
+            // `integer[.integer]`
+            for part in pkgrel.splitn(2, '.') {
+                if part.is_empty() {
+                    return Err(InvalidVersion::EmptyField);
+                }
+                if !part.bytes().all(|c| c.is_ascii_digit()) {
+                    return Err(InvalidVersion::InvalidCharacter);
+                }
+            }
+        }
+        Ok(PacmanVersion {
+            epoch,
+            pkgver: pkgver.to_owned(),
+            pkgrel: pkgrel.map(|r| r.to_owned()),
+        })
+    }
+
+    /// The epoch, `0` if not specified.
+    pub fn epoch(&self) -> u64 {
+        self.epoch.unwrap_or(0)
+    }
+
+    /// The version of the packaged software.
+    pub fn pkgver(&self) -> &str {
+        &self.pkgver
+    }
+
+    /// The release of the package, e.g. `1` or `2.1`.
+    pub fn pkgrel(&self) -> Option<&str> {
+        self.pkgrel.as_deref()
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PacmanVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<PacmanVersion, InvalidVersion> {
+        PacmanVersion::parse(string)
+    }
+}
+
+impl fmt::Display for PacmanVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(epoch) = self.epoch {
+            write!(f, "{}:", epoch)?;
+        }
+        write!(f, "{}", self.pkgver)?;
+        if let Some(ref pkgrel) = self.pkgrel {
+            write!(f, "-{}", pkgrel)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<PacmanVersion> for PacmanVersion {
+    fn eq(&self, other: &PacmanVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PacmanVersion {}
+
+impl PartialOrd<PacmanVersion> for PacmanVersion {
+    fn partial_cmp(&self, other: &PacmanVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PacmanVersion {
+    fn cmp(&self, other: &PacmanVersion) -> Ordering {
+        self.epoch().cmp(&other.epoch())
+            .then_with(|| compare_segments(&self.pkgver, &other.pkgver))
+            .then_with(|| compare_segments(
+                self.pkgrel().unwrap_or(""),
+                other.pkgrel().unwrap_or(""),
+            ))
+    }
+}
+
+/// Append the encoding of a pkgver or pkgrel to a `Version` string.
+///
+/// Each segment is written with a prefix that depends on whether it is
+/// numeric and whether it follows a separator, sorting in the same order as
+/// pacman sorts what follows the previous segment:
+///
+/// * `~` for letters right after a number, which sort before the end,
+/// * `-` for a number right after letters,
+/// * `-` for letters after a separator (greater, as letters continue the
+///   non-digit part),
+/// * `.` for a number after a separator.
+///
+/// A first numeric segment has no prefix. Letters are terminated by `~~`,
+/// and uppercase letters are escaped as `~` and the lowercase letter, so they
+/// sort first, like in pacman.
+fn encode_segments(version: &mut String, string: &str) -> Result<(), ConversionError> {
+    let bytes = string.as_bytes();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        while pos < bytes.len() && !bytes[pos].is_ascii_alphanumeric() {
+            pos += 1;
+        }
+        let separated = match pos - start {
+            0 => false,
+            1 if start != 0 && pos != bytes.len() => true,
+            _ => return Err(ConversionError::Separators),
+        };
+        let numeric = bytes[pos].is_ascii_digit();
+        let end = pos + bytes[pos ..].iter()
+            .take_while(|c| if numeric { c.is_ascii_digit() } else { c.is_ascii_alphabetic() })
+            .count();
+        let segment = &string[pos .. end];
+        if numeric {
+            if separated {
+                version.push('.');
+            } else if start != 0 {
+                version.push('-');
+            }
+            version.push_str(segment);
+        } else {
+            version.push(if separated { '-' } else { '~' });
+            for c in segment.chars() {
+                if c.is_ascii_uppercase() {
+                    version.push('~');
+                }
+                version.push(c.to_ascii_lowercase());
+            }
+            version.push_str("~~");
+        }
+        pos = end;
+    }
+    Ok(())
+}
+
+/// Read segments written by `encode_segments()`, returning the original
+/// string and the rest of the input.
+fn decode_segments(mut rest: &str) -> Result<(String, &str), ConversionError> {
+    let mut string = String::new();
+    let mut last_numeric = None;
+    loop {
+        let digits = |s: &str| s.bytes().take_while(|c| c.is_ascii_digit()).count();
+        let (separated, numeric, skip) = match rest.as_bytes().first() {
+            Some(c) if c.is_ascii_digit() && last_numeric.is_none() => (false, true, 0),
+            Some(b'.') if last_numeric.is_some() => (true, true, 1),
+            Some(b'-') if rest[1 ..].starts_with(|c: char| c.is_ascii_digit()) => {
+                if last_numeric != Some(false) {
+                    return Err(ConversionError::InvalidEncoding);
+                }
+                (false, true, 1)
+            }
+            Some(b'-') if last_numeric.is_some() => (true, false, 1),
+            Some(b'~') if last_numeric != Some(false) => (false, false, 1),
+            _ => break,
+        };
+        rest = &rest[skip ..];
+        if separated {
+            string.push('.');
+        }
+        if numeric {
+            let end = digits(rest);
+            if end == 0 {
+                return Err(ConversionError::InvalidEncoding);
+            }
+            string.push_str(&rest[.. end]);
+            rest = &rest[end ..];
+        } else {
+            let start = string.len();
+            loop {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some('~'), Some('~')) => {
+                        rest = &rest[2 ..];
+                        break;
+                    }
+                    (Some('~'), Some(c)) if c.is_ascii_lowercase() => {
+                        string.push(c.to_ascii_uppercase());
+                        rest = &rest[2 ..];
+                    }
+                    (Some(c), _) if c.is_ascii_lowercase() => {
+                        string.push(c);
+                        rest = &rest[1 ..];
+                    }
+                    _ => return Err(ConversionError::InvalidEncoding),
+                }
+            }
+            if string.len() == start {
+                return Err(ConversionError::InvalidEncoding);
+            }
+        }
+        last_numeric = Some(numeric);
+    }
+    if last_numeric.is_none() {
+        return Err(ConversionError::InvalidEncoding);
+    }
+    Ok((string, rest))
+}
+
+impl<'a> TryFrom<&'a PacmanVersion> for Version {
+    type Error = ConversionError;
+
+    /// Encode the version so that `Version`'s order matches pacman's.
+    ///
+    /// Versions with a number as the first segment and single dots as
+    /// separators are unchanged, e.g. `1.2.3`. The pkgrel is appended after
+    /// a `+`, e.g. `1.2.3-1` becomes `1.2.3+1`, and `1.0rc1` becomes
+    /// `1.0~rc~~-1`.
+    fn try_from(pacman: &'a PacmanVersion) -> Result<Version, ConversionError> {
+        if pacman.epoch() != 0 {
+            return Err(ConversionError::HasEpoch);
+        }
+        let mut version = String::new();
+        encode_segments(&mut version, &pacman.pkgver)?;
+        if let Some(ref pkgrel) = pacman.pkgrel {
+            version.push('+');
+            encode_segments(&mut version, pkgrel)?;
+        }
+        Ok(Version(version))
+    }
+}
+
+impl<'a> TryFrom<&'a Version> for PacmanVersion {
+    type Error = ConversionError;
+
+    /// Decode a `Version` converted from a pacman version.
+    fn try_from(version: &'a Version) -> Result<PacmanVersion, ConversionError> {
+        let (pkgver, rest) = decode_segments(version)?;
+        let pkgrel = match rest.strip_prefix('+') {
+            Some(rest) => {
+                let (pkgrel, rest) = decode_segments(rest)?;
+                if !rest.is_empty() {
+                    return Err(ConversionError::InvalidEncoding);
+                }
+                Some(pkgrel)
+            }
+            None if rest.is_empty() => None,
+            None => return Err(ConversionError::InvalidEncoding),
+        };
+        let string = match pkgrel {
+            Some(pkgrel) => format!("{}-{}", pkgver, pkgrel),
+            None => pkgver,
+        };
+        PacmanVersion::parse(&string).map_err(|_| ConversionError::InvalidEncoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{InvalidVersion, Version};
+    use super::{ConversionError, PacmanVersion, vercmp};
+
+    fn parse(s: &str) -> PacmanVersion {
+        PacmanVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_vercmp() {
+        // From pacman's test/util/vercmptest.sh
+        let fixtures = [
+            ("1.5.0", "1.5.0", 0),
+            ("1.5.1", "1.5.0", 1),
+            ("1.5.1", "1.5", 1),
+            ("1.5.0-1", "1.5.0-1", 0),
+            ("1.5.0-1", "1.5.0-2", -1),
+            ("1.5.0-1", "1.5.1-1", -1),
+            ("1.5.0-2", "1.5.1-1", -1),
+            ("1.5-1", "1.5.1-1", -1),
+            ("1.5-2", "1.5.1-1", -1),
+            ("1.5-2", "1.5.1-2", -1),
+            ("1.5", "1.5-1", 0),
+            ("1.5-1", "1.5", 0),
+            ("1.1-1", "1.1", 0),
+            ("1.0-1", "1.1", -1),
+            ("1.1-1", "1.0", 1),
+            ("1.5b-1", "1.5-1", -1),
+            ("1.5b", "1.5", -1),
+            ("1.5b-1", "1.5", -1),
+            ("1.5b", "1.5.1", -1),
+            ("1.0a", "1.0alpha", -1),
+            ("1.0alpha", "1.0b", -1),
+            ("1.0b", "1.0beta", -1),
+            ("1.0beta", "1.0rc", -1),
+            ("1.0rc", "1.0", -1),
+            ("1.5.a", "1.5", 1),
+            ("1.5.b", "1.5.a", 1),
+            ("1.5.1", "1.5.b", 1),
+            ("1.5.b-1", "1.5.b", 0),
+            ("1.5-1", "1.5.b", -1),
+            ("2.0", "2_0", 0),
+            ("2.0_a", "2_0.a", 0),
+            ("2.0a", "2.0.a", -1),
+            ("2___a", "2_a", 1),
+            ("0:1.0", "0:1.0", 0),
+            ("0:1.0", "0:1.1", -1),
+            ("1:1.0", "0:1.0", 1),
+            ("1:1.0", "0:1.1", 1),
+            ("1:1.0", "2:1.1", -1),
+            ("1:1.0", "0:1.0-1", 1),
+            ("1:1.0-1", "0:1.1-1", 1),
+            ("0:1.0", "1.0", 0),
+            ("0:1.0", "1.1", -1),
+            ("0:1.1", "1.0", 1),
+            ("1:1.0", "1.0", 1),
+            ("1:1.0", "1.1", 1),
+            ("1:1.1", "1.1", 1),
+        ];
+        for &(a, b, expected) in &fixtures {
+            assert_eq!(vercmp(a, b), expected.cmp(&0), "{} {}", a, b);
+            assert_eq!(vercmp(b, a), 0.cmp(&expected), "{} {}", b, a);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let version = parse("2:1.0rc1-1.1");
+        assert_eq!(version.epoch(), 2);
+        assert_eq!(version.pkgver(), "1.0rc1");
+        assert_eq!(version.pkgrel(), Some("1.1"));
+        assert_eq!(version.to_string(), "2:1.0rc1-1.1");
+        assert_eq!(parse("r1234.abcdef").pkgrel(), None);
+
+        for &(invalid, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            (":1.0", InvalidVersion::EmptyField),
+            ("1.0-", InvalidVersion::EmptyField),
+            ("1.0-1.", InvalidVersion::EmptyField),
+            ("a:1.0", InvalidVersion::InvalidCharacter),
+            ("1.0-1-1", InvalidVersion::InvalidCharacter),
+            ("1.0-1.1.1", InvalidVersion::InvalidCharacter),
+            ("1.0-rc1", InvalidVersion::InvalidCharacter),
+            ("1 0", InvalidVersion::InvalidCharacter),
+            ("1:2:3", InvalidVersion::InvalidCharacter),
+            ("99999999999999999999:1", InvalidVersion::NumberTooLarge),
+        ] {
+            assert_eq!(&PacmanVersion::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    /// Versions in increasing order.
+    const ORDERED: &[&str] = &[
+        "a",
+        "r1234.abc",
+        "0.9",
+        "1.0A",
+        "1.0a",
+        "1.0alpha",
+        "1.0alpha1",
+        "1.0alpha2",
+        "1.0b",
+        "1.0rc",
+        "1.0rc1",
+        "1.0",
+        "1.0-1",
+        "1.0-1.1",
+        "1.0-2",
+        "1.0.A",
+        "1.0.a",
+        "1.0.b",
+        "1.0.b-1",
+        "1.0.0",
+        "1.0.1",
+        "1.01.1",
+        "1.2",
+        "1.10",
+        "1234",
+    ];
+
+    #[test]
+    fn test_ordering() {
+        for pair in ORDERED.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(parse("2.0"), parse("2_0"));
+        assert_eq!(parse("0:1.0"), parse("1.0"));
+        assert!(parse("1:0.1") > parse("1.0"));
+    }
+
+    #[test]
+    fn test_version() {
+        for &(pacman, encoded) in &[
+            ("1.2.3", "1.2.3"),
+            ("1.2.3-1", "1.2.3+1"),
+            ("1.0rc1-2.1", "1.0~rc~~-1+2.1"),
+            ("r1234.abc", "~r~~-1234-abc~~"),
+            ("1.0RC", "1.0~~r~c~~"),
+            ("0:2_0", "2.0"),
+        ] {
+            let version = Version::try_from(&parse(pacman)).unwrap();
+            assert_eq!(&*version, encoded);
+            let back = PacmanVersion::try_from(&version).unwrap();
+            assert_eq!(back, parse(pacman));
+        }
+
+        let versions: Vec<Version> = ORDERED.iter()
+            .map(|s| Version::try_from(&parse(s)).unwrap())
+            .collect();
+        for (i, a) in versions.iter().enumerate() {
+            assert_eq!(&PacmanVersion::try_from(a).unwrap().to_string(), ORDERED[i]);
+            for (j, b) in versions.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{} {}", ORDERED[i], ORDERED[j]);
+            }
+        }
+
+        for &(pacman, ref error) in &[
+            ("1:1.0", ConversionError::HasEpoch),
+            ("2__0", ConversionError::Separators),
+            ("1.0.", ConversionError::Separators),
+            ("_1.0", ConversionError::Separators),
+        ] {
+            assert_eq!(&Version::try_from(&parse(pacman)).unwrap_err(), error, "{}", pacman);
+        }
+        for &invalid in &["", "~~", "1-2", "1~a~~~b~~", "1.0~", "-a~~", "1+", "1+2+3", "1+a"] {
+            let version = Version(invalid.to_owned());
+            assert_eq!(
+                PacmanVersion::try_from(&version).unwrap_err(),
+                ConversionError::InvalidEncoding,
+                "{}", invalid,
+            );
+        }
+    }
+}
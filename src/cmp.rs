@@ -1,8 +1,9 @@
 //! Provides the comparison logic for the `Version`.
 
 use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 
-pub const CHAR_ORDER: &'static [u8] = &[
+pub const CHAR_ORDER: &[u8] = &[
     255u8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
     255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
     255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 27,
@@ -70,9 +71,16 @@ where F: FnMut(&T) -> bool {
     slice[start ..].iter().position(pred).map(|idx| idx + start).unwrap_or(slice.len())
 }
 
-fn parse_int(slice: &[u8]) -> u32 {
-   use std::str::from_utf8;
-   from_utf8(slice).ok().and_then(|s| s.parse::<u32>().ok()).unwrap_or(0)
+/// Compare strings of digits numerically, whatever their length.
+fn compare_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    fn strip_zeros(slice: &[u8]) -> &[u8] {
+        let zeros = slice.iter().take_while(|&&c| c == b'0').count();
+        &slice[zeros ..]
+    }
+
+    let a = strip_zeros(a);
+    let b = strip_zeros(b);
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
 }
 
 /// Compare version strings.
@@ -126,9 +134,7 @@ pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
        {
            let end_num_a = position(a, pos_a, |c| !c.is_ascii_digit());
            let end_num_b = position(b, pos_b, |c| !c.is_ascii_digit());
-           let num_a = parse_int(&a[pos_a .. end_num_a]);
-           let num_b = parse_int(&b[pos_b .. end_num_b]);
-           match num_a.cmp(&num_b) {
+           match compare_numbers(&a[pos_a .. end_num_a], &b[pos_b .. end_num_b]) {
                Ordering::Equal => {},
                o => return o,
            }
@@ -139,9 +145,35 @@ pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
     Ordering::Equal
 }
 
+/// Hash a version string, so that versions equal according to
+/// `compare_versions()` have the same hash.
+///
+/// Non-digit characters are hashed by their rank, numbers without their
+/// leading zeros, and trailing empty parts are ignored, so that `1.01`, `1.1`
+/// and `1.1` followed by `0` hash alike.
+pub fn hash_version<H: Hasher>(version: &str, state: &mut H) {
+    let version = version.as_bytes();
+    let mut parts = Vec::new();
+    let mut pos = 0;
+    while pos < version.len() {
+        let end_alpha = position(version, pos, u8::is_ascii_digit);
+        let end_num = position(version, end_alpha, |c| !c.is_ascii_digit());
+        let alpha: Vec<u8> = version[pos .. end_alpha].iter().map(|&c| CHAR_ORDER[usize::from(c)]).collect();
+        let zeros = version[end_alpha .. end_num].iter().take_while(|&&c| c == b'0').count();
+        parts.push((alpha, &version[end_alpha + zeros .. end_num]));
+        pos = end_num;
+    }
+    while parts.last().is_some_and(|(alpha, num)| alpha.is_empty() && num.is_empty()) {
+        parts.pop();
+    }
+    parts.hash(state);
+}
+
 #[cfg(test)]
 mod tests {
     use std::cmp::Ordering;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
 
     use crate::Version;
     use super::{CHAR_ORDER, compare_alpha};
@@ -230,5 +262,32 @@ mod tests {
         assert!(Version("1.1.3".into()) < Version("1.3.1".into()));
         assert!(Version("1.1~rc1".into()) < Version("1.1".into()));
         assert!(Version("1.1-fix1".into()) > Version("1.1".into()));
+        assert!(Version("1.01".into()) == Version("1.1".into()));
+        assert!(Version("1.0".into()) == Version("1.".into()));
+        assert!(Version("4294967296".into()) > Version("4294967295".into()));
+        assert!(Version("99999999999999999999".into()) > Version("1".into()));
+    }
+
+    fn hash(version: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        Version(version.into()).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_hash() {
+        for &(a, b) in &[
+            ("1.01", "1.1"),
+            ("1.0", "1."),
+            ("", "0"),
+            ("1a", "1a0"),
+            ("1.0", "1:0"),
+            ("007", "7"),
+        ] {
+            assert!(Version(a.into()) == Version(b.into()), "{} == {}", a, b);
+            assert_eq!(hash(a), hash(b), "{} {}", a, b);
+        }
+        assert_ne!(hash("1.0"), hash("1.0.0"));
+        assert_ne!(hash("1~rc1"), hash("1rc1"));
     }
 }
@@ -0,0 +1,687 @@
+//! Implement Composer's versioning scheme, as implemented by
+//! `composer/semver`.
+//!
+//! Versions are normalized to four numbers, followed by an optional
+//! stability modifier and a `-dev` suffix: `1.0-b2` becomes
+//! `1.0.0.0-beta2`, and `1.2.x-dev` becomes `1.2.9999999.9999999-dev`.
+//! Date-based versions (`2010-01-02`) keep their own number of fields.
+//! Branches are written `dev-name`, e.g. `dev-master`; they can't be ordered
+//! against other versions, so they only match constraints on the same
+//! branch.
+//!
+//! Normalized versions are compared like PHP's `version_compare()`: they
+//! are split into numbers and words, and `dev < alpha < beta < RC <
+//! (missing) < numbers < patch`, so `1.0.0.0-beta2 < 1.0.0.0 <
+//! 1.0.0.0-patch1`.
+//!
+//! Each version also has a stability: `stable`, `RC`, `beta`, `alpha` or
+//! `dev`. Constraints such as `^1.2 || >=2.0@beta` can be parsed, and only
+//! match versions at least as stable as the `minimum-stability` or the
+//! constraint's own stability flag.
+
+mod constraint;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{InvalidVersion, Version};
+
+pub use self::constraint::{Comparison, Constraint, InvalidConstraint, Operator};
+
+/// Words that can start a version modifier, in the order Composer tries
+/// them.
+const MODIFIERS: &[&str] = &["stable", "beta", "b", "rc", "alpha", "a", "patch", "pl", "p"];
+
+/// The stability of a version, from the most to the least stable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stability {
+    Stable,
+    Rc,
+    Beta,
+    Alpha,
+    Dev,
+}
+
+impl Stability {
+    /// Read a stability name, e.g. `beta` or `RC`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Stability> {
+        match &*name.to_ascii_lowercase() {
+            "stable" => Some(Stability::Stable),
+            "rc" => Some(Stability::Rc),
+            "beta" => Some(Stability::Beta),
+            "alpha" => Some(Stability::Alpha),
+            "dev" => Some(Stability::Dev),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Stability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Stability::Stable => "stable",
+            Stability::Rc => "RC",
+            Stability::Beta => "beta",
+            Stability::Alpha => "alpha",
+            Stability::Dev => "dev",
+        })
+    }
+}
+
+/// Guess the stability of a version or a constraint from its suffix, as
+/// Composer's `VersionParser::parseStability()` does.
+fn parse_stability(version: &str) -> Stability {
+    let version = version.split('#').next().unwrap();
+    if version.starts_with("dev-") || version.ends_with("-dev") {
+        return Stability::Dev;
+    }
+    let version = version.split('+').next().unwrap().to_ascii_lowercase();
+    if version.ends_with("dev") {
+        return Stability::Dev;
+    }
+    let word = version.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-');
+    if word.ends_with("beta") || word.ends_with('b') {
+        Stability::Beta
+    } else if word.ends_with("alpha") || word.ends_with('a') {
+        Stability::Alpha
+    } else if word.ends_with("rc") {
+        Stability::Rc
+    } else {
+        Stability::Stable
+    }
+}
+
+/// A version modifier, e.g. `-beta.2-dev`.
+struct Modifier {
+    /// The stability word, lowercase.
+    word: Option<String>,
+    /// The numbers after the word, e.g. `.2`.
+    number: String,
+    dev: bool,
+}
+
+impl Modifier {
+    /// Match `[._-]?(?:(word)((?:[.-]?\d+)*))?([.-]?dev)?`.
+    fn parse(string: &str) -> Option<Modifier> {
+        let lower = string.to_ascii_lowercase();
+        let rest = lower.strip_prefix(['.', '_', '-']).unwrap_or(&lower);
+        let is_dev = |tail: &str| match tail {
+            "" => Some(false),
+            "dev" | ".dev" | "-dev" => Some(true),
+            _ => None,
+        };
+        if let Some(dev) = is_dev(rest) {
+            return Some(Modifier { word: None, number: String::new(), dev });
+        }
+        for &word in MODIFIERS {
+            let after = match rest.strip_prefix(word) {
+                Some(after) => after,
+                None => continue,
+            };
+            // Numbers, each optionally preceded by `.` or `-`
+            let mut end = 0;
+            loop {
+                let start = if after[end ..].starts_with(['.', '-']) { end + 1 } else { end };
+                let digits = after[start ..].bytes().take_while(|c| c.is_ascii_digit()).count();
+                if digits == 0 {
+                    break;
+                }
+                end = start + digits;
+            }
+            if let Some(dev) = is_dev(&after[end ..]) {
+                return Some(Modifier {
+                    word: Some(word.to_owned()),
+                    number: after[.. end].to_owned(),
+                    dev,
+                });
+            }
+        }
+        None
+    }
+
+    /// Append the modifier to a normalized version.
+    fn apply(&self, version: &mut String) {
+        if let Some(ref word) = self.word {
+            if word == "stable" {
+                return;
+            }
+            let word = match &**word {
+                "a" => "alpha",
+                "b" => "beta",
+                "p" | "pl" => "patch",
+                "rc" => "RC",
+                word => word,
+            };
+            version.push('-');
+            version.push_str(word);
+            version.push_str(self.number.trim_start_matches(['.', '-']));
+        }
+        if self.dev {
+            version.push_str("-dev");
+        }
+    }
+}
+
+/// A classical version, `v?\d+(\.\d+){0,3}` followed by a modifier.
+struct Classical<'a> {
+    numbers: Vec<&'a str>,
+    modifier: Modifier,
+}
+
+impl<'a> Classical<'a> {
+    fn parse(version: &'a str) -> Option<Classical<'a>> {
+        let mut rest = version.strip_prefix(['v', 'V']).unwrap_or(version);
+        let mut numbers = Vec::new();
+        while numbers.len() < 4 {
+            let digits = match rest.strip_prefix('.') {
+                Some(after) if !numbers.is_empty() => after,
+                _ if numbers.is_empty() => rest,
+                _ => break,
+            };
+            let count = digits.bytes().take_while(|c| c.is_ascii_digit()).count();
+            if count == 0 {
+                if numbers.is_empty() {
+                    return None;
+                }
+                break;
+            }
+            numbers.push(&digits[.. count]);
+            rest = &digits[count ..];
+        }
+        let modifier = Modifier::parse(rest)?;
+        Some(Classical { numbers, modifier })
+    }
+}
+
+/// Whether the string matches `(?:[.:-]?\d{2}){1,6}(?:[.:-]?\d{1,3}){0,2}`,
+/// given how many groups of each kind were already matched.
+fn match_date(string: &[u8], pairs: usize, smalls: usize) -> bool {
+    if string.is_empty() {
+        return pairs >= 1;
+    }
+    let body = match string[0] {
+        b'.' | b':' | b'-' => &string[1 ..],
+        _ => string,
+    };
+    let digits = body.iter().take_while(|c| c.is_ascii_digit()).count();
+    if smalls == 0 && pairs < 6 && digits >= 2 && match_date(&body[2 ..], pairs + 1, 0) {
+        return true;
+    }
+    pairs >= 1 && smalls < 2 && (1 ..= digits.min(3)).any(|n| match_date(&body[n ..], pairs, smalls + 1))
+}
+
+/// Normalize a date-based version, e.g. `2010-01-02` to `2010.01.02`.
+fn normalize_date(version: &str) -> Option<String> {
+    let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    let bytes = version.as_bytes();
+    // The date is followed by a modifier, which can't start with a digit
+    let mut end = 0;
+    while end < bytes.len() {
+        if bytes[end].is_ascii_digit() {
+            end += 1;
+        } else if b".:-".contains(&bytes[end]) && bytes.get(end + 1).is_some_and(|c| c.is_ascii_digit()) {
+            end += 2;
+        } else {
+            break;
+        }
+    }
+    let date = &bytes[.. end];
+    if date.len() < 4 || !date[.. 4].iter().all(|c| c.is_ascii_digit()) || !match_date(&date[4 ..], 0, 0) {
+        return None;
+    }
+    let modifier = Modifier::parse(&version[end ..])?;
+    let mut normalized: String = version[.. end]
+        .chars()
+        .map(|c| if c.is_ascii_digit() { c } else { '.' })
+        .collect();
+    modifier.apply(&mut normalized);
+    Some(normalized)
+}
+
+/// Normalize a numeric branch name, e.g. `1.2.x` to
+/// `1.2.9999999.9999999-dev`.
+fn normalize_branch(name: &str) -> Option<String> {
+    let name = name.strip_prefix(['v', 'V']).unwrap_or(name);
+    let parts: Vec<&str> = name.split('.').collect();
+    let is_number = |p: &str| !p.is_empty() && p.bytes().all(|c| c.is_ascii_digit());
+    if parts.len() > 4
+        || !is_number(parts[0])
+        || !parts[1 ..].iter().all(|&p| is_number(p) || p == "x" || p == "X" || p == "*")
+    {
+        return None;
+    }
+    let mut normalized: Vec<&str> = parts
+        .iter()
+        .map(|&p| if is_number(p) { p } else { "9999999" })
+        .collect();
+    normalized.resize(4, "9999999");
+    Some(format!("{}-dev", normalized.join(".")))
+}
+
+/// Normalize a version, as Composer's `VersionParser::normalize()` does.
+fn normalize(version: &str) -> Result<String, InvalidVersion> {
+    let mut version = version.trim();
+    if version.is_empty() {
+        return Err(InvalidVersion::EmptyField);
+    }
+    // Strip an inline alias, `1.0.x-dev as 1.0.0`
+    if let [actual, "as", _] = version.split_whitespace().collect::<Vec<_>>()[..] {
+        version = actual;
+    }
+    // Strip a stability flag, `@dev`
+    if let Some((rest, flag)) = version.rsplit_once('@') {
+        if Stability::from_name(flag).is_some() {
+            version = rest;
+        }
+    }
+    if ["master", "trunk", "default"].contains(&version) {
+        return Ok(format!("dev-{}", version));
+    }
+    if version.get(.. 4).is_some_and(|prefix| prefix.eq_ignore_ascii_case("dev-")) {
+        if version.len() == 4 {
+            return Err(InvalidVersion::EmptyField);
+        }
+        return Ok(format!("dev-{}", &version[4 ..]));
+    }
+    // Strip build metadata
+    if let Some((rest, metadata)) = version.split_once('+') {
+        if !rest.is_empty() && !metadata.is_empty() && !rest.contains(',') {
+            version = rest;
+        }
+    }
+
+    if let Some(classical) = Classical::parse(version) {
+        if classical.numbers[0].len() <= 5 {
+            let mut numbers = classical.numbers;
+            numbers.resize(4, "0");
+            let mut normalized = numbers.join(".");
+            classical.modifier.apply(&mut normalized);
+            return Ok(normalized);
+        }
+    }
+    if let Some(normalized) = normalize_date(version) {
+        return Ok(normalized);
+    }
+    // A numeric branch, `1.x-dev`
+    let lower = version.to_ascii_lowercase();
+    if let Some(name) = lower.strip_suffix("dev") {
+        let name = name.strip_suffix(['.', '-']).unwrap_or(name);
+        if let Some(normalized) = normalize_branch(&version[.. name.len()]) {
+            return Ok(normalized);
+        }
+    }
+    Err(InvalidVersion::InvalidCharacter)
+}
+
+/// A Composer version, e.g. `1.0.0`, `v2.1-beta2` or `dev-master`.
+#[derive(Clone, Debug)]
+pub struct ComposerVersion {
+    string: String,
+    normalized: String,
+}
+
+impl ComposerVersion {
+    /// Parse and normalize a version.
+    ///
+    /// An inline alias (`dev-master as 1.0.0`) or a stability flag
+    /// (`1.0@dev`) is ignored.
+    pub fn parse(string: &str) -> Result<ComposerVersion, InvalidVersion> {
+        Ok(ComposerVersion { string: string.to_owned(), normalized: normalize(string)? })
+    }
+
+    /// A version from its normalized form, displayed as such.
+    fn from_normalized(normalized: String) -> ComposerVersion {
+        ComposerVersion { string: normalized.clone(), normalized }
+    }
+
+    /// The normalized version, e.g. `1.0.0.0-beta2` for `1.0-b2`.
+    pub fn normalized(&self) -> &str {
+        &self.normalized
+    }
+
+    /// Whether this is a branch, e.g. `dev-master`.
+    pub fn is_branch(&self) -> bool {
+        self.normalized.starts_with("dev-")
+    }
+
+    /// The stability, e.g. `Beta` for `1.0-b2`, and `Dev` for branches.
+    pub fn stability(&self) -> Stability {
+        parse_stability(&self.normalized)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ComposerVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<ComposerVersion, InvalidVersion> {
+        ComposerVersion::parse(string)
+    }
+}
+
+impl fmt::Display for ComposerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+/// A part of a version, as split by PHP's `version_compare()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part<'a> {
+    /// A number, without leading zeros.
+    Number(&'a str),
+    /// The rank of a word: `-6` for unknown words, then `dev`, `alpha`,
+    /// `beta`, `RC`, and `5` for `patch`. Numbers have rank `4`.
+    Word(i8),
+}
+
+impl<'a> Part<'a> {
+    fn rank(&self) -> i8 {
+        match *self {
+            Part::Number(_) => 4,
+            Part::Word(rank) => rank,
+        }
+    }
+}
+
+impl<'a> PartialOrd for Part<'a> {
+    fn partial_cmp(&self, other: &Part<'a>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for Part<'a> {
+    fn cmp(&self, other: &Part<'a>) -> Ordering {
+        match (self, other) {
+            (Part::Number(a), Part::Number(b)) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// Split a version into parts, on `.`, `-`, `_` and `+`, and between digits
+/// and other characters.
+fn split_parts<'a>(version: &'a str) -> Vec<Part<'a>> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let bytes = version.as_bytes();
+    for i in 1 ..= bytes.len() {
+        let boundary = i == bytes.len()
+            || b".-_+".contains(&bytes[i])
+            || b".-_+".contains(&bytes[i - 1])
+            || bytes[i].is_ascii_digit() != bytes[i - 1].is_ascii_digit();
+        if !boundary {
+            continue;
+        }
+        let part = &version[start .. i];
+        start = i;
+        if part.is_empty() || b".-_+".contains(&part.as_bytes()[0]) {
+            continue;
+        }
+        parts.push(if part.as_bytes()[0].is_ascii_digit() {
+            Part::Number(part.trim_start_matches('0'))
+        } else {
+            // PHP only compares the start of words
+            let rank = [("dev", 0), ("alpha", 1), ("a", 1), ("beta", 2), ("b", 2), ("RC", 3), ("rc", 3), ("#", 4), ("pl", 5), ("p", 5)]
+                .iter()
+                .find(|&&(name, _)| part.starts_with(name))
+                .map_or(-6, |&(_, rank)| rank);
+            Part::Word(rank)
+        });
+    }
+    parts
+}
+
+/// Compare two normalized versions, as PHP's `version_compare()` does.
+fn version_compare(a: &str, b: &str) -> Ordering {
+    let (a, b) = (split_parts(a), split_parts(b));
+    for (x, y) in a.iter().zip(&b) {
+        match x.cmp(y) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
+    }
+    // Only the first remaining part matters
+    match (a.get(b.len()), b.get(a.len())) {
+        (Some(extra), _) => extra.rank().cmp(&4).then(Ordering::Greater),
+        (_, Some(extra)) => extra.rank().cmp(&4).then(Ordering::Greater).reverse(),
+        (None, None) => Ordering::Equal,
+    }
+}
+
+impl PartialEq<ComposerVersion> for ComposerVersion {
+    fn eq(&self, other: &ComposerVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ComposerVersion {}
+
+impl PartialOrd<ComposerVersion> for ComposerVersion {
+    fn partial_cmp(&self, other: &ComposerVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ComposerVersion {
+    /// Compare normalized versions, see the module documentation.
+    ///
+    /// Composer doesn't order branches: here they are greater than other
+    /// versions and sorted by name.
+    fn cmp(&self, other: &ComposerVersion) -> Ordering {
+        match (self.is_branch(), other.is_branch()) {
+            (false, false) => version_compare(&self.normalized, &other.normalized),
+            (false, true) => Ordering::Less,
+            (true, false) => Ordering::Greater,
+            (true, true) => self.normalized.cmp(&other.normalized),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a ComposerVersion> for Version {
+    type Error = InvalidVersion;
+
+    /// Encode the normalized version so that `Version`'s order matches
+    /// Composer's.
+    ///
+    /// Each part is written as a non-digit part and a number: `-N` for
+    /// numbers, `.0` for `patch`, and a letter giving the rank of the word
+    /// followed by `0` for other words, prefixed with `~` as they are less
+    /// than a missing part. E.g. `1.0.0.0-beta2` becomes `0-1-0-0-0~d0-2`.
+    ///
+    /// Branches can't be converted.
+    fn try_from(composer: &'a ComposerVersion) -> Result<Version, InvalidVersion> {
+        if composer.is_branch() {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+        let mut encoded = "0".to_owned();
+        for part in split_parts(&composer.normalized) {
+            match part {
+                Part::Number(n) => {
+                    encoded.push('-');
+                    encoded.push_str(if n.is_empty() { "0" } else { n });
+                }
+                Part::Word(5) => encoded.push_str(".0"),
+                Part::Word(rank) => {
+                    // Unknown words are `a`, `dev` is `b`, and so on
+                    let letter = if rank < 0 { b'a' } else { b'b' + rank as u8 };
+                    encoded.push('~');
+                    encoded.push(letter as char);
+                    encoded.push('0');
+                }
+            }
+        }
+        Ok(Version(encoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{InvalidVersion, Version};
+    use super::{ComposerVersion, Stability};
+
+    fn parse(s: &str) -> ComposerVersion {
+        ComposerVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_normalize() {
+        // From composer/semver's VersionParserTest
+        for &(version, normalized) in &[
+            ("1.0.0", "1.0.0.0"),
+            ("1.2.3.4", "1.2.3.4"),
+            ("1.0.0RC1dev", "1.0.0.0-RC1-dev"),
+            ("1.0.0-rC15-dev", "1.0.0.0-RC15-dev"),
+            ("1.0.0.RC.15-dev", "1.0.0.0-RC15-dev"),
+            ("1.0.0-rc1", "1.0.0.0-RC1"),
+            ("1.0.0.pl3-dev", "1.0.0.0-patch3-dev"),
+            ("1.0-dev", "1.0.0.0-dev"),
+            ("0", "0.0.0.0"),
+            ("10.4.13-beta", "10.4.13.0-beta"),
+            ("10.4.13beta2", "10.4.13.0-beta2"),
+            ("10.4.13beta.2", "10.4.13.0-beta2"),
+            ("v1.13.11-beta.0", "1.13.11.0-beta0"),
+            ("1.13.11.0-beta0", "1.13.11.0-beta0"),
+            ("10.4.13-b", "10.4.13.0-beta"),
+            ("10.4.13-b5", "10.4.13.0-beta5"),
+            ("v1.0.0", "1.0.0.0"),
+            ("v20100102", "20100102"),
+            ("2010.01", "2010.01.0.0"),
+            ("2010.01.02", "2010.01.02.0"),
+            ("2010-01-02", "2010.01.02"),
+            ("2010-01-02.5", "2010.01.02.5"),
+            ("2010.1.555", "2010.1.555.0"),
+            ("2010-01-02-10-20-30.5", "2010.01.02.10.20.30.5"),
+            ("20100102-203040", "20100102.203040"),
+            ("20100102203040-10", "20100102203040.10"),
+            ("20100102-203040-p1", "20100102.203040-patch1"),
+            ("1.0.0-beta.5+foo", "1.0.0.0-beta5"),
+            ("1.0.0+foo", "1.0.0.0"),
+            ("1.0.0-alpha.3.1+foo", "1.0.0.0-alpha3.1"),
+            ("1.0.0-stable", "1.0.0.0"),
+            ("1.0.0-patch1", "1.0.0.0-patch1"),
+            ("dev-master", "dev-master"),
+            ("master", "dev-master"),
+            ("dev-trunk", "dev-trunk"),
+            ("1.x-dev", "1.9999999.9999999.9999999-dev"),
+            ("dev-feature-foo", "dev-feature-foo"),
+            ("DEV-FOOBAR", "dev-FOOBAR"),
+            ("dev-feature/foo", "dev-feature/foo"),
+            ("dev-master as 1.0.0", "dev-master"),
+            ("1.0.0@dev", "1.0.0.0"),
+            ("1.2.x-dev", "1.2.9999999.9999999-dev"),
+            ("2.*-dev", "2.9999999.9999999.9999999-dev"),
+        ] {
+            assert_eq!(parse(version).normalized(), normalized, "{}", version);
+        }
+        assert_eq!(parse("v1.0-beta2").to_string(), "v1.0-beta2");
+
+        for &(version, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            (" ", InvalidVersion::EmptyField),
+            ("a", InvalidVersion::InvalidCharacter),
+            ("1.0.0-meh", InvalidVersion::InvalidCharacter),
+            ("1.0.0.0.0", InvalidVersion::InvalidCharacter),
+            ("feature-foo", InvalidVersion::InvalidCharacter),
+            ("1.0 .2", InvalidVersion::InvalidCharacter),
+            ("^1", InvalidVersion::InvalidCharacter),
+            ("1.*", InvalidVersion::InvalidCharacter),
+            ("1.0é", InvalidVersion::InvalidCharacter),
+            ("dév", InvalidVersion::InvalidCharacter),
+            ("1.0-é", InvalidVersion::InvalidCharacter),
+        ] {
+            assert_eq!(&ComposerVersion::parse(version).unwrap_err(), error, "{}", version);
+        }
+    }
+
+    #[test]
+    fn test_stability() {
+        for &(version, stability) in &[
+            ("1", Stability::Stable),
+            ("1.0-patch1", Stability::Stable),
+            ("1.0-RC1", Stability::Rc),
+            ("1.0-beta", Stability::Beta),
+            ("1.0b2", Stability::Beta),
+            ("1.0-alpha.1", Stability::Alpha),
+            ("1.0-a1", Stability::Alpha),
+            ("1.0-beta2-dev", Stability::Dev),
+            ("1.x-dev", Stability::Dev),
+            ("dev-master", Stability::Dev),
+        ] {
+            assert_eq!(parse(version).stability(), stability, "{}", version);
+        }
+        assert_eq!(Stability::from_name("RC"), Some(Stability::Rc));
+        assert_eq!(Stability::from_name("gamma"), None);
+        assert!(Stability::Stable < Stability::Dev);
+    }
+
+    /// Versions in increasing order, with equal ones grouped.
+    const VERSIONS: &[&[&str]] = &[
+        &["0.9"],
+        &["1.0-dev", "1.0.0.0-dev"],
+        &["1.0-alpha1-dev"],
+        &["1.0-alpha1", "1.0a1"],
+        &["1.0-alpha2"],
+        &["1.0-beta"],
+        &["1.0-beta1"],
+        &["1.0-beta2", "1.0b2", "1.0-b2"],
+        &["1.0-RC1"],
+        &["1.0-RC10"],
+        &["1", "1.0", "1.0.0.0", "v1.0.0+abc", "1.0.0-stable"],
+        &["1.0.0-patch1"],
+        &["1.0.0-patch1.1"],
+        &["1.0.0-patch2"],
+        &["1.0.0.1"],
+        &["1.0.1"],
+        &["1.9999999.9999999.9999999-dev", "1.x-dev"],
+        &["2.0"],
+        &["2010.01.02"],
+        &["dev-feature"],
+        &["dev-master"],
+    ];
+
+    #[test]
+    fn test_ordering() {
+        for (i, equal) in VERSIONS.iter().enumerate() {
+            for &a in *equal {
+                for &b in *equal {
+                    assert_eq!(parse(a), parse(b), "{} == {}", a, b);
+                }
+                for &b in VERSIONS[i + 1 ..].iter().flat_map(|v| v.iter()) {
+                    assert!(parse(a) < parse(b), "{} < {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_version() {
+        for &(composer, expected) in &[
+            ("1.2.3", "0-1-2-3-0"),
+            ("1.0-beta2", "0-1-0-0-0~d0-2"),
+            ("1.0.0-patch1", "0-1-0-0-0.0-1"),
+            ("1.0-RC1-dev", "0-1-0-0-0~e0-1~b0"),
+        ] {
+            assert_eq!(&*Version::try_from(&parse(composer)).unwrap(), expected, "{}", composer);
+        }
+        assert_eq!(
+            Version::try_from(&parse("dev-master")).unwrap_err(),
+            InvalidVersion::InvalidCharacter,
+        );
+
+        let versions: Vec<&str> = VERSIONS.iter()
+            .flat_map(|v| v.iter())
+            .cloned()
+            .filter(|v| !v.starts_with("dev-"))
+            .collect();
+        for &a in &versions {
+            for &b in &versions {
+                let (a, b) = (parse(a), parse(b));
+                let (x, y) = (Version::try_from(&a).unwrap(), Version::try_from(&b).unwrap());
+                assert_eq!(x.cmp(&y), a.cmp(&b), "{} ({}) {} ({})", a, x, b, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,589 @@
+//! Constraints, e.g. `^1.2 || >=2.0@beta`, as implemented by
+//! `VersionParser::parseConstraints()`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+use super::{parse_stability, version_compare, Classical, ComposerVersion, Modifier, Stability};
+
+/// Error for the constraint parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidConstraint {
+    /// The constraint, or one of its alternatives, is empty.
+    Empty,
+    /// The operator is not known, e.g. `=>` or `~>`.
+    InvalidOperator,
+    /// A version is invalid.
+    InvalidVersion(InvalidVersion),
+}
+
+impl From<InvalidVersion> for InvalidConstraint {
+    fn from(error: InvalidVersion) -> InvalidConstraint {
+        InvalidConstraint::InvalidVersion(error)
+    }
+}
+
+/// A comparison operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `==`, also written `=` or omitted
+    Equal,
+    /// `!=`, also written `<>`
+    NotEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEqual,
+}
+
+const OPERATORS: &[(&str, Operator)] = &[
+    ("==", Operator::Equal),
+    ("=", Operator::Equal),
+    ("!=", Operator::NotEqual),
+    ("<>", Operator::NotEqual),
+    ("<", Operator::Less),
+    ("<=", Operator::LessEqual),
+    (">", Operator::Greater),
+    (">=", Operator::GreaterEqual),
+];
+
+impl Operator {
+    /// Whether `version` satisfies the operator with the given operand.
+    ///
+    /// Branches are only equal to themselves, and different from anything
+    /// else.
+    pub fn matches(self, version: &ComposerVersion, operand: &ComposerVersion) -> bool {
+        if version.is_branch() || operand.is_branch() {
+            let equal = version.normalized == operand.normalized;
+            return match self {
+                Operator::Equal => equal,
+                Operator::NotEqual => !equal,
+                _ => false,
+            };
+        }
+        let ordering = version_compare(&version.normalized, &operand.normalized);
+        match self {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::NotEqual => ordering != Ordering::Equal,
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessEqual => ordering != Ordering::Greater,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::GreaterEqual => ordering != Ordering::Less,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(OPERATORS.iter().find(|&&(_, op)| op == *self).unwrap().0)
+    }
+}
+
+/// A comparison with a normalized version, e.g. `>= 1.2.0.0-dev`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comparison {
+    operator: Operator,
+    version: ComposerVersion,
+}
+
+impl Comparison {
+    fn new(operator: Operator, normalized: String) -> Comparison {
+        Comparison { operator, version: ComposerVersion::from_normalized(normalized) }
+    }
+
+    /// The comparison operator, e.g. `>=`.
+    pub fn operator(&self) -> Operator {
+        self.operator
+    }
+
+    /// The version compared with, e.g. `1.2.0.0-dev`.
+    pub fn version(&self) -> &ComposerVersion {
+        &self.version
+    }
+
+    /// Whether the version satisfies the comparison, ignoring stability.
+    pub fn matches(&self, version: &ComposerVersion) -> bool {
+        self.operator.matches(version, &self.version)
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.operator, self.version.normalized)
+    }
+}
+
+/// Add one to a decimal number.
+fn increment(number: &str) -> String {
+    let mut digits = number.as_bytes().to_vec();
+    for digit in digits.iter_mut().rev() {
+        if *digit == b'9' {
+            *digit = b'0';
+        } else {
+            *digit += 1;
+            return String::from_utf8(digits).unwrap();
+        }
+    }
+    digits.insert(0, b'1');
+    String::from_utf8(digits).unwrap()
+}
+
+/// Build a four-number version from the first numbers, keeping the number at
+/// `position` (starting at 1), optionally incremented, and zeroing the
+/// following ones.
+fn manipulate(numbers: &[&str], position: usize, add_one: bool) -> String {
+    let mut result = Vec::new();
+    for i in 0 .. 4 {
+        let number = numbers.get(i).cloned().unwrap_or("0");
+        result.push(if i + 1 > position {
+            "0".to_owned()
+        } else if i + 1 == position && add_one {
+            increment(number)
+        } else {
+            number.to_owned()
+        });
+    }
+    result.join(".")
+}
+
+/// Parse a version used in a range, allowing build metadata.
+fn parse_range_version<'a>(version: &'a str) -> Option<Classical<'a>> {
+    let version = match version.split_once('+') {
+        Some((version, metadata)) if !metadata.is_empty() && !metadata.contains(char::is_whitespace) => version,
+        _ => version,
+    };
+    Classical::parse(version)
+}
+
+/// Whether the version is only made of wildcards, e.g. `*` or `x.x`.
+fn is_wildcard(string: &str) -> bool {
+    string.split('.').all(|p| ["*", "x", "X"].contains(&p))
+}
+
+/// Parse one constraint from a list of constraints that must all match,
+/// returning its comparisons.
+fn parse_single(
+    constraint: &str,
+    flag: &mut Option<Stability>,
+    aliases: &mut Vec<(ComposerVersion, ComposerVersion)>,
+) -> Result<Vec<Comparison>, InvalidConstraint> {
+    let mut constraint = constraint;
+
+    // Strip an inline alias, `dev-master as 1.0.x-dev`
+    if let [actual, "as", alias] = constraint.split_whitespace().collect::<Vec<_>>()[..] {
+        aliases.push((ComposerVersion::parse(actual)?, ComposerVersion::parse(alias)?));
+        constraint = actual;
+    }
+
+    // Strip a stability flag, `@dev`
+    let mut modifier = None;
+    if let Some((rest, name)) = constraint.rsplit_once('@') {
+        if let Some(stability) = Stability::from_name(name) {
+            constraint = if rest.is_empty() { "*" } else { rest };
+            if stability != Stability::Stable {
+                modifier = Some(stability);
+            }
+            *flag = Some(flag.map_or(stability, |f| f.max(stability)));
+        }
+    }
+
+    // Strip a reference, `dev-master#abc123`
+    if let Some((rest, _)) = constraint.split_once('#') {
+        if rest.starts_with("dev-") || rest.to_ascii_lowercase().ends_with(".x-dev") {
+            constraint = rest;
+        }
+    }
+
+    if constraint.strip_prefix(['v', 'V']).is_some_and(is_wildcard) {
+        return Ok(vec![Comparison::new(Operator::GreaterEqual, "0.0.0.0-dev".to_owned())]);
+    }
+    if is_wildcard(constraint) {
+        return Ok(if constraint.contains('.') {
+            vec![Comparison::new(Operator::GreaterEqual, "0.0.0.0-dev".to_owned())]
+        } else {
+            Vec::new()
+        });
+    }
+
+    // Tilde and caret ranges
+    if constraint.starts_with("~>") {
+        return Err(InvalidConstraint::InvalidOperator);
+    }
+    for &tilde in &[true, false] {
+        let rest = match constraint.strip_prefix(if tilde { '~' } else { '^' }) {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let version = match parse_range_version(rest) {
+            Some(version) => version,
+            None => return Err(InvalidVersion::InvalidCharacter.into()),
+        };
+        let numbers = &version.numbers;
+        let mut low = rest.to_owned();
+        if version.modifier.word.is_none() && !version.modifier.dev {
+            low.push_str("-dev");
+        }
+        let position = if tilde {
+            1.max(numbers.len() - 1)
+        } else if numbers[0] != "0" || numbers.len() < 2 {
+            1
+        } else if numbers[1] != "0" || numbers.len() < 3 {
+            2
+        } else {
+            3
+        };
+        let low = ComposerVersion::parse(&low)?;
+        return Ok(vec![
+            Comparison { operator: Operator::GreaterEqual, version: low },
+            Comparison::new(Operator::Less, manipulate(numbers, position, true) + "-dev"),
+        ]);
+    }
+
+    // X-ranges, `1.2.*`
+    let parts: Vec<&str> = constraint.strip_prefix(['v', 'V']).unwrap_or(constraint).split('.').collect();
+    let count = parts.iter().take_while(|p| !p.is_empty() && p.bytes().all(|c| c.is_ascii_digit())).count();
+    if (1 ..= 3).contains(&count) && count < parts.len() && is_wildcard(&parts[count ..].join(".")) {
+        let low = manipulate(&parts, count, false) + "-dev";
+        let high = Comparison::new(Operator::Less, manipulate(&parts, count, true) + "-dev");
+        if low == "0.0.0.0-dev" {
+            return Ok(vec![high]);
+        }
+        return Ok(vec![Comparison::new(Operator::GreaterEqual, low), high]);
+    }
+
+    // Hyphen ranges, `1.0 - 2.0`
+    if let Some((from, to)) = constraint.split_once(" - ") {
+        let (from, to) = (from.trim_end(), to.trim_start());
+        if let (Some(low), Some(high)) = (parse_range_version(from), parse_range_version(to)) {
+            let mut lower = ComposerVersion::parse(from)?.normalized;
+            if low.modifier.word.is_none() && !low.modifier.dev {
+                lower.push_str("-dev");
+            }
+            let upper = ComposerVersion::parse(to)?;
+            let upper = if high.numbers.len() >= 3 || high.modifier.word.is_some() || high.modifier.dev {
+                Comparison { operator: Operator::LessEqual, version: upper }
+            } else {
+                let position = if high.numbers.len() < 2 { 1 } else { 2 };
+                Comparison::new(Operator::Less, manipulate(&high.numbers, position, true) + "-dev")
+            };
+            return Ok(vec![Comparison::new(Operator::GreaterEqual, lower), upper]);
+        }
+    }
+
+    // Comparisons, `>= 1.0`
+    let end = constraint.find(|c| !"<>=!".contains(c)).unwrap_or(constraint.len());
+    let operator = match &constraint[.. end] {
+        "" => Operator::Equal,
+        op => match OPERATORS.iter().find(|&&(o, _)| o == op) {
+            Some(&(_, operator)) => operator,
+            None => return Err(InvalidConstraint::InvalidOperator),
+        },
+    };
+    let raw = constraint[end ..].trim_start();
+    let mut version = match ComposerVersion::parse(raw) {
+        Ok(version) => version,
+        // Recover from `feature-dev`, meaning `dev-feature`
+        Err(error) => match raw.strip_suffix("-dev") {
+            Some(name) if raw.bytes().all(|c| c.is_ascii_alphanumeric() || b"-./".contains(&c)) => {
+                ComposerVersion::parse(&format!("dev-{}", name))?
+            }
+            _ => return Err(error.into()),
+        },
+    };
+    let modifier = modifier.filter(|_| operator != Operator::Equal && version.stability() == Stability::Stable);
+    if let Some(modifier) = modifier {
+        let normalized = format!("{}-{}", version.normalized, modifier);
+        version = ComposerVersion::from_normalized(normalized);
+    } else if operator == Operator::Less || operator == Operator::GreaterEqual {
+        let lower = raw.to_ascii_lowercase();
+        let has_modifier = lower.match_indices('-').any(|(i, _)| Modifier::parse(&lower[i + 1 ..]).is_some());
+        if !has_modifier && !raw.starts_with("dev-") {
+            version = ComposerVersion::from_normalized(version.normalized + "-dev");
+        }
+    }
+    Ok(vec![Comparison { operator, version }])
+}
+
+/// Split a list of constraints that must all match, on spaces or commas.
+///
+/// Operators stick to the following version, and `-` and `as` to both
+/// sides, so `>= 1.0 - 2.0, 3 as 4` has two parts.
+fn split_and(string: &str) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    for token in string.split([' ', '\t', '\n', ',']).filter(|t| !t.is_empty()) {
+        match parts.last_mut() {
+            Some(last) if last.ends_with(['<', '>', '=', '-'])
+                || last.ends_with("as")
+                || token.starts_with('-')
+                || token.starts_with("as") =>
+            {
+                last.push(' ');
+                last.push_str(token);
+            }
+            _ => parts.push(token.to_owned()),
+        }
+    }
+    parts
+}
+
+/// A version constraint, e.g. `^1.2 || >=2.0@beta`.
+///
+/// A version matches if it satisfies all the comparisons of one of the
+/// alternatives, and is stable enough (see `matches()`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraint {
+    string: String,
+    alternatives: Vec<Vec<Comparison>>,
+    stability_flag: Option<Stability>,
+    explicit: bool,
+    aliases: Vec<(ComposerVersion, ComposerVersion)>,
+}
+
+impl Constraint {
+    /// Parse a constraint, e.g. `~1.2`, `1.2.*`, `>=1.0 <2.0 || ^3`,
+    /// `1.0 - 2.0`, `dev-master as 1.0.x-dev` or `^2.0@beta`.
+    pub fn parse(string: &str) -> Result<Constraint, InvalidConstraint> {
+        let mut alternatives = Vec::new();
+        let mut explicit = None;
+        let mut aliases = Vec::new();
+        let mut parts = Vec::new();
+        for alternative in string.replace("||", "|").split('|') {
+            let and_parts = split_and(alternative);
+            if and_parts.is_empty() {
+                return Err(InvalidConstraint::Empty);
+            }
+            let mut comparisons = Vec::new();
+            for part in &and_parts {
+                comparisons.extend(parse_single(part, &mut explicit, &mut aliases)?);
+            }
+            alternatives.push(comparisons);
+            parts.extend(and_parts);
+        }
+
+        // Without an explicit flag, infer one from unstable versions
+        let inferred = parts
+            .iter()
+            .map(|p| p.split(" as ").next().unwrap())
+            .filter(|p| !p.contains([' ', ',', '@']))
+            .map(parse_stability)
+            .filter(|&s| s != Stability::Stable)
+            .max();
+
+        Ok(Constraint {
+            string: string.to_owned(),
+            alternatives,
+            stability_flag: explicit.or(inferred),
+            explicit: explicit.is_some(),
+            aliases,
+        })
+    }
+
+    /// The alternatives, each a list of comparisons that must all match.
+    ///
+    /// An empty list of comparisons, e.g. from `*`, matches everything.
+    pub fn alternatives(&self) -> &[Vec<Comparison>] {
+        &self.alternatives
+    }
+
+    /// The stability flag: the least stable one given explicitly, e.g.
+    /// `beta` for `^2.0@beta`, or else the least stable version used, e.g.
+    /// `RC` for `>=1.0-RC1`.
+    pub fn stability_flag(&self) -> Option<Stability> {
+        self.stability_flag
+    }
+
+    /// The inline aliases, e.g. `(dev-master, 1.0.x-dev)` for
+    /// `dev-master as 1.0.x-dev`.
+    pub fn aliases(&self) -> &[(ComposerVersion, ComposerVersion)] {
+        &self.aliases
+    }
+
+    /// Whether a version satisfies the constraint, regardless of its
+    /// stability.
+    pub fn contains(&self, version: &ComposerVersion) -> bool {
+        self.alternatives.iter().any(|a| a.iter().all(|c| c.matches(version)))
+    }
+
+    /// Whether a version satisfies the constraint and is stable enough.
+    ///
+    /// An explicit stability flag overrides the `minimum-stability`, which
+    /// an inferred one can only lower, as in Composer: `^1.0@RC` rejects
+    /// `1.0-beta1` even with a `dev` minimum-stability, while `>=1.0-beta1`
+    /// accepts it with a `stable` one.
+    pub fn matches(&self, version: &ComposerVersion, minimum_stability: Stability) -> bool {
+        let allowed = match self.stability_flag {
+            Some(flag) if self.explicit => flag,
+            Some(flag) => flag.max(minimum_stability),
+            None => minimum_stability,
+        };
+        version.stability() <= allowed && self.contains(version)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Constraint {
+    type Error = InvalidConstraint;
+
+    fn try_from(string: &'a str) -> Result<Constraint, InvalidConstraint> {
+        Constraint::parse(string)
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::super::{ComposerVersion, Stability};
+    use super::{Constraint, InvalidConstraint};
+
+    fn comparisons(constraint: &str) -> Vec<String> {
+        Constraint::parse(constraint).unwrap().alternatives().iter()
+            .map(|a| a.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", "))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        // From composer/semver's VersionParserTest
+        for &(constraint, expected) in &[
+            ("*", &[""][..]),
+            ("x.x", &[">= 0.0.0.0-dev"]),
+            ("v*", &[">= 0.0.0.0-dev"]),
+            ("1.0.0", &["== 1.0.0.0"]),
+            ("=1.0", &["== 1.0.0.0"]),
+            ("<>1.0", &["!= 1.0.0.0"]),
+            (">1.0", &["> 1.0.0.0"]),
+            ("<1.2.3.4", &["< 1.2.3.4-dev"]),
+            ("<=1.2.3", &["<= 1.2.3.0"]),
+            (">=1.2.3", &[">= 1.2.3.0-dev"]),
+            (">=1.2.3-beta", &[">= 1.2.3.0-beta"]),
+            ("<1.2.3-stable", &["< 1.2.3.0"]),
+            (">=1.0@beta", &[">= 1.0.0.0-beta"]),
+            ("dev-master", &["== dev-master"]),
+            ("dev-feature-a#abc123", &["== dev-feature-a"]),
+            ("feature-dev", &["== dev-feature"]),
+            ("1.0.x-dev", &["== 1.0.9999999.9999999-dev"]),
+            ("dev-master as 1.0.0", &["== dev-master"]),
+            ("@dev", &[""]),
+            ("~1.2", &[">= 1.2.0.0-dev, < 2.0.0.0-dev"]),
+            ("~1.2.3", &[">= 1.2.3.0-dev, < 1.3.0.0-dev"]),
+            ("~1", &[">= 1.0.0.0-dev, < 2.0.0.0-dev"]),
+            ("~1.2-beta", &[">= 1.2.0.0-beta, < 2.0.0.0-dev"]),
+            ("~1.2-dev", &[">= 1.2.0.0-dev, < 2.0.0.0-dev"]),
+            ("^1.2.3", &[">= 1.2.3.0-dev, < 2.0.0.0-dev"]),
+            ("^0.2.3", &[">= 0.2.3.0-dev, < 0.3.0.0-dev"]),
+            ("^0.0.3", &[">= 0.0.3.0-dev, < 0.0.4.0-dev"]),
+            ("^0.0", &[">= 0.0.0.0-dev, < 0.1.0.0-dev"]),
+            ("^0", &[">= 0.0.0.0-dev, < 1.0.0.0-dev"]),
+            ("^9.9", &[">= 9.9.0.0-dev, < 10.0.0.0-dev"]),
+            ("^1.2.3-beta.2", &[">= 1.2.3.0-beta2, < 2.0.0.0-dev"]),
+            ("1.2.*", &[">= 1.2.0.0-dev, < 1.3.0.0-dev"]),
+            ("v1.x", &[">= 1.0.0.0-dev, < 2.0.0.0-dev"]),
+            ("0.*", &["< 1.0.0.0-dev"]),
+            ("1 - 2", &[">= 1.0.0.0-dev, < 3.0.0.0-dev"]),
+            ("1.2.3 - 2.3.4.5", &[">= 1.2.3.0-dev, <= 2.3.4.5"]),
+            ("1.2-beta - 2.3", &[">= 1.2.0.0-beta, < 2.4.0.0-dev"]),
+            ("1.2 - 2.3-dev", &[">= 1.2.0.0-dev, <= 2.3.0.0-dev"]),
+            (">=1.0 <2.0", &[">= 1.0.0.0-dev, < 2.0.0.0-dev"]),
+            (">= 1.0, <2.0", &[">= 1.0.0.0-dev, < 2.0.0.0-dev"]),
+            ("^1.0 || ^2.0", &[">= 1.0.0.0-dev, < 2.0.0.0-dev", ">= 2.0.0.0-dev, < 3.0.0.0-dev"]),
+            ("1.0|2.0", &["== 1.0.0.0", "== 2.0.0.0"]),
+            ("^1.0 || *", &[">= 1.0.0.0-dev, < 2.0.0.0-dev", ""]),
+        ] {
+            assert_eq!(comparisons(constraint), expected, "{}", constraint);
+        }
+
+        let constraint = Constraint::parse("dev-master as 1.0.x-dev").unwrap();
+        assert_eq!(constraint.to_string(), "dev-master as 1.0.x-dev");
+        let (ref actual, ref alias) = constraint.aliases()[0];
+        assert_eq!(actual.normalized(), "dev-master");
+        assert_eq!(alias.normalized(), "1.0.9999999.9999999-dev");
+
+        for &(constraint, ref error) in &[
+            ("", InvalidConstraint::Empty),
+            ("^1 ||", InvalidConstraint::Empty),
+            ("~>1.2", InvalidConstraint::InvalidOperator),
+            ("=>1.2", InvalidConstraint::InvalidOperator),
+            ("1.0.0.0.0", InvalidConstraint::InvalidVersion(InvalidVersion::InvalidCharacter)),
+            ("^a", InvalidConstraint::InvalidVersion(InvalidVersion::InvalidCharacter)),
+            (">= foo", InvalidConstraint::InvalidVersion(InvalidVersion::InvalidCharacter)),
+            ("1.0@gamma", InvalidConstraint::InvalidVersion(InvalidVersion::InvalidCharacter)),
+            ("1.0é", InvalidConstraint::InvalidVersion(InvalidVersion::InvalidCharacter)),
+            (">=dév", InvalidConstraint::InvalidVersion(InvalidVersion::InvalidCharacter)),
+            ("^1.0é", InvalidConstraint::InvalidVersion(InvalidVersion::InvalidCharacter)),
+        ] {
+            assert_eq!(&Constraint::parse(constraint).unwrap_err(), error, "{}", constraint);
+        }
+    }
+
+    fn check(constraint: &str, minimum: Stability, matching: &[&str], not_matching: &[&str]) {
+        let parsed = Constraint::parse(constraint).unwrap();
+        for &version in matching {
+            let version = ComposerVersion::parse(version).unwrap();
+            assert!(parsed.matches(&version, minimum), "{} {}", constraint, version);
+        }
+        for &version in not_matching {
+            let version = ComposerVersion::parse(version).unwrap();
+            assert!(!parsed.matches(&version, minimum), "{} {}", constraint, version);
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let (stable, dev) = (Stability::Stable, Stability::Dev);
+        check("^1.2", stable, &["1.2", "1.9.9", "1.2.0.1"], &["1.1", "2.0", "1.3-beta", "1.x-dev"]);
+        check("^1.2", dev, &["1.3-beta", "1.x-dev", "1.2.x-dev"], &["2.0-dev", "1.1.x-dev", "dev-master"]);
+        check("~1.2", stable, &["1.2", "1.9"], &["2.0", "1.1"]);
+        check("~1.2.3", stable, &["1.2.3", "1.2.9"], &["1.3", "1.2.2"]);
+        check("1.2.*", stable, &["1.2.0", "1.2.99"], &["1.3", "1.1.9"]);
+        check(">=1.0 <2.0", stable, &["1.0", "1.5"], &["2.0", "0.9"]);
+        check("^1.0 || ^3.0", stable, &["1.5", "3.1"], &["2.0", "4.0"]);
+        check("1.0 - 2.0", stable, &["1.0", "2.0.5"], &["2.1", "0.9"]);
+        check("1.0.0 - 2.0.0", stable, &["1.0", "2.0.0"], &["2.0.1"]);
+        check("!=1.0", stable, &["1.1", "0.9"], &["1.0.0.0"]);
+        check("*", stable, &["0.1", "99"], &["1.0-RC1", "dev-master"]);
+        check("dev-master", stable, &["dev-master"], &["dev-main", "1.0"]);
+        check("dev-master as 1.0.0", stable, &["dev-master"], &["1.0"]);
+        check("!=dev-master", dev, &["dev-main", "1.0"], &["dev-master"]);
+        check(">dev-master", dev, &[], &["dev-main", "1.0"]);
+
+        // Stability flags
+        check("^2.0@beta", stable, &["2.0-beta1", "2.1-RC1", "2.0"], &["2.0-alpha1", "2.0.x-dev"]);
+        check("^2.0@RC", dev, &["2.0-RC1"], &["2.0-beta1", "2.0.x-dev"]);
+        check("^2.0@stable", dev, &["2.0"], &["2.1-RC1"]);
+        check("@dev", stable, &["1.0-dev", "dev-master"], &[]);
+        check("dev-master@dev", stable, &["dev-master"], &[]);
+        check(">=1.0@dev <2.0", stable, &["1.0-alpha", "1.1.x-dev"], &["2.0-alpha"]);
+        check("1.0.x-dev", stable, &["1.0.x-dev"], &["1.0"]);
+        check(">=1.0-RC1", stable, &["1.0-RC1", "1.0"], &["1.0-beta1", "1.1-beta1"]);
+        check(">=1.0-RC1", Stability::Alpha, &["1.1-beta1"], &["1.1-dev"]);
+        check("^1.0", Stability::Beta, &["1.1-beta1", "1.1-RC1"], &["1.1-alpha1"]);
+    }
+
+    #[test]
+    fn test_stability_flag() {
+        for &(constraint, flag) in &[
+            ("^1.0", None),
+            ("^1.0@beta", Some(Stability::Beta)),
+            ("^1.0@beta || ^2.0@alpha", Some(Stability::Alpha)),
+            ("^1.0@stable", Some(Stability::Stable)),
+            ("1.0-RC1", Some(Stability::Rc)),
+            (">=1.0-beta1", Some(Stability::Beta)),
+            (">= 1.0-beta1", None),
+            ("dev-master as 1.0.0", Some(Stability::Dev)),
+            ("1.0.x-dev || 1.0-beta", Some(Stability::Dev)),
+        ] {
+            assert_eq!(Constraint::parse(constraint).unwrap().stability_flag(), flag, "{}", constraint);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! Implement conda's versioning scheme, as implemented by its `VersionOrder`
+//! class.
+//!
+//! A version is an optional epoch followed by `!`, dot-separated components,
+//! and an optional local version after `+`. Underscores separate components
+//! like dots, and dashes do too when the version contains no underscore.
+//! Comparisons are case-insensitive.
+//!
+//! Each component is split into runs of digits and letters, and a component
+//! starting with letters gets a `0` prepended so numbers and strings stay in
+//! phase. Numbers compare greater than strings, and strings compare
+//! lexicographically, except for the special strings: `dev` sorts before
+//! every other string, `_` (only allowed at the very end, as in openssl's
+//! `1.1.1_`) before letters, and `post` after every number.
+//!
+//! Missing components are considered to be `0`, so `1.1 == 1.1.0`.
+//!
+//! Package match specifications (`numpy >=1.20,<2|1.19.5`) can be parsed
+//! and evaluated against packages.
+
+mod match_spec;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+
+pub use self::match_spec::{InvalidMatchSpec, MatchSpec, VersionSpec};
+
+/// A run of digits or letters in a version component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    /// Letters, with `dev` upper-cased so it sorts before `_` and other
+    /// letters.
+    String(String),
+    /// Digits, without leading zeros.
+    Number(String),
+    /// `post`, greater than any number.
+    Post,
+}
+
+impl Part {
+    fn zero() -> Part {
+        Part::Number(String::new())
+    }
+
+    fn rank(&self) -> u8 {
+        match *self {
+            Part::String(_) => 0,
+            Part::Number(_) => 1,
+            Part::Post => 2,
+        }
+    }
+}
+
+impl PartialOrd for Part {
+    fn partial_cmp(&self, other: &Part) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Part {
+    fn cmp(&self, other: &Part) -> Ordering {
+        match (self, other) {
+            (Part::String(a), Part::String(b)) => a.cmp(b),
+            (Part::Number(a), Part::Number(b)) => {
+                a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// Compare components, padding the shorter one with zeros.
+fn compare_component(a: &[Part], b: &[Part]) -> Ordering {
+    let zero = Part::zero();
+    for i in 0 .. a.len().max(b.len()) {
+        let ordering = a.get(i).unwrap_or(&zero).cmp(b.get(i).unwrap_or(&zero));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Compare lists of components, padding the shorter one with zeros.
+fn compare_components(a: &[Vec<Part>], b: &[Vec<Part>]) -> Ordering {
+    for i in 0 .. a.len().max(b.len()) {
+        let (x, y) = (a.get(i).map_or(&[][..], |c| c), b.get(i).map_or(&[][..], |c| c));
+        let ordering = compare_component(x, y);
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn parse_component(component: &str) -> Result<Vec<Part>, InvalidVersion> {
+    if component.is_empty() {
+        return Err(InvalidVersion::EmptyField);
+    }
+    let mut parts = Vec::new();
+    // Keep numbers and strings in phase
+    if !component.starts_with(|c: char| c.is_ascii_digit()) {
+        parts.push(Part::zero());
+    }
+    let mut rest = component;
+    while !rest.is_empty() {
+        let is_digit = rest.starts_with(|c: char| c.is_ascii_digit());
+        let end = rest.find(|c: char| c.is_ascii_digit() != is_digit).unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        parts.push(match run {
+            _ if is_digit => Part::Number(run.trim_start_matches('0').to_owned()),
+            "post" => Part::Post,
+            "dev" => Part::String("DEV".to_owned()),
+            _ => Part::String(run.to_owned()),
+        });
+        rest = tail;
+    }
+    Ok(parts)
+}
+
+/// A conda package version, e.g. `1!1.2.0rc1+cuda.11`.
+#[derive(Clone, Debug)]
+pub struct CondaVersion {
+    string: String,
+    epoch: u64,
+    /// The components, starting with the epoch.
+    version: Vec<Vec<Part>>,
+    local: Vec<Vec<Part>>,
+}
+
+impl CondaVersion {
+    /// Parse a version, e.g. `1!1.2.0rc1+cuda.11`.
+    pub fn parse(string: &str) -> Result<CondaVersion, InvalidVersion> {
+        let string = string.trim();
+        if string.is_empty() {
+            return Err(InvalidVersion::EmptyField);
+        }
+        let is_valid = |s: &str| {
+            s.bytes().all(|c| c.is_ascii_alphanumeric() || b".+!_".contains(&c))
+        };
+        let mut normalized = string.to_ascii_lowercase();
+        if !is_valid(&normalized) && normalized.contains('-') && !normalized.contains('_') {
+            normalized = normalized.replace('-', "_");
+        }
+        if !is_valid(&normalized) {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+
+        let (epoch, rest) = match normalized.split_once('!') {
+            Some((epoch, rest)) => {
+                if epoch.is_empty() || !epoch.bytes().all(|c| c.is_ascii_digit()) {
+                    return Err(InvalidVersion::InvalidCharacter);
+                }
+                (epoch, rest)
+            }
+            None => ("0", &normalized[..]),
+        };
+        let (main, local) = match rest.split_once('+') {
+            Some((main, local)) => (main, Some(local)),
+            None => (rest, None),
+        };
+        if rest.contains('!') || local.is_some_and(|l| l.contains('+')) {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+
+        let mut version = vec![parse_component(epoch)?];
+        // A trailing underscore stays attached to the last component
+        let (main, underscore) = match main.strip_suffix('_') {
+            Some(main) => (main, "_"),
+            None => (main, ""),
+        };
+        let components: Vec<&str> = main.split(['.', '_']).collect();
+        for (i, component) in components.iter().enumerate() {
+            if i + 1 == components.len() && !underscore.is_empty() {
+                version.push(parse_component(&format!("{}{}", component, underscore))?);
+            } else {
+                version.push(parse_component(component)?);
+            }
+        }
+        let local = match local {
+            Some(local) => local.split(['.', '_']).map(parse_component).collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(CondaVersion {
+            string: string.to_owned(),
+            epoch: epoch.parse().map_err(|_| InvalidVersion::NumberTooLarge)?,
+            version,
+            local,
+        })
+    }
+
+    /// The epoch, `0` if not specified.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// The local version, after `+`.
+    pub fn local(&self) -> Option<&str> {
+        self.string.split_once('+').map(|(_, local)| local)
+    }
+
+    /// Whether this version starts with `prefix`, as in the `1.2.*` match
+    /// specification.
+    ///
+    /// All the components of `prefix` but the last must be equal; the last
+    /// one must be equal too, except that its trailing string may be a
+    /// prefix, e.g. `1.2.0rc*` matches `1.2.0rc1` but `1.2*` doesn't match
+    /// `1.20`.
+    pub fn starts_with(&self, prefix: &CondaVersion) -> bool {
+        let (ours, theirs) = if prefix.local.is_empty() {
+            (&self.version, &prefix.version)
+        } else {
+            if compare_components(&self.version, &prefix.version) != Ordering::Equal {
+                return false;
+            }
+            (&self.local, &prefix.local)
+        };
+
+        let n = theirs.len() - 1;
+        if compare_components(&ours[.. n.min(ours.len())], &theirs[.. n]) != Ordering::Equal {
+            return false;
+        }
+        let (ours, theirs) = (ours.get(n).map_or(&[][..], |c| c), &theirs[n]);
+        let m = theirs.len() - 1;
+        if compare_component(&ours[.. m.min(ours.len())], &theirs[.. m]) != Ordering::Equal {
+            return false;
+        }
+        // Missing parts are filled with zeros
+        let zero = Part::zero();
+        match (ours.get(m).unwrap_or(&zero), &theirs[m]) {
+            (Part::String(ours), Part::String(theirs)) => ours.starts_with(theirs.as_str()),
+            (ours, theirs) => ours == theirs,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for CondaVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<CondaVersion, InvalidVersion> {
+        CondaVersion::parse(string)
+    }
+}
+
+impl fmt::Display for CondaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+impl PartialEq<CondaVersion> for CondaVersion {
+    fn eq(&self, other: &CondaVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CondaVersion {}
+
+impl PartialOrd<CondaVersion> for CondaVersion {
+    fn partial_cmp(&self, other: &CondaVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CondaVersion {
+    fn cmp(&self, other: &CondaVersion) -> Ordering {
+        compare_components(&self.version, &other.version)
+            .then_with(|| compare_components(&self.local, &other.local))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::CondaVersion;
+
+    fn parse(s: &str) -> CondaVersion {
+        CondaVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let version = parse(" 1!1.2.0RC1+Cuda.11 ");
+        assert_eq!(version.epoch(), 1);
+        assert_eq!(version.local(), Some("Cuda.11"));
+        assert_eq!(version.to_string(), "1!1.2.0RC1+Cuda.11");
+        assert_eq!(parse("1.2").epoch(), 0);
+        assert_eq!(parse("1.2").local(), None);
+
+        for &(invalid, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            ("1..2", InvalidVersion::EmptyField),
+            ("1.2.", InvalidVersion::EmptyField),
+            ("1.2+", InvalidVersion::EmptyField),
+            ("1!", InvalidVersion::EmptyField),
+            ("1.2*", InvalidVersion::InvalidCharacter),
+            ("1.2 3", InvalidVersion::InvalidCharacter),
+            ("1-2_3", InvalidVersion::InvalidCharacter),
+            ("a!1.2", InvalidVersion::InvalidCharacter),
+            ("1!2!3", InvalidVersion::InvalidCharacter),
+            ("1+2+3", InvalidVersion::InvalidCharacter),
+            ("99999999999999999999!1", InvalidVersion::NumberTooLarge),
+        ] {
+            assert_eq!(&CondaVersion::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        // From the VersionOrder documentation
+        let versions = [
+            &["0.4", "0.4.0"][..],
+            &["0.4.1.rc", "0.4.1.RC"],
+            &["0.4.1"],
+            &["0.5a1"],
+            &["0.5b3"],
+            &["0.5C1"],
+            &["0.5"],
+            &["0.9.6"],
+            &["0.960923"],
+            &["1.0"],
+            &["1.1dev1"],
+            &["1.1_"],
+            &["1.1a1"],
+            &["1.1.0dev1", "1.1.dev1"],
+            &["1.1.a1"],
+            &["1.1.0rc1"],
+            &["1.1.0", "1.1"],
+            &["1.1.0post1", "1.1.post1"],
+            &["1.1post1"],
+            &["1996.07.12"],
+            &["1!0.4.1"],
+            &["1!3.1.1.6"],
+            &["2!0.4.1"],
+        ];
+        for (i, equal) in versions.iter().enumerate() {
+            for &a in *equal {
+                for &b in *equal {
+                    assert_eq!(parse(a), parse(b), "{} == {}", a, b);
+                }
+                for &b in versions[i + 1 ..].iter().flat_map(|v| v.iter()) {
+                    assert!(parse(a) < parse(b), "{} < {}", a, b);
+                }
+            }
+        }
+
+        for &(a, b) in &[
+            ("1.0-1", "1.0_1"),
+            ("1.0-1", "1.0.1"),
+            ("01.2", "1.2"),
+            ("1.2+abc", "1.2+ABC"),
+        ] {
+            assert_eq!(parse(a), parse(b), "{} == {}", a, b);
+        }
+        assert!(parse("1.2") < parse("1.2+1"));
+        assert!(parse("1.2+1") < parse("1.2+2"));
+        assert!(parse("1.2+9") < parse("1.2.1"));
+    }
+
+    #[test]
+    fn test_starts_with() {
+        for &(version, prefix, expected) in &[
+            ("1.2", "1.2", true),
+            ("1.2.3", "1.2", true),
+            ("1.2a", "1.2", true),
+            ("1.2.0rc1", "1.2.0rc", true),
+            ("1.2.0rc1", "1.2.0r", true),
+            ("1.20", "1.2", false),
+            ("1", "1.0", true),
+            ("1.0", "1.0.0", true),
+            ("1", "1.1", false),
+            ("1", "1.0a", false),
+            ("1!1.2", "1.2", false),
+            ("1.2+cuda.11", "1.2+cuda", true),
+            ("1.2.1+cuda.11", "1.2+cuda", false),
+        ] {
+            assert_eq!(parse(version).starts_with(&parse(prefix)), expected, "{} {}", version, prefix);
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! Package match specifications, e.g. `numpy >=1.20,<2|1.19.5 py39*`.
+//!
+//! This supports the common subset of conda's syntax: a name, an optional
+//! version specification and an optional build string, separated by spaces
+//! (`numpy 1.2.* py39*`) or by `=` (`numpy=1.2=py39_0`). Channels, brackets
+//! and parentheses are not supported.
+//!
+//! In version specifications, `|` has a lower precedence than `,`. A bare
+//! version (`1.2`) or `==1.2` is an exact match, while `1.2.*`, `1.2*` and
+//! `=1.2` match versions starting with `1.2` (see
+//! [`CondaVersion::starts_with`]). Build strings may contain `*` wildcards.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+use super::CondaVersion;
+
+/// Error for the match specification parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidMatchSpec {
+    /// The specification is empty.
+    Empty,
+    /// The package name contains invalid characters.
+    InvalidName,
+    /// A version constraint is empty, or can't be used with its operator.
+    InvalidConstraint,
+    /// A version is invalid.
+    InvalidVersion(InvalidVersion),
+    /// The build string contains invalid characters.
+    InvalidBuild,
+}
+
+impl From<InvalidVersion> for InvalidMatchSpec {
+    fn from(error: InvalidVersion) -> InvalidMatchSpec {
+        InvalidMatchSpec::InvalidVersion(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    LessEqual,
+    GreaterEqual,
+    Less,
+    Greater,
+    Compatible,
+    StartsWith,
+}
+
+const OPERATORS: &[(&str, Operator)] = &[
+    ("==", Operator::Equal),
+    ("!=", Operator::NotEqual),
+    ("<=", Operator::LessEqual),
+    (">=", Operator::GreaterEqual),
+    ("~=", Operator::Compatible),
+    ("<", Operator::Less),
+    (">", Operator::Greater),
+    ("=", Operator::StartsWith),
+];
+
+/// A single constraint of a version specification.
+#[derive(Clone, Debug)]
+enum Constraint {
+    Any,
+    Compare(Operator, CondaVersion),
+    StartsWith(CondaVersion),
+    NotStartsWith(CondaVersion),
+    /// `~=`, with the version and the prefix it must start with.
+    Compatible(CondaVersion, CondaVersion),
+}
+
+impl Constraint {
+    fn parse(string: &str) -> Result<Constraint, InvalidMatchSpec> {
+        if string == "*" {
+            return Ok(Constraint::Any);
+        }
+        let (operator, rest) = match OPERATORS.iter().find(|&&(op, _)| string.starts_with(op)) {
+            Some(&(op, operator)) => (Some(operator), &string[op.len() ..]),
+            None => (None, string),
+        };
+        let (rest, wildcard) = match rest.strip_suffix('*') {
+            Some(rest) => (rest.strip_suffix('.').unwrap_or(rest), true),
+            None => (rest, false),
+        };
+        if rest.is_empty() {
+            return Err(InvalidMatchSpec::InvalidConstraint);
+        }
+        let version = CondaVersion::parse(rest)?;
+
+        Ok(match (operator, wildcard) {
+            (None, false) => Constraint::Compare(Operator::Equal, version),
+            (None, true) | (Some(Operator::Equal), true) | (Some(Operator::StartsWith), _) => {
+                Constraint::StartsWith(version)
+            }
+            (Some(Operator::NotEqual), true) => Constraint::NotStartsWith(version),
+            (Some(Operator::Compatible), true) => return Err(InvalidMatchSpec::InvalidConstraint),
+            (Some(Operator::Compatible), false) => {
+                let (prefix, _) = rest.rsplit_once('.').ok_or(InvalidMatchSpec::InvalidConstraint)?;
+                Constraint::Compatible(version, CondaVersion::parse(prefix)?)
+            }
+            // The wildcard is superfluous with other operators
+            (Some(operator), _) => Constraint::Compare(operator, version),
+        })
+    }
+
+    fn contains(&self, version: &CondaVersion) -> bool {
+        match *self {
+            Constraint::Any => true,
+            Constraint::Compare(operator, ref operand) => match operator {
+                Operator::Equal => version == operand,
+                Operator::NotEqual => version != operand,
+                Operator::LessEqual => version <= operand,
+                Operator::GreaterEqual => version >= operand,
+                Operator::Less => version < operand,
+                Operator::Greater => version > operand,
+                Operator::Compatible | Operator::StartsWith => unreachable!(),
+            },
+            Constraint::StartsWith(ref prefix) => version.starts_with(prefix),
+            Constraint::NotStartsWith(ref prefix) => !version.starts_with(prefix),
+            Constraint::Compatible(ref operand, ref prefix) => {
+                version >= operand && version.starts_with(prefix)
+            }
+        }
+    }
+}
+
+/// A version specification, e.g. `>=1.20,<2|1.19.5`.
+#[derive(Clone, Debug)]
+pub struct VersionSpec {
+    string: String,
+    /// Alternatives separated by `|`, of constraints separated by `,`.
+    alternatives: Vec<Vec<Constraint>>,
+}
+
+impl VersionSpec {
+    /// Parse a version specification, e.g. `>=1.20,<2|1.19.5`.
+    ///
+    /// Whitespace is ignored.
+    pub fn parse(string: &str) -> Result<VersionSpec, InvalidMatchSpec> {
+        let string: String = string.split_whitespace().collect();
+        let alternatives = string.split('|')
+            .map(|all| all.split(',').map(Constraint::parse).collect())
+            .collect::<Result<_, _>>()?;
+        Ok(VersionSpec { string, alternatives })
+    }
+
+    /// Whether the version satisfies this specification.
+    pub fn contains(&self, version: &CondaVersion) -> bool {
+        self.alternatives.iter().any(|all| all.iter().all(|c| c.contains(version)))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for VersionSpec {
+    type Error = InvalidMatchSpec;
+
+    fn try_from(string: &'a str) -> Result<VersionSpec, InvalidMatchSpec> {
+        VersionSpec::parse(string)
+    }
+}
+
+impl fmt::Display for VersionSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+/// A package match specification, e.g. `numpy >=1.20,<2|1.19.5 py39*`.
+#[derive(Clone, Debug)]
+pub struct MatchSpec {
+    name: String,
+    version: Option<VersionSpec>,
+    build: Option<String>,
+}
+
+const OPERATOR_CHARS: &[char] = &['=', '!', '<', '>', '~', '|', ','];
+
+/// Split the build string, which follows a space or `=` and contains no
+/// operators, from the version specification.
+fn split_build(rest: &str) -> (&str, Option<&str>) {
+    if let Some(separator) = rest.rfind(|c: char| c.is_whitespace() || c == '=') {
+        let (version, build) = (rest[.. separator].trim_end(), &rest[separator + 1 ..]);
+        if !build.is_empty()
+            && !build.contains(OPERATOR_CHARS)
+            && version.ends_with(|c: char| !OPERATOR_CHARS.contains(&c))
+        {
+            return (version, Some(build));
+        }
+    }
+    (rest, None)
+}
+
+/// Match a string against a pattern where `*` matches any characters.
+fn glob_match(pattern: &str, string: &str) -> bool {
+    let mut pieces = pattern.split('*');
+    // There is always a first piece, which must be a prefix
+    let first = pieces.next().unwrap_or_default();
+    let mut rest = match string.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let pieces: Vec<&str> = pieces.collect();
+    let last = match pieces.split_last() {
+        Some((last, middle)) => {
+            for piece in middle {
+                match rest.find(piece) {
+                    Some(i) => rest = &rest[i + piece.len() ..],
+                    None => return false,
+                }
+            }
+            last
+        }
+        None => return rest.is_empty(),
+    };
+    rest.ends_with(last)
+}
+
+impl MatchSpec {
+    /// Parse a match specification, e.g. `numpy >=1.20,<2|1.19.5`,
+    /// `numpy 1.2.* py39*` or `numpy=1.2=py39_0`.
+    pub fn parse(string: &str) -> Result<MatchSpec, InvalidMatchSpec> {
+        let string = string.trim();
+        if string.is_empty() {
+            return Err(InvalidMatchSpec::Empty);
+        }
+        let end = string.find(|c: char| c.is_whitespace() || OPERATOR_CHARS.contains(&c))
+            .unwrap_or(string.len());
+        let (name, rest) = string.split_at(end);
+        let valid_name = name.bytes().all(|c| c.is_ascii_alphanumeric() || b"-_.".contains(&c));
+        if name.is_empty() || !valid_name {
+            return Err(InvalidMatchSpec::InvalidName);
+        }
+
+        let (version, build) = split_build(rest.trim());
+        let mut version: String = version.split_whitespace().collect();
+        // `=1.2` means `1.2*`, but `=1.2=build` and `==1.2` are exact
+        if let Some(single) = version.strip_prefix('=') {
+            if version.starts_with("==") && build.is_none() {
+                version = version[2 ..].to_owned();
+            } else if !single.contains(['=', ',', '|']) {
+                version = match build {
+                    None if !single.ends_with('*') => format!("{}*", single),
+                    _ => single.to_owned(),
+                };
+            }
+        }
+        let version = match &version[..] {
+            "" => None,
+            version => Some(VersionSpec::parse(version)?),
+        };
+
+        let build = match build {
+            Some(build) => {
+                let valid = build.bytes().all(|c| c.is_ascii_alphanumeric() || b"_.+!*".contains(&c));
+                if !valid {
+                    return Err(InvalidMatchSpec::InvalidBuild);
+                }
+                Some(build.to_owned())
+            }
+            None => None,
+        };
+
+        Ok(MatchSpec { name: name.to_owned(), version, build })
+    }
+
+    /// The package name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The version specification, if any.
+    pub fn version(&self) -> Option<&VersionSpec> {
+        self.version.as_ref()
+    }
+
+    /// The build string pattern, if any.
+    pub fn build(&self) -> Option<&str> {
+        self.build.as_deref()
+    }
+
+    /// Whether the version satisfies the version specification, if any.
+    pub fn matches_version(&self, version: &CondaVersion) -> bool {
+        self.version.as_ref().is_none_or(|spec| spec.contains(version))
+    }
+
+    /// Whether the build string matches the pattern, if any.
+    pub fn matches_build(&self, build: &str) -> bool {
+        self.build.as_ref().is_none_or(|pattern| glob_match(pattern, build))
+    }
+
+    /// Whether the package with this name, version and build string matches.
+    pub fn matches(&self, name: &str, version: &CondaVersion, build: &str) -> bool {
+        self.name == name && self.matches_version(version) && self.matches_build(build)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for MatchSpec {
+    type Error = InvalidMatchSpec;
+
+    fn try_from(string: &'a str) -> Result<MatchSpec, InvalidMatchSpec> {
+        MatchSpec::parse(string)
+    }
+}
+
+impl fmt::Display for MatchSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        match (&self.version, &self.build) {
+            (Some(version), Some(build)) => write!(f, " {} {}", version, build),
+            (Some(version), None) => write!(f, " {}", version),
+            (None, Some(build)) => write!(f, " * {}", build),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::super::CondaVersion;
+    use super::{glob_match, InvalidMatchSpec, MatchSpec, VersionSpec};
+
+    fn version(s: &str) -> CondaVersion {
+        CondaVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_version_spec() {
+        for &(spec, matching, not_matching) in &[
+            (">=1.20,<2|1.19.5", &["1.20", "1.26.4", "1.19.5", "2.0.0rc1"][..], &["1.19", "1.19.6", "2.0"][..]),
+            ("1.2", &["1.2", "1.2.0"], &["1.2.1", "1.20"]),
+            ("==1.2", &["1.2", "1.2.0"], &["1.2.1"]),
+            ("1.2.*", &["1.2", "1.2.3", "1.2a"], &["1.20", "1.3", "1"]),
+            ("1.0.*", &["1", "1.0", "1.0.3"], &["1.1", "0.9"]),
+            ("==1.0.*", &["1", "1.0.3"], &["1.1"]),
+            ("1.2*", &["1.2", "1.2.3"], &["1.20"]),
+            ("=1.2", &["1.2", "1.2.3"], &["1.20"]),
+            ("!=1.2.*", &["1.3", "1.20"], &["1.2", "1.2.3"]),
+            ("!=1.2", &["1.2.1"], &["1.2"]),
+            ("~=1.2.3", &["1.2.3", "1.2.10"], &["1.2.2", "1.3"]),
+            (">=1.2.*", &["1.2", "1.3"], &["1.1"]),
+            ("> 1.0 , < 1.1", &["1.0.1"], &["1.0", "1.1"]),
+            ("*", &["0", "1!2"], &[]),
+        ] {
+            let spec = VersionSpec::parse(spec).unwrap();
+            for &v in matching {
+                assert!(spec.contains(&version(v)), "{} contains {}", spec, v);
+            }
+            for &v in not_matching {
+                assert!(!spec.contains(&version(v)), "{} doesn't contain {}", spec, v);
+            }
+        }
+        assert_eq!(VersionSpec::parse("> 1.0 , < 1.1").unwrap().to_string(), ">1.0,<1.1");
+
+        for &(invalid, ref error) in &[
+            ("", InvalidMatchSpec::InvalidConstraint),
+            (">=1,", InvalidMatchSpec::InvalidConstraint),
+            ("1|", InvalidMatchSpec::InvalidConstraint),
+            ("~=1", InvalidMatchSpec::InvalidConstraint),
+            ("~=1.*", InvalidMatchSpec::InvalidConstraint),
+            (">=", InvalidMatchSpec::InvalidConstraint),
+            ("1.*.2", InvalidMatchSpec::InvalidVersion(InvalidVersion::InvalidCharacter)),
+            ("(1.2)", InvalidMatchSpec::InvalidVersion(InvalidVersion::InvalidCharacter)),
+        ] {
+            assert_eq!(&VersionSpec::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        for &(spec, name, version, build) in &[
+            ("numpy", "numpy", None, None),
+            ("numpy >=1.20,<2|1.19.5", "numpy", Some(">=1.20,<2|1.19.5"), None),
+            ("numpy >=1.20, <2", "numpy", Some(">=1.20,<2"), None),
+            ("numpy>=1.20", "numpy", Some(">=1.20"), None),
+            ("numpy 1.2.* py39*", "numpy", Some("1.2.*"), Some("py39*")),
+            ("numpy=1.2", "numpy", Some("1.2*"), None),
+            ("numpy =1.2", "numpy", Some("1.2*"), None),
+            ("numpy==1.2", "numpy", Some("1.2"), None),
+            ("numpy=1.2=py39_0", "numpy", Some("1.2"), Some("py39_0")),
+            ("numpy==1.2=py39_0", "numpy", Some("==1.2"), Some("py39_0")),
+            ("numpy * *_cpython", "numpy", Some("*"), Some("*_cpython")),
+            ("python_abi 3.11.* *_cp311", "python_abi", Some("3.11.*"), Some("*_cp311")),
+        ] {
+            let parsed = MatchSpec::parse(spec).unwrap();
+            assert_eq!(parsed.name(), name, "{}", spec);
+            assert_eq!(parsed.version().map(|v| v.to_string()).as_deref(), version, "{}", spec);
+            assert_eq!(parsed.build(), build, "{}", spec);
+        }
+        assert_eq!(
+            MatchSpec::parse("numpy=1.2=py39_0").unwrap().to_string(),
+            "numpy 1.2 py39_0",
+        );
+
+        for &(invalid, ref error) in &[
+            ("", InvalidMatchSpec::Empty),
+            (">=1.2", InvalidMatchSpec::InvalidName),
+            ("conda-forge::numpy", InvalidMatchSpec::InvalidName),
+            ("numpy[version='>=1.2']", InvalidMatchSpec::InvalidName),
+            ("numpy 1.2 py/39", InvalidMatchSpec::InvalidBuild),
+            ("numpy >=1.2 <2", InvalidMatchSpec::InvalidVersion(InvalidVersion::InvalidCharacter)),
+        ] {
+            assert_eq!(&MatchSpec::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let spec = MatchSpec::parse("numpy >=1.20,<2|1.19.5 py3*_0").unwrap();
+        assert!(spec.matches("numpy", &version("1.26.4"), "py311_0"));
+        assert!(spec.matches("numpy", &version("1.19.5"), "py39_0"));
+        assert!(!spec.matches("numpy", &version("1.19.4"), "py39_0"));
+        assert!(!spec.matches("numpy", &version("1.26.4"), "py311_1"));
+        assert!(!spec.matches("scipy", &version("1.26.4"), "py311_0"));
+
+        let spec = MatchSpec::parse("numpy").unwrap();
+        assert!(spec.matches("numpy", &version("0.1"), "anything"));
+
+        for &(pattern, string, expected) in &[
+            ("py39_0", "py39_0", true),
+            ("py39_0", "py39_1", false),
+            ("*", "", true),
+            ("py*", "py39_0", true),
+            ("*_0", "py39_0", true),
+            ("*39*", "py39_0", true),
+            ("py*_*0", "py39_0", true),
+            ("py*_*1", "py39_0", false),
+            ("py39*0", "py390", true),
+            ("py39*90", "py390", false),
+        ] {
+            assert_eq!(glob_match(pattern, string), expected, "{} {}", pattern, string);
+        }
+    }
+}
@@ -2,7 +2,13 @@
 //!
 //! This package uses Debian's versioning rules by default, so this is a thin
 //! wrapper around `Version`, but it adds some Debian-specific accessors.
+//!
+//! Version constraints, as used in package relationships (`Depends: foo (>=
+//! 1.0) | bar, foo (<< 2.0)`), can be built to express other ecosystems'
+//! requirements.
 
+use std::cmp::Ordering;
+use std::fmt;
 use std::ops::Deref;
 
 use crate::Version;
@@ -27,7 +33,7 @@ impl DebianVersion {
     pub fn upstream_version(&self) -> &str {
         match self.rfind('-') {
             Some(hyphen) => &self[0..hyphen],
-            None => &self
+            None => self
         }
     }
 
@@ -39,3 +45,147 @@ impl DebianVersion {
         }
     }
 }
+
+/// A relation operator, as used in package relationships.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `<<`, strictly earlier.
+    Less,
+    /// `<=`
+    LessEqual,
+    /// `=`
+    Equal,
+    /// `>=`
+    GreaterEqual,
+    /// `>>`, strictly later.
+    Greater,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match *self {
+            Operator::Less => "<<",
+            Operator::LessEqual => "<=",
+            Operator::Equal => "=",
+            Operator::GreaterEqual => ">=",
+            Operator::Greater => ">>",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// A version constraint of a package relationship, e.g. `>= 1.0`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraint {
+    operator: Operator,
+    version: Version,
+}
+
+impl Constraint {
+    /// Constrain versions to be in the given relation with `version`.
+    pub fn new(operator: Operator, version: Version) -> Constraint {
+        Constraint { operator, version }
+    }
+
+    /// The relation operator.
+    pub fn operator(&self) -> Operator {
+        self.operator
+    }
+
+    /// The version the constraint is relative to.
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Whether the version satisfies the constraint.
+    pub fn contains(&self, version: &Version) -> bool {
+        let ordering = version.cmp(&self.version);
+        match self.operator {
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessEqual => ordering != Ordering::Greater,
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::GreaterEqual => ordering != Ordering::Less,
+            Operator::Greater => ordering == Ordering::Greater,
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.operator, self.version)
+    }
+}
+
+/// Format constraints on a package as a relationship field.
+///
+/// The constraints are in conjunctive normal form: each element of
+/// `constraints` is a list of alternatives, one of which must be satisfied,
+/// e.g. `foo (<= 1.0) | foo (>= 1.2), foo (<< 2.0)`. An empty list means the
+/// package is required without constraining its version.
+pub fn format_relationship(package: &str, constraints: &[Vec<Constraint>]) -> String {
+    if constraints.is_empty() {
+        return package.to_owned();
+    }
+    constraints.iter()
+        .map(|alternatives| {
+            alternatives.iter()
+                .map(|constraint| format!("{} ({})", package, constraint))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Version;
+    use super::{Constraint, Operator, format_relationship};
+
+    fn constraint(operator: Operator, version: &str) -> Constraint {
+        Constraint::new(operator, Version(version.into()))
+    }
+
+    #[test]
+    fn test_constraint() {
+        for &(operator, matching, not_matching) in &[
+            (Operator::Less, &["0.9", "1.0~rc1"][..], &["1.0", "1.0-1", "1.1"][..]),
+            (Operator::LessEqual, &["0.9", "1.0~rc1", "1.0", "1.00"], &["1.0-1", "1.1"]),
+            (Operator::Equal, &["1.0", "1.00"], &["1.0~rc1", "1.0-1", "1.0.0"]),
+            (Operator::GreaterEqual, &["1.0", "1.0-1", "1.1"], &["0.9", "1.0~rc1"]),
+            (Operator::Greater, &["1.0-1", "1.0.0", "1.1"], &["0.9", "1.0~rc1", "1.0"]),
+        ] {
+            let constraint = constraint(operator, "1.0");
+            assert_eq!(constraint.operator(), operator);
+            assert_eq!(&**constraint.version(), "1.0");
+            for &v in matching {
+                assert!(constraint.contains(&Version(v.into())), "{} contains {}", constraint, v);
+            }
+            for &v in not_matching {
+                assert!(!constraint.contains(&Version(v.into())), "{} doesn't contain {}", constraint, v);
+            }
+        }
+
+        assert_eq!(constraint(Operator::Less, "2.0").to_string(), "<< 2.0");
+        assert_eq!(constraint(Operator::LessEqual, "2.0").to_string(), "<= 2.0");
+        assert_eq!(constraint(Operator::Equal, "1:2.0-1").to_string(), "= 1:2.0-1");
+        assert_eq!(constraint(Operator::GreaterEqual, "2.0").to_string(), ">= 2.0");
+        assert_eq!(constraint(Operator::Greater, "2.0").to_string(), ">> 2.0");
+    }
+
+    #[test]
+    fn test_format_relationship() {
+        assert_eq!(format_relationship("foo", &[]), "foo");
+        assert_eq!(
+            format_relationship("foo", &[vec![constraint(Operator::GreaterEqual, "1.0")]]),
+            "foo (>= 1.0)",
+        );
+        assert_eq!(
+            format_relationship("foo", &[
+                vec![constraint(Operator::LessEqual, "1.0"), constraint(Operator::GreaterEqual, "1.2")],
+                vec![constraint(Operator::Less, "2.0")],
+            ]),
+            "foo (<= 1.0) | foo (>= 1.2), foo (<< 2.0)",
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Implement Gentoo's versioning scheme, as specified by the
+//! [Package Manager Specification](https://projects.gentoo.org/pms/latest/pms.html).
+//!
+//! Ebuild versions are dot-separated numbers, an optional letter, suffixes
+//! (`_alpha`, `_beta`, `_pre`, `_rc` and `_p`, each with an optional number),
+//! and an optional revision `-rN`, e.g. `1.2.3b_rc1_p2-r3`.
+//!
+//! Components after the first one that start with a zero are compared as
+//! strings once trailing zeros are removed, as if they were decimals: so
+//! `1.01 < 1.1`, `1.010 == 1.01` and `1.09 > 1.010`.
+//!
+//! Package atoms (`>=cat/pkg-1.2.3-r1:slot/subslot[use,-flag]`) can be parsed
+//! and matched against packages.
+
+mod atom;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+
+pub use self::atom::{
+    Atom, Blocker, InvalidAtom, Operator, SlotDep, SlotOperator, UseDep,
+    UseDepKind,
+};
+
+/// A version suffix, e.g. `_alpha` or `_p`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suffix {
+    Alpha,
+    Beta,
+    Pre,
+    Rc,
+    P,
+}
+
+const SUFFIXES: &[(&str, Suffix)] = &[
+    ("alpha", Suffix::Alpha),
+    ("beta", Suffix::Beta),
+    ("pre", Suffix::Pre),
+    ("rc", Suffix::Rc),
+    ("p", Suffix::P),
+];
+
+impl fmt::Display for Suffix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let &(name, _) = SUFFIXES.iter().find(|&&(_, s)| s == *self).unwrap();
+        f.write_str(name)
+    }
+}
+
+/// An ebuild version, e.g. `1.2.3b_rc1_p2-r3`.
+#[derive(Clone, Debug)]
+pub struct EbuildVersion {
+    numbers: Vec<String>,
+    letter: Option<char>,
+    suffixes: Vec<(Suffix, Option<u64>)>,
+    revision: Option<u64>,
+}
+
+/// Compare numbers of any length as integers.
+fn compare_integers(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Compare the components after the first one (PMS algorithm 3.3).
+fn compare_components(a: &str, b: &str) -> Ordering {
+    if a.starts_with('0') || b.starts_with('0') {
+        a.trim_end_matches('0').cmp(b.trim_end_matches('0'))
+    } else {
+        compare_integers(a, b)
+    }
+}
+
+/// A part of a version, for prefix matching.
+#[derive(Clone, Copy, Debug)]
+enum Part<'a> {
+    Number(usize, &'a str),
+    Letter(char),
+    Suffix(Suffix, u64),
+    Revision(u64),
+}
+
+impl<'a> PartialEq for Part<'a> {
+    fn eq(&self, other: &Part<'a>) -> bool {
+        match (*self, *other) {
+            (Part::Number(0, a), Part::Number(0, b)) => compare_integers(a, b).is_eq(),
+            (Part::Number(i, a), Part::Number(j, b)) => {
+                i == j && compare_components(a, b).is_eq()
+            }
+            (Part::Letter(a), Part::Letter(b)) => a == b,
+            (Part::Suffix(a, m), Part::Suffix(b, n)) => a == b && m == n,
+            (Part::Revision(a), Part::Revision(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Simple cursor over a version string.
+struct Parser<'a> {
+    string: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.string.as_bytes().get(self.pos).cloned()
+    }
+
+    fn take_while<F: Fn(u8) -> bool>(&mut self, pred: F) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.pos += 1;
+        }
+        &self.string[start .. self.pos]
+    }
+
+    fn digits(&mut self) -> Result<&'a str, InvalidVersion> {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            return Err(match self.peek() {
+                Some(b'.') | None => InvalidVersion::EmptyField,
+                Some(_) => InvalidVersion::InvalidCharacter,
+            });
+        }
+        Ok(digits)
+    }
+
+    fn number(&mut self) -> Result<u64, InvalidVersion> {
+        self.digits()?.parse().map_err(|_| InvalidVersion::NumberTooLarge)
+    }
+}
+
+impl EbuildVersion {
+    /// Parse a version, e.g. `1.2.3b_rc1_p2-r3`.
+    pub fn parse(string: &str) -> Result<EbuildVersion, InvalidVersion> {
+        let mut parser = Parser { string, pos: 0 };
+
+        let mut numbers = vec![parser.digits()?.to_owned()];
+        while parser.peek() == Some(b'.') {
+            parser.pos += 1;
+            numbers.push(parser.digits()?.to_owned());
+        }
+
+        let mut letter = None;
+        if let Some(c) = parser.peek().filter(|c| c.is_ascii_lowercase()) {
+            parser.pos += 1;
+            letter = Some(c as char);
+        }
+
+        let mut suffixes = Vec::new();
+        while parser.peek() == Some(b'_') {
+            parser.pos += 1;
+            let name = parser.take_while(|c| c.is_ascii_lowercase());
+            let suffix = SUFFIXES.iter()
+                .find(|&&(s, _)| s == name)
+                .map(|&(_, suffix)| suffix)
+                .ok_or(InvalidVersion::InvalidCharacter)?;
+            let number = match parser.peek() {
+                Some(c) if c.is_ascii_digit() => Some(parser.number()?),
+                _ => None,
+            };
+            suffixes.push((suffix, number));
+        }
+
+        let mut revision = None;
+        if string[parser.pos ..].starts_with("-r") {
+            parser.pos += 2;
+            revision = Some(parser.number()?);
+        }
+
+        if parser.pos != string.len() {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+        Ok(EbuildVersion { numbers, letter, suffixes, revision })
+    }
+
+    /// The dot-separated numbers, as written.
+    pub fn numbers(&self) -> &[String] {
+        &self.numbers
+    }
+
+    /// The letter following the numbers, e.g. `b` in `1.2b`.
+    pub fn letter(&self) -> Option<char> {
+        self.letter
+    }
+
+    /// The suffixes and their numbers, e.g. `[(Rc, Some(1)), (P, None)]` for
+    /// `1.0_rc1_p`.
+    pub fn suffixes(&self) -> &[(Suffix, Option<u64>)] {
+        &self.suffixes
+    }
+
+    /// The revision, e.g. `3` for `1.0-r3`.
+    pub fn revision(&self) -> Option<u64> {
+        self.revision
+    }
+
+    /// The same version without the revision.
+    pub fn without_revision(&self) -> EbuildVersion {
+        EbuildVersion { revision: None, ..self.clone() }
+    }
+
+    fn parts(&self) -> Vec<Part<'_>> {
+        let mut parts: Vec<Part> = self.numbers.iter()
+            .enumerate()
+            .map(|(i, number)| Part::Number(i, number))
+            .collect();
+        parts.extend(self.letter.map(Part::Letter));
+        parts.extend(self.suffixes.iter().map(|&(suffix, number)| {
+            Part::Suffix(suffix, number.unwrap_or(0))
+        }));
+        parts.extend(self.revision.map(Part::Revision));
+        parts
+    }
+
+    /// Whether this version starts with the components of `prefix`, as in
+    /// `=cat/pkg-1.2*`.
+    ///
+    /// Components are compared whole, so `1.2*` matches `1.2`, `1.2.3` or
+    /// `1.2_rc1`, but not `1.20`.
+    pub fn starts_with(&self, prefix: &EbuildVersion) -> bool {
+        let (parts, prefix) = (self.parts(), prefix.parts());
+        parts.len() >= prefix.len() && parts[.. prefix.len()] == prefix[..]
+    }
+}
+
+impl<'a> TryFrom<&'a str> for EbuildVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<EbuildVersion, InvalidVersion> {
+        EbuildVersion::parse(string)
+    }
+}
+
+impl fmt::Display for EbuildVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.numbers.join("."))?;
+        if let Some(letter) = self.letter {
+            write!(f, "{}", letter)?;
+        }
+        for &(suffix, number) in &self.suffixes {
+            write!(f, "_{}", suffix)?;
+            if let Some(number) = number {
+                write!(f, "{}", number)?;
+            }
+        }
+        if let Some(revision) = self.revision {
+            write!(f, "-r{}", revision)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<EbuildVersion> for EbuildVersion {
+    fn eq(&self, other: &EbuildVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for EbuildVersion {}
+
+impl PartialOrd<EbuildVersion> for EbuildVersion {
+    fn partial_cmp(&self, other: &EbuildVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EbuildVersion {
+    /// Compare versions following PMS algorithm 3.1.
+    fn cmp(&self, other: &EbuildVersion) -> Ordering {
+        // Numeric components
+        let ordering = compare_integers(&self.numbers[0], &other.numbers[0]);
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        for (a, b) in self.numbers[1 ..].iter().zip(&other.numbers[1 ..]) {
+            let ordering = compare_components(a, b);
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        let ordering = self.numbers.len().cmp(&other.numbers.len())
+            .then_with(|| self.letter.cmp(&other.letter));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        // Suffixes
+        for (&(a, m), &(b, n)) in self.suffixes.iter().zip(&other.suffixes) {
+            let ordering = a.cmp(&b).then_with(|| m.unwrap_or(0).cmp(&n.unwrap_or(0)));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        let common = self.suffixes.len().min(other.suffixes.len());
+        // An extra suffix makes the version newer only if it is `_p`
+        if let Some(&(suffix, _)) = self.suffixes.get(common) {
+            return if suffix == Suffix::P { Ordering::Greater } else { Ordering::Less };
+        }
+        if let Some(&(suffix, _)) = other.suffixes.get(common) {
+            return if suffix == Suffix::P { Ordering::Less } else { Ordering::Greater };
+        }
+
+        self.revision.unwrap_or(0).cmp(&other.revision.unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::{EbuildVersion, Suffix};
+
+    fn parse(s: &str) -> EbuildVersion {
+        EbuildVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let version = parse("1.2.03b_rc1_p-r3");
+        assert_eq!(version.numbers(), &["1", "2", "03"]);
+        assert_eq!(version.letter(), Some('b'));
+        assert_eq!(version.suffixes(), &[(Suffix::Rc, Some(1)), (Suffix::P, None)]);
+        assert_eq!(version.revision(), Some(3));
+        assert_eq!(version.to_string(), "1.2.03b_rc1_p-r3");
+        assert_eq!(version.without_revision().to_string(), "1.2.03b_rc1_p");
+
+        for &(invalid, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            ("1.", InvalidVersion::EmptyField),
+            ("1..2", InvalidVersion::EmptyField),
+            ("1.0-r", InvalidVersion::EmptyField),
+            ("1.0_foo", InvalidVersion::InvalidCharacter),
+            ("1.0_cvs", InvalidVersion::InvalidCharacter),
+            ("1.0ab", InvalidVersion::InvalidCharacter),
+            ("1.0-1", InvalidVersion::InvalidCharacter),
+            ("1.0-r1_p1", InvalidVersion::InvalidCharacter),
+            ("v1.0", InvalidVersion::InvalidCharacter),
+            ("1.0_p99999999999999999999", InvalidVersion::NumberTooLarge),
+        ] {
+            assert_eq!(&EbuildVersion::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let versions = [
+            "0.9",
+            "1",
+            "1.0_alpha_beta",
+            "1.0_alpha",
+            "1.0_alpha1",
+            "1.0_alpha1_p1",
+            "1.0_beta",
+            "1.0_pre",
+            "1.0_rc1",
+            "1.0_rc2",
+            "1.0",
+            "1.0-r1",
+            "1.0-r10",
+            "1.0_p",
+            "1.0_p1",
+            "1.0a",
+            "1.0b_rc1",
+            "1.0b",
+            "1.0.1",
+            "1.001",
+            "1.01",
+            "1.01.1",
+            "1.02",
+            "1.09",
+            "1.1",
+            "1.2",
+            "1.10",
+            "12.2b",
+            "12.2.5",
+            "0100",
+        ];
+        for pair in versions.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        for &(a, b) in &[
+            ("1.0", "1.00"),
+            ("1.01", "1.010"),
+            ("01.2", "1.2"),
+            ("1.0-r0", "1.0"),
+            ("1.0_alpha", "1.0_alpha0"),
+        ] {
+            assert_eq!(parse(a), parse(b), "{} == {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_starts_with() {
+        let prefix = parse("1.2");
+        for &(version, expected) in &[
+            ("1.2", true),
+            ("1.2.3", true),
+            ("1.2_rc1", true),
+            ("1.2b", true),
+            ("1.2-r1", true),
+            ("1.20", false),
+            ("1.3", false),
+            ("1", false),
+        ] {
+            assert_eq!(parse(version).starts_with(&prefix), expected, "{}", version);
+        }
+        assert!(parse("1.2.3_rc1_p1").starts_with(&parse("1.2.3_rc1")));
+        assert!(!parse("1.2.3_rc1").starts_with(&parse("1.2.3_rc")));
+        assert!(!parse("1.2.3_rc1").starts_with(&parse("1.2.3_rc2")));
+        assert!(parse("1.020").starts_with(&parse("1.02")));
+    }
+}
@@ -0,0 +1,616 @@
+//! Package dependency atoms, e.g. `>=cat/pkg-1.2.3-r1:slot/subslot[use,-flag]`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+use super::EbuildVersion;
+
+/// Error for the atom parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidAtom {
+    /// The atom is empty.
+    Empty,
+    /// The category contains invalid characters.
+    InvalidCategory,
+    /// The package name contains invalid characters.
+    InvalidName,
+    /// An operator is given without a version.
+    MissingVersion,
+    /// A version is given without an operator.
+    MissingOperator,
+    /// The version is invalid.
+    InvalidVersion(InvalidVersion),
+    /// A wildcard is used with an operator other than `=`.
+    InvalidWildcard,
+    /// The slot dependency is invalid.
+    InvalidSlot,
+    /// A USE dependency is invalid, or the list isn't closed.
+    InvalidUseDep,
+}
+
+impl From<InvalidVersion> for InvalidAtom {
+    fn from(error: InvalidVersion) -> InvalidAtom {
+        InvalidAtom::InvalidVersion(error)
+    }
+}
+
+/// A blocker, `!` or `!!`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Blocker {
+    /// `!`: the blocking package may be uninstalled after this one is
+    /// installed.
+    Weak,
+    /// `!!`: the blocking package must be uninstalled first.
+    Strong,
+}
+
+/// A version operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `<`
+    Less,
+    /// `<=`
+    LessEqual,
+    /// `=`
+    Equal,
+    /// `=` with a trailing `*`: the version starts with the given components.
+    EqualWildcard,
+    /// `~`: equal, ignoring the revision.
+    Approximate,
+    /// `>=`
+    GreaterEqual,
+    /// `>`
+    Greater,
+}
+
+const OPERATORS: &[(&str, Operator)] = &[
+    ("<=", Operator::LessEqual),
+    (">=", Operator::GreaterEqual),
+    ("<", Operator::Less),
+    (">", Operator::Greater),
+    ("=", Operator::Equal),
+    ("~", Operator::Approximate),
+];
+
+impl Operator {
+    /// Whether `version` satisfies the operator with the given operand.
+    pub fn matches(self, version: &EbuildVersion, operand: &EbuildVersion) -> bool {
+        let ordering = version.cmp(operand);
+        match self {
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessEqual => ordering != Ordering::Greater,
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::EqualWildcard => version.starts_with(operand),
+            Operator::Approximate => version.without_revision() == operand.without_revision(),
+            Operator::GreaterEqual => ordering != Ordering::Less,
+            Operator::Greater => ordering == Ordering::Greater,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Operator::EqualWildcard => "=",
+            _ => OPERATORS.iter().find(|&&(_, op)| op == self).unwrap().0,
+        }
+    }
+}
+
+/// A slot operator, `=` or `*`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SlotOperator {
+    /// `:=` or `:slot=`: rebuild when the slot or subslot changes.
+    Equal,
+    /// `:*`: any slot will do.
+    Any,
+}
+
+/// A slot dependency, e.g. `:3`, `:3/3.1`, `:=` or `:*`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SlotDep {
+    slot: Option<String>,
+    subslot: Option<String>,
+    operator: Option<SlotOperator>,
+}
+
+impl SlotDep {
+    fn parse(string: &str) -> Result<SlotDep, InvalidAtom> {
+        let (rest, operator) = match string {
+            "*" => ("", Some(SlotOperator::Any)),
+            _ => match string.strip_suffix('=') {
+                Some(rest) => (rest, Some(SlotOperator::Equal)),
+                None => (string, None),
+            },
+        };
+        if rest.is_empty() {
+            return match operator {
+                Some(_) => Ok(SlotDep { slot: None, subslot: None, operator }),
+                None => Err(InvalidAtom::InvalidSlot),
+            };
+        }
+        let (slot, subslot) = match rest.find('/') {
+            Some(slash) => (&rest[.. slash], Some(&rest[slash + 1 ..])),
+            None => (rest, None),
+        };
+        if !is_slot_name(slot) || !subslot.is_none_or(is_slot_name) {
+            return Err(InvalidAtom::InvalidSlot);
+        }
+        Ok(SlotDep {
+            slot: Some(slot.to_owned()),
+            subslot: subslot.map(ToOwned::to_owned),
+            operator,
+        })
+    }
+
+    /// The slot, if any.
+    pub fn slot(&self) -> Option<&str> {
+        self.slot.as_deref()
+    }
+
+    /// The subslot, if any.
+    pub fn subslot(&self) -> Option<&str> {
+        self.subslot.as_deref()
+    }
+
+    /// The slot operator, if any.
+    pub fn operator(&self) -> Option<SlotOperator> {
+        self.operator
+    }
+
+    /// Whether a package in the given slot and subslot satisfies this
+    /// dependency. Slot operators don't restrict the slot.
+    pub fn matches(&self, slot: &str, subslot: Option<&str>) -> bool {
+        self.slot.as_ref().is_none_or(|s| s == slot)
+            && self.subslot.as_ref().is_none_or(|s| Some(s.as_str()) == subslot)
+    }
+}
+
+impl fmt::Display for SlotDep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref slot) = self.slot {
+            write!(f, "{}", slot)?;
+        }
+        if let Some(ref subslot) = self.subslot {
+            write!(f, "/{}", subslot)?;
+        }
+        match self.operator {
+            Some(SlotOperator::Equal) => write!(f, "="),
+            Some(SlotOperator::Any) => write!(f, "*"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The kind of a USE dependency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UseDepKind {
+    /// `flag`: the flag must be enabled.
+    Enabled,
+    /// `-flag`: the flag must be disabled.
+    Disabled,
+    /// `flag?`: the flag must be enabled if it is enabled on the dependent.
+    Conditional,
+    /// `!flag?`: the flag must be disabled if it is disabled on the
+    /// dependent.
+    NegatedConditional,
+    /// `flag=`: the flag must have the same state as on the dependent.
+    Equal,
+    /// `!flag=`: the flag must have the opposite state as on the dependent.
+    NegatedEqual,
+}
+
+/// A USE dependency, e.g. `-flag` or `flag(+)?`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UseDep {
+    flag: String,
+    kind: UseDepKind,
+    default: Option<bool>,
+}
+
+impl UseDep {
+    fn parse(string: &str) -> Result<UseDep, InvalidAtom> {
+        let (negated, rest) = match string.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, string),
+        };
+        let (kind, rest) = if let Some(rest) = rest.strip_suffix('?') {
+            (if negated { UseDepKind::NegatedConditional } else { UseDepKind::Conditional }, rest)
+        } else if let Some(rest) = rest.strip_suffix('=') {
+            (if negated { UseDepKind::NegatedEqual } else { UseDepKind::Equal }, rest)
+        } else if negated {
+            return Err(InvalidAtom::InvalidUseDep);
+        } else if let Some(rest) = rest.strip_prefix('-') {
+            (UseDepKind::Disabled, rest)
+        } else {
+            (UseDepKind::Enabled, rest)
+        };
+        let (flag, default) = if let Some(flag) = rest.strip_suffix("(+)") {
+            (flag, Some(true))
+        } else if let Some(flag) = rest.strip_suffix("(-)") {
+            (flag, Some(false))
+        } else {
+            (rest, None)
+        };
+        if !is_use_flag(flag) {
+            return Err(InvalidAtom::InvalidUseDep);
+        }
+        Ok(UseDep { flag: flag.to_owned(), kind, default })
+    }
+
+    /// The name of the flag.
+    pub fn flag(&self) -> &str {
+        &self.flag
+    }
+
+    /// What the dependency requires of the flag.
+    pub fn kind(&self) -> UseDepKind {
+        self.kind
+    }
+
+    /// The state assumed when the package doesn't have the flag, given by
+    /// `(+)` or `(-)`.
+    pub fn default(&self) -> Option<bool> {
+        self.default
+    }
+}
+
+impl fmt::Display for UseDep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (prefix, suffix) = match self.kind {
+            UseDepKind::Enabled => ("", ""),
+            UseDepKind::Disabled => ("-", ""),
+            UseDepKind::Conditional => ("", "?"),
+            UseDepKind::NegatedConditional => ("!", "?"),
+            UseDepKind::Equal => ("", "="),
+            UseDepKind::NegatedEqual => ("!", "="),
+        };
+        let default = match self.default {
+            Some(true) => "(+)",
+            Some(false) => "(-)",
+            None => "",
+        };
+        write!(f, "{}{}{}{}", prefix, self.flag, default, suffix)
+    }
+}
+
+/// A package dependency, e.g. `>=cat/pkg-1.2.3-r1:slot/subslot[use,-flag]`.
+///
+/// The category may be omitted, as in `=pkg-1.2*`, to match packages in any
+/// category.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Atom {
+    blocker: Option<Blocker>,
+    category: Option<String>,
+    name: String,
+    constraint: Option<(Operator, EbuildVersion)>,
+    slot: Option<SlotDep>,
+    use_deps: Vec<UseDep>,
+}
+
+fn is_name_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'+' || c == b'_' || c == b'-'
+}
+
+fn is_category(s: &str) -> bool {
+    !s.starts_with(['-', '+', '.']) && !s.is_empty()
+        && s.bytes().all(|c| is_name_char(c) || c == b'.')
+}
+
+fn is_slot_name(s: &str) -> bool {
+    is_category(s)
+}
+
+fn is_use_flag(s: &str) -> bool {
+    s.bytes().next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && s.bytes().all(|c| is_name_char(c) || c == b'@')
+}
+
+/// Split `name-version` at the first hyphen followed by a valid version.
+fn split_version(string: &str) -> Option<(&str, &str)> {
+    string.match_indices('-')
+        .map(|(hyphen, _)| (&string[.. hyphen], &string[hyphen + 1 ..]))
+        .find(|&(_, version)| {
+            EbuildVersion::parse(version.strip_suffix('*').unwrap_or(version)).is_ok()
+        })
+}
+
+impl Atom {
+    /// Parse an atom, e.g. `>=cat/pkg-1.2.3-r1:slot/subslot[use,-flag]`.
+    pub fn parse(string: &str) -> Result<Atom, InvalidAtom> {
+        if string.is_empty() {
+            return Err(InvalidAtom::Empty);
+        }
+
+        let (blocker, rest) = if let Some(rest) = string.strip_prefix("!!") {
+            (Some(Blocker::Strong), rest)
+        } else if let Some(rest) = string.strip_prefix('!') {
+            (Some(Blocker::Weak), rest)
+        } else {
+            (None, string)
+        };
+        let (operator, rest) = match OPERATORS.iter().find(|&&(op, _)| rest.starts_with(op)) {
+            Some(&(op, operator)) => (Some(operator), &rest[op.len() ..]),
+            None => (None, rest),
+        };
+
+        let (rest, use_deps) = match rest.find('[') {
+            Some(bracket) => {
+                let list = rest[bracket + 1 ..].strip_suffix(']')
+                    .ok_or(InvalidAtom::InvalidUseDep)?;
+                let use_deps = list.split(',')
+                    .map(UseDep::parse)
+                    .collect::<Result<Vec<_>, _>>()?;
+                (&rest[.. bracket], use_deps)
+            }
+            None => (rest, Vec::new()),
+        };
+        let (rest, slot) = match rest.find(':') {
+            Some(colon) => (&rest[.. colon], Some(SlotDep::parse(&rest[colon + 1 ..])?)),
+            None => (rest, None),
+        };
+
+        let (category, package) = match rest.find('/') {
+            Some(slash) => {
+                let category = &rest[.. slash];
+                if !is_category(category) {
+                    return Err(InvalidAtom::InvalidCategory);
+                }
+                (Some(category.to_owned()), &rest[slash + 1 ..])
+            }
+            None => (None, rest),
+        };
+
+        let (name, constraint) = match (operator, split_version(package)) {
+            (Some(operator), Some((name, version))) => {
+                let (operator, version) = match version.strip_suffix('*') {
+                    Some(version) if operator == Operator::Equal => {
+                        (Operator::EqualWildcard, version)
+                    }
+                    Some(_) => return Err(InvalidAtom::InvalidWildcard),
+                    None => (operator, version),
+                };
+                (name, Some((operator, EbuildVersion::parse(version)?)))
+            }
+            (Some(_), None) => {
+                // Report why what looks like the version is invalid
+                let hyphen = package.rfind('-').ok_or(InvalidAtom::MissingVersion)?;
+                let version = &package[hyphen + 1 ..];
+                EbuildVersion::parse(version.strip_suffix('*').unwrap_or(version))?;
+                return Err(InvalidAtom::MissingVersion);
+            }
+            (None, Some(_)) => return Err(InvalidAtom::MissingOperator),
+            (None, None) => (package, None),
+        };
+        let valid_start = name.bytes().next()
+            .is_some_and(|c| is_name_char(c) && c != b'-' && c != b'+');
+        if !valid_start || !name.bytes().all(is_name_char) {
+            return Err(InvalidAtom::InvalidName);
+        }
+
+        Ok(Atom {
+            blocker,
+            category,
+            name: name.to_owned(),
+            constraint,
+            slot,
+            use_deps,
+        })
+    }
+
+    /// The blocker, if this atom blocks the packages it matches.
+    pub fn blocker(&self) -> Option<Blocker> {
+        self.blocker
+    }
+
+    /// The category, e.g. `dev-lang`.
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    /// The package name, e.g. `python`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The version operator and operand, if any.
+    pub fn constraint(&self) -> Option<(Operator, &EbuildVersion)> {
+        self.constraint.as_ref().map(|(operator, version)| (*operator, version))
+    }
+
+    /// The slot dependency, if any.
+    pub fn slot(&self) -> Option<&SlotDep> {
+        self.slot.as_ref()
+    }
+
+    /// The USE dependencies.
+    pub fn use_deps(&self) -> &[UseDep] {
+        &self.use_deps
+    }
+
+    /// Whether the version satisfies the version constraint, if any.
+    pub fn matches_version(&self, version: &EbuildVersion) -> bool {
+        self.constraint().is_none_or(|(operator, operand)| operator.matches(version, operand))
+    }
+
+    /// Whether this atom designates the given package.
+    ///
+    /// The blocker, slot and USE dependencies are not taken into account.
+    pub fn matches(&self, category: &str, name: &str, version: &EbuildVersion) -> bool {
+        self.category().is_none_or(|c| c == category)
+            && self.name == name
+            && self.matches_version(version)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Atom {
+    type Error = InvalidAtom;
+
+    fn try_from(string: &'a str) -> Result<Atom, InvalidAtom> {
+        Atom::parse(string)
+    }
+}
+
+impl fmt::Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.blocker {
+            Some(Blocker::Weak) => write!(f, "!")?,
+            Some(Blocker::Strong) => write!(f, "!!")?,
+            None => {}
+        }
+        if let Some((operator, _)) = self.constraint {
+            write!(f, "{}", operator.as_str())?;
+        }
+        if let Some(ref category) = self.category {
+            write!(f, "{}/", category)?;
+        }
+        write!(f, "{}", self.name)?;
+        if let Some((operator, ref version)) = self.constraint {
+            write!(f, "-{}", version)?;
+            if operator == Operator::EqualWildcard {
+                write!(f, "*")?;
+            }
+        }
+        if let Some(ref slot) = self.slot {
+            write!(f, ":{}", slot)?;
+        }
+        if !self.use_deps.is_empty() {
+            let use_deps: Vec<String> = self.use_deps.iter().map(|u| u.to_string()).collect();
+            write!(f, "[{}]", use_deps.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::super::EbuildVersion;
+    use super::{Atom, Blocker, InvalidAtom, Operator, SlotOperator, UseDepKind};
+
+    fn version(s: &str) -> EbuildVersion {
+        EbuildVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let atom = Atom::parse(">=dev-lang/python-3.11.4-r1:3.11/3.11[sqlite,-tk]").unwrap();
+        assert_eq!(atom.blocker(), None);
+        assert_eq!(atom.category(), Some("dev-lang"));
+        assert_eq!(atom.name(), "python");
+        let (operator, operand) = atom.constraint().unwrap();
+        assert_eq!(operator, Operator::GreaterEqual);
+        assert_eq!(operand.to_string(), "3.11.4-r1");
+        let slot = atom.slot().unwrap();
+        assert_eq!((slot.slot(), slot.subslot(), slot.operator()), (Some("3.11"), Some("3.11"), None));
+        let use_deps: Vec<_> = atom.use_deps().iter().map(|u| (u.flag(), u.kind())).collect();
+        assert_eq!(use_deps, [("sqlite", UseDepKind::Enabled), ("tk", UseDepKind::Disabled)]);
+
+        let atom = Atom::parse("!!<sys-libs/glibc-2.38:=").unwrap();
+        assert_eq!(atom.blocker(), Some(Blocker::Strong));
+        assert_eq!(atom.slot().unwrap().operator(), Some(SlotOperator::Equal));
+        assert_eq!(atom.slot().unwrap().slot(), None);
+
+        let atom = Atom::parse("!app-misc/foo-bar").unwrap();
+        assert_eq!(atom.blocker(), Some(Blocker::Weak));
+        assert_eq!(atom.name(), "foo-bar");
+        assert!(atom.constraint().is_none());
+
+        let atom = Atom::parse("=pkg-1.2*").unwrap();
+        assert_eq!(atom.category(), None);
+        assert_eq!(atom.constraint().unwrap().0, Operator::EqualWildcard);
+
+        let atom = Atom::parse("~media-libs/libsdl2-2.28.5").unwrap();
+        assert_eq!(atom.constraint().unwrap().0, Operator::Approximate);
+
+        // Names may contain hyphens followed by something that isn't a version
+        let atom = Atom::parse("=x11-libs/gtk+-3-3.24.41").unwrap();
+        assert_eq!(atom.name(), "gtk+-3");
+
+        let atom = Atom::parse("dev-qt/qtbase:6[gui(+)?,!X=,!wayland?,icu(-)=]").unwrap();
+        let use_deps: Vec<_> = atom.use_deps().iter()
+            .map(|u| (u.flag(), u.kind(), u.default()))
+            .collect();
+        assert_eq!(use_deps, [
+            ("gui", UseDepKind::Conditional, Some(true)),
+            ("X", UseDepKind::NegatedEqual, None),
+            ("wayland", UseDepKind::NegatedConditional, None),
+            ("icu", UseDepKind::Equal, Some(false)),
+        ]);
+
+        for &atom in &[
+            ">=dev-lang/python-3.11.4-r1:3.11/3.11[sqlite,-tk]",
+            "!!<sys-libs/glibc-2.38:=",
+            "=pkg-1.2*",
+            "~media-libs/libsdl2-2.28.5",
+            "dev-qt/qtbase:6[gui(+)?,!X=,!wayland?,icu(-)=]",
+            "dev-lang/rust:*",
+            "dev-libs/openssl:0/3=",
+        ] {
+            assert_eq!(Atom::parse(atom).unwrap().to_string(), atom);
+        }
+
+        for &(invalid, ref error) in &[
+            ("", InvalidAtom::Empty),
+            (">=dev-lang/python", InvalidAtom::MissingVersion),
+            ("dev-lang/python-3.11", InvalidAtom::MissingOperator),
+            (">=dev-lang/python-3.11*", InvalidAtom::InvalidWildcard),
+            ("=dev-lang/python-3.11.*", InvalidAtom::InvalidVersion(InvalidVersion::EmptyField)),
+            ("-cat/pkg", InvalidAtom::InvalidCategory),
+            ("+cat/pkg", InvalidAtom::InvalidCategory),
+            ("cat/-pkg", InvalidAtom::InvalidName),
+            ("cat/+pkg", InvalidAtom::InvalidName),
+            ("cat/pkg$", InvalidAtom::InvalidName),
+            ("cat/pkg:", InvalidAtom::InvalidSlot),
+            ("cat/pkg:1/", InvalidAtom::InvalidSlot),
+            ("cat/pkg:+slot", InvalidAtom::InvalidSlot),
+            ("cat/pkg[foo", InvalidAtom::InvalidUseDep),
+            ("cat/pkg[]", InvalidAtom::InvalidUseDep),
+            ("cat/pkg[!foo]", InvalidAtom::InvalidUseDep),
+            ("cat/pkg[@foo]", InvalidAtom::InvalidUseDep),
+            ("cat/pkg[foo,,bar]", InvalidAtom::InvalidUseDep),
+            (
+                "=cat/pkg-1.0_p99999999999999999999",
+                InvalidAtom::InvalidVersion(InvalidVersion::NumberTooLarge),
+            ),
+        ] {
+            assert_eq!(&Atom::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_matches_version() {
+        for &(atom, matching, not_matching) in &[
+            (">=cat/pkg-1.2.3-r1", &["1.2.3-r1", "1.2.3_p", "1.2.4", "2"][..], &["1.2.3", "1.2.3_rc1-r2"][..]),
+            (">cat/pkg-1.2", &["1.2-r1", "1.2_p1", "1.2.0"], &["1.2", "1.2-r0", "1.2_rc1"]),
+            ("<=cat/pkg-1.2", &["1.2", "1.2-r0", "1.1", "1.2_rc1"], &["1.2-r1", "1.2.0"]),
+            ("<cat/pkg-1.2", &["1.1", "1.2_rc1"], &["1.2", "1.2-r1"]),
+            ("=cat/pkg-1.2", &["1.2", "1.2-r0", "01.2"], &["1.2-r1", "1.2.0"]),
+            ("=cat/pkg-1.2*", &["1.2", "1.2.3", "1.2_rc1", "1.2-r3", "1.2b"], &["1.20", "1.3", "1"]),
+            ("~cat/pkg-1.2", &["1.2", "1.2-r1", "1.2-r7"], &["1.2.1", "1.2_p1", "1.1"]),
+        ] {
+            let atom = Atom::parse(atom).unwrap();
+            for &v in matching {
+                assert!(atom.matches_version(&version(v)), "{} matches {}", atom, v);
+            }
+            for &v in not_matching {
+                assert!(!atom.matches_version(&version(v)), "{} doesn't match {}", atom, v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let atom = Atom::parse("!>=dev-lang/python-3.11:3.11").unwrap();
+        assert!(atom.matches("dev-lang", "python", &version("3.12.1")));
+        assert!(!atom.matches("dev-lang", "python", &version("3.10.13")));
+        assert!(!atom.matches("dev-python", "python", &version("3.12.1")));
+        assert!(!atom.matches("dev-lang", "python-exec", &version("3.12.1")));
+        assert!(atom.slot().unwrap().matches("3.11", None));
+        assert!(!atom.slot().unwrap().matches("3.12", None));
+
+        let atom = Atom::parse("python").unwrap();
+        assert!(atom.matches("dev-lang", "python", &version("0")));
+        assert!(atom.matches("dev-python", "python", &version("0")));
+    }
+}
@@ -0,0 +1,388 @@
+//! Implement Go modules' versioning scheme.
+//!
+//! Module versions are semver versions prefixed with `v`, e.g. `v1.2.3`. As
+//! in `golang.org/x/mod/semver`, `v1` and `v1.2` are shorthands for `v1.0.0`
+//! and `v1.2.0`, and build metadata is ignored when comparing, so
+//! `v2.0.0+incompatible == v2.0.0`. The `+incompatible` suffix marks a major
+//! version 2 or more of a module that doesn't have a `go.mod` file.
+//!
+//! Revisions without a tag are identified by pseudo-versions, which embed a
+//! UTC timestamp and a commit hash and sort just after the tag they are
+//! based on, e.g. `v1.2.4-0.20191109021931-daa7c04131f5` is a commit after
+//! `v1.2.3`.
+//!
+//! `go.mod` files can be parsed, and Minimal Version Selection can be run
+//! over a module graph.
+
+mod modfile;
+mod mvs;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::semver::{Identifier, InvalidSemver, SemverVersion};
+
+pub use self::modfile::{
+    InvalidDirective, InvalidModFile, ModFile, ModuleVersion, Replace,
+    ReplaceTarget, Require,
+};
+pub use self::mvs::{ModuleGraph, MvsError};
+
+/// A Go module version, e.g. `v1.2.3` or `v2.0.0+incompatible`.
+#[derive(Clone, Debug)]
+pub struct GoVersion {
+    string: String,
+    semver: SemverVersion,
+}
+
+/// The time of the commit of a pseudo-version, in UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl Timestamp {
+    /// Parse a timestamp written as `yyyymmddhhmmss`.
+    fn parse(string: &str) -> Option<Timestamp> {
+        if string.len() != 14 || !string.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let field = |start: usize, end: usize| string[start .. end].parse::<u8>().unwrap();
+        let timestamp = Timestamp {
+            year: string[.. 4].parse().unwrap(),
+            month: field(4, 6),
+            day: field(6, 8),
+            hour: field(8, 10),
+            minute: field(10, 12),
+            second: field(12, 14),
+        };
+        let valid = (1 ..= 12).contains(&timestamp.month)
+            && (1 ..= 31).contains(&timestamp.day)
+            && timestamp.hour < 24
+            && timestamp.minute < 60
+            && timestamp.second < 60;
+        if valid { Some(timestamp) } else { None }
+    }
+
+    /// The year, e.g. `2019`.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// The month, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// The day of the month, from 1 to 31.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// The hour, from 0 to 23.
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// The minute, from 0 to 59.
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// The second, from 0 to 59.
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+}
+
+impl fmt::Display for Timestamp {
+    /// Format as RFC 3339, e.g. `2019-11-09T02:19:31Z`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second,
+        )
+    }
+}
+
+/// The information embedded in a pseudo-version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PseudoVersion {
+    base: Option<GoVersion>,
+    timestamp: Timestamp,
+    revision: String,
+}
+
+impl PseudoVersion {
+    /// The tagged version this pseudo-version follows, if any.
+    ///
+    /// This is `v1.2.3` for `v1.2.4-0.20191109021931-daa7c04131f5` and
+    /// `v1.2.3-pre` for `v1.2.3-pre.0.20191109021931-daa7c04131f5`, but there
+    /// is none for `v0.0.0-20191109021931-daa7c04131f5`.
+    pub fn base(&self) -> Option<&GoVersion> {
+        self.base.as_ref()
+    }
+
+    /// The time of the commit.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// The commit hash, usually its first 12 characters.
+    pub fn revision(&self) -> &str {
+        &self.revision
+    }
+}
+
+impl GoVersion {
+    /// Parse a version, e.g. `v1.2.3`, `v1.2` or `v2.0.0+incompatible`.
+    pub fn parse(string: &str) -> Result<GoVersion, InvalidSemver> {
+        let rest = string.strip_prefix('v').ok_or(InvalidSemver::InvalidCharacter)?;
+        // Shorthands can't have a pre-release or build metadata
+        let semver = match rest.matches('.').count() {
+            0 if !rest.contains(['-', '+']) => SemverVersion::parse(&format!("{}.0.0", rest))?,
+            1 if !rest.contains(['-', '+']) => SemverVersion::parse(&format!("{}.0", rest))?,
+            _ => SemverVersion::parse(rest)?,
+        };
+        Ok(GoVersion { string: string.to_owned(), semver })
+    }
+
+    /// The version as semver.
+    pub fn semver(&self) -> &SemverVersion {
+        &self.semver
+    }
+
+    /// The canonical form of the version, with all three numbers and without
+    /// build metadata, e.g. `v1.2.0` for `v1.2`.
+    pub fn canonical(&self) -> String {
+        let semver = &self.semver;
+        let mut canonical = format!("v{}.{}.{}", semver.major(), semver.minor(), semver.patch());
+        if semver.is_prerelease() {
+            let pre: Vec<String> = semver.pre().iter().map(|i| i.to_string()).collect();
+            canonical.push('-');
+            canonical.push_str(&pre.join("."));
+        }
+        canonical
+    }
+
+    /// Whether this is a major version 2 or more of a module that doesn't
+    /// have a `go.mod` file, e.g. `v2.0.0+incompatible`.
+    pub fn is_incompatible(&self) -> bool {
+        self.semver.build() == ["incompatible"]
+    }
+
+    /// Whether this is a pre-release, which includes most pseudo-versions.
+    pub fn is_prerelease(&self) -> bool {
+        self.semver.is_prerelease()
+    }
+
+    /// Read the information embedded in a pseudo-version.
+    ///
+    /// Returns `None` if this isn't a pseudo-version, of one of the forms
+    /// `vX.0.0-yyyymmddhhmmss-abcdefabcdef`,
+    /// `vX.Y.Z-pre.0.yyyymmddhhmmss-abcdefabcdef` or
+    /// `vX.Y.(Z+1)-0.yyyymmddhhmmss-abcdefabcdef`.
+    pub fn pseudo(&self) -> Option<PseudoVersion> {
+        let semver = &self.semver;
+        let pre = semver.pre();
+        let (time, revision) = match pre.last() {
+            Some(Identifier::AlphaNumeric(last)) => last.split_once('-')?,
+            _ => return None,
+        };
+        let timestamp = Timestamp::parse(time)?;
+        if revision.is_empty() || !revision.bytes().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+
+        let base = match pre.len() {
+            1 if semver.minor() == 0 && semver.patch() == 0 => None,
+            1 => return None,
+            _ if pre[pre.len() - 2] != Identifier::Numeric(0) => return None,
+            2 => {
+                // The patch number was incremented
+                let patch = semver.patch().checked_sub(1)?;
+                Some(format!("v{}.{}.{}", semver.major(), semver.minor(), patch))
+            }
+            n => {
+                let base: Vec<String> = pre[.. n - 2].iter().map(|i| i.to_string()).collect();
+                Some(format!(
+                    "v{}.{}.{}-{}",
+                    semver.major(), semver.minor(), semver.patch(), base.join("."),
+                ))
+            }
+        };
+        let base = match base {
+            Some(base) if self.is_incompatible() => Some(format!("{}+incompatible", base)),
+            base => base,
+        };
+
+        Some(PseudoVersion {
+            base: base.map(|b| GoVersion::parse(&b).unwrap()),
+            timestamp,
+            revision: revision.to_owned(),
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for GoVersion {
+    type Error = InvalidSemver;
+
+    fn try_from(string: &'a str) -> Result<GoVersion, InvalidSemver> {
+        GoVersion::parse(string)
+    }
+}
+
+impl fmt::Display for GoVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+impl PartialEq<GoVersion> for GoVersion {
+    fn eq(&self, other: &GoVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for GoVersion {}
+
+impl Hash for GoVersion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.semver.hash(state);
+    }
+}
+
+impl PartialOrd<GoVersion> for GoVersion {
+    fn partial_cmp(&self, other: &GoVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GoVersion {
+    fn cmp(&self, other: &GoVersion) -> Ordering {
+        self.semver.cmp(&other.semver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semver::InvalidSemver;
+    use super::GoVersion;
+
+    fn parse(s: &str) -> GoVersion {
+        GoVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        for &(version, canonical) in &[
+            ("v1", "v1.0.0"),
+            ("v1.2", "v1.2.0"),
+            ("v1.2.3", "v1.2.3"),
+            ("v1.2.3-pre.1+meta", "v1.2.3-pre.1"),
+            ("v2.0.0+incompatible", "v2.0.0"),
+        ] {
+            let parsed = parse(version);
+            assert_eq!(parsed.to_string(), version);
+            assert_eq!(parsed.canonical(), canonical);
+        }
+        assert!(parse("v2.0.0+incompatible").is_incompatible());
+        assert!(!parse("v2.0.0").is_incompatible());
+        assert!(parse("v2.0.0-rc.1").is_prerelease());
+
+        for &(invalid, ref error) in &[
+            ("bad", InvalidSemver::InvalidCharacter),
+            ("1.2.3", InvalidSemver::InvalidCharacter),
+            ("v1-pre", InvalidSemver::WrongFieldCount),
+            ("v1+meta", InvalidSemver::WrongFieldCount),
+            ("v1.2-pre", InvalidSemver::WrongFieldCount),
+            ("v1.2+meta", InvalidSemver::WrongFieldCount),
+            ("v1.2.3.4", InvalidSemver::WrongFieldCount),
+            ("v01.2.3", InvalidSemver::LeadingZero),
+            ("v1.2.", InvalidSemver::EmptyField),
+            ("v", InvalidSemver::EmptyField),
+        ] {
+            assert_eq!(&GoVersion::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        // From golang.org/x/mod/semver's tests
+        let versions = [
+            &["v1.0.0-alpha"][..],
+            &["v1.0.0-alpha.1"],
+            &["v1.0.0-alpha.beta"],
+            &["v1.0.0-beta"],
+            &["v1.0.0-beta.2"],
+            &["v1.0.0-beta.11"],
+            &["v1.0.0-rc.1"],
+            &["v1", "v1.0", "v1.0.0"],
+            &["v1.2", "v1.2.0"],
+            &["v1.2.3-456"],
+            &["v1.2.3-456.789"],
+            &["v1.2.3-456-789"],
+            &["v1.2.3-456a"],
+            &["v1.2.3-pre", "v1.2.3-pre+meta"],
+            &["v1.2.3-pre.1"],
+            &["v1.2.3-zzz"],
+            &["v1.2.3", "v1.2.3+meta", "v1.2.3+meta-pre", "v1.2.3+meta-pre.sha.256a"],
+            &["v1.2.4-0.20191109021931-daa7c04131f5"],
+            &["v1.2.4"],
+            &["v2.0.0", "v2.0.0+incompatible"],
+        ];
+        for (i, equal) in versions.iter().enumerate() {
+            for &a in *equal {
+                for &b in *equal {
+                    assert_eq!(parse(a), parse(b), "{} == {}", a, b);
+                }
+                for &b in versions[i + 1 ..].iter().flat_map(|v| v.iter()) {
+                    assert!(parse(a) < parse(b), "{} < {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pseudo() {
+        let pseudo = parse("v0.0.0-20191109021931-daa7c04131f5").pseudo().unwrap();
+        assert_eq!(pseudo.base(), None);
+        assert_eq!(pseudo.timestamp().to_string(), "2019-11-09T02:19:31Z");
+        assert_eq!(pseudo.timestamp().year(), 2019);
+        assert_eq!(pseudo.revision(), "daa7c04131f5");
+
+        for &(version, base) in &[
+            ("v1.2.4-0.20191109021931-daa7c04131f5", "v1.2.3"),
+            ("v1.2.3-pre.0.20191109021931-daa7c04131f5", "v1.2.3-pre"),
+            ("v1.2.3-rc.1.0.20191109021931-daa7c04131f5", "v1.2.3-rc.1"),
+            ("v2.0.1-0.20191109021931-daa7c04131f5+incompatible", "v2.0.0+incompatible"),
+        ] {
+            let pseudo = parse(version).pseudo().unwrap();
+            assert_eq!(pseudo.base().unwrap().to_string(), base, "{}", version);
+            assert_eq!(pseudo.timestamp().to_string(), "2019-11-09T02:19:31Z");
+            assert_eq!(pseudo.revision(), "daa7c04131f5");
+        }
+
+        for &version in &[
+            "v1.2.3",
+            "v1.2.3-pre",
+            "v1.2.0-20191109021931-daa7c04131f5",
+            "v1.2.0-0.20191109021931-daa7c04131f5",
+            "v1.2.3-pre.1.20191109021931-daa7c04131f5",
+            "v0.0.0-2019110902193-daa7c04131f5",
+            "v0.0.0-20191309021931-daa7c04131f5",
+            "v0.0.0-20191109021931-",
+            "v0.0.0-20191109021931-daa7c04131f5-1",
+        ] {
+            assert!(parse(version).pseudo().is_none(), "{}", version);
+        }
+    }
+}
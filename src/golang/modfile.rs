@@ -0,0 +1,524 @@
+//! Parse `go.mod` files.
+//!
+//! Only the `module`, `go`, `require`, `replace` and `exclude` directives are
+//! recorded. Other known directives (`toolchain`, `retract`, `godebug`,
+//! `tool` and `ignore`) are skipped.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::semver::InvalidSemver;
+use super::GoVersion;
+
+/// Directives that accept the block syntax, `directive ( ... )`.
+const BLOCK_DIRECTIVES: &[&str] = &[
+    "require", "replace", "exclude", "retract", "godebug", "tool", "ignore",
+];
+
+/// Known directives that are not recorded.
+const SKIPPED_DIRECTIVES: &[&str] = &["toolchain", "retract", "godebug", "tool", "ignore"];
+
+/// Error for a line of a `go.mod` file.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidDirective {
+    /// The directive is not known.
+    UnknownDirective(String),
+    /// The directive doesn't accept the block syntax.
+    UnexpectedBlock,
+    /// A block is not closed, or a `)` is outside of a block.
+    UnbalancedBlock,
+    /// The directive has too few or too many arguments.
+    WrongArgumentCount,
+    /// The `module` or `go` directive is repeated.
+    Duplicate,
+    /// A module path is invalid.
+    InvalidPath,
+    /// A version is invalid.
+    InvalidVersion(InvalidSemver),
+    /// A replacement without a version is not a directory path.
+    InvalidReplacement,
+    /// A quoted string is not terminated.
+    UnterminatedString,
+}
+
+impl From<InvalidSemver> for InvalidDirective {
+    fn from(error: InvalidSemver) -> InvalidDirective {
+        InvalidDirective::InvalidVersion(error)
+    }
+}
+
+/// Error for the `go.mod` parser.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidModFile {
+    line: usize,
+    error: InvalidDirective,
+}
+
+impl InvalidModFile {
+    /// The line number of the error, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The error on that line.
+    pub fn error(&self) -> &InvalidDirective {
+        &self.error
+    }
+}
+
+/// A module at a given version, e.g. `golang.org/x/mod@v0.14.0`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ModuleVersion {
+    path: String,
+    version: GoVersion,
+}
+
+impl ModuleVersion {
+    /// Pair a module path with a version.
+    pub fn new(path: &str, version: GoVersion) -> ModuleVersion {
+        ModuleVersion { path: path.to_owned(), version }
+    }
+
+    /// The module path, e.g. `golang.org/x/mod`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The version, e.g. `v0.14.0`.
+    pub fn version(&self) -> &GoVersion {
+        &self.version
+    }
+
+    fn parse(path: &str, version: &str) -> Result<ModuleVersion, InvalidDirective> {
+        check_path(path)?;
+        Ok(ModuleVersion::new(path, GoVersion::parse(version)?))
+    }
+}
+
+impl fmt::Display for ModuleVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}@{}", self.path, self.version)
+    }
+}
+
+/// A `require` directive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Require {
+    module: ModuleVersion,
+    indirect: bool,
+}
+
+impl Require {
+    /// The required module and its minimum version.
+    pub fn module(&self) -> &ModuleVersion {
+        &self.module
+    }
+
+    /// Whether the requirement is marked with an `// indirect` comment.
+    pub fn is_indirect(&self) -> bool {
+        self.indirect
+    }
+}
+
+/// What a `replace` directive substitutes a module with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplaceTarget {
+    /// Another module, or the same module at another version.
+    Module(ModuleVersion),
+    /// A local directory, e.g. `../fork`.
+    Directory(String),
+}
+
+/// A `replace` directive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replace {
+    path: String,
+    version: Option<GoVersion>,
+    target: ReplaceTarget,
+}
+
+impl Replace {
+    /// The path of the replaced module.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The replaced version, or `None` if all versions are replaced.
+    pub fn version(&self) -> Option<&GoVersion> {
+        self.version.as_ref()
+    }
+
+    /// What the module is replaced with.
+    pub fn target(&self) -> &ReplaceTarget {
+        &self.target
+    }
+
+    /// Whether this directive replaces the given module.
+    pub fn applies_to(&self, module: &ModuleVersion) -> bool {
+        self.path == module.path
+            && self.version.as_ref().is_none_or(|v| v.to_string() == module.version.to_string())
+    }
+}
+
+/// A parsed `go.mod` file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModFile {
+    module: Option<String>,
+    go: Option<String>,
+    requires: Vec<Require>,
+    replaces: Vec<Replace>,
+    excludes: Vec<ModuleVersion>,
+}
+
+impl ModFile {
+    /// Parse the contents of a `go.mod` file.
+    pub fn parse(contents: &str) -> Result<ModFile, InvalidModFile> {
+        let mut modfile = ModFile::default();
+        // The directive and the first line of the current block
+        let mut block: Option<(String, usize)> = None;
+        for (index, line) in contents.lines().enumerate() {
+            let number = index + 1;
+            let error = |error| InvalidModFile { line: number, error };
+            let (tokens, comment) = tokenize(line).map_err(error)?;
+            let tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+            match (block.as_ref(), tokens.as_slice()) {
+                (_, []) => {}
+                (Some(_), [")"]) => block = None,
+                (Some((directive, _)), arguments) => {
+                    modfile.directive(directive, arguments, comment).map_err(error)?;
+                }
+                (None, [directive, "("]) => {
+                    if !BLOCK_DIRECTIVES.contains(directive) {
+                        return Err(error(InvalidDirective::UnexpectedBlock));
+                    }
+                    block = Some((directive.to_string(), number));
+                }
+                (None, [directive, "(", ")"]) => {
+                    if !BLOCK_DIRECTIVES.contains(directive) {
+                        return Err(error(InvalidDirective::UnexpectedBlock));
+                    }
+                }
+                (None, [directive, arguments @ ..]) => {
+                    modfile.directive(directive, arguments, comment).map_err(error)?;
+                }
+            }
+        }
+        match block {
+            Some((_, line)) => Err(InvalidModFile { line, error: InvalidDirective::UnbalancedBlock }),
+            None => Ok(modfile),
+        }
+    }
+
+    /// The path of the module, from the `module` directive.
+    pub fn module(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    /// The minimum Go version, from the `go` directive, e.g. `1.21`.
+    pub fn go(&self) -> Option<&str> {
+        self.go.as_deref()
+    }
+
+    /// The `require` directives, in order.
+    pub fn requires(&self) -> &[Require] {
+        &self.requires
+    }
+
+    /// The `replace` directives, in order.
+    pub fn replaces(&self) -> &[Replace] {
+        &self.replaces
+    }
+
+    /// The module versions from the `exclude` directives, in order.
+    pub fn excludes(&self) -> &[ModuleVersion] {
+        &self.excludes
+    }
+
+    /// Whether a module version is excluded.
+    pub fn is_excluded(&self, module: &ModuleVersion) -> bool {
+        self.excludes.iter().any(|e| {
+            e.path == module.path && e.version.to_string() == module.version.to_string()
+        })
+    }
+
+    /// Find the replacement of a module, if any.
+    ///
+    /// A replacement of the specific version wins over one of all versions.
+    pub fn replacement(&self, module: &ModuleVersion) -> Option<&ReplaceTarget> {
+        let mut matching = self.replaces.iter().filter(|r| r.applies_to(module));
+        let first = matching.next()?;
+        let replace = if first.version.is_some() {
+            first
+        } else {
+            matching.find(|r| r.version.is_some()).unwrap_or(first)
+        };
+        Some(&replace.target)
+    }
+
+    fn directive(
+        &mut self,
+        directive: &str,
+        arguments: &[&str],
+        comment: Option<&str>,
+    ) -> Result<(), InvalidDirective> {
+        match (directive, arguments) {
+            ("(", _) | (")", _) => return Err(InvalidDirective::UnbalancedBlock),
+            (_, args) if args.contains(&"(") || args.contains(&")") => {
+                return Err(InvalidDirective::UnbalancedBlock);
+            }
+            ("module", &[path]) => {
+                if self.module.is_some() {
+                    return Err(InvalidDirective::Duplicate);
+                }
+                check_path(path)?;
+                self.module = Some(path.to_owned());
+            }
+            ("go", &[version]) => {
+                if self.go.is_some() {
+                    return Err(InvalidDirective::Duplicate);
+                }
+                // e.g. 1.21, 1.21.0 or 1.21rc1
+                let valid = version.starts_with(|c: char| c.is_ascii_digit())
+                    && version.split('.').all(|n| !n.is_empty() && n.bytes().all(|c| c.is_ascii_alphanumeric()));
+                if !valid {
+                    return Err(InvalidDirective::InvalidVersion(InvalidSemver::InvalidCharacter));
+                }
+                self.go = Some(version.to_owned());
+            }
+            ("require", &[path, version]) => {
+                let indirect = comment.is_some_and(|c| c == "indirect" || c.starts_with("indirect;"));
+                let module = ModuleVersion::parse(path, version)?;
+                self.requires.push(Require { module, indirect });
+            }
+            ("exclude", &[path, version]) => {
+                self.excludes.push(ModuleVersion::parse(path, version)?);
+            }
+            ("replace", arguments) => {
+                let arrow = arguments.iter().position(|&a| a == "=>");
+                let (old, new) = match arrow {
+                    Some(i) => (&arguments[.. i], &arguments[i + 1 ..]),
+                    None => return Err(InvalidDirective::WrongArgumentCount),
+                };
+                let (path, version) = match *old {
+                    [path] => (path, None),
+                    [path, version] => (path, Some(GoVersion::parse(version)?)),
+                    _ => return Err(InvalidDirective::WrongArgumentCount),
+                };
+                check_path(path)?;
+                let target = match *new {
+                    [directory] if is_directory(directory) => {
+                        ReplaceTarget::Directory(directory.to_owned())
+                    }
+                    [_] => return Err(InvalidDirective::InvalidReplacement),
+                    [path, version] => ReplaceTarget::Module(ModuleVersion::parse(path, version)?),
+                    _ => return Err(InvalidDirective::WrongArgumentCount),
+                };
+                self.replaces.push(Replace { path: path.to_owned(), version, target });
+            }
+            ("module", _) | ("go", _) | ("require", _) | ("exclude", _) => {
+                return Err(InvalidDirective::WrongArgumentCount);
+            }
+            (directive, _) if SKIPPED_DIRECTIVES.contains(&directive) => {}
+            (directive, _) => {
+                return Err(InvalidDirective::UnknownDirective(directive.to_owned()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ModFile {
+    type Error = InvalidModFile;
+
+    fn try_from(contents: &'a str) -> Result<ModFile, InvalidModFile> {
+        ModFile::parse(contents)
+    }
+}
+
+/// Check a module path.
+///
+/// This is looser than Go's rules, which also restrict the first element
+/// and forbid some file names.
+fn check_path(path: &str) -> Result<(), InvalidDirective> {
+    let valid = !path.is_empty()
+        && path.split('/').all(|element| {
+            !element.is_empty()
+                && !element.starts_with('.')
+                && !element.ends_with('.')
+                && element.bytes().all(|c| c.is_ascii_alphanumeric() || b"-._~+".contains(&c))
+        })
+        && !path.starts_with('-');
+    if valid { Ok(()) } else { Err(InvalidDirective::InvalidPath) }
+}
+
+/// Whether the target of a `replace` directive is a directory path.
+fn is_directory(path: &str) -> bool {
+    path == "." || path == ".." || path.starts_with("./") || path.starts_with("../")
+        || path.starts_with('/')
+}
+
+/// Split a line into tokens, and the comment if any.
+///
+/// Tokens are separated by whitespace. Parentheses are tokens on their own,
+/// and strings can be quoted with `"` (with escapes) or with backquotes.
+fn tokenize(line: &str) -> Result<(Vec<String>, Option<&str>), InvalidDirective> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("//") {
+            return Ok((tokens, Some(comment.trim())));
+        }
+        let mut chars = rest.char_indices();
+        let end = match chars.next().unwrap().1 {
+            '(' | ')' => {
+                tokens.push(rest[.. 1].to_owned());
+                1
+            }
+            '`' => {
+                let end = rest[1 ..].find('`').ok_or(InvalidDirective::UnterminatedString)?;
+                tokens.push(rest[1 .. end + 1].to_owned());
+                end + 2
+            }
+            '"' => {
+                let mut token = String::new();
+                let mut end = None;
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '"' => {
+                            end = Some(i + 1);
+                            break;
+                        }
+                        '\\' => match chars.next() {
+                            Some((_, c)) => token.push(c),
+                            None => break,
+                        },
+                        c => token.push(c),
+                    }
+                }
+                tokens.push(token);
+                end.ok_or(InvalidDirective::UnterminatedString)?
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    .unwrap_or(rest.len());
+                let end = rest[.. end].find("//").unwrap_or(end);
+                tokens.push(rest[.. end].to_owned());
+                end
+            }
+        };
+        rest = rest[end ..].trim_start();
+    }
+    Ok((tokens, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semver::InvalidSemver;
+    use super::super::GoVersion;
+    use super::{InvalidDirective, ModFile, ModuleVersion, ReplaceTarget};
+
+    fn module(path: &str, version: &str) -> ModuleVersion {
+        ModuleVersion::new(path, GoVersion::parse(version).unwrap())
+    }
+
+    #[test]
+    fn test_parse() {
+        let modfile = ModFile::parse(
+            r#"// Example module
+module example.com/app
+
+go 1.21
+
+toolchain go1.21.5
+
+require golang.org/x/mod v0.14.0
+
+require (
+	github.com/pkg/errors v0.9.1 // indirect
+	"golang.org/x/text" v0.3.0
+	gopkg.in/yaml.v2 v2.4.0 // a comment
+)
+
+exclude golang.org/x/text v0.3.1
+
+replace (
+	golang.org/x/net => golang.org/x/net v0.1.0
+	github.com/pkg/errors v0.9.1 => ../errors
+)
+
+retract [v1.0.0, v1.0.5] // broken
+"#,
+        ).unwrap();
+        assert_eq!(modfile.module(), Some("example.com/app"));
+        assert_eq!(modfile.go(), Some("1.21"));
+
+        let requires: Vec<(String, bool)> = modfile
+            .requires()
+            .iter()
+            .map(|r| (r.module().to_string(), r.is_indirect()))
+            .collect();
+        assert_eq!(
+            requires,
+            vec![
+                ("golang.org/x/mod@v0.14.0".to_owned(), false),
+                ("github.com/pkg/errors@v0.9.1".to_owned(), true),
+                ("golang.org/x/text@v0.3.0".to_owned(), false),
+                ("gopkg.in/yaml.v2@v2.4.0".to_owned(), false),
+            ],
+        );
+        assert_eq!(modfile.excludes(), &[module("golang.org/x/text", "v0.3.1")]);
+        assert!(modfile.is_excluded(&module("golang.org/x/text", "v0.3.1")));
+        assert!(!modfile.is_excluded(&module("golang.org/x/text", "v0.3.0")));
+
+        assert_eq!(modfile.replaces().len(), 2);
+        assert_eq!(modfile.replaces()[0].path(), "golang.org/x/net");
+        assert_eq!(modfile.replaces()[0].version(), None);
+        assert_eq!(
+            modfile.replacement(&module("golang.org/x/net", "v0.0.1")),
+            Some(&ReplaceTarget::Module(module("golang.org/x/net", "v0.1.0"))),
+        );
+        assert_eq!(
+            modfile.replacement(&module("github.com/pkg/errors", "v0.9.1")),
+            Some(&ReplaceTarget::Directory("../errors".to_owned())),
+        );
+        assert_eq!(modfile.replacement(&module("github.com/pkg/errors", "v0.9.0")), None);
+    }
+
+    #[test]
+    fn test_replacement_precedence() {
+        let modfile = ModFile::parse(
+            "replace example.com/a => ./a\nreplace example.com/a v1.0.0 => ./a1\n",
+        ).unwrap();
+        assert_eq!(
+            modfile.replacement(&module("example.com/a", "v1.0.0")),
+            Some(&ReplaceTarget::Directory("./a1".to_owned())),
+        );
+        assert_eq!(
+            modfile.replacement(&module("example.com/a", "v1.1.0")),
+            Some(&ReplaceTarget::Directory("./a".to_owned())),
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        for &(contents, line, ref error) in &[
+            ("module a\nmodule b", 2, InvalidDirective::Duplicate),
+            ("module", 1, InvalidDirective::WrongArgumentCount),
+            ("require a.com/b", 1, InvalidDirective::WrongArgumentCount),
+            ("require a.com/b 1.0.0", 1, InvalidDirective::InvalidVersion(InvalidSemver::InvalidCharacter)),
+            ("require a..com/b/ v1.0.0", 1, InvalidDirective::InvalidPath),
+            ("require (\na.com/b v1.0.0\n", 1, InvalidDirective::UnbalancedBlock),
+            (")", 1, InvalidDirective::UnbalancedBlock),
+            ("module (\n)", 1, InvalidDirective::UnexpectedBlock),
+            ("replace a.com/b => a.com/c", 1, InvalidDirective::InvalidReplacement),
+            ("replace a.com/b v1.0.0", 1, InvalidDirective::WrongArgumentCount),
+            ("require \"a.com/b v1.0.0", 1, InvalidDirective::UnterminatedString),
+            ("\nfoo bar", 2, InvalidDirective::UnknownDirective("foo".to_owned())),
+        ] {
+            let invalid = ModFile::parse(contents).unwrap_err();
+            assert_eq!(invalid.line(), line, "{}", contents);
+            assert_eq!(invalid.error(), error, "{}", contents);
+        }
+    }
+}
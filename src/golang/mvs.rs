@@ -0,0 +1,215 @@
+//! Minimal Version Selection over an in-memory module graph.
+//!
+//! The build list of a main module holds, for each module path reachable
+//! from its requirements, the highest version required. The `replace` and
+//! `exclude` directives of the main module apply: a replaced module's
+//! requirements are those of its replacement, and requirements on an
+//! excluded version are ignored, as since Go 1.16.
+
+use std::collections::{HashMap, HashSet};
+
+use super::modfile::{ModFile, ModuleVersion, ReplaceTarget};
+
+/// Error for Minimal Version Selection.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MvsError {
+    /// The requirements of a module version are not in the graph.
+    MissingModule(ModuleVersion),
+    /// The requirements of a replacement directory are not in the graph.
+    MissingDirectory(String),
+}
+
+/// The requirements of every known module version.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleGraph {
+    // Keyed by path and exact version, as `v2.0.0+incompatible` and `v2.0.0`
+    // are different module versions
+    modules: HashMap<(String, String), Vec<ModuleVersion>>,
+    directories: HashMap<String, Vec<ModuleVersion>>,
+}
+
+impl ModuleGraph {
+    /// Create an empty graph.
+    pub fn new() -> ModuleGraph {
+        ModuleGraph::default()
+    }
+
+    /// Add the requirements of a module version.
+    pub fn add(&mut self, module: ModuleVersion, requires: Vec<ModuleVersion>) {
+        self.modules.insert(key(&module), requires);
+    }
+
+    /// Add the requirements of a module version from its `go.mod` file.
+    pub fn add_mod_file(&mut self, module: ModuleVersion, modfile: &ModFile) {
+        let requires = modfile.requires().iter().map(|r| r.module().clone()).collect();
+        self.add(module, requires);
+    }
+
+    /// Add the requirements of a directory used as a replacement.
+    pub fn add_directory(&mut self, directory: &str, requires: Vec<ModuleVersion>) {
+        self.directories.insert(directory.to_owned(), requires);
+    }
+
+    /// The requirements of a module version, if known.
+    pub fn requires(&self, module: &ModuleVersion) -> Option<&[ModuleVersion]> {
+        self.modules.get(&key(module)).map(|r| r.as_slice())
+    }
+
+    /// Compute the build list of a main module, sorted by path.
+    ///
+    /// The main module itself is not part of the list. Replaced modules are
+    /// listed with their original version.
+    pub fn build_list(&self, main: &ModFile) -> Result<Vec<ModuleVersion>, MvsError> {
+        let mut selected: HashMap<&str, &ModuleVersion> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut queue: Vec<&ModuleVersion> = main.requires().iter().map(|r| r.module()).collect();
+        queue.retain(|module| !main.is_excluded(module) && visited.insert(key(module)));
+
+        while let Some(module) = queue.pop() {
+            let current = selected.entry(module.path()).or_insert(module);
+            if module.version() > current.version() {
+                *current = module;
+            }
+            let requires = match main.replacement(module) {
+                Some(ReplaceTarget::Module(target)) => self
+                    .requires(target)
+                    .ok_or_else(|| MvsError::MissingModule(target.clone()))?,
+                Some(ReplaceTarget::Directory(directory)) => self
+                    .directories
+                    .get(directory)
+                    .ok_or_else(|| MvsError::MissingDirectory(directory.clone()))?,
+                None => self
+                    .requires(module)
+                    .ok_or_else(|| MvsError::MissingModule(module.clone()))?,
+            };
+            for require in requires {
+                // The main module can't be required at another version
+                if Some(require.path()) != main.module()
+                    && !main.is_excluded(require)
+                    && visited.insert(key(require))
+                {
+                    queue.push(require);
+                }
+            }
+        }
+
+        let mut list: Vec<ModuleVersion> = selected.into_values().cloned().collect();
+        list.sort_by(|a, b| a.path().cmp(b.path()));
+        Ok(list)
+    }
+}
+
+fn key(module: &ModuleVersion) -> (String, String) {
+    (module.path().to_owned(), module.version().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::modfile::{ModFile, ModuleVersion};
+    use super::super::GoVersion;
+    use super::{ModuleGraph, MvsError};
+
+    fn module(path: &str, version: &str) -> ModuleVersion {
+        ModuleVersion::new(path, GoVersion::parse(version).unwrap())
+    }
+
+    fn modules(list: &[(&str, &str)]) -> Vec<ModuleVersion> {
+        list.iter().map(|&(path, version)| module(path, version)).collect()
+    }
+
+    /// The example graph of https://research.swtch.com/vgo-mvs
+    fn graph() -> ModuleGraph {
+        let mut graph = ModuleGraph::new();
+        for &((path, version), requires) in &[
+            (("b", "v1.1.0"), &[("d", "v1.1.0")][..]),
+            (("b", "v1.2.0"), &[("d", "v1.3.0")]),
+            (("c", "v1.1.0"), &[]),
+            (("c", "v1.2.0"), &[("d", "v1.4.0")]),
+            (("c", "v1.3.0"), &[("f", "v1.1.0")]),
+            (("d", "v1.1.0"), &[("e", "v1.1.0")]),
+            (("d", "v1.2.0"), &[("e", "v1.1.0")]),
+            (("d", "v1.3.0"), &[("e", "v1.2.0")]),
+            (("d", "v1.4.0"), &[("e", "v1.2.0")]),
+            (("e", "v1.1.0"), &[]),
+            (("e", "v1.2.0"), &[]),
+            (("e", "v1.3.0"), &[]),
+            (("f", "v1.1.0"), &[("g", "v1.1.0")]),
+            (("g", "v1.1.0"), &[("f", "v1.1.0")]),
+        ] {
+            graph.add(module(path, version), modules(requires));
+        }
+        graph
+    }
+
+    #[test]
+    fn test_build_list() {
+        let graph = graph();
+        let main = ModFile::parse("module a\nrequire b v1.2.0\nrequire c v1.2.0\n").unwrap();
+        assert_eq!(
+            graph.build_list(&main).unwrap(),
+            modules(&[("b", "v1.2.0"), ("c", "v1.2.0"), ("d", "v1.4.0"), ("e", "v1.2.0")]),
+        );
+
+        // Cycles are fine
+        let main = ModFile::parse("module a\nrequire c v1.3.0\n").unwrap();
+        assert_eq!(
+            graph.build_list(&main).unwrap(),
+            modules(&[("c", "v1.3.0"), ("f", "v1.1.0"), ("g", "v1.1.0")]),
+        );
+
+        let main = ModFile::parse("module a\n").unwrap();
+        assert_eq!(graph.build_list(&main).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_exclude() {
+        let graph = graph();
+        let main = ModFile::parse(
+            "module a\nrequire (\nb v1.2.0\nc v1.2.0\n)\nexclude d v1.4.0\n",
+        ).unwrap();
+        assert_eq!(
+            graph.build_list(&main).unwrap(),
+            modules(&[("b", "v1.2.0"), ("c", "v1.2.0"), ("d", "v1.3.0"), ("e", "v1.2.0")]),
+        );
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut graph = graph();
+        let main = ModFile::parse(
+            "module a\nrequire (\nb v1.2.0\nc v1.2.0\n)\nreplace c v1.2.0 => c v1.3.0\n",
+        ).unwrap();
+        assert_eq!(
+            graph.build_list(&main).unwrap(),
+            modules(&[
+                ("b", "v1.2.0"), ("c", "v1.2.0"), ("d", "v1.3.0"), ("e", "v1.2.0"),
+                ("f", "v1.1.0"), ("g", "v1.1.0"),
+            ]),
+        );
+
+        let main = ModFile::parse("module a\nrequire b v1.2.0\nreplace d => ../d\n").unwrap();
+        assert_eq!(
+            graph.build_list(&main).unwrap_err(),
+            MvsError::MissingDirectory("../d".to_owned()),
+        );
+        graph.add_directory("../d", modules(&[("e", "v1.3.0")]));
+        assert_eq!(
+            graph.build_list(&main).unwrap(),
+            modules(&[("b", "v1.2.0"), ("d", "v1.3.0"), ("e", "v1.3.0")]),
+        );
+    }
+
+    #[test]
+    fn test_missing() {
+        let mut graph = ModuleGraph::new();
+        let main = ModFile::parse("module a\nrequire b v1.0.0\n").unwrap();
+        assert_eq!(
+            graph.build_list(&main).unwrap_err(),
+            MvsError::MissingModule(module("b", "v1.0.0")),
+        );
+
+        let b = ModFile::parse("module b\nrequire a v0.1.0\n").unwrap();
+        graph.add_mod_file(module("b", "v1.0.0"), &b);
+        assert_eq!(graph.build_list(&main).unwrap(), modules(&[("b", "v1.0.0")]));
+    }
+}
@@ -0,0 +1,196 @@
+//! Implement the Haskell Package Versioning Policy (PVP), and Cabal's version
+//! ranges.
+//!
+//! Versions are lists of numbers of any length, e.g. `1.2.3.4`, compared
+//! lexicographically so that `1.2 < 1.2.0 < 1.2.1`. The first two numbers
+//! form the major version, and a breaking change must increase it: going
+//! from `1.2.3` to `1.3.0` may break dependents, while going to `1.2.4` must
+//! not.
+//!
+//! Version ranges (`^>=1.2.3 || (>=2.0 && <2.1)`) can be parsed, checked,
+//! and simplified into a union of disjoint intervals.
+
+mod range;
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{InvalidVersion, SimpleVersion, Version};
+
+pub use self::range::{InvalidRange, Operator, VersionInterval, VersionRange};
+
+/// A PVP version, e.g. `1.2.3`.
+///
+/// Only numbers and dots are allowed, and numbers can't have leading zeros.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PvpVersion {
+    components: Vec<u64>,
+    version: SimpleVersion,
+}
+
+impl PvpVersion {
+    /// Parse a version, e.g. `1.2.3`.
+    pub fn parse(string: &str) -> Result<PvpVersion, InvalidVersion> {
+        let version = SimpleVersion::try_from(string.to_owned())?;
+        // `SimpleVersion` only rejects a leading dot
+        let components = string
+            .split('.')
+            .map(|c| match c {
+                "" => Err(InvalidVersion::EmptyField),
+                c => c.parse().map_err(|_| InvalidVersion::NumberTooLarge),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(PvpVersion { components, version })
+    }
+
+    /// Build a version from its numbers, of which there must be at least
+    /// one.
+    fn from_components(components: Vec<u64>) -> PvpVersion {
+        let string: Vec<String> = components.iter().map(|c| c.to_string()).collect();
+        let version = SimpleVersion::try_from(string.join(".")).unwrap();
+        PvpVersion { components, version }
+    }
+
+    /// The numbers, e.g. `[1, 2, 3]` for `1.2.3`.
+    pub fn components(&self) -> &[u64] {
+        &self.components
+    }
+
+    /// The major version, `A.B`, missing numbers being `0`.
+    pub fn major(&self) -> (u64, u64) {
+        (self.components[0], self.components.get(1).cloned().unwrap_or(0))
+    }
+
+    /// The minor version, `C`, `0` if missing.
+    pub fn minor(&self) -> u64 {
+        self.components.get(2).cloned().unwrap_or(0)
+    }
+
+    /// Whether upgrading from this version to `newer` may break dependents,
+    /// i.e. the major versions differ, or `newer` is actually older.
+    ///
+    /// Adding functions in a minor version can also break modules that
+    /// import this package without an import list, which the PVP leaves to
+    /// dependents to avoid.
+    pub fn is_breaking_upgrade(&self, newer: &PvpVersion) -> bool {
+        newer < self || self.major() != newer.major()
+    }
+
+    /// The least version with a greater major version, e.g. `1.3` for
+    /// `1.2.3`, `1.1` for `1`, and `2` for `1.18446744073709551615`.
+    ///
+    /// Returns `None` if there is no such version, because both numbers are
+    /// the largest possible.
+    pub fn next_major(&self) -> Option<PvpVersion> {
+        let (a, b) = self.major();
+        PvpVersion::following(vec![a, b])
+    }
+
+    /// The least version that doesn't start with the given numbers, e.g.
+    /// `1.3` for `1.2`, or `None` if all of them are the largest possible.
+    fn following(mut components: Vec<u64>) -> Option<PvpVersion> {
+        while let Some(last) = components.pop() {
+            if let Some(next) = last.checked_add(1) {
+                components.push(next);
+                return Some(PvpVersion::from_components(components));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> TryFrom<&'a str> for PvpVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<PvpVersion, InvalidVersion> {
+        PvpVersion::parse(string)
+    }
+}
+
+impl AsRef<SimpleVersion> for PvpVersion {
+    fn as_ref(&self) -> &SimpleVersion {
+        &self.version
+    }
+}
+
+impl<'a> From<&'a PvpVersion> for Version {
+    /// PVP versions are simple versions, whose order is the same.
+    fn from(pvp: &'a PvpVersion) -> Version {
+        let version: &Version = pvp.version.as_ref();
+        version.clone()
+    }
+}
+
+impl fmt::Display for PvpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", AsRef::<Version>::as_ref(&self.version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InvalidVersion, Version};
+    use super::PvpVersion;
+
+    fn parse(s: &str) -> PvpVersion {
+        PvpVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let version = parse("1.2.3.4");
+        assert_eq!(version.components(), &[1, 2, 3, 4]);
+        assert_eq!(version.major(), (1, 2));
+        assert_eq!(version.minor(), 3);
+        assert_eq!(version.to_string(), "1.2.3.4");
+        assert_eq!(parse("1").major(), (1, 0));
+        assert_eq!(parse("1.2").minor(), 0);
+
+        for &(version, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            ("1.", InvalidVersion::EmptyField),
+            (".1", InvalidVersion::EmptyField),
+            ("1..2", InvalidVersion::EmptyField),
+            ("01.2", InvalidVersion::LeadingZero),
+            ("1.2a", InvalidVersion::InvalidCharacter),
+            ("1.2-3", InvalidVersion::InvalidCharacter),
+            ("1.*", InvalidVersion::InvalidCharacter),
+            ("99999999999999999999", InvalidVersion::NumberTooLarge),
+        ] {
+            assert_eq!(&PvpVersion::parse(version).unwrap_err(), error, "{}", version);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let versions = ["0", "0.0", "0.1", "1", "1.0", "1.0.0", "1.0.1", "1.2", "1.10", "2"];
+        for (i, a) in versions.iter().enumerate() {
+            for b in &versions[i + 1 ..] {
+                assert!(parse(a) < parse(b), "{} < {}", a, b);
+                assert!(Version::from(&parse(a)) < Version::from(&parse(b)), "{} < {}", a, b);
+            }
+        }
+        assert_eq!(&*Version::from(&parse("1.2.3")), "1.2.3");
+    }
+
+    #[test]
+    fn test_breaking_upgrade() {
+        for &(old, new, breaking) in &[
+            ("1.2.3", "1.2.4", false),
+            ("1.2.3", "1.2.3.1", false),
+            ("1.2.3", "1.2", true),
+            ("1.2.3", "1.2.1", true),
+            ("1.2.3", "1.2.3", false),
+            ("1", "1.0.5", false),
+            ("1.2.3", "1.3", true),
+            ("1.2.3", "2.2.3", true),
+            ("0.1", "0.2", true),
+        ] {
+            assert_eq!(parse(old).is_breaking_upgrade(&parse(new)), breaking, "{} -> {}", old, new);
+        }
+        assert_eq!(parse("1.2.3").next_major(), Some(parse("1.3")));
+        assert_eq!(parse("1").next_major(), Some(parse("1.1")));
+        assert_eq!(parse("1.18446744073709551615.2").next_major(), Some(parse("2")));
+        assert_eq!(parse("18446744073709551615.18446744073709551615").next_major(), None);
+    }
+}
@@ -0,0 +1,559 @@
+//! Cabal's version ranges, e.g. `^>=1.2.3 || (>=2.0 && <2.1)`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+use crate::InvalidVersion;
+use super::PvpVersion;
+
+/// Error for the version range parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidRange {
+    /// A parenthesis, an operator or a version is missing or misplaced.
+    InvalidSyntax,
+    /// The operator is not known, e.g. `=>`, or is used with a wildcard,
+    /// e.g. `>=1.*`.
+    InvalidOperator,
+    /// A version is invalid.
+    InvalidVersion(InvalidVersion),
+}
+
+impl From<InvalidVersion> for InvalidRange {
+    fn from(error: InvalidVersion) -> InvalidRange {
+        InvalidRange::InvalidVersion(error)
+    }
+}
+
+/// A version operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `==`
+    Equal,
+    /// `>`
+    Greater,
+    /// `<`
+    Less,
+    /// `>=`
+    GreaterEqual,
+    /// `<=`
+    LessEqual,
+    /// `^>=`: greater or equal, and less than the next major version, so
+    /// `^>=1.2.3` means `>=1.2.3 && <1.3`.
+    Major,
+}
+
+const OPERATORS: &[(&str, Operator)] = &[
+    ("==", Operator::Equal),
+    (">", Operator::Greater),
+    ("<", Operator::Less),
+    (">=", Operator::GreaterEqual),
+    ("<=", Operator::LessEqual),
+    ("^>=", Operator::Major),
+];
+
+impl Operator {
+    /// Whether `version` satisfies the operator with the given operand.
+    pub fn matches(self, version: &PvpVersion, operand: &PvpVersion) -> bool {
+        let ordering = version.cmp(operand);
+        match self {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::Less => ordering == Ordering::Less,
+            Operator::GreaterEqual => ordering != Ordering::Less,
+            Operator::LessEqual => ordering != Ordering::Greater,
+            Operator::Major => {
+                ordering != Ordering::Less && operand.next_major().is_none_or(|next| *version < next)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(OPERATORS.iter().find(|&&(_, op)| op == *self).unwrap().0)
+    }
+}
+
+/// The version following all the versions that start with the given one,
+/// e.g. `1.3` for `1.2`, if any.
+fn wildcard_end(version: &PvpVersion) -> Option<PvpVersion> {
+    PvpVersion::following(version.components.clone())
+}
+
+/// An interval of versions, e.g. `>=1.2 && <1.3`.
+///
+/// The lower bound is always included or excluded, as no version is less
+/// than `0`; the upper bound may be unbounded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionInterval {
+    lower: Bound<PvpVersion>,
+    upper: Bound<PvpVersion>,
+}
+
+/// Compare lower bounds, which are never unbounded.
+fn compare_lower(a: &Bound<PvpVersion>, b: &Bound<PvpVersion>) -> Ordering {
+    match (a, b) {
+        (Bound::Included(x), Bound::Included(y)) | (Bound::Excluded(x), Bound::Excluded(y)) => x.cmp(y),
+        (Bound::Included(x), Bound::Excluded(y)) => x.cmp(y).then(Ordering::Less),
+        (Bound::Excluded(x), Bound::Included(y)) => x.cmp(y).then(Ordering::Greater),
+        _ => unreachable!(),
+    }
+}
+
+/// Compare upper bounds.
+fn compare_upper(a: &Bound<PvpVersion>, b: &Bound<PvpVersion>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Greater,
+        (_, Bound::Unbounded) => Ordering::Less,
+        (Bound::Included(x), Bound::Included(y)) | (Bound::Excluded(x), Bound::Excluded(y)) => x.cmp(y),
+        (Bound::Included(x), Bound::Excluded(y)) => x.cmp(y).then(Ordering::Greater),
+        (Bound::Excluded(x), Bound::Included(y)) => x.cmp(y).then(Ordering::Less),
+    }
+}
+
+/// Whether versions between an upper bound and the next lower bound exist,
+/// i.e. whether the intervals can't be merged.
+fn is_gap(upper: &Bound<PvpVersion>, lower: &Bound<PvpVersion>) -> bool {
+    match (upper, lower) {
+        (Bound::Unbounded, _) => false,
+        (Bound::Excluded(x), Bound::Excluded(y)) => x <= y,
+        (Bound::Included(x), Bound::Included(y))
+        | (Bound::Included(x), Bound::Excluded(y))
+        | (Bound::Excluded(x), Bound::Included(y)) => x < y,
+        (_, Bound::Unbounded) => unreachable!(),
+    }
+}
+
+impl VersionInterval {
+    fn new(lower: Bound<PvpVersion>, upper: Bound<PvpVersion>) -> Option<VersionInterval> {
+        let empty = match (&lower, &upper) {
+            (_, Bound::Unbounded) => false,
+            (Bound::Included(x), Bound::Included(y)) => x > y,
+            (Bound::Included(x), Bound::Excluded(y))
+            | (Bound::Excluded(x), Bound::Included(y))
+            | (Bound::Excluded(x), Bound::Excluded(y)) => x >= y,
+            (Bound::Unbounded, _) => unreachable!(),
+        };
+        if empty { None } else { Some(VersionInterval { lower, upper }) }
+    }
+
+    /// The interval of all versions.
+    fn any() -> VersionInterval {
+        VersionInterval {
+            lower: Bound::Included(PvpVersion::from_components(vec![0])),
+            upper: Bound::Unbounded,
+        }
+    }
+
+    /// The lower bound, which is never unbounded.
+    pub fn lower(&self) -> Bound<&PvpVersion> {
+        self.lower.as_ref()
+    }
+
+    /// The upper bound.
+    pub fn upper(&self) -> Bound<&PvpVersion> {
+        self.upper.as_ref()
+    }
+
+    /// Whether the version is within the bounds.
+    pub fn contains(&self, version: &PvpVersion) -> bool {
+        (self.lower.as_ref(), self.upper.as_ref()).contains(version)
+    }
+
+    fn intersect(&self, other: &VersionInterval) -> Option<VersionInterval> {
+        let lower = match compare_lower(&self.lower, &other.lower) {
+            Ordering::Less => other.lower.clone(),
+            _ => self.lower.clone(),
+        };
+        let upper = match compare_upper(&self.upper, &other.upper) {
+            Ordering::Greater => other.upper.clone(),
+            _ => self.upper.clone(),
+        };
+        VersionInterval::new(lower, upper)
+    }
+
+    /// The interval as a range, e.g. `>=1.2 && <1.3`.
+    fn to_range(&self) -> VersionRange {
+        if let (Bound::Included(ref v), Bound::Included(ref u)) = (&self.lower, &self.upper) {
+            if u == v {
+                return VersionRange::Comparison(Operator::Equal, v.clone());
+            }
+        }
+        let lower = match self.lower {
+            Bound::Included(ref v) if v.components == [0] => None,
+            Bound::Included(ref v) => Some(VersionRange::Comparison(Operator::GreaterEqual, v.clone())),
+            Bound::Excluded(ref v) => Some(VersionRange::Comparison(Operator::Greater, v.clone())),
+            Bound::Unbounded => unreachable!(),
+        };
+        let upper = match self.upper {
+            Bound::Included(ref v) => Some(VersionRange::Comparison(Operator::LessEqual, v.clone())),
+            Bound::Excluded(ref v) => Some(VersionRange::Comparison(Operator::Less, v.clone())),
+            Bound::Unbounded => None,
+        };
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => VersionRange::Intersection(Box::new(lower), Box::new(upper)),
+            (Some(range), None) | (None, Some(range)) => range,
+            (None, None) => VersionRange::AnyVersion,
+        }
+    }
+}
+
+impl fmt::Display for VersionInterval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_range())
+    }
+}
+
+/// Sort intervals and merge the overlapping or adjacent ones.
+fn normalize(mut intervals: Vec<VersionInterval>) -> Vec<VersionInterval> {
+    intervals.sort_by(|a, b| compare_lower(&a.lower, &b.lower));
+    let mut merged: Vec<VersionInterval> = Vec::new();
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if !is_gap(&last.upper, &interval.lower) => {
+                if compare_upper(&interval.upper, &last.upper) == Ordering::Greater {
+                    last.upper = interval.upper;
+                }
+            }
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+/// A version range, e.g. `^>=1.2.3 || (>=2.0 && <2.1)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionRange {
+    /// `-any`, matching all versions.
+    AnyVersion,
+    /// `-none`, matching no version.
+    NoVersion,
+    /// A comparison, e.g. `>=1.2`.
+    Comparison(Operator, PvpVersion),
+    /// A wildcard, e.g. `==1.2.*`, matching `1.2` and versions starting
+    /// with `1.2.`.
+    Wildcard(PvpVersion),
+    /// `||`
+    Union(Box<VersionRange>, Box<VersionRange>),
+    /// `&&`, which binds tighter than `||`.
+    Intersection(Box<VersionRange>, Box<VersionRange>),
+}
+
+/// A token of a version range.
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Or,
+    And,
+    Open,
+    Close,
+    Operator(&'a str),
+    Word(&'a str),
+}
+
+fn tokenize<'a>(string: &'a str) -> Result<Vec<Token<'a>>, InvalidRange> {
+    let mut tokens = Vec::new();
+    let mut rest = string.trim_start();
+    while !rest.is_empty() {
+        let (token, length) = if rest.starts_with("||") {
+            (Token::Or, 2)
+        } else if rest.starts_with("&&") {
+            (Token::And, 2)
+        } else if rest.starts_with('(') {
+            (Token::Open, 1)
+        } else if rest.starts_with(')') {
+            (Token::Close, 1)
+        } else if rest.starts_with(['<', '>', '=', '^']) {
+            let length = rest.find(|c| !"<>=^".contains(c)).unwrap_or(rest.len());
+            (Token::Operator(&rest[.. length]), length)
+        } else {
+            let length = rest
+                .find(|c: char| c.is_whitespace() || "()&|<>=^".contains(c))
+                .unwrap_or(rest.len());
+            if length == 0 {
+                return Err(InvalidRange::InvalidSyntax);
+            }
+            (Token::Word(&rest[.. length]), length)
+        };
+        tokens.push(token);
+        rest = rest[length ..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the tokens.
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<&Token<'a>> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn union(&mut self) -> Result<VersionRange, InvalidRange> {
+        let mut range = self.intersection()?;
+        while self.tokens.get(self.position) == Some(&Token::Or) {
+            self.position += 1;
+            range = VersionRange::Union(Box::new(range), Box::new(self.intersection()?));
+        }
+        Ok(range)
+    }
+
+    fn intersection(&mut self) -> Result<VersionRange, InvalidRange> {
+        let mut range = self.atom()?;
+        while self.tokens.get(self.position) == Some(&Token::And) {
+            self.position += 1;
+            range = VersionRange::Intersection(Box::new(range), Box::new(self.atom()?));
+        }
+        Ok(range)
+    }
+
+    fn atom(&mut self) -> Result<VersionRange, InvalidRange> {
+        match self.next() {
+            Some(&Token::Open) => {
+                let range = self.union()?;
+                match self.next() {
+                    Some(&Token::Close) => Ok(range),
+                    _ => Err(InvalidRange::InvalidSyntax),
+                }
+            }
+            Some(&Token::Word("-any")) => Ok(VersionRange::AnyVersion),
+            Some(&Token::Word("-none")) => Ok(VersionRange::NoVersion),
+            Some(&Token::Operator(op)) => {
+                let operator = match OPERATORS.iter().find(|&&(o, _)| o == op) {
+                    Some(&(_, operator)) => operator,
+                    None => return Err(InvalidRange::InvalidOperator),
+                };
+                let version = match self.next() {
+                    Some(&Token::Word(version)) => version,
+                    _ => return Err(InvalidRange::InvalidSyntax),
+                };
+                match version.strip_suffix(".*") {
+                    Some(prefix) if operator == Operator::Equal => {
+                        Ok(VersionRange::Wildcard(PvpVersion::parse(prefix)?))
+                    }
+                    Some(_) => Err(InvalidRange::InvalidOperator),
+                    None => Ok(VersionRange::Comparison(operator, PvpVersion::parse(version)?)),
+                }
+            }
+            _ => Err(InvalidRange::InvalidSyntax),
+        }
+    }
+}
+
+impl VersionRange {
+    /// Parse a version range, e.g. `>=1.2 && <1.3`, `==1.2.*`, `^>=1.2.3`,
+    /// `-any` or `-none`, combined with `&&`, `||` and parentheses.
+    pub fn parse(string: &str) -> Result<VersionRange, InvalidRange> {
+        let mut parser = Parser { tokens: tokenize(string)?, position: 0 };
+        let range = parser.union()?;
+        if parser.position != parser.tokens.len() {
+            return Err(InvalidRange::InvalidSyntax);
+        }
+        Ok(range)
+    }
+
+    /// Whether the version matches the range.
+    pub fn contains(&self, version: &PvpVersion) -> bool {
+        match *self {
+            VersionRange::AnyVersion => true,
+            VersionRange::NoVersion => false,
+            VersionRange::Comparison(op, ref operand) => op.matches(version, operand),
+            VersionRange::Wildcard(ref prefix) => version.components.starts_with(&prefix.components),
+            VersionRange::Union(ref a, ref b) => a.contains(version) || b.contains(version),
+            VersionRange::Intersection(ref a, ref b) => a.contains(version) && b.contains(version),
+        }
+    }
+
+    /// The versions matched by the range, as sorted disjoint intervals.
+    ///
+    /// No interval is returned if no version matches.
+    pub fn intervals(&self) -> Vec<VersionInterval> {
+        let zero = || Bound::Included(PvpVersion::from_components(vec![0]));
+        let interval = |lower, upper| VersionInterval::new(lower, upper).into_iter().collect();
+        let exclude = |end: Option<PvpVersion>| end.map_or(Bound::Unbounded, Bound::Excluded);
+        match *self {
+            VersionRange::AnyVersion => vec![VersionInterval::any()],
+            VersionRange::NoVersion => Vec::new(),
+            VersionRange::Comparison(op, ref v) => match op {
+                Operator::Equal => interval(Bound::Included(v.clone()), Bound::Included(v.clone())),
+                Operator::Greater => interval(Bound::Excluded(v.clone()), Bound::Unbounded),
+                Operator::Less => interval(zero(), Bound::Excluded(v.clone())),
+                Operator::GreaterEqual => interval(Bound::Included(v.clone()), Bound::Unbounded),
+                Operator::LessEqual => interval(zero(), Bound::Included(v.clone())),
+                Operator::Major => interval(Bound::Included(v.clone()), exclude(v.next_major())),
+            },
+            VersionRange::Wildcard(ref v) => interval(Bound::Included(v.clone()), exclude(wildcard_end(v))),
+            VersionRange::Union(ref a, ref b) => {
+                let mut intervals = a.intervals();
+                intervals.extend(b.intervals());
+                normalize(intervals)
+            }
+            VersionRange::Intersection(ref a, ref b) => {
+                let b = b.intervals();
+                let intervals = a.intervals()
+                    .iter()
+                    .flat_map(|x| b.iter().filter_map(move |y| x.intersect(y)))
+                    .collect();
+                normalize(intervals)
+            }
+        }
+    }
+
+    /// The equivalent range in canonical form: a union of the disjoint
+    /// intervals, e.g. `>=1.2 && <=1.3` for `^>=1.2 || ==1.3`.
+    pub fn simplify(&self) -> VersionRange {
+        self.intervals()
+            .iter()
+            .map(VersionInterval::to_range)
+            .reduce(|a, b| VersionRange::Union(Box::new(a), Box::new(b)))
+            .unwrap_or(VersionRange::NoVersion)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for VersionRange {
+    type Error = InvalidRange;
+
+    fn try_from(string: &'a str) -> Result<VersionRange, InvalidRange> {
+        VersionRange::parse(string)
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VersionRange::AnyVersion => f.write_str("-any"),
+            VersionRange::NoVersion => f.write_str("-none"),
+            VersionRange::Comparison(op, ref version) => write!(f, "{}{}", op, version),
+            VersionRange::Wildcard(ref version) => write!(f, "=={}.*", version),
+            VersionRange::Union(ref a, ref b) => write!(f, "{} || {}", a, b),
+            VersionRange::Intersection(ref a, ref b) => {
+                // Unions bind less tightly
+                let operand = |range: &VersionRange| match *range {
+                    VersionRange::Union(..) => format!("({})", range),
+                    _ => range.to_string(),
+                };
+                write!(f, "{} && {}", operand(a), operand(b))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::super::PvpVersion;
+    use super::{InvalidRange, VersionRange};
+
+    fn check(range: &str, matching: &[&str], not_matching: &[&str]) {
+        let parsed = VersionRange::parse(range).unwrap();
+        let simplified = parsed.simplify();
+        for &version in matching {
+            let version = PvpVersion::parse(version).unwrap();
+            assert!(parsed.contains(&version), "{} {}", range, version);
+            assert!(simplified.contains(&version), "{} ({}) {}", range, simplified, version);
+        }
+        for &version in not_matching {
+            let version = PvpVersion::parse(version).unwrap();
+            assert!(!parsed.contains(&version), "{} {}", range, version);
+            assert!(!simplified.contains(&version), "{} ({}) {}", range, simplified, version);
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        check("==1.2", &["1.2"], &["1.2.0", "1.1"]);
+        check(">1.2", &["1.2.0", "2"], &["1.2", "1"]);
+        check("<1.2", &["1.1.9", "0"], &["1.2", "1.2.0"]);
+        check(">=1.2 && <1.3", &["1.2", "1.2.9"], &["1.3", "1.1"]);
+        check("<=1.2", &["1.2", "1"], &["1.2.0"]);
+        check("^>=1.2.3", &["1.2.3", "1.2.9.1"], &["1.2.2", "1.3", "2"]);
+        check("^>=1", &["1", "1.0.5"], &["1.1", "0.9"]);
+        check("==1.2.*", &["1.2", "1.2.0", "1.2.99"], &["1.3", "1.1", "1"]);
+        check("==1.*", &["1", "1.99"], &["2", "0.1"]);
+        check("<1 || >=2 && <3", &["0.5", "2.5"], &["1", "3"]);
+        check("(<1 || >=2) && <3", &["0.5", "2.5"], &["1", "3"]);
+        check("^>=1.2 || ^>=2.0", &["1.2.1", "2.0.3"], &["1.3", "2.1"]);
+        check("-any", &["0", "99"], &[]);
+        check("-none", &[], &["0", "1"]);
+        check(">2 && <1", &[], &["0", "1.5", "3"]);
+
+        // The largest numbers have no successor
+        let max = u64::MAX;
+        check(&format!("==1.{}.*", max), &[&format!("1.{}.3", max)], &["2", "1.5"]);
+        check(&format!("^>=1.{}", max), &[&format!("1.{}.3", max)], &["2", "1.5"]);
+        check(&format!("^>={0}.{0}", max), &[&format!("{0}.{0}.3", max)], &["2"]);
+        check(&format!("=={}.*", max), &[&format!("{}.3", max)], &["2"]);
+    }
+
+    #[test]
+    fn test_parse() {
+        for &(range, displayed) in &[
+            (">=1.2&&<1.3", ">=1.2 && <1.3"),
+            ("  ^>= 1.2.3  ", "^>=1.2.3"),
+            ("== 1.2.*", "==1.2.*"),
+            ("(>=1 || <0.5) && <2", "(>=1 || <0.5) && <2"),
+            ("((==1))", "==1"),
+            ("-any || -none", "-any || -none"),
+        ] {
+            assert_eq!(VersionRange::parse(range).unwrap().to_string(), displayed, "{}", range);
+        }
+
+        for &(range, ref error) in &[
+            ("", InvalidRange::InvalidSyntax),
+            ("1.2", InvalidRange::InvalidSyntax),
+            (">=", InvalidRange::InvalidSyntax),
+            (">=1 &&", InvalidRange::InvalidSyntax),
+            ("(>=1", InvalidRange::InvalidSyntax),
+            (">=1)", InvalidRange::InvalidSyntax),
+            (">=1 <2", InvalidRange::InvalidSyntax),
+            ("=>1", InvalidRange::InvalidOperator),
+            ("=1", InvalidRange::InvalidOperator),
+            (">=1.*", InvalidRange::InvalidOperator),
+            (">=1.2a", InvalidRange::InvalidVersion(InvalidVersion::InvalidCharacter)),
+            ("==01", InvalidRange::InvalidVersion(InvalidVersion::LeadingZero)),
+        ] {
+            assert_eq!(&VersionRange::parse(range).unwrap_err(), error, "{}", range);
+        }
+    }
+
+    #[test]
+    fn test_simplify() {
+        for &(range, simplified) in &[
+            ("^>=1.2.3", ">=1.2.3 && <1.3"),
+            ("==1.2.*", ">=1.2 && <1.3"),
+            ("^>=1.2 || ==1.3", ">=1.2 && <=1.3"),
+            (">=1 && <2 || >=1.5 && <3", ">=1 && <3"),
+            ("<1 || >=1", "-any"),
+            ("<=1 || >1", "-any"),
+            ("<1 || >1", "<1 || >1"),
+            (">=1 && <=1", "==1"),
+            (">=1 && <1", "-none"),
+            ("<0", "-none"),
+            ("<=0", "==0"),
+            (">=0", "-any"),
+            ("(>=1 || <0.5) && <2", "<0.5 || >=1 && <2"),
+            (">=2 && <3 || >=1 && <1.5", ">=1 && <1.5 || >=2 && <3"),
+        ] {
+            assert_eq!(VersionRange::parse(range).unwrap().simplify().to_string(), simplified, "{}", range);
+        }
+
+        let intervals = VersionRange::parse("^>=1.2 || >=3").unwrap().intervals();
+        assert_eq!(intervals.len(), 2);
+        assert_eq!(intervals[0].to_string(), ">=1.2 && <1.3");
+        assert_eq!(intervals[1].to_string(), ">=3");
+
+        let intervals = VersionRange::parse("^>=1.18446744073709551615").unwrap().intervals();
+        assert_eq!(intervals[0].to_string(), ">=1.18446744073709551615 && <2");
+        let simplified = VersionRange::parse("==1.18446744073709551615.*").unwrap().simplify();
+        assert_eq!(simplified.to_string(), ">=1.18446744073709551615 && <2");
+        let simplified = VersionRange::parse("==18446744073709551615.*").unwrap().simplify();
+        assert_eq!(simplified.to_string(), ">=18446744073709551615");
+    }
+}
@@ -36,22 +36,34 @@
 //! If you want to parse random version numbers that you can't assume follow
 //! semver, this library is probably what you want.
 
+pub mod alpine;
+pub mod arch;
 mod cmp;
+pub mod composer;
+pub mod conda;
 pub mod debian;
+pub mod gentoo;
+pub mod golang;
+pub mod haskell;
+pub mod maven;
+pub mod nuget;
 pub mod python;
+pub mod ruby;
+pub mod rpm;
 pub mod semver;
 mod utils;
 
 use std::cmp::{Ordering, PartialOrd};
 use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 
-use cmp::{CHAR_ORDER, compare_versions};
+use cmp::{CHAR_ORDER, compare_versions, hash_version};
 use utils::NumChecker;
 
 /// A version number.
-#[derive(Clone, Debug, Hash)]
+#[derive(Clone, Debug)]
 pub struct Version(String);
 
 impl Deref for Version {
@@ -88,6 +100,14 @@ impl Ord for Version {
     }
 }
 
+impl Hash for Version {
+    /// Hash the version consistently with its equality, e.g. `1.01` and
+    /// `1.1` have the same hash.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_version(&self.0, state)
+    }
+}
+
 impl Version {
     pub fn epoch() -> u32 {
         unimplemented!() // TODO: Read epoch (default to 0)
@@ -95,6 +115,8 @@ impl Version {
 }
 
 /// Error for the version parser.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum InvalidVersion {
     /// The version contains invalid characters.
     InvalidCharacter,
@@ -102,8 +124,8 @@ pub enum InvalidVersion {
     LeadingZero,
     /// Empty field (for example, two consecutive dots).
     EmptyField,
-    #[doc(hidden)]
-    __Nonexhaustive,
+    /// A numeric field is too large for the versioning scheme.
+    NumberTooLarge,
 }
 
 impl TryFrom<String> for Version {
@@ -145,7 +167,7 @@ impl TryFrom<String> for SimpleVersion {
                 if num_check == NumChecker::Start {
                     return Err(InvalidVersion::EmptyField);
                 }
-            } else if c < b'0' || b'9' < c {
+            } else if !c.is_ascii_digit() {
                 return Err(InvalidVersion::InvalidCharacter);
             }
             if !num_check.check(c) {
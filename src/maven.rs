@@ -0,0 +1,502 @@
+//! Implement Maven's versioning scheme, as implemented by its
+//! `ComparableVersion` class.
+//!
+//! Versions are split into items on `.` and `-`, and on transitions between
+//! digits and letters. A `-` or a transition starts a sub-list, so
+//! `1.0-alpha-1` is `[1, 0, [alpha, [1]]]`; a transition from letters to
+//! digits after a single letter also expands `a`, `b` and `m` to `alpha`,
+//! `beta` and `milestone`. Comparisons are case-insensitive, and `cr` is an
+//! alias of `rc` while `ga`, `final` and `release` mean the release itself.
+//! Null items (`0`, the release qualifier and empty lists) at the end of a
+//! list are removed, so `1 == 1.0 == 1.0.0 == 1-ga`.
+//!
+//! Items are then compared in order. Numbers are greater than sub-lists,
+//! which are greater than qualifiers. Known qualifiers sort as `alpha < beta
+//! < milestone < rc < snapshot < (release) < sp`, followed by other
+//! qualifiers in alphabetical order. A missing item compares like a null
+//! one, so `1-alpha < 1 < 1-sp < 1.1`; a sub-list is compared to a missing
+//! item by its first non-null item.
+//!
+//! Version ranges (`[1.0,2.0)`) can be parsed, and converted to Debian
+//! version constraints.
+
+mod range;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{InvalidVersion, Version};
+
+pub use self::range::{InvalidRange, Restriction, VersionRange};
+
+const QUALIFIERS: &[&str] = &["alpha", "beta", "milestone", "rc", "snapshot", "", "sp"];
+
+/// The position of the release in `QUALIFIERS`.
+const RELEASE: usize = 5;
+
+/// An item of a parsed version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Item {
+    /// A number, without leading zeros.
+    Number(String),
+    /// A qualifier, with aliases resolved.
+    Qualifier(String),
+    List(Vec<Item>),
+}
+
+impl Item {
+    fn number(digits: &str) -> Item {
+        Item::Number(digits.trim_start_matches('0').to_owned())
+    }
+
+    fn qualifier(string: &str, followed_by_digit: bool) -> Item {
+        let string = match string {
+            "a" if followed_by_digit => "alpha",
+            "b" if followed_by_digit => "beta",
+            "m" if followed_by_digit => "milestone",
+            "ga" | "final" | "release" => "",
+            "cr" => "rc",
+            _ => string,
+        };
+        Item::Qualifier(string.to_owned())
+    }
+
+    fn is_null(&self) -> bool {
+        match *self {
+            Item::Number(ref n) => n.is_empty(),
+            Item::Qualifier(ref q) => q.is_empty(),
+            Item::List(ref items) => items.is_empty(),
+        }
+    }
+
+    /// Compare with a missing item.
+    fn cmp_missing(&self) -> Ordering {
+        match *self {
+            Item::Number(ref n) => if n.is_empty() { Ordering::Equal } else { Ordering::Greater },
+            Item::Qualifier(ref q) => qualifier_rank(q).cmp(&(RELEASE, "")),
+            Item::List(ref items) => {
+                items.iter().map(Item::cmp_missing)
+                    .find(|&o| o != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
+            }
+        }
+    }
+}
+
+/// Known qualifiers sort by position, others after them by name.
+fn qualifier_rank(qualifier: &str) -> (usize, &str) {
+    match QUALIFIERS.iter().position(|&q| q == qualifier) {
+        Some(i) => (i, ""),
+        None => (QUALIFIERS.len(), qualifier),
+    }
+}
+
+impl PartialOrd for Item {
+    fn partial_cmp(&self, other: &Item) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Item {
+    fn cmp(&self, other: &Item) -> Ordering {
+        match (self, other) {
+            (Item::Number(a), Item::Number(b)) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+            (Item::Number(_), _) => Ordering::Greater,
+            (Item::Qualifier(a), Item::Qualifier(b)) => qualifier_rank(a).cmp(&qualifier_rank(b)),
+            (Item::Qualifier(_), _) => Ordering::Less,
+            (Item::List(a), Item::List(b)) => compare_lists(a, b),
+            (Item::List(_), Item::Number(_)) => Ordering::Less,
+            (Item::List(_), Item::Qualifier(_)) => Ordering::Greater,
+        }
+    }
+}
+
+fn compare_lists(a: &[Item], b: &[Item]) -> Ordering {
+    for i in 0 .. a.len().max(b.len()) {
+        let ordering = match (a.get(i), b.get(i)) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(a), None) => a.cmp_missing(),
+            (None, Some(b)) => b.cmp_missing().reverse(),
+            (None, None) => unreachable!(),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Remove the null items at the end of a list, ignoring sub-lists.
+fn normalize(items: &mut Vec<Item>) {
+    let mut i = items.len();
+    while i > 0 {
+        i -= 1;
+        if items[i].is_null() {
+            items.remove(i);
+        } else if let Item::List(_) = items[i] {
+            continue;
+        } else {
+            break;
+        }
+    }
+}
+
+fn parse_items(version: &str) -> Vec<Item> {
+    let version = version.to_lowercase();
+    let parse_item = |is_digit: bool, string: &str| {
+        if is_digit { Item::number(string) } else { Item::qualifier(string, false) }
+    };
+
+    // Each new list is the last item of the previous one
+    let mut stack = vec![Vec::new()];
+    let mut is_digit = false;
+    let mut start = 0;
+    for (i, c) in version.char_indices() {
+        let list = stack.last_mut().unwrap();
+        if c == '.' || c == '-' {
+            list.push(if i == start { Item::number("") } else { parse_item(is_digit, &version[start .. i]) });
+            start = i + 1;
+            if c == '-' {
+                stack.push(Vec::new());
+            }
+        } else if c.is_ascii_digit() {
+            if !is_digit && i > start {
+                list.push(Item::qualifier(&version[start .. i], true));
+                start = i;
+                stack.push(Vec::new());
+            }
+            is_digit = true;
+        } else {
+            if is_digit && i > start {
+                list.push(parse_item(true, &version[start .. i]));
+                start = i;
+                stack.push(Vec::new());
+            }
+            is_digit = false;
+        }
+    }
+    if version.len() > start {
+        stack.last_mut().unwrap().push(parse_item(is_digit, &version[start ..]));
+    }
+
+    let mut items = stack.pop().unwrap();
+    normalize(&mut items);
+    while let Some(mut parent) = stack.pop() {
+        parent.push(Item::List(items));
+        normalize(&mut parent);
+        items = parent;
+    }
+    items
+}
+
+/// A Maven artifact version, e.g. `1.0-beta-2` or `2.1.0-SNAPSHOT`.
+#[derive(Clone, Debug)]
+pub struct MavenVersion {
+    string: String,
+    items: Vec<Item>,
+}
+
+/// Split a timestamped snapshot, e.g. `1.0-20240101.123456-1`, into the
+/// version before the timestamp and the build number.
+fn split_timestamp(version: &str) -> Option<&str> {
+    let is_digits = |s: &str, len: usize| s.len() == len && s.bytes().all(|c| c.is_ascii_digit());
+    let (rest, build) = version.rsplit_once('-')?;
+    let (base, timestamp) = rest.rsplit_once('-')?;
+    let (date, time) = timestamp.split_once('.')?;
+    let valid = !build.is_empty() && build.bytes().all(|c| c.is_ascii_digit())
+        && is_digits(date, 8) && is_digits(time, 6);
+    if valid { Some(base) } else { None }
+}
+
+impl MavenVersion {
+    /// Parse a version.
+    ///
+    /// Any string is a valid Maven version, except that it can't be empty
+    /// and this rejects whitespace and the characters used in version
+    /// ranges, `[](),`.
+    pub fn parse(string: &str) -> Result<MavenVersion, InvalidVersion> {
+        if string.is_empty() {
+            return Err(InvalidVersion::EmptyField);
+        }
+        if string.chars().any(|c| c.is_whitespace() || c.is_control() || "[](),".contains(c)) {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+        Ok(MavenVersion { string: string.to_owned(), items: parse_items(string) })
+    }
+
+    /// Whether this is a snapshot, either ending with `SNAPSHOT` or
+    /// timestamped like `1.0-20240101.123456-1`.
+    pub fn is_snapshot(&self) -> bool {
+        self.string.to_ascii_uppercase().ends_with("SNAPSHOT")
+            || split_timestamp(&self.string).is_some()
+    }
+
+    /// The base version of a snapshot, where the timestamp and build number
+    /// are replaced by `SNAPSHOT`, e.g. `1.0-SNAPSHOT` for
+    /// `1.0-20240101.123456-1`.
+    pub fn base_version(&self) -> MavenVersion {
+        match split_timestamp(&self.string) {
+            Some(base) => {
+                let string = format!("{}-SNAPSHOT", base);
+                MavenVersion { items: parse_items(&string), string }
+            }
+            None => self.clone(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for MavenVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<MavenVersion, InvalidVersion> {
+        MavenVersion::parse(string)
+    }
+}
+
+impl fmt::Display for MavenVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+impl PartialEq<MavenVersion> for MavenVersion {
+    fn eq(&self, other: &MavenVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MavenVersion {}
+
+impl PartialOrd<MavenVersion> for MavenVersion {
+    fn partial_cmp(&self, other: &MavenVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MavenVersion {
+    fn cmp(&self, other: &MavenVersion) -> Ordering {
+        compare_lists(&self.items, &other.items)
+    }
+}
+
+/// An item, once the nested lists are flattened.
+enum Token<'a> {
+    Number(&'a str),
+    Qualifier(&'a str),
+    List,
+}
+
+fn flatten<'a>(items: &'a [Item], tokens: &mut Vec<Token<'a>>) {
+    for item in items {
+        match *item {
+            Item::Number(ref n) => tokens.push(Token::Number(n)),
+            Item::Qualifier(ref q) => tokens.push(Token::Qualifier(q)),
+            Item::List(ref items) => {
+                tokens.push(Token::List);
+                flatten(items, tokens);
+            }
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a MavenVersion> for Version {
+    type Error = InvalidVersion;
+
+    /// Encode the version so that `Version`'s order matches Maven's.
+    ///
+    /// Sub-lists are always the last item of their list, so the items can be
+    /// flattened. Each item is then written as a non-digit part and a number:
+    /// `.N` for numbers, `-0` for the start of a sub-list, and a letter
+    /// giving the rank of the qualifier followed by `0` for qualifiers, with
+    /// the name after `h` for unknown ones, e.g. `1.0-sp-1` becomes
+    /// `0.1-0g0-0.1`.
+    ///
+    /// Whether a version is greater than another one it is a prefix of
+    /// depends on the first non-null item that follows. So each item is
+    /// prefixed with `~` when the items from there compare less than missing
+    /// ones, e.g. `1-alpha` becomes `0.1~-0~a0`.
+    ///
+    /// Maven's order is not transitive when a `0` or a release qualifier is
+    /// followed by other items, or when a qualifier is compared to a
+    /// sub-list: `1.0.alpha < 1 < 1-sp` but `1.0.alpha > 1-sp`, as numbers
+    /// are greater than sub-lists. Such versions are sorted by how they
+    /// compare to their common prefix instead, so `1.0.alpha` converts to a
+    /// lower version than `1-sp`.
+    ///
+    /// Qualifiers must only contain ASCII letters.
+    fn try_from(maven: &'a MavenVersion) -> Result<Version, InvalidVersion> {
+        let mut tokens = Vec::new();
+        flatten(&maven.items, &mut tokens);
+
+        let mut encoded = Vec::with_capacity(tokens.len());
+        let mut rest = Ordering::Equal;
+        for token in tokens.iter().rev() {
+            let (own, part) = match *token {
+                Token::Number(n) => {
+                    let own = if n.is_empty() { Ordering::Equal } else { Ordering::Greater };
+                    (own, format!(".{}", if n.is_empty() { "0" } else { n }))
+                }
+                Token::Qualifier(q) => {
+                    if !q.bytes().all(|c| c.is_ascii_lowercase()) {
+                        return Err(InvalidVersion::InvalidCharacter);
+                    }
+                    let (rank, name) = qualifier_rank(q);
+                    let own = rank.cmp(&RELEASE);
+                    (own, format!("{}{}0", (b'a' + rank as u8) as char, name))
+                }
+                Token::List => (Ordering::Equal, "-0".to_owned()),
+            };
+            if own != Ordering::Equal {
+                rest = own;
+            }
+            let marker = if rest == Ordering::Less { "~" } else { "" };
+            encoded.push(format!("{}{}", marker, part));
+        }
+        encoded.push("0".to_owned());
+        encoded.reverse();
+        Ok(Version(encoded.concat()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{InvalidVersion, Version};
+    use super::MavenVersion;
+
+    fn parse(s: &str) -> MavenVersion {
+        MavenVersion::parse(s).unwrap()
+    }
+
+    // From Maven's ComparableVersionTest
+    const QUALIFIER_VERSIONS: &[&str] = &[
+        "1-alpha2snapshot", "1-alpha2", "1-alpha-123", "1-beta-2", "1-beta123",
+        "1-m2", "1-m11", "1-rc", "1-cr2", "1-rc123", "1-SNAPSHOT", "1", "1-sp",
+        "1-sp2", "1-sp123", "1-abc", "1-def", "1-pom-1", "1-1-snapshot", "1-1",
+        "1-2", "1-123",
+    ];
+
+    const NUMBER_VERSIONS: &[&str] = &[
+        "2.0", "2-1", "2.0.a", "2.0.0.a", "2.0.2", "2.0.123", "2.1.0", "2.1-a",
+        "2.1b", "2.1-c", "2.1-1", "2.1.0.1", "2.2", "2.123", "11.a2", "11.a11",
+        "11.b2", "11.b11", "11.m2", "11.m11", "11", "11.a", "11b", "11c", "11m",
+    ];
+
+    const EQUAL_VERSIONS: &[&[&str]] = &[
+        &["1", "1.0", "1.0.0", "1-0", "1.0-0", "1-ga", "1-GA", "1-final", "1-release", "1.0.0-ga"],
+        &["1a1", "1-a1", "1-alpha-1", "1A1", "1-alpha1"],
+        &["1b2", "1-b2", "1-beta-2", "1-beta2"],
+        &["1m3", "1-m3", "1-milestone-3"],
+        &["1-cr1", "1-rc1", "1-RC-1"],
+        &["1x", "1-x", "1.0-x", "1.0.0-x", "1X"],
+        &["1-snapshot", "1-SNAPSHOT", "1.0-SNAPSHOT"],
+    ];
+
+    fn check_ordered(versions: &[&str]) {
+        for (i, &a) in versions.iter().enumerate() {
+            for (j, &b) in versions.iter().enumerate() {
+                assert_eq!(parse(a).cmp(&parse(b)), i.cmp(&j), "{} {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("1.0-SNAPSHOT").to_string(), "1.0-SNAPSHOT");
+        for &(invalid, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            ("1.0 ", InvalidVersion::InvalidCharacter),
+            ("[1.0]", InvalidVersion::InvalidCharacter),
+            ("1,0", InvalidVersion::InvalidCharacter),
+        ] {
+            assert_eq!(&MavenVersion::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        check_ordered(QUALIFIER_VERSIONS);
+        check_ordered(NUMBER_VERSIONS);
+        for equal in EQUAL_VERSIONS {
+            for &a in *equal {
+                for &b in *equal {
+                    assert_eq!(parse(a), parse(b), "{} == {}", a, b);
+                }
+            }
+        }
+        for &(a, b) in &[
+            ("1-alpha", "1"),
+            ("1.0.alpha", "1"),
+            ("1-0.alpha", "1"),
+            ("1", "1-sp"),
+            ("1-sp", "1.1"),
+            ("1", "1-0.1"),
+            ("1-abc", "1-abcd"),
+            ("1.0-SNAPSHOT", "1.0"),
+            ("1.0", "1.0.1-SNAPSHOT"),
+            ("1.9", "1.10"),
+            ("1.0.99999999999999999999", "1.0.100000000000000000000"),
+        ] {
+            assert!(parse(a) < parse(b), "{} < {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        assert!(parse("1.0-SNAPSHOT").is_snapshot());
+        assert!(parse("1.0-snapshot").is_snapshot());
+        assert!(!parse("1.0").is_snapshot());
+        assert!(!parse("1.0-SNAPSHOT-1").is_snapshot());
+
+        let timestamped = parse("1.0-20240101.123456-12");
+        assert!(timestamped.is_snapshot());
+        assert_eq!(timestamped.base_version().to_string(), "1.0-SNAPSHOT");
+        assert!(timestamped.base_version().is_snapshot());
+        assert_eq!(parse("1.0-2024.1-1").base_version().to_string(), "1.0-2024.1-1");
+    }
+
+    #[test]
+    fn test_to_version() {
+        for &(maven, expected) in &[
+            ("1.2.3", "0.1.2.3"),
+            ("1.0-sp-1", "0.1-0g0-0.1"),
+            ("1.0-rc-1", "0.1~-0~d0-0.1"),
+            ("1-alpha", "0.1~-0~a0"),
+            ("1.0.alpha", "0.1~.0~a0"),
+            ("1-foo", "0.1-0hfoo0"),
+        ] {
+            assert_eq!(&*Version::try_from(&parse(maven)).unwrap(), expected, "{}", maven);
+        }
+
+        // Maven's order isn't transitive here, the encoding follows the
+        // comparisons with the release
+        for &(a, b) in &[("1.0.alpha", "1-sp"), ("1-0.alpha", "1-x"), ("1-alpha", "1.ga.1")] {
+            let (a, b, release) = (parse(a), parse(b), parse("1"));
+            assert!(a > b && a < release && release < b, "{} {}", a, b);
+            assert!(Version::try_from(&a).unwrap() < Version::try_from(&b).unwrap(), "{} {}", a, b);
+        }
+        assert_eq!(
+            Version::try_from(&parse("1-foo_bar")).unwrap_err(),
+            InvalidVersion::InvalidCharacter,
+        );
+
+        let mut versions: Vec<&str> = QUALIFIER_VERSIONS.iter()
+            .chain(NUMBER_VERSIONS)
+            .chain(EQUAL_VERSIONS.iter().flat_map(|v| v.iter()))
+            .cloned()
+            .collect();
+        versions.extend(&[
+            "1-0.1", "1-abcd", "1.0.0.x1", "1.0.0-x2", "1-sp.1", "1-alpha-0-1",
+            "1-1.alpha", "0", "alpha", "1.0.0.0.1",
+        ]);
+        for &a in &versions {
+            for &b in &versions {
+                let (a, b) = (parse(a), parse(b));
+                let (x, y) = (Version::try_from(&a).unwrap(), Version::try_from(&b).unwrap());
+                assert_eq!(x.cmp(&y), a.cmp(&b), "{} ({}) {} ({})", a, x, b, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Version ranges, e.g. `[1.0,2.0)` or `(,1.0],[1.2,)`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Bound;
+
+use crate::{InvalidVersion, Version};
+use crate::debian::{Constraint, Operator};
+use super::MavenVersion;
+
+/// Error for the version range parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidRange {
+    /// A bracket or a comma is missing or misplaced.
+    InvalidSyntax,
+    /// A single version is not enclosed in `[]`, e.g. `(1.0)`.
+    InvalidSingleVersion,
+    /// A version is invalid.
+    InvalidVersion(InvalidVersion),
+    /// The lower bound is greater than the upper bound, or equal to it while
+    /// one of them is exclusive.
+    DefiesOrdering,
+    /// A restriction overlaps with, or comes before, the previous one.
+    Overlap,
+}
+
+impl From<InvalidVersion> for InvalidRange {
+    fn from(error: InvalidVersion) -> InvalidRange {
+        InvalidRange::InvalidVersion(error)
+    }
+}
+
+/// An interval of versions, e.g. `[1.0,2.0)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Restriction {
+    lower: Bound<MavenVersion>,
+    upper: Bound<MavenVersion>,
+}
+
+fn parse_bound(string: &str, inclusive: bool) -> Result<Bound<MavenVersion>, InvalidRange> {
+    let string = string.trim();
+    if string.is_empty() {
+        return Ok(Bound::Unbounded);
+    }
+    let version = MavenVersion::parse(string)?;
+    Ok(if inclusive { Bound::Included(version) } else { Bound::Excluded(version) })
+}
+
+impl Restriction {
+    /// Parse a restriction, including its brackets.
+    fn parse(string: &str) -> Result<Restriction, InvalidRange> {
+        let lower_inclusive = string.starts_with('[');
+        let upper_inclusive = string.ends_with(']');
+        let inner = &string[1 .. string.len() - 1];
+        match inner.split_once(',') {
+            None => {
+                if !lower_inclusive || !upper_inclusive {
+                    return Err(InvalidRange::InvalidSingleVersion);
+                }
+                let version = MavenVersion::parse(inner.trim())?;
+                Ok(Restriction {
+                    lower: Bound::Included(version.clone()),
+                    upper: Bound::Included(version),
+                })
+            }
+            Some((_, upper)) if upper.contains(',') => Err(InvalidRange::InvalidSyntax),
+            Some((lower, upper)) => {
+                let lower = parse_bound(lower, lower_inclusive)?;
+                let upper = parse_bound(upper, upper_inclusive)?;
+                if let (Some(l), Some(u)) = (bound_version(&lower), bound_version(&upper)) {
+                    let exclusive = matches!(lower, Bound::Excluded(_))
+                        || matches!(upper, Bound::Excluded(_));
+                    match l.cmp(u) {
+                        Ordering::Greater => return Err(InvalidRange::DefiesOrdering),
+                        Ordering::Equal if exclusive => return Err(InvalidRange::DefiesOrdering),
+                        _ => {}
+                    }
+                }
+                Ok(Restriction { lower, upper })
+            }
+        }
+    }
+
+    /// The lower bound.
+    pub fn lower(&self) -> Bound<&MavenVersion> {
+        self.lower.as_ref()
+    }
+
+    /// The upper bound.
+    pub fn upper(&self) -> Bound<&MavenVersion> {
+        self.upper.as_ref()
+    }
+
+    /// Whether the version is within the bounds.
+    pub fn contains(&self, version: &MavenVersion) -> bool {
+        let above = match self.lower {
+            Bound::Included(ref lower) => version >= lower,
+            Bound::Excluded(ref lower) => version > lower,
+            Bound::Unbounded => true,
+        };
+        let below = match self.upper {
+            Bound::Included(ref upper) => version <= upper,
+            Bound::Excluded(ref upper) => version < upper,
+            Bound::Unbounded => true,
+        };
+        above && below
+    }
+
+    /// The Debian constraints that must all be satisfied, see
+    /// [`VersionRange::to_debian()`].
+    pub fn to_debian(&self) -> Result<Vec<Constraint>, InvalidVersion> {
+        if let (Bound::Included(lower), Bound::Included(upper)) = (&self.lower, &self.upper) {
+            if lower == upper {
+                return Ok(vec![encode(Operator::Equal, lower)?]);
+            }
+        }
+        let lower = self.lower_to_debian()?;
+        let upper = self.upper_to_debian()?;
+        Ok(lower.into_iter().chain(upper).collect())
+    }
+
+    /// The Debian constraint for the lower bound, if any.
+    fn lower_to_debian(&self) -> Result<Option<Constraint>, InvalidVersion> {
+        match self.lower {
+            Bound::Included(ref lower) => encode(Operator::GreaterEqual, lower).map(Some),
+            Bound::Excluded(ref lower) => encode(Operator::Greater, lower).map(Some),
+            Bound::Unbounded => Ok(None),
+        }
+    }
+
+    /// The Debian constraint for the upper bound, if any.
+    fn upper_to_debian(&self) -> Result<Option<Constraint>, InvalidVersion> {
+        match self.upper {
+            Bound::Included(ref upper) => encode(Operator::LessEqual, upper).map(Some),
+            Bound::Excluded(ref upper) => encode(Operator::Less, upper).map(Some),
+            Bound::Unbounded => Ok(None),
+        }
+    }
+}
+
+fn encode(operator: Operator, version: &MavenVersion) -> Result<Constraint, InvalidVersion> {
+    Ok(Constraint::new(operator, Version::try_from(version)?))
+}
+
+fn bound_version(bound: &Bound<MavenVersion>) -> Option<&MavenVersion> {
+    match *bound {
+        Bound::Included(ref version) | Bound::Excluded(ref version) => Some(version),
+        Bound::Unbounded => None,
+    }
+}
+
+impl fmt::Display for Restriction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let (Bound::Included(lower), Bound::Included(upper)) = (&self.lower, &self.upper) {
+            if lower == upper {
+                return write!(f, "[{}]", lower);
+            }
+        }
+        match self.lower {
+            Bound::Included(ref lower) => write!(f, "[{}", lower)?,
+            Bound::Excluded(ref lower) => write!(f, "({}", lower)?,
+            Bound::Unbounded => write!(f, "(")?,
+        }
+        match self.upper {
+            Bound::Included(ref upper) => write!(f, ",{}]", upper),
+            Bound::Excluded(ref upper) => write!(f, ",{})", upper),
+            Bound::Unbounded => write!(f, ",)"),
+        }
+    }
+}
+
+/// A version range, e.g. `[1.0,2.0)`, `(,1.0],[1.2,)` or `1.0`.
+///
+/// A bare version is a soft requirement: it is the recommended version, but
+/// any version satisfies the range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionRange {
+    recommended: Option<MavenVersion>,
+    restrictions: Vec<Restriction>,
+}
+
+impl VersionRange {
+    /// Parse a version range.
+    pub fn parse(string: &str) -> Result<VersionRange, InvalidRange> {
+        if !string.starts_with(['[', '(']) {
+            let version = MavenVersion::parse(string)?;
+            return Ok(VersionRange {
+                recommended: Some(version),
+                restrictions: vec![Restriction { lower: Bound::Unbounded, upper: Bound::Unbounded }],
+            });
+        }
+
+        let mut restrictions: Vec<Restriction> = Vec::new();
+        let mut rest = string;
+        loop {
+            if !rest.starts_with(['[', '(']) {
+                return Err(InvalidRange::InvalidSyntax);
+            }
+            let end = rest.find([']', ')']).ok_or(InvalidRange::InvalidSyntax)?;
+            let restriction = Restriction::parse(&rest[..= end])?;
+            if let Some(previous) = restrictions.last() {
+                // Adjacent restrictions sharing a bound, like `[1.0,2.0],[2.0,3.0]`,
+                // are allowed
+                let overlaps = match (previous.upper(), restriction.lower()) {
+                    (Bound::Unbounded, _) | (_, Bound::Unbounded) => true,
+                    (
+                        Bound::Included(upper) | Bound::Excluded(upper),
+                        Bound::Included(lower) | Bound::Excluded(lower),
+                    ) => lower < upper,
+                };
+                if overlaps {
+                    return Err(InvalidRange::Overlap);
+                }
+            }
+            restrictions.push(restriction);
+
+            rest = &rest[end + 1 ..];
+            if rest.is_empty() {
+                break;
+            }
+            rest = rest.strip_prefix(',').ok_or(InvalidRange::InvalidSyntax)?;
+        }
+        Ok(VersionRange { recommended: None, restrictions })
+    }
+
+    /// The recommended version of a soft requirement.
+    pub fn recommended_version(&self) -> Option<&MavenVersion> {
+        self.recommended.as_ref()
+    }
+
+    /// The intervals of versions in the range.
+    pub fn restrictions(&self) -> &[Restriction] {
+        &self.restrictions
+    }
+
+    /// Whether the version is in the range.
+    pub fn contains(&self, version: &MavenVersion) -> bool {
+        self.restrictions.iter().any(|r| r.contains(version))
+    }
+
+    /// Convert the range to Debian version constraints, in conjunctive normal
+    /// form (see [`debian::format_relationship()`]), on versions converted
+    /// with `Version::try_from()`.
+    ///
+    /// `(,1.0],[1.2,2.0)` becomes `(<= 1.0 | >= 1.2), (<< 2.0)`: the
+    /// restrictions are sorted and disjoint, so the range is the lower bound
+    /// of the first one, the upper bound of the last one, and one clause
+    /// excluding each gap between them. A soft requirement doesn't constrain
+    /// the version, so it becomes an empty list.
+    ///
+    /// [`debian::format_relationship()`]: crate::debian::format_relationship
+    pub fn to_debian(&self) -> Result<Vec<Vec<Constraint>>, InvalidVersion> {
+        let (first, last) = match (self.restrictions.first(), self.restrictions.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Ok(Vec::new()),
+        };
+        if self.restrictions.len() == 1 {
+            let constraints = first.to_debian()?;
+            return Ok(constraints.into_iter().map(|constraint| vec![constraint]).collect());
+        }
+
+        let mut clauses = Vec::new();
+        if let Some(lower) = first.lower_to_debian()? {
+            clauses.push(vec![lower]);
+        }
+        for pair in self.restrictions.windows(2) {
+            // Only the first restriction has no lower bound, and only the
+            // last one no upper bound
+            let clause = pair[0].upper_to_debian()?.into_iter()
+                .chain(pair[1].lower_to_debian()?)
+                .collect();
+            clauses.push(clause);
+        }
+        if let Some(upper) = last.upper_to_debian()? {
+            clauses.push(vec![upper]);
+        }
+        Ok(clauses)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for VersionRange {
+    type Error = InvalidRange;
+
+    fn try_from(string: &'a str) -> Result<VersionRange, InvalidRange> {
+        VersionRange::parse(string)
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref recommended) = self.recommended {
+            return write!(f, "{}", recommended);
+        }
+        let restrictions: Vec<String> = self.restrictions.iter().map(|r| r.to_string()).collect();
+        f.write_str(&restrictions.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::ops::Bound;
+
+    use crate::{InvalidVersion, Version};
+    use crate::debian::format_relationship;
+    use super::super::MavenVersion;
+    use super::{InvalidRange, VersionRange};
+
+    fn version(s: &str) -> MavenVersion {
+        MavenVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let range = VersionRange::parse("[1.0,2.0)").unwrap();
+        assert_eq!(range.recommended_version(), None);
+        let restriction = &range.restrictions()[0];
+        assert_eq!(restriction.lower(), Bound::Included(&version("1.0")));
+        assert_eq!(restriction.upper(), Bound::Excluded(&version("2.0")));
+
+        let range = VersionRange::parse("1.0").unwrap();
+        assert_eq!(range.recommended_version(), Some(&version("1.0")));
+        assert_eq!(range.restrictions()[0].lower(), Bound::Unbounded);
+        assert_eq!(range.restrictions()[0].upper(), Bound::Unbounded);
+
+        for &range in &["[1.0,2.0)", "(,1.0],[1.2,)", "[1.0]", "(1.0,)", "[1.0],[1.1]", "1.0-SNAPSHOT"] {
+            assert_eq!(VersionRange::parse(range).unwrap().to_string(), range);
+        }
+        assert_eq!(VersionRange::parse("[ 1.0 , 2.0 ]").unwrap().to_string(), "[1.0,2.0]");
+
+        for &(invalid, ref error) in &[
+            ("", InvalidRange::InvalidVersion(InvalidVersion::EmptyField)),
+            ("[1.0,2.0", InvalidRange::InvalidSyntax),
+            ("[1.0,2.0),", InvalidRange::InvalidSyntax),
+            ("[1.0,2.0)[3.0,)", InvalidRange::InvalidSyntax),
+            ("[1.0,2.0),3.0", InvalidRange::InvalidSyntax),
+            ("[1.0,2.0,3.0]", InvalidRange::InvalidSyntax),
+            ("(1.0)", InvalidRange::InvalidSingleVersion),
+            ("[1.0)", InvalidRange::InvalidSingleVersion),
+            ("[]", InvalidRange::InvalidVersion(InvalidVersion::EmptyField)),
+            ("[2.0,1.0]", InvalidRange::DefiesOrdering),
+            ("[1.0,1.0)", InvalidRange::DefiesOrdering),
+            ("[1.0,2.0],[1.5,3.0]", InvalidRange::Overlap),
+            ("[2.0,3.0],[1.0,1.5]", InvalidRange::Overlap),
+            ("[1.0,),[2.0,3.0]", InvalidRange::Overlap),
+            ("(,1.0],(,2.0]", InvalidRange::Overlap),
+        ] {
+            assert_eq!(&VersionRange::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+        for &range in &["[1.0,2.0),[2.0,3.0]", "[1.0,2.0],[2.0,3.0]", "[1.0],[1.0,2.0)", "(1.0,2.0),(2.0,3.0)"] {
+            assert!(VersionRange::parse(range).is_ok(), "{}", range);
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        for &(range, matching, not_matching) in &[
+            ("[1.0,2.0)", &["1.0", "1", "1.5", "2.0-SNAPSHOT", "2.0-rc1"][..], &["0.9", "1.0-SNAPSHOT", "2.0", "2.0-sp1"][..]),
+            ("(,1.0],[1.2,)", &["0.1", "1.0", "1.2", "5"], &["1.0.1", "1.1", "1.2-alpha"]),
+            ("[1.0]", &["1.0", "1.0.0", "1-ga"], &["1.0.1", "1.0-SNAPSHOT"]),
+            ("(1.0,1.5]", &["1.0.1", "1.5"], &["1.0", "1.5.1"]),
+            ("1.0", &["0.1", "1.0", "99"], &[]),
+        ] {
+            let range = VersionRange::parse(range).unwrap();
+            for &v in matching {
+                assert!(range.contains(&version(v)), "{} contains {}", range, v);
+            }
+            for &v in not_matching {
+                assert!(!range.contains(&version(v)), "{} doesn't contain {}", range, v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_debian() {
+        for &(range, expected) in &[
+            ("[1.0,2.0)", "foo (>= 0.1), foo (<< 0.2)"),
+            ("(,1.0],[1.2,)", "foo (<= 0.1) | foo (>= 0.1.2)"),
+            ("(,1.0],[1.2,2.0)", "foo (<= 0.1) | foo (>= 0.1.2), foo (<< 0.2)"),
+            ("[1.0],[1.2]", "foo (>= 0.1), foo (<= 0.1) | foo (>= 0.1.2), foo (<= 0.1.2)"),
+            ("[1.0-rc1]", "foo (= 0.1~-0~d0-0.1)"),
+            ("(1.0,)", "foo (>> 0.1)"),
+            ("1.0", "foo"),
+            ("(,)", "foo"),
+        ] {
+            let range = VersionRange::parse(range).unwrap();
+            let constraints = range.to_debian().unwrap();
+            assert_eq!(format_relationship("foo", &constraints), expected, "{}", range);
+        }
+
+        for &range in &["(,1.0],[1.2,2.0)", "[1.0],(1.1,1.2],[1.5,2.0),[3,)", "[1.0,1.2],[1.2,2.0)"] {
+            let range = VersionRange::parse(range).unwrap();
+            let constraints = range.to_debian().unwrap();
+            for &v in &["0.1", "1.0", "1.0.1", "1.1", "1.2", "1.2-rc1", "1.5", "2.0-alpha1", "2.0", "3"] {
+                let maven = version(v);
+                let debian = Version::try_from(&maven).unwrap();
+                let satisfied = constraints.iter()
+                    .all(|any| any.iter().any(|constraint| constraint.contains(&debian)));
+                assert_eq!(satisfied, range.contains(&maven), "{} {}", range, v);
+            }
+        }
+
+        // The number of clauses grows linearly with the restrictions
+        let many: Vec<String> = (1 .. 100).map(|i| format!("[{}.0,{}.5)", i, i)).collect();
+        let range = VersionRange::parse(&many.join(",")).unwrap();
+        assert_eq!(range.to_debian().unwrap().len(), 100);
+
+        let range = VersionRange::parse("[1-foo_bar,)").unwrap();
+        assert_eq!(range.to_debian().unwrap_err(), InvalidVersion::InvalidCharacter);
+    }
+}
@@ -0,0 +1,286 @@
+//! Implement NuGet's versioning scheme, as implemented by `NuGetVersion`.
+//!
+//! Versions have up to four numbers, `major.minor.patch.revision`, missing
+//! ones being `0`, followed by an optional SemVer 2 pre-release
+//! (`-beta.2`) and build metadata (`+abc`). Unlike SemVer, numbers may have
+//! leading zeros, which are ignored.
+//!
+//! Numbers are compared first, then a pre-release is less than the release.
+//! Pre-release labels are compared in order: numeric labels numerically and
+//! less than other labels, which are compared case-insensitively, and a
+//! shorter list of labels is less. Build metadata is ignored, so
+//! `1.0.0-BETA+abc == 1.0-beta`.
+//!
+//! Version ranges (`[1.0,2.0)`) can be parsed, including floating ones
+//! (`1.*`, `1.0.0-*`) that pick the best match among available versions.
+
+mod range;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::semver::InvalidSemver;
+
+pub use self::range::{FloatBehavior, FloatRange, InvalidRange, VersionRange};
+
+/// A NuGet version, e.g. `1.2.3`, `1.2.3.4` or `1.0.0-beta.2+abc`.
+#[derive(Clone, Debug)]
+pub struct NuGetVersion {
+    string: String,
+    numbers: [u64; 4],
+    labels: Vec<String>,
+    metadata: Option<String>,
+}
+
+/// Check a dot-separated label or metadata identifier.
+fn check_identifier(identifier: &str) -> Result<(), InvalidSemver> {
+    if identifier.is_empty() {
+        return Err(InvalidSemver::EmptyField);
+    }
+    if !identifier.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(InvalidSemver::InvalidCharacter);
+    }
+    Ok(())
+}
+
+fn is_numeric(label: &str) -> bool {
+    label.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Compare two pre-release labels.
+fn compare_labels(a: &str, b: &str) -> Ordering {
+    match (is_numeric(a), is_numeric(b)) {
+        (true, true) => {
+            let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+            a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+        }
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => {
+            a.bytes().map(|c| c.to_ascii_lowercase()).cmp(b.bytes().map(|c| c.to_ascii_lowercase()))
+        }
+    }
+}
+
+impl NuGetVersion {
+    /// Parse a version, e.g. `1.2`, `1.2.3.4` or `1.0.0-beta.2+abc`.
+    pub fn parse(string: &str) -> Result<NuGetVersion, InvalidSemver> {
+        let (rest, metadata) = match string.split_once('+') {
+            Some((rest, metadata)) => {
+                metadata.split('.').try_for_each(check_identifier)?;
+                (rest, Some(metadata.to_owned()))
+            }
+            None => (string, None),
+        };
+        let (release, labels) = match rest.split_once('-') {
+            Some((release, pre)) => {
+                pre.split('.').try_for_each(check_identifier)?;
+                (release, pre.split('.').map(|l| l.to_owned()).collect())
+            }
+            None => (rest, Vec::new()),
+        };
+
+        let mut numbers = [0; 4];
+        let fields: Vec<&str> = release.split('.').collect();
+        if fields.len() > 4 {
+            return Err(InvalidSemver::WrongFieldCount);
+        }
+        for (number, field) in numbers.iter_mut().zip(fields) {
+            if field.is_empty() {
+                return Err(InvalidSemver::EmptyField);
+            }
+            if !is_numeric(field) {
+                return Err(InvalidSemver::InvalidCharacter);
+            }
+            *number = field.parse().map_err(|_| InvalidSemver::NumberTooLarge)?;
+        }
+
+        Ok(NuGetVersion { string: string.to_owned(), numbers, labels, metadata })
+    }
+
+    /// The first number, e.g. `1` for `1.2.3.4`.
+    pub fn major(&self) -> u64 {
+        self.numbers[0]
+    }
+
+    /// The second number, `0` if missing.
+    pub fn minor(&self) -> u64 {
+        self.numbers[1]
+    }
+
+    /// The third number, `0` if missing.
+    pub fn patch(&self) -> u64 {
+        self.numbers[2]
+    }
+
+    /// The fourth number, `0` if missing.
+    pub fn revision(&self) -> u64 {
+        self.numbers[3]
+    }
+
+    /// The pre-release labels, e.g. `["beta", "2"]` for `1.0.0-beta.2`.
+    pub fn release_labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// The build metadata, e.g. `abc` for `1.0.0+abc`.
+    pub fn metadata(&self) -> Option<&str> {
+        self.metadata.as_deref()
+    }
+
+    /// Whether the version has pre-release labels, e.g. `1.0.0-beta`.
+    pub fn is_prerelease(&self) -> bool {
+        !self.labels.is_empty()
+    }
+
+    /// Whether the numbers of both versions are equal, ignoring pre-release
+    /// labels.
+    pub fn same_release(&self, other: &NuGetVersion) -> bool {
+        self.numbers == other.numbers
+    }
+
+    /// The normalized form of the version: three or four numbers without
+    /// leading zeros, the revision only if it isn't zero, and the
+    /// pre-release labels, without build metadata. E.g. `1.2.0` for
+    /// `01.2.0.0+abc`.
+    pub fn normalized(&self) -> String {
+        let count = if self.revision() == 0 { 3 } else { 4 };
+        let numbers: Vec<String> = self.numbers[.. count].iter().map(|n| n.to_string()).collect();
+        let mut normalized = numbers.join(".");
+        if self.is_prerelease() {
+            normalized.push('-');
+            normalized.push_str(&self.labels.join("."));
+        }
+        normalized
+    }
+}
+
+impl<'a> TryFrom<&'a str> for NuGetVersion {
+    type Error = InvalidSemver;
+
+    fn try_from(string: &'a str) -> Result<NuGetVersion, InvalidSemver> {
+        NuGetVersion::parse(string)
+    }
+}
+
+impl fmt::Display for NuGetVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+impl PartialEq<NuGetVersion> for NuGetVersion {
+    fn eq(&self, other: &NuGetVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NuGetVersion {}
+
+impl PartialOrd<NuGetVersion> for NuGetVersion {
+    fn partial_cmp(&self, other: &NuGetVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NuGetVersion {
+    fn cmp(&self, other: &NuGetVersion) -> Ordering {
+        self.numbers.cmp(&other.numbers).then_with(|| {
+            match (self.is_prerelease(), other.is_prerelease()) {
+                (false, false) => Ordering::Equal,
+                (false, true) => Ordering::Greater,
+                (true, false) => Ordering::Less,
+                (true, true) => {
+                    for (a, b) in self.labels.iter().zip(&other.labels) {
+                        match compare_labels(a, b) {
+                            Ordering::Equal => {}
+                            ordering => return ordering,
+                        }
+                    }
+                    self.labels.len().cmp(&other.labels.len())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semver::InvalidSemver;
+    use super::NuGetVersion;
+
+    fn parse(s: &str) -> NuGetVersion {
+        NuGetVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        for &(version, normalized) in &[
+            ("1", "1.0.0"),
+            ("1.2", "1.2.0"),
+            ("01.002.3", "1.2.3"),
+            ("1.2.3.0", "1.2.3"),
+            ("1.2.3.4", "1.2.3.4"),
+            ("1.0.0-Beta.02+abc.def", "1.0.0-Beta.02"),
+            ("1.0-rc-1", "1.0.0-rc-1"),
+        ] {
+            let parsed = parse(version);
+            assert_eq!(parsed.to_string(), version);
+            assert_eq!(parsed.normalized(), normalized, "{}", version);
+        }
+
+        let version = parse("1.2.3.4-beta.2+abc");
+        assert_eq!(
+            (version.major(), version.minor(), version.patch(), version.revision()),
+            (1, 2, 3, 4),
+        );
+        assert_eq!(version.release_labels(), &["beta", "2"]);
+        assert_eq!(version.metadata(), Some("abc"));
+        assert!(version.is_prerelease());
+        assert!(!parse("1.0+abc").is_prerelease());
+
+        for &(version, ref error) in &[
+            ("", InvalidSemver::EmptyField),
+            ("1.", InvalidSemver::EmptyField),
+            ("1.0-", InvalidSemver::EmptyField),
+            ("1.0-beta..1", InvalidSemver::EmptyField),
+            ("1.0+", InvalidSemver::EmptyField),
+            ("1.2.3.4.5", InvalidSemver::WrongFieldCount),
+            ("v1.0", InvalidSemver::InvalidCharacter),
+            ("1.0-beta_1", InvalidSemver::InvalidCharacter),
+            ("1.0 ", InvalidSemver::InvalidCharacter),
+            ("99999999999999999999", InvalidSemver::NumberTooLarge),
+        ] {
+            assert_eq!(&NuGetVersion::parse(version).unwrap_err(), error, "{}", version);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let versions = [
+            &["1.0.0-alpha"][..],
+            &["1.0.0-alpha.1"],
+            &["1.0.0-alpha.beta", "1.0.0-ALPHA.Beta"],
+            &["1.0.0-beta", "1.0-BETA+abc"],
+            &["1.0.0-beta.2", "1.0.0-beta.02"],
+            &["1.0.0-beta.11"],
+            &["1.0.0-beta-2"],
+            &["1.0.0-rc.1"],
+            &["1", "1.0", "1.0.0", "1.0.0.0", "01.0.0+abc"],
+            &["1.0.0.1"],
+            &["1.0.1"],
+            &["1.10"],
+        ];
+        for (i, equal) in versions.iter().enumerate() {
+            for &a in *equal {
+                for &b in *equal {
+                    assert_eq!(parse(a), parse(b), "{} == {}", a, b);
+                }
+                for &b in versions[i + 1 ..].iter().flat_map(|v| v.iter()) {
+                    assert!(parse(a) < parse(b), "{} < {}", a, b);
+                }
+            }
+        }
+    }
+}
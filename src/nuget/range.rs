@@ -0,0 +1,482 @@
+//! Version ranges, e.g. `[1.0,2.0)`, `1.0` or the floating `1.*`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Bound;
+
+use crate::semver::InvalidSemver;
+use super::NuGetVersion;
+
+/// Error for the version range parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidRange {
+    /// A bracket or a comma is missing or misplaced.
+    InvalidSyntax,
+    /// A version is invalid.
+    InvalidVersion(InvalidSemver),
+    /// A `*` is misplaced, or is in the upper bound.
+    InvalidFloat,
+    /// The lower bound is greater than the upper bound, or equal to it while
+    /// one of them is exclusive.
+    DefiesOrdering,
+}
+
+impl From<InvalidSemver> for InvalidRange {
+    fn from(error: InvalidSemver) -> InvalidRange {
+        InvalidRange::InvalidVersion(error)
+    }
+}
+
+/// Which part of a floating version is floating.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FloatBehavior {
+    /// `1.0.0-*` or `1.0.0-beta*`: the pre-release labels.
+    Prerelease,
+    /// `1.0.0.*`: the revision.
+    Revision,
+    /// `1.0.*`: the patch number.
+    Patch,
+    /// `1.*`: the minor number.
+    Minor,
+    /// `*`: the major number.
+    Major,
+    /// `1.0.0.*-*`: the revision, including pre-releases.
+    PrereleaseRevision,
+    /// `1.0.*-*`: the patch number, including pre-releases.
+    PrereleasePatch,
+    /// `1.*-*`: the minor number, including pre-releases.
+    PrereleaseMinor,
+    /// `*-*`: any version, including pre-releases.
+    AbsoluteLatest,
+}
+
+/// A floating version, e.g. `1.*` or `1.0.0-beta*`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FloatRange {
+    string: String,
+    behavior: FloatBehavior,
+    min_version: NuGetVersion,
+    release_prefix: String,
+}
+
+impl FloatRange {
+    fn parse(string: &str) -> Result<FloatRange, InvalidRange> {
+        let (release, label) = match string.split_once('-') {
+            Some((release, label)) => (release, Some(label)),
+            None => (string, None),
+        };
+        let release_prefix = match label {
+            Some(label) => match label.strip_suffix('*') {
+                Some(prefix) if !prefix.contains('*') => Some(prefix),
+                _ => return Err(InvalidRange::InvalidFloat),
+            },
+            None => None,
+        };
+
+        // The fixed numbers, and whether the next one floats
+        let (numbers, floating) = match release {
+            "*" => ("", true),
+            _ => match release.strip_suffix(".*") {
+                Some(numbers) => (numbers, true),
+                None => (release, false),
+            },
+        };
+        if numbers.contains('*') {
+            return Err(InvalidRange::InvalidFloat);
+        }
+        let count = if numbers.is_empty() { 0 } else { numbers.split('.').count() };
+        let behavior = match (floating, count, release_prefix) {
+            (false, _, None) => return Err(InvalidRange::InvalidFloat),
+            (false, _, Some(_)) => FloatBehavior::Prerelease,
+            (true, 0, None) => FloatBehavior::Major,
+            (true, 1, None) => FloatBehavior::Minor,
+            (true, 2, None) => FloatBehavior::Patch,
+            (true, 3, None) => FloatBehavior::Revision,
+            (true, 0, Some("")) => FloatBehavior::AbsoluteLatest,
+            (true, 1, Some(_)) => FloatBehavior::PrereleaseMinor,
+            (true, 2, Some(_)) => FloatBehavior::PrereleasePatch,
+            (true, 3, Some(_)) => FloatBehavior::PrereleaseRevision,
+            _ => return Err(InvalidRange::InvalidFloat),
+        };
+
+        let mut min_version = match (numbers, floating) {
+            ("", _) => "0".to_owned(),
+            (numbers, true) => format!("{}.0", numbers),
+            (numbers, false) => numbers.to_owned(),
+        };
+        if let Some(prefix) = release_prefix {
+            // The lowest label with the prefix
+            let label = match prefix {
+                "" => "0".to_owned(),
+                p if p.ends_with(['.', '-']) => format!("{}0", p),
+                p => p.to_owned(),
+            };
+            min_version = format!("{}-{}", min_version, label);
+        }
+
+        Ok(FloatRange {
+            string: string.to_owned(),
+            behavior,
+            min_version: NuGetVersion::parse(&min_version)?,
+            release_prefix: release_prefix.unwrap_or("").to_owned(),
+        })
+    }
+
+    /// Which part of the version floats, e.g. `Minor` for `1.*`.
+    pub fn behavior(&self) -> FloatBehavior {
+        self.behavior
+    }
+
+    /// The lowest version matching the floating version, e.g. `1.0.0` for
+    /// `1.*` and `1.0.0-beta` for `1.0.0-beta*`.
+    pub fn min_version(&self) -> &NuGetVersion {
+        &self.min_version
+    }
+
+    /// Whether a version matches the floating version, e.g. `1.2.0` for
+    /// `1.*` but not `2.0.0` or `1.2.0-beta`.
+    pub fn contains(&self, version: &NuGetVersion) -> bool {
+        let min = &self.min_version;
+        let label_matches = !version.is_prerelease() || {
+            let release = version.release_labels().join(".");
+            release.len() >= self.release_prefix.len()
+                && release[.. self.release_prefix.len()].eq_ignore_ascii_case(&self.release_prefix)
+        };
+        match self.behavior {
+            FloatBehavior::AbsoluteLatest => true,
+            FloatBehavior::Major => !version.is_prerelease(),
+            FloatBehavior::Minor => !version.is_prerelease() && version.major() == min.major(),
+            FloatBehavior::Patch => {
+                !version.is_prerelease()
+                    && (version.major(), version.minor()) == (min.major(), min.minor())
+            }
+            FloatBehavior::Revision => {
+                !version.is_prerelease()
+                    && (version.major(), version.minor(), version.patch())
+                        == (min.major(), min.minor(), min.patch())
+            }
+            FloatBehavior::Prerelease => version.same_release(min) && label_matches,
+            FloatBehavior::PrereleaseMinor => version.major() == min.major() && label_matches,
+            FloatBehavior::PrereleasePatch => {
+                (version.major(), version.minor()) == (min.major(), min.minor()) && label_matches
+            }
+            FloatBehavior::PrereleaseRevision => {
+                (version.major(), version.minor(), version.patch())
+                    == (min.major(), min.minor(), min.patch())
+                    && label_matches
+            }
+        }
+    }
+}
+
+impl fmt::Display for FloatRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+/// A version range, e.g. `[1.0,2.0)`, `(,1.0]`, `[1.0]` or `1.*`.
+///
+/// A bare version is a minimum: `1.0` means `[1.0,)`. The lower bound may
+/// float, which only matters when picking the best match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionRange {
+    lower: Bound<NuGetVersion>,
+    upper: Bound<NuGetVersion>,
+    float: Option<FloatRange>,
+}
+
+/// Parse a bound, which may float if it's the lower bound.
+fn parse_bound(
+    string: &str,
+    inclusive: bool,
+    lower: bool,
+) -> Result<(Bound<NuGetVersion>, Option<FloatRange>), InvalidRange> {
+    let string = string.trim();
+    if string.is_empty() {
+        return Ok((Bound::Unbounded, None));
+    }
+    let (version, float) = if string.contains('*') {
+        if !lower {
+            return Err(InvalidRange::InvalidFloat);
+        }
+        let float = FloatRange::parse(string)?;
+        (float.min_version.clone(), Some(float))
+    } else {
+        (NuGetVersion::parse(string)?, None)
+    };
+    let bound = if inclusive { Bound::Included(version) } else { Bound::Excluded(version) };
+    Ok((bound, float))
+}
+
+impl VersionRange {
+    /// Parse a version range.
+    pub fn parse(string: &str) -> Result<VersionRange, InvalidRange> {
+        let string = string.trim();
+        if !string.starts_with(['[', '(']) {
+            let (lower, float) = parse_bound(string, true, true)?;
+            if lower == Bound::Unbounded {
+                return Err(InvalidRange::InvalidSyntax);
+            }
+            return Ok(VersionRange { lower, upper: Bound::Unbounded, float });
+        }
+
+        if string.len() < 2 || !string.ends_with([']', ')']) {
+            return Err(InvalidRange::InvalidSyntax);
+        }
+        let lower_inclusive = string.starts_with('[');
+        let upper_inclusive = string.ends_with(']');
+        let inner = &string[1 .. string.len() - 1];
+        let (lower, upper) = match inner.split_once(',') {
+            None => {
+                // A single version must be enclosed in `[]`
+                if !lower_inclusive || !upper_inclusive || inner.contains('*') {
+                    return Err(InvalidRange::InvalidSyntax);
+                }
+                (inner, inner)
+            }
+            Some((_, upper)) if upper.contains(',') => return Err(InvalidRange::InvalidSyntax),
+            Some(bounds) => bounds,
+        };
+        let (lower, float) = parse_bound(lower, lower_inclusive, true)?;
+        let (upper, _) = parse_bound(upper, upper_inclusive, false)?;
+        if let (Some(l), Some(u)) = (bound_version(&lower), bound_version(&upper)) {
+            let exclusive = matches!(lower, Bound::Excluded(_)) || matches!(upper, Bound::Excluded(_));
+            match l.cmp(u) {
+                Ordering::Greater => return Err(InvalidRange::DefiesOrdering),
+                Ordering::Equal if exclusive => return Err(InvalidRange::DefiesOrdering),
+                _ => {}
+            }
+        } else if lower == Bound::Unbounded && upper == Bound::Unbounded {
+            return Err(InvalidRange::InvalidSyntax);
+        }
+        Ok(VersionRange { lower, upper, float })
+    }
+
+    /// The lower bound.
+    pub fn lower(&self) -> Bound<&NuGetVersion> {
+        self.lower.as_ref()
+    }
+
+    /// The upper bound.
+    pub fn upper(&self) -> Bound<&NuGetVersion> {
+        self.upper.as_ref()
+    }
+
+    /// The floating lower bound, if any.
+    pub fn float_range(&self) -> Option<&FloatRange> {
+        self.float.as_ref()
+    }
+
+    /// Whether the version is within the bounds.
+    ///
+    /// A floating range contains all the versions above its minimum, e.g.
+    /// `1.*` contains `2.0.0`.
+    pub fn contains(&self, version: &NuGetVersion) -> bool {
+        let above = match self.lower {
+            Bound::Included(ref lower) => version >= lower,
+            Bound::Excluded(ref lower) => version > lower,
+            Bound::Unbounded => true,
+        };
+        let below = match self.upper {
+            Bound::Included(ref upper) => version <= upper,
+            Bound::Excluded(ref upper) => version < upper,
+            Bound::Unbounded => true,
+        };
+        above && below
+    }
+
+    /// Pick the version NuGet would resolve the range to.
+    ///
+    /// Without a floating bound, this is the lowest version in the range.
+    /// Otherwise, this is the highest version matching the floating version,
+    /// or if there is none the lowest version above it, or the highest one
+    /// below it.
+    pub fn find_best_match<'a, I>(&self, versions: I) -> Option<&'a NuGetVersion>
+    where
+        I: IntoIterator<Item = &'a NuGetVersion>,
+    {
+        let mut best: Option<&NuGetVersion> = None;
+        for version in versions {
+            if !self.contains(version) {
+                continue;
+            }
+            best = match best {
+                Some(current) if !self.is_better(current, version) => Some(current),
+                _ => Some(version),
+            };
+        }
+        best
+    }
+
+    /// Whether `considering` is a better match than `current`.
+    fn is_better(&self, current: &NuGetVersion, considering: &NuGetVersion) -> bool {
+        let float = match self.float {
+            Some(ref float) => float,
+            // Favor lower versions
+            None => return considering < current,
+        };
+        match (float.contains(current), float.contains(considering)) {
+            (true, false) => false,
+            (false, true) => true,
+            (true, true) => considering > current,
+            (false, false) => {
+                let min = float.min_version();
+                match (current < min, considering < min) {
+                    (true, false) => true,
+                    (false, true) => false,
+                    // Favor the lowest version above the floating version
+                    (false, false) => considering < current,
+                    // Favor the highest version below it
+                    (true, true) => considering > current,
+                }
+            }
+        }
+    }
+}
+
+fn bound_version(bound: &Bound<NuGetVersion>) -> Option<&NuGetVersion> {
+    match *bound {
+        Bound::Included(ref version) | Bound::Excluded(ref version) => Some(version),
+        Bound::Unbounded => None,
+    }
+}
+
+impl<'a> TryFrom<&'a str> for VersionRange {
+    type Error = InvalidRange;
+
+    fn try_from(string: &'a str) -> Result<VersionRange, InvalidRange> {
+        VersionRange::parse(string)
+    }
+}
+
+impl fmt::Display for VersionRange {
+    /// Format the range with normalized versions, as NuGet does, e.g.
+    /// `[1.0.0, )` for `1.0`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let (Some(float), Bound::Unbounded) = (&self.float, &self.upper) {
+            return write!(f, "{}", float);
+        }
+        if let (Bound::Included(lower), Bound::Included(upper)) = (&self.lower, &self.upper) {
+            if lower == upper {
+                return write!(f, "[{}]", lower.normalized());
+            }
+        }
+        let lower = match self.float {
+            Some(ref float) => float.to_string(),
+            None => bound_version(&self.lower).map_or(String::new(), |v| v.normalized()),
+        };
+        match self.lower {
+            Bound::Included(_) => write!(f, "[{}, ", lower)?,
+            Bound::Excluded(_) | Bound::Unbounded => write!(f, "({}, ", lower)?,
+        }
+        match self.upper {
+            Bound::Included(ref upper) => write!(f, "{}]", upper.normalized()),
+            Bound::Excluded(ref upper) => write!(f, "{})", upper.normalized()),
+            Bound::Unbounded => write!(f, ")"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use crate::semver::InvalidSemver;
+    use super::super::NuGetVersion;
+    use super::{FloatBehavior, InvalidRange, VersionRange};
+
+    fn parse(s: &str) -> NuGetVersion {
+        NuGetVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        for &(range, display) in &[
+            ("1.0", "[1.0.0, )"),
+            (" [1.0] ", "[1.0.0]"),
+            ("(1.0,)", "(1.0.0, )"),
+            ("(,1.0]", "(, 1.0.0]"),
+            ("(,1.0)", "(, 1.0.0)"),
+            ("[1.0, 2.0)", "[1.0.0, 2.0.0)"),
+            ("(1.0,2.0.0.0]", "(1.0.0, 2.0.0]"),
+            ("1.*", "1.*"),
+            ("[1.*,2.0)", "[1.*, 2.0.0)"),
+        ] {
+            assert_eq!(VersionRange::parse(range).unwrap().to_string(), display, "{}", range);
+        }
+        let range = VersionRange::parse("[1.0,2.0)").unwrap();
+        assert_eq!(range.lower(), Bound::Included(&parse("1.0")));
+        assert_eq!(range.upper(), Bound::Excluded(&parse("2.0")));
+
+        for &(range, ref error) in &[
+            ("", InvalidRange::InvalidSyntax),
+            ("[1.0", InvalidRange::InvalidSyntax),
+            ("(1.0)", InvalidRange::InvalidSyntax),
+            ("[1.0,2.0,3.0]", InvalidRange::InvalidSyntax),
+            ("(,)", InvalidRange::InvalidSyntax),
+            ("[2.0,1.0]", InvalidRange::DefiesOrdering),
+            ("[1.0,1.0)", InvalidRange::DefiesOrdering),
+            ("[1.0,2.*]", InvalidRange::InvalidFloat),
+            ("1.*.3", InvalidRange::InvalidFloat),
+            ("1.0.0-*-beta", InvalidRange::InvalidFloat),
+            ("1.2.3.4.*", InvalidRange::InvalidFloat),
+            ("[1.0,x]", InvalidRange::InvalidVersion(InvalidSemver::InvalidCharacter)),
+        ] {
+            assert_eq!(&VersionRange::parse(range).unwrap_err(), error, "{}", range);
+        }
+    }
+
+    #[test]
+    fn test_float() {
+        for &(range, behavior, min) in &[
+            ("*", FloatBehavior::Major, "0.0.0"),
+            ("1.*", FloatBehavior::Minor, "1.0.0"),
+            ("1.2.*", FloatBehavior::Patch, "1.2.0"),
+            ("1.2.3.*", FloatBehavior::Revision, "1.2.3"),
+            ("1.0.0-*", FloatBehavior::Prerelease, "1.0.0-0"),
+            ("1.0.0-beta*", FloatBehavior::Prerelease, "1.0.0-beta"),
+            ("1.0.0-beta.*", FloatBehavior::Prerelease, "1.0.0-beta.0"),
+            ("1.*-*", FloatBehavior::PrereleaseMinor, "1.0.0-0"),
+            ("1.2.*-rc*", FloatBehavior::PrereleasePatch, "1.2.0-rc"),
+            ("1.2.3.*-*", FloatBehavior::PrereleaseRevision, "1.2.3-0"),
+            ("*-*", FloatBehavior::AbsoluteLatest, "0.0.0-0"),
+        ] {
+            let range = VersionRange::parse(range).unwrap();
+            let float = range.float_range().unwrap();
+            assert_eq!(float.behavior(), behavior, "{}", range);
+            assert_eq!(float.min_version().normalized(), min, "{}", range);
+            assert_eq!(range.lower(), Bound::Included(float.min_version()));
+        }
+        assert!(VersionRange::parse("1.*").unwrap().contains(&parse("2.0")));
+    }
+
+    #[test]
+    fn test_find_best_match() {
+        for &(range, versions, expected) in &[
+            ("[1.0,2.0)", &["0.9", "1.5", "1.1", "2.0"][..], Some("1.1")),
+            ("1.0", &["0.9", "1.0.0-beta"], None),
+            ("1.*", &["1.0.0", "1.1.0", "1.2.0-beta", "2.0.0"], Some("1.1.0")),
+            ("1.*", &["0.9", "3.0.0", "2.0.0", "2.0.0-beta"], Some("2.0.0-beta")),
+            ("1.1.*", &["1.0.5", "1.2.0"], Some("1.2.0")),
+            ("[1.1.*,2.0)", &["1.0.5", "1.2.0", "2.0"], Some("1.2.0")),
+            ("1.2.*", &["1.2.0", "1.2.9", "1.3.0"], Some("1.2.9")),
+            ("1.0.0-*", &["1.0.0-alpha", "1.0.0-beta", "1.0.1"], Some("1.0.0-beta")),
+            ("1.0.0-*", &["1.0.0-alpha", "1.0.0", "1.0.1"], Some("1.0.0")),
+            ("1.0.0-beta*", &["1.0.0-alpha2", "1.0.0-beta1", "1.0.0-BETA2", "1.0.0-rc1"], Some("1.0.0-BETA2")),
+            ("1.2.*-*", &["1.2.0", "1.2.5-beta", "1.3.0"], Some("1.2.5-beta")),
+            ("*", &["1.0", "2.0-beta", "1.5"], Some("1.5")),
+            ("*-*", &["1.0", "2.0-beta", "1.5"], Some("2.0-beta")),
+        ] {
+            let range = VersionRange::parse(range).unwrap();
+            let versions: Vec<NuGetVersion> = versions.iter().map(|v| parse(v)).collect();
+            assert_eq!(
+                range.find_best_match(&versions).map(|v| v.to_string()),
+                expected.map(|v| v.to_owned()),
+                "{}",
+                range,
+            );
+        }
+    }
+}
@@ -8,7 +8,504 @@
 //!
 //! It is unusual in that it gives meaning to specific identifiers, such as
 //! `post`, `dev`, `rc``, `a` (for alpha), and `b` (for beta).
+//!
+//! Versions that predate PEP-440 can still be compared using
+//! [`LegacyVersion`](struct.LegacyVersion.html), or
+//! [`AnyVersion`](enum.AnyVersion.html) which accepts both.
+//!
+//! The name of wheels and source distributions can also be parsed, to get the
+//! project name and version from a file.
+//!
+//! Requirements (`name[extras] >=1.0,<2`) can be parsed, as well as pip's
+//! requirements files. The files matching a requirement can be selected from
+//! a package index's project page.
+
+mod filename;
+mod index;
+mod legacy;
+mod requirements;
+mod specifier;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+
+pub use self::filename::{
+    BuildTag, DistFilename, InvalidFilename, SdistFilename, Tag, WheelFilename,
+    normalize_name,
+};
+pub use self::index::{Candidate, IndexFile, InvalidPage, ProjectPage};
+pub use self::legacy::{AnyVersion, LegacyVersion};
+pub use self::requirements::{
+    FileOption, InvalidLine, InvalidRequirement, Location, Requirement,
+    RequirementLine, RequirementsError, RequirementsFile, requirements_file,
+};
+pub use self::specifier::{
+    InvalidSpecifier, Operator, Specifier, SpecifierSet,
+};
+
+/// The kind of pre-release, e.g. alpha, beta, or release candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PreRelease {
+    /// Alpha release (`a`, also spelled `alpha`).
+    Alpha,
+    /// Beta release (`b`, also spelled `beta`).
+    Beta,
+    /// Release candidate (`rc`, also spelled `c`, `pre` or `preview`).
+    ReleaseCandidate,
+}
+
+impl fmt::Display for PreRelease {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PreRelease::Alpha => write!(f, "a"),
+            PreRelease::Beta => write!(f, "b"),
+            PreRelease::ReleaseCandidate => write!(f, "rc"),
+        }
+    }
+}
+
+/// A segment of the local version label (after the `+`).
+///
+/// Alphanumeric segments sort before numeric ones.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LocalSegment {
+    Alpha(String),
+    Numeric(u64),
+}
+
+impl fmt::Display for LocalSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LocalSegment::Alpha(ref s) => write!(f, "{}", s),
+            LocalSegment::Numeric(n) => write!(f, "{}", n),
+        }
+    }
+}
 
 /// A PEP-440-compliant Python version number.
+///
+/// The version is normalized when parsed, so `1.0-ALPHA.1` and `1.0a1` are the
+/// same version, and display the same way.
 #[derive(Clone, Debug)]
-pub struct PythonVersion(String);
+pub struct PythonVersion {
+    epoch: u64,
+    release: Vec<u64>,
+    pre: Option<(PreRelease, u64)>,
+    post: Option<u64>,
+    dev: Option<u64>,
+    local: Vec<LocalSegment>,
+}
+
+impl PythonVersion {
+    /// The epoch, `0` if not specified.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// The release segment, e.g. `[1, 2, 3]` for `1.2.3`.
+    pub fn release(&self) -> &[u64] {
+        &self.release
+    }
+
+    /// The pre-release segment, e.g. `(Alpha, 1)` for `1.0a1`.
+    pub fn pre(&self) -> Option<(PreRelease, u64)> {
+        self.pre
+    }
+
+    /// The post-release number, e.g. `2` for `1.0.post2`.
+    pub fn post(&self) -> Option<u64> {
+        self.post
+    }
+
+    /// The development release number, e.g. `3` for `1.0.dev3`.
+    pub fn dev(&self) -> Option<u64> {
+        self.dev
+    }
+
+    /// The local version label, e.g. `[ubuntu, 1]` for `1.0+ubuntu.1`.
+    pub fn local(&self) -> &[LocalSegment] {
+        &self.local
+    }
+
+    /// Whether this is a pre-release or development release.
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    fn cmp_key(&self) -> CmpKey<'_> {
+        // Trailing zeros are not significant in the release segment
+        let mut release = &self.release[..];
+        while let Some((&0, rest)) = release.split_last() {
+            release = rest;
+        }
+        // A development release without a pre-release sorts before all
+        // pre-releases of the same version
+        let pre = match (self.pre, self.post, self.dev) {
+            (Some((kind, num)), _, _) => PreKey::Pre(kind, num),
+            (None, None, Some(_)) => PreKey::DevOnly,
+            (None, _, _) => PreKey::Final,
+        };
+        (
+            self.epoch,
+            release,
+            pre,
+            self.post,
+            // A development release sorts before the same version without it
+            (self.dev.is_none(), self.dev),
+            &self.local,
+        )
+    }
+}
+
+/// The fields of a version, in the order in which they are compared.
+type CmpKey<'a> = (
+    u64,
+    &'a [u64],
+    PreKey,
+    Option<u64>,
+    (bool, Option<u64>),
+    &'a [LocalSegment],
+);
+
+/// How the pre-release segment sorts, accounting for dev releases.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum PreKey {
+    DevOnly,
+    Pre(PreRelease, u64),
+    Final,
+}
+
+impl fmt::Display for PythonVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.epoch != 0 {
+            write!(f, "{}!", self.epoch)?;
+        }
+        for (i, num) in self.release.iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", num)?;
+        }
+        if let Some((kind, num)) = self.pre {
+            write!(f, "{}{}", kind, num)?;
+        }
+        if let Some(num) = self.post {
+            write!(f, ".post{}", num)?;
+        }
+        if let Some(num) = self.dev {
+            write!(f, ".dev{}", num)?;
+        }
+        for (i, segment) in self.local.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { '+' } else { '.' }, segment)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<PythonVersion> for PythonVersion {
+    fn eq(&self, other: &PythonVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PythonVersion {}
+
+impl PartialOrd<PythonVersion> for PythonVersion {
+    fn partial_cmp(&self, other: &PythonVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PythonVersion {
+    fn cmp(&self, other: &PythonVersion) -> Ordering {
+        self.cmp_key().cmp(&other.cmp_key())
+    }
+}
+
+/// Simple cursor over a lowercased version string.
+struct Parser<'a> {
+    string: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.string.get(self.pos).cloned()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_separator(&mut self) -> bool {
+        match self.peek() {
+            Some(b'-') | Some(b'_') | Some(b'.') => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Read the first of the given words that matches.
+    fn eat_word<T: Copy>(&mut self, words: &[(&str, T)]) -> Option<T> {
+        for &(word, value) in words {
+            if self.string[self.pos ..].starts_with(word.as_bytes()) {
+                self.pos += word.len();
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn number(&mut self) -> Result<Option<u64>, InvalidVersion> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Ok(None);
+        }
+        let mut num: u64 = 0;
+        for &c in &self.string[start .. self.pos] {
+            num = num.checked_mul(10)
+                .and_then(|n| n.checked_add(u64::from(c - b'0')))
+                .ok_or(InvalidVersion::NumberTooLarge)?;
+        }
+        Ok(Some(num))
+    }
+
+    /// Read a labelled segment, like `.post1` or `-rc`.
+    ///
+    /// If there is no label, nothing is consumed.
+    fn labelled<T: Copy>(
+        &mut self,
+        words: &[(&str, T)],
+    ) -> Result<Option<(T, u64)>, InvalidVersion> {
+        let start = self.pos;
+        self.eat_separator();
+        let label = match self.eat_word(words) {
+            Some(label) => label,
+            None => {
+                self.pos = start;
+                return Ok(None);
+            }
+        };
+        self.eat_separator();
+        let num = self.number()?.unwrap_or(0);
+        Ok(Some((label, num)))
+    }
+}
+
+const PRE_LABELS: &[(&str, PreRelease)] = &[
+    ("alpha", PreRelease::Alpha),
+    ("a", PreRelease::Alpha),
+    ("beta", PreRelease::Beta),
+    ("b", PreRelease::Beta),
+    ("preview", PreRelease::ReleaseCandidate),
+    ("pre", PreRelease::ReleaseCandidate),
+    ("c", PreRelease::ReleaseCandidate),
+    ("rc", PreRelease::ReleaseCandidate),
+];
+
+const POST_LABELS: &[(&str, ())] = &[("post", ()), ("rev", ()), ("r", ())];
+
+const DEV_LABELS: &[(&str, ())] = &[("dev", ())];
+
+impl TryFrom<String> for PythonVersion {
+    type Error = InvalidVersion;
+
+    /// Parse a version, accepting all the variations allowed by PEP-440.
+    fn try_from(string: String) -> Result<PythonVersion, InvalidVersion> {
+        let string = string.trim().to_ascii_lowercase();
+        if string.is_empty() {
+            return Err(InvalidVersion::EmptyField);
+        }
+        let mut parser = Parser { string: string.as_bytes(), pos: 0 };
+        parser.eat(b'v');
+
+        // Epoch and release
+        let mut epoch = 0;
+        let mut first = parser.number()?.ok_or(InvalidVersion::EmptyField)?;
+        if parser.eat(b'!') {
+            epoch = first;
+            first = parser.number()?.ok_or(InvalidVersion::EmptyField)?;
+        }
+        let mut release = vec![first];
+        while parser.peek() == Some(b'.') {
+            let dot = parser.pos;
+            parser.pos += 1;
+            match parser.number()? {
+                Some(num) => release.push(num),
+                None => {
+                    parser.pos = dot;
+                    break;
+                }
+            }
+        }
+
+        // Pre-release
+        let pre = parser.labelled(PRE_LABELS)?;
+
+        // Post-release, either implicit (`-1`) or labelled
+        let mut post = None;
+        let dash = parser.pos;
+        if parser.eat(b'-') {
+            post = parser.number()?;
+            if post.is_none() {
+                parser.pos = dash;
+            }
+        }
+        if post.is_none() {
+            post = parser.labelled(POST_LABELS)?.map(|(_, num)| num);
+        }
+
+        // Development release
+        let dev = parser.labelled(DEV_LABELS)?.map(|(_, num)| num);
+
+        // Local version label
+        let mut local = Vec::new();
+        if parser.eat(b'+') {
+            loop {
+                let start = parser.pos;
+                while parser.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+                    parser.pos += 1;
+                }
+                if start == parser.pos {
+                    return Err(InvalidVersion::EmptyField);
+                }
+                let segment = &string[start .. parser.pos];
+                local.push(match segment.parse() {
+                    Ok(num) => LocalSegment::Numeric(num),
+                    Err(_) => LocalSegment::Alpha(segment.to_owned()),
+                });
+                if !parser.eat_separator() {
+                    break;
+                }
+            }
+        }
+
+        if parser.pos != string.len() {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+        Ok(PythonVersion { epoch, release, pre, post, dev, local })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::InvalidVersion;
+    use super::{LocalSegment, PreRelease, PythonVersion};
+
+    fn parse(s: &str) -> PythonVersion {
+        PythonVersion::try_from(s.to_owned()).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let version = parse("2!1.2.3rc4.post5.dev6+ubuntu.7");
+        assert_eq!(version.epoch(), 2);
+        assert_eq!(version.release(), &[1, 2, 3]);
+        assert_eq!(version.pre(), Some((PreRelease::ReleaseCandidate, 4)));
+        assert_eq!(version.post(), Some(5));
+        assert_eq!(version.dev(), Some(6));
+        assert_eq!(
+            version.local(),
+            &[LocalSegment::Alpha("ubuntu".into()), LocalSegment::Numeric(7)],
+        );
+        assert!(version.is_prerelease());
+        assert!(!parse("1.0.post1").is_prerelease());
+
+        assert_eq!(
+            PythonVersion::try_from("".to_owned()).unwrap_err(),
+            InvalidVersion::EmptyField,
+        );
+        for invalid in &["1.0-", "1..0", "1.0.", "french toast", "1.0+", "1.0+a..b", "1.0a1a2"] {
+            assert!(PythonVersion::try_from(invalid.to_string()).is_err(), "{}", invalid);
+        }
+        for &too_large in &["99999999999999999999", "1.0.post99999999999999999999"] {
+            assert_eq!(
+                PythonVersion::try_from(too_large.to_owned()).unwrap_err(),
+                InvalidVersion::NumberTooLarge,
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        for &(input, normalized) in &[
+            ("1.0", "1.0"),
+            ("v1.0", "1.0"),
+            ("  1.0\n", "1.0"),
+            ("0!1.0", "1.0"),
+            ("01.002", "1.2"),
+            ("1.0ALPHA", "1.0a0"),
+            ("1.0-alpha.1", "1.0a1"),
+            ("1.0beta_2", "1.0b2"),
+            ("1.0c3", "1.0rc3"),
+            ("1.0-pre4", "1.0rc4"),
+            ("1.0preview5", "1.0rc5"),
+            ("1.0-1", "1.0.post1"),
+            ("1.0-r2", "1.0.post2"),
+            ("1.0rev", "1.0.post0"),
+            ("1.0_post_3", "1.0.post3"),
+            ("1.0-dev", "1.0.dev0"),
+            ("1.0a.", "1.0a0"),
+            ("1.0+Ubuntu-1_2", "1.0+ubuntu.1.2"),
+            ("1.0+007", "1.0+7"),
+        ] {
+            assert_eq!(parse(input).to_string(), normalized);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let versions = [
+            "1.0.dev456",
+            "1.0a1",
+            "1.0a2.dev456",
+            "1.0a12.dev456",
+            "1.0a12",
+            "1.0b1.dev456",
+            "1.0b2",
+            "1.0b2.post345.dev456",
+            "1.0b2.post345",
+            "1.0b2-346",
+            "1.0c1.dev456",
+            "1.0c1",
+            "1.0rc2",
+            "1.0c3",
+            "1.0",
+            "1.0.post456.dev34",
+            "1.0.post456",
+            "1.1.dev1",
+            "1.2+123abc",
+            "1.2+123abc456",
+            "1.2+abc",
+            "1.2+abc123",
+            "1.2+abc123def",
+            "1.2+1234.abc",
+            "1.2+123456",
+            "1.2.r32+123456",
+            "1.2.rev33+123456",
+            "1!1.0.dev456",
+            "1!1.0",
+        ];
+        for pair in versions.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(parse("1.0"), parse("1.0.0"));
+        assert_eq!(parse("1.0c1"), parse("1.0rc1"));
+        assert!(parse("1.0") < parse("1.0+0"));
+    }
+}
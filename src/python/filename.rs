@@ -0,0 +1,407 @@
+//! Parse the names of Python distribution files.
+//!
+//! Wheels are named according to
+//! [PEP-427](https://www.python.org/dev/peps/pep-0427/):
+//! `{name}-{version}(-{build tag})?-{python tag}-{abi tag}-{platform tag}.whl`,
+//! where the name and version have their hyphens escaped as underscores, and
+//! each tag can be a set of dot-separated tags (`py2.py3`).
+//!
+//! Source distributions are simply named `{name}-{version}.tar.gz` (or
+//! `.zip`). Since a normalized version cannot contain a hyphen, the version is
+//! everything after the last hyphen.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+use super::PythonVersion;
+
+/// Normalize a project name, as per
+/// [PEP-503](https://www.python.org/dev/peps/pep-0503/#normalized-names).
+///
+/// Runs of `-`, `_` and `.` are replaced by a single `-`, and the name is
+/// lowercased.
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut separator = false;
+    for c in name.chars() {
+        if c == '-' || c == '_' || c == '.' {
+            separator = true;
+        } else {
+            if separator {
+                normalized.push('-');
+                separator = false;
+            }
+            normalized.extend(c.to_lowercase());
+        }
+    }
+    if separator {
+        normalized.push('-');
+    }
+    normalized
+}
+
+/// Error for the filename parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidFilename {
+    /// The file is neither a wheel nor a source distribution.
+    UnknownExtension,
+    /// The filename doesn't have the expected number of fields.
+    WrongFieldCount,
+    /// The project name contains invalid characters.
+    InvalidName,
+    /// The version is not a valid PEP-440 version.
+    InvalidVersion(InvalidVersion),
+    /// The build tag doesn't start with a digit.
+    InvalidBuildTag,
+}
+
+impl From<InvalidVersion> for InvalidFilename {
+    fn from(error: InvalidVersion) -> InvalidFilename {
+        InvalidFilename::InvalidVersion(error)
+    }
+}
+
+/// A compatibility tag, describing where a wheel can be installed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tag {
+    interpreter: String,
+    abi: String,
+    platform: String,
+}
+
+impl Tag {
+    pub fn new(interpreter: &str, abi: &str, platform: &str) -> Tag {
+        Tag {
+            interpreter: interpreter.to_ascii_lowercase(),
+            abi: abi.to_ascii_lowercase(),
+            platform: platform.to_ascii_lowercase(),
+        }
+    }
+
+    /// Expand a compressed tag set, like `py2.py3-none-any`.
+    ///
+    /// This returns every combination of the dot-separated values.
+    pub fn parse_compressed(tags: &str) -> Result<Vec<Tag>, InvalidFilename> {
+        let fields: Vec<&str> = tags.split('-').collect();
+        if fields.len() != 3 || fields.iter().any(|f| f.is_empty()) {
+            return Err(InvalidFilename::WrongFieldCount);
+        }
+        let mut result = Vec::new();
+        for interpreter in fields[0].split('.') {
+            for abi in fields[1].split('.') {
+                for platform in fields[2].split('.') {
+                    result.push(Tag::new(interpreter, abi, platform));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// The Python tag, e.g. `py3` or `cp39`.
+    pub fn interpreter(&self) -> &str {
+        &self.interpreter
+    }
+
+    /// The ABI tag, e.g. `none` or `cp39m`.
+    pub fn abi(&self) -> &str {
+        &self.abi
+    }
+
+    /// The platform tag, e.g. `any` or `manylinux1_x86_64`.
+    pub fn platform(&self) -> &str {
+        &self.platform
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.interpreter, self.abi, self.platform)
+    }
+}
+
+/// The optional build tag of a wheel, used as a tie-breaker.
+///
+/// It sorts by its leading number first, then by the rest of the string.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BuildTag {
+    number: u64,
+    suffix: String,
+}
+
+impl BuildTag {
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+}
+
+impl<'a> TryFrom<&'a str> for BuildTag {
+    type Error = InvalidFilename;
+
+    fn try_from(tag: &'a str) -> Result<BuildTag, InvalidFilename> {
+        let digits = tag.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(InvalidFilename::InvalidBuildTag);
+        }
+        let number = tag[.. digits].parse()
+            .map_err(|_| InvalidFilename::InvalidBuildTag)?;
+        Ok(BuildTag { number, suffix: tag[digits ..].to_owned() })
+    }
+}
+
+impl fmt::Display for BuildTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.number, self.suffix)
+    }
+}
+
+/// The information contained in the name of a wheel file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WheelFilename {
+    name: String,
+    version: PythonVersion,
+    build_tag: Option<BuildTag>,
+    tags: Vec<Tag>,
+}
+
+impl WheelFilename {
+    /// The normalized project name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &PythonVersion {
+        &self.version
+    }
+
+    pub fn build_tag(&self) -> Option<&BuildTag> {
+        self.build_tag.as_ref()
+    }
+
+    /// All the tags this wheel is compatible with, expanded.
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+}
+
+impl<'a> TryFrom<&'a str> for WheelFilename {
+    type Error = InvalidFilename;
+
+    fn try_from(filename: &'a str) -> Result<WheelFilename, InvalidFilename> {
+        let stem = filename.strip_suffix(".whl")
+            .ok_or(InvalidFilename::UnknownExtension)?;
+
+        // The name and version have their hyphens escaped, so the number of
+        // fields is fixed, with only the build tag being optional
+        let fields: Vec<&str> = stem.split('-').collect();
+        if fields.len() != 5 && fields.len() != 6 {
+            return Err(InvalidFilename::WrongFieldCount);
+        }
+
+        let name = fields[0];
+        if
+            name.is_empty() || name.contains("__") ||
+            !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        {
+            return Err(InvalidFilename::InvalidName);
+        }
+        let version = PythonVersion::try_from(fields[1].to_owned())?;
+        let build_tag = if fields.len() == 6 {
+            Some(BuildTag::try_from(fields[2])?)
+        } else {
+            None
+        };
+        let tags = Tag::parse_compressed(&fields[fields.len() - 3 ..].join("-"))?;
+
+        Ok(WheelFilename {
+            name: normalize_name(name),
+            version,
+            build_tag,
+            tags,
+        })
+    }
+}
+
+/// The information contained in the name of a source distribution file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SdistFilename {
+    name: String,
+    version: PythonVersion,
+}
+
+impl SdistFilename {
+    /// The normalized project name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &PythonVersion {
+        &self.version
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SdistFilename {
+    type Error = InvalidFilename;
+
+    fn try_from(filename: &'a str) -> Result<SdistFilename, InvalidFilename> {
+        let stem = filename.strip_suffix(".tar.gz")
+            .or_else(|| filename.strip_suffix(".zip"))
+            .ok_or(InvalidFilename::UnknownExtension)?;
+
+        // Hyphens are allowed in the name, but not in the version
+        let hyphen = stem.rfind('-').ok_or(InvalidFilename::WrongFieldCount)?;
+        let name = &stem[.. hyphen];
+        if name.is_empty() {
+            return Err(InvalidFilename::InvalidName);
+        }
+        let version = PythonVersion::try_from(stem[hyphen + 1 ..].to_owned())?;
+
+        Ok(SdistFilename {
+            name: normalize_name(name),
+            version,
+        })
+    }
+}
+
+/// The name of a distribution file, either a wheel or a source distribution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DistFilename {
+    Wheel(WheelFilename),
+    Sdist(SdistFilename),
+}
+
+impl DistFilename {
+    /// The normalized project name.
+    pub fn name(&self) -> &str {
+        match *self {
+            DistFilename::Wheel(ref w) => w.name(),
+            DistFilename::Sdist(ref s) => s.name(),
+        }
+    }
+
+    pub fn version(&self) -> &PythonVersion {
+        match *self {
+            DistFilename::Wheel(ref w) => w.version(),
+            DistFilename::Sdist(ref s) => s.version(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for DistFilename {
+    type Error = InvalidFilename;
+
+    fn try_from(filename: &'a str) -> Result<DistFilename, InvalidFilename> {
+        if filename.ends_with(".whl") {
+            WheelFilename::try_from(filename).map(DistFilename::Wheel)
+        } else {
+            SdistFilename::try_from(filename).map(DistFilename::Sdist)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::InvalidVersion;
+    use super::{
+        BuildTag, DistFilename, InvalidFilename, SdistFilename, Tag,
+        WheelFilename, normalize_name,
+    };
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Foo.Bar__baz-qux"), "foo-bar-baz-qux");
+        assert_eq!(normalize_name("zope.interface"), "zope-interface");
+        assert_eq!(normalize_name("requests"), "requests");
+    }
+
+    #[test]
+    fn test_wheel() {
+        let wheel = WheelFilename::try_from(
+            "Foo_Bar-1.0_post1-py2.py3-none-any.whl",
+        ).unwrap();
+        assert_eq!(wheel.name(), "foo-bar");
+        assert_eq!(wheel.version().to_string(), "1.0.post1");
+        assert_eq!(wheel.build_tag(), None);
+        assert_eq!(
+            wheel.tags(),
+            &[Tag::new("py2", "none", "any"), Tag::new("py3", "none", "any")],
+        );
+
+        let wheel = WheelFilename::try_from(
+            "numpy-1.21.0-12abc-cp39-cp39-manylinux1_x86_64.manylinux2010_x86_64.whl",
+        ).unwrap();
+        assert_eq!(wheel.name(), "numpy");
+        assert_eq!(
+            wheel.build_tag(),
+            Some(&BuildTag::try_from("12abc").unwrap()),
+        );
+        assert_eq!(wheel.build_tag().unwrap().number(), 12);
+        assert_eq!(wheel.build_tag().unwrap().suffix(), "abc");
+        assert_eq!(wheel.tags().len(), 2);
+        assert_eq!(wheel.tags()[1].to_string(), "cp39-cp39-manylinux2010_x86_64");
+
+        assert!(BuildTag::try_from("2").unwrap() < BuildTag::try_from("10").unwrap());
+        assert!(BuildTag::try_from("1").unwrap() < BuildTag::try_from("1a").unwrap());
+
+        assert_eq!(
+            WheelFilename::try_from("foo-bar-1.0-py3-none-any.whl"),
+            Err(InvalidFilename::InvalidVersion(InvalidVersion::EmptyField)),
+        );
+        assert_eq!(
+            WheelFilename::try_from("foo-bar-1.0-1-py3-none-any.whl"),
+            Err(InvalidFilename::WrongFieldCount),
+        );
+        assert_eq!(
+            WheelFilename::try_from("foo__bar-1.0-py3-none-any.whl"),
+            Err(InvalidFilename::InvalidName),
+        );
+        assert_eq!(
+            WheelFilename::try_from("foo-1.0-abc-py3-none-any.whl"),
+            Err(InvalidFilename::InvalidBuildTag),
+        );
+        assert_eq!(
+            WheelFilename::try_from("foo-1.0-py3-none-any.tar.gz"),
+            Err(InvalidFilename::UnknownExtension),
+        );
+        assert_eq!(
+            WheelFilename::try_from("foo-1..0-py3-none-any.whl"),
+            Err(InvalidFilename::InvalidVersion(InvalidVersion::InvalidCharacter)),
+        );
+    }
+
+    #[test]
+    fn test_sdist() {
+        let sdist = SdistFilename::try_from("django-rest-framework-3.12.4.tar.gz")
+            .unwrap();
+        assert_eq!(sdist.name(), "django-rest-framework");
+        assert_eq!(sdist.version().to_string(), "3.12.4");
+
+        let sdist = SdistFilename::try_from("Zope.Interface-5.4.0rc1.zip").unwrap();
+        assert_eq!(sdist.name(), "zope-interface");
+        assert_eq!(sdist.version().to_string(), "5.4.0rc1");
+
+        assert_eq!(
+            SdistFilename::try_from("foo.tar.gz"),
+            Err(InvalidFilename::WrongFieldCount),
+        );
+        assert_eq!(
+            SdistFilename::try_from("foo-1.0.tar.bz2"),
+            Err(InvalidFilename::UnknownExtension),
+        );
+
+        match DistFilename::try_from("foo-1.0.tar.gz").unwrap() {
+            DistFilename::Sdist(s) => assert_eq!(s.name(), "foo"),
+            DistFilename::Wheel(_) => panic!(),
+        }
+        match DistFilename::try_from("foo-1.0-py3-none-any.whl").unwrap() {
+            DistFilename::Wheel(w) => assert_eq!(w.name(), "foo"),
+            DistFilename::Sdist(_) => panic!(),
+        }
+    }
+}
@@ -0,0 +1,723 @@
+//! Select files from a Python package index, the way pip does.
+//!
+//! A project page from a "simple" repository lists the files available for a
+//! project. It is either HTML, as per
+//! [PEP-503](https://www.python.org/dev/peps/pep-0503/), or JSON, as per
+//! [PEP-691](https://www.python.org/dev/peps/pep-0691/).
+//!
+//! From such a page, the candidates for a requirement are the files with a
+//! matching version, which are not yanked
+//! ([PEP-592](https://www.python.org/dev/peps/pep-0592/)) unless the version
+//! is pinned exactly, and which are compatible with the target platform.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+use super::filename::{BuildTag, DistFilename, Tag, normalize_name};
+use super::specifier::{Operator, SpecifierSet};
+use super::PythonVersion;
+
+/// Error for the project page parsers.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidPage {
+    /// The JSON document is malformed.
+    InvalidJson,
+    /// A required key is missing from the JSON document, or has the wrong
+    /// type.
+    MissingField(&'static str),
+    /// The API version is not supported (only 1.x is).
+    UnsupportedApiVersion(String),
+}
+
+/// A file listed on a project page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexFile {
+    filename: String,
+    url: String,
+    hashes: Vec<(String, String)>,
+    requires_python: Option<String>,
+    yanked: bool,
+    yanked_reason: Option<String>,
+}
+
+impl IndexFile {
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// The URL of the file, as written on the page (it can be relative).
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The known hashes of the file, as `(algorithm, hex digest)` pairs.
+    pub fn hashes(&self) -> &[(String, String)] {
+        &self.hashes
+    }
+
+    /// The `Requires-Python` metadata of the file, if provided.
+    pub fn requires_python(&self) -> Option<&str> {
+        self.requires_python.as_deref()
+    }
+
+    pub fn is_yanked(&self) -> bool {
+        self.yanked
+    }
+
+    pub fn yanked_reason(&self) -> Option<&str> {
+        self.yanked_reason.as_deref()
+    }
+}
+
+/// A project page from a simple repository.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectPage {
+    name: Option<String>,
+    files: Vec<IndexFile>,
+}
+
+/// A file that can be installed, with the information from its name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate<'a> {
+    file: &'a IndexFile,
+    filename: DistFilename,
+    /// Position of the best supported tag, `None` for source distributions.
+    priority: Option<usize>,
+}
+
+impl<'a> Candidate<'a> {
+    pub fn file(&self) -> &'a IndexFile {
+        self.file
+    }
+
+    pub fn filename(&self) -> &DistFilename {
+        &self.filename
+    }
+
+    pub fn version(&self) -> &PythonVersion {
+        self.filename.version()
+    }
+
+    fn build_tag(&self) -> Option<&BuildTag> {
+        match self.filename {
+            DistFilename::Wheel(ref w) => w.build_tag(),
+            DistFilename::Sdist(_) => None,
+        }
+    }
+
+    /// Compare by preference, the preferred candidate first.
+    ///
+    /// Non-yanked files come first, then higher versions, then wheels with
+    /// a better tag, then source distributions, then higher build tags.
+    fn cmp_preference(&self, other: &Candidate) -> Ordering {
+        self.file.yanked.cmp(&other.file.yanked)
+            .then_with(|| other.version().cmp(self.version()))
+            .then_with(|| match (self.priority, other.priority) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+            .then_with(|| other.build_tag().cmp(&self.build_tag()))
+    }
+}
+
+impl ProjectPage {
+    /// The normalized project name, if the page includes it.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn files(&self) -> &[IndexFile] {
+        &self.files
+    }
+
+    /// Parse a PEP-691 JSON page.
+    pub fn from_json(page: &str) -> Result<ProjectPage, InvalidPage> {
+        let document = JsonParser::parse(page)?;
+
+        let version = document.get("meta")
+            .and_then(|m| m.get("api-version"))
+            .and_then(Json::as_str)
+            .ok_or(InvalidPage::MissingField("meta.api-version"))?;
+        if version.split('.').next() != Some("1") {
+            return Err(InvalidPage::UnsupportedApiVersion(version.to_owned()));
+        }
+
+        let name = document.get("name")
+            .and_then(Json::as_str)
+            .ok_or(InvalidPage::MissingField("name"))?;
+        let files = match document.get("files") {
+            Some(Json::Array(files)) => files,
+            _ => return Err(InvalidPage::MissingField("files")),
+        };
+
+        let mut result = Vec::with_capacity(files.len());
+        for file in files {
+            let filename = file.get("filename")
+                .and_then(Json::as_str)
+                .ok_or(InvalidPage::MissingField("files.filename"))?;
+            let url = file.get("url")
+                .and_then(Json::as_str)
+                .ok_or(InvalidPage::MissingField("files.url"))?;
+            let hashes = match file.get("hashes") {
+                Some(Json::Object(hashes)) => {
+                    hashes.iter()
+                        .filter_map(|(k, v)| {
+                            v.as_str().map(|v| (k.clone(), v.to_owned()))
+                        })
+                        .collect()
+                }
+                _ => return Err(InvalidPage::MissingField("files.hashes")),
+            };
+            let requires_python = file.get("requires-python")
+                .and_then(Json::as_str)
+                .map(|r| r.to_owned());
+            let (yanked, yanked_reason) = match file.get("yanked") {
+                Some(&Json::Bool(yanked)) => (yanked, None),
+                Some(Json::String(reason)) => (true, Some(reason.clone())),
+                _ => (false, None),
+            };
+            result.push(IndexFile {
+                filename: filename.to_owned(),
+                url: url.to_owned(),
+                hashes,
+                requires_python,
+                yanked,
+                yanked_reason,
+            });
+        }
+
+        Ok(ProjectPage {
+            name: Some(normalize_name(name)),
+            files: result,
+        })
+    }
+
+    /// Parse a PEP-503 HTML page.
+    ///
+    /// The page doesn't include the project name, so it is not known.
+    pub fn from_html(page: &str) -> ProjectPage {
+        let mut files = Vec::new();
+        let lower = page.to_ascii_lowercase();
+        let mut pos = 0;
+        while let Some(start) = lower[pos ..].find("<a").map(|i| i + pos) {
+            pos = start + 2;
+            // Make sure this is an anchor, not e.g. `<abbr>`
+            if !lower[pos ..].starts_with(|c: char| c.is_whitespace() || c == '>') {
+                continue;
+            }
+            let tag_end = match find_tag_end(page, pos) {
+                Some(end) => end,
+                None => break,
+            };
+            let attributes = parse_attributes(&page[pos .. tag_end]);
+            let text_end = lower[tag_end ..].find("</a")
+                .map(|i| i + tag_end)
+                .unwrap_or(page.len());
+            let text = decode_entities(page[tag_end + 1 .. text_end].trim());
+            pos = text_end;
+
+            let href = match attributes.iter().find(|a| a.0 == "href") {
+                Some((_, Some(href))) => href.clone(),
+                _ => continue,
+            };
+            let (url, hashes) = match href.find('#') {
+                Some(hash) => {
+                    let hashes = href[hash + 1 ..].split('&')
+                        .filter_map(|param| {
+                            let eq = param.find('=')?;
+                            Some((param[.. eq].to_owned(), param[eq + 1 ..].to_owned()))
+                        })
+                        .collect();
+                    (href[.. hash].to_owned(), hashes)
+                }
+                None => (href, Vec::new()),
+            };
+            let requires_python = attributes.iter()
+                .find(|a| a.0 == "data-requires-python")
+                .and_then(|a| a.1.clone());
+            let yanked = attributes.iter().find(|a| a.0 == "data-yanked");
+            files.push(IndexFile {
+                filename: text,
+                url,
+                hashes,
+                requires_python,
+                yanked: yanked.is_some(),
+                yanked_reason: yanked
+                    .and_then(|a| a.1.clone())
+                    .filter(|r| !r.is_empty()),
+            });
+        }
+        ProjectPage { name: None, files }
+    }
+
+    /// Find the files that can be installed for the given requirement, the
+    /// preferred one first.
+    ///
+    /// `tags` are the tags supported by the target, the preferred one first
+    /// (wheels that don't match any are ignored). Pre-releases are accepted
+    /// if `prereleases` is `Some(true)`, or, if it is `None`, using the
+    /// default policy of [`SpecifierSet::filter()`].
+    ///
+    /// [`SpecifierSet::filter()`]: struct.SpecifierSet.html#method.filter
+    pub fn candidates(
+        &self,
+        specifier: &SpecifierSet,
+        prereleases: Option<bool>,
+        tags: &[Tag],
+    ) -> Vec<Candidate<'_>> {
+        // Yanked files can only be installed with an exact pin
+        let pinned = specifier.specifiers().iter().any(|s| {
+            s.operator() == Operator::Equal && !s.is_wildcard() ||
+                s.operator() == Operator::ArbitraryEqual
+        });
+
+        let candidates = self.files.iter().filter_map(|file| {
+            if file.yanked && !pinned {
+                return None;
+            }
+            let filename = DistFilename::try_from(file.filename.as_str())
+                .ok()?;
+            if let Some(ref name) = self.name {
+                if filename.name() != name {
+                    return None;
+                }
+            }
+            let priority = match filename {
+                DistFilename::Wheel(ref wheel) => {
+                    Some(wheel.tags().iter()
+                        .filter_map(|t| tags.iter().position(|s| s == t))
+                        .min()?)
+                }
+                DistFilename::Sdist(_) => None,
+            };
+            Some(Candidate { file, filename, priority })
+        });
+
+        let mut candidates = specifier.filter_by(
+            candidates,
+            |c| c.version(),
+            prereleases,
+        );
+        candidates.sort_by(Candidate::cmp_preference);
+        candidates
+    }
+
+    /// Find the file pip would install for the given requirement.
+    ///
+    /// See [`candidates()`](#method.candidates).
+    pub fn best_candidate(
+        &self,
+        specifier: &SpecifierSet,
+        prereleases: Option<bool>,
+        tags: &[Tag],
+    ) -> Option<Candidate<'_>> {
+        self.candidates(specifier, prereleases, tags).into_iter().next()
+    }
+}
+
+/// Find the `>` closing a tag, skipping quoted attribute values.
+fn find_tag_end(page: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in page[start ..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return Some(start + idx),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+/// Parse the attributes of an HTML tag, lowercasing their names and decoding
+/// their values.
+fn parse_attributes(tag: &str) -> Vec<(String, Option<String>)> {
+    let mut attributes = Vec::new();
+    let mut rest = tag.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        if name_end == 0 {
+            rest = rest[1 ..].trim_start();
+            continue;
+        }
+        let name = rest[.. name_end].to_ascii_lowercase();
+        rest = rest[name_end ..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+                let (value, remaining) = match after.chars().next() {
+                    Some(q) if q == '"' || q == '\'' => {
+                        let end = after[1 ..].find(q)
+                            .map(|i| i + 1)
+                            .unwrap_or(after.len());
+                        (&after[1 .. end], &after[(end + 1).min(after.len()) ..])
+                    }
+                    _ => {
+                        let end = after.find(char::is_whitespace)
+                            .unwrap_or(after.len());
+                        (&after[.. end], &after[end ..])
+                    }
+                };
+                rest = remaining.trim_start();
+                Some(decode_entities(value))
+            }
+            None => None,
+        };
+        attributes.push((name, value));
+    }
+    attributes
+}
+
+/// Decode the HTML character references that can appear in a project page.
+fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        result.push_str(&rest[.. amp]);
+        rest = &rest[amp ..];
+        let decoded = rest.find(';').and_then(|semicolon| {
+            let entity = &rest[1 .. semicolon];
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    let code = if let Some(hex) = entity.strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                    {
+                        u32::from_str_radix(hex, 16).ok()
+                    } else if let Some(dec) = entity.strip_prefix('#') {
+                        dec.parse().ok()
+                    } else {
+                        None
+                    };
+                    code.and_then(std::char::from_u32)
+                }
+            };
+            c.map(|c| (c, semicolon))
+        });
+        match decoded {
+            Some((c, semicolon)) => {
+                result.push(c);
+                rest = &rest[semicolon + 1 ..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1 ..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// A JSON value.
+#[derive(Debug)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number,
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref entries) => {
+                entries.iter().find(|e| e.0 == key).map(|e| &e.1)
+            }
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match *self {
+            Json::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// Minimal JSON parser, enough to read project pages.
+struct JsonParser<'a> {
+    string: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn parse(string: &str) -> Result<Json, InvalidPage> {
+        let mut parser = JsonParser { string: string.as_bytes(), pos: 0 };
+        let value = parser.value()?;
+        parser.whitespace();
+        if parser.pos != parser.string.len() {
+            return Err(InvalidPage::InvalidJson);
+        }
+        Ok(value)
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') =
+            self.string.get(self.pos)
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn next(&mut self) -> Result<u8, InvalidPage> {
+        let c = *self.string.get(self.pos).ok_or(InvalidPage::InvalidJson)?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn literal(&mut self, word: &[u8], value: Json) -> Result<Json, InvalidPage> {
+        if self.string[self.pos ..].starts_with(word) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(InvalidPage::InvalidJson)
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, InvalidPage> {
+        self.whitespace();
+        match self.string.get(self.pos) {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut entries = Vec::new();
+                self.whitespace();
+                if self.string.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(entries));
+                }
+                loop {
+                    self.whitespace();
+                    if self.next()? != b'"' {
+                        return Err(InvalidPage::InvalidJson);
+                    }
+                    let key = self.string()?;
+                    self.whitespace();
+                    if self.next()? != b':' {
+                        return Err(InvalidPage::InvalidJson);
+                    }
+                    entries.push((key, self.value()?));
+                    self.whitespace();
+                    match self.next()? {
+                        b',' => {}
+                        b'}' => return Ok(Json::Object(entries)),
+                        _ => return Err(InvalidPage::InvalidJson),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.whitespace();
+                if self.string.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.whitespace();
+                    match self.next()? {
+                        b',' => {}
+                        b']' => return Ok(Json::Array(items)),
+                        _ => return Err(InvalidPage::InvalidJson),
+                    }
+                }
+            }
+            Some(b'"') => {
+                self.pos += 1;
+                self.string().map(Json::String)
+            }
+            Some(b't') => self.literal(b"true", Json::Bool(true)),
+            Some(b'f') => self.literal(b"false", Json::Bool(false)),
+            Some(b'n') => self.literal(b"null", Json::Null),
+            Some(&c) if c == b'-' || c.is_ascii_digit() => {
+                self.pos += 1;
+                while let Some(&c) = self.string.get(self.pos) {
+                    if c.is_ascii_digit() || b".eE+-".contains(&c) {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                Ok(Json::Number)
+            }
+            _ => Err(InvalidPage::InvalidJson),
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, InvalidPage> {
+        let digits = self.string.get(self.pos .. self.pos + 4)
+            .ok_or(InvalidPage::InvalidJson)?;
+        self.pos += 4;
+        std::str::from_utf8(digits).ok()
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or(InvalidPage::InvalidJson)
+    }
+
+    /// Read a string, after the opening quote.
+    fn string(&mut self) -> Result<String, InvalidPage> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.next()? {
+                b'"' => break,
+                b'\\' => {
+                    let c = match self.next()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            if (0xD800 .. 0xDC00).contains(&code) {
+                                // Surrogate pair
+                                if self.next()? != b'\\' || self.next()? != b'u' {
+                                    return Err(InvalidPage::InvalidJson);
+                                }
+                                let low = self.hex4()?;
+                                code = 0x10000 + ((code - 0xD800) << 10) +
+                                    low.wrapping_sub(0xDC00);
+                            }
+                            std::char::from_u32(code)
+                                .ok_or(InvalidPage::InvalidJson)?
+                        }
+                        _ => return Err(InvalidPage::InvalidJson),
+                    };
+                    let mut buf = [0; 4];
+                    bytes.extend(c.encode_utf8(&mut buf).as_bytes());
+                }
+                c => bytes.push(c),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| InvalidPage::InvalidJson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::super::{SpecifierSet, Tag};
+    use super::{InvalidPage, ProjectPage};
+
+    const JSON_PAGE: &str = r#"{
+        "meta": {"api-version": "1.1"},
+        "name": "Example_Project",
+        "versions": ["1.0", "1.1", "2.0b1"],
+        "files": [
+            {"filename": "example_project-1.0.tar.gz", "url": "/f/example_project-1.0.tar.gz", "hashes": {"sha256": "aa"}},
+            {"filename": "example_project-1.0-py3-none-any.whl", "url": "/f/a.whl", "hashes": {}},
+            {"filename": "example_project-1.1-cp39-cp39-manylinux1_x86_64.whl", "url": "/f/b.whl", "hashes": {}, "requires-python": ">=3.9"},
+            {"filename": "example_project-1.1-py3-none-any.whl", "url": "/f/c.whl", "hashes": {}},
+            {"filename": "example_project-1.1-1-py3-none-any.whl", "url": "/f/d.whl", "hashes": {}},
+            {"filename": "example_project-1.2-py3-none-any.whl", "url": "/f/e.whl", "hashes": {}, "yanked": "broken é"},
+            {"filename": "example_project-2.0b1-py3-none-any.whl", "url": "/f/f.whl", "hashes": {}, "yanked": false},
+            {"filename": "example_project-1.3-cp27-cp27m-win32.whl", "url": "/f/g.whl", "hashes": {}},
+            {"filename": "other-3.0.tar.gz", "url": "/f/h.tar.gz", "hashes": {}},
+            {"filename": "example_project-latest.zip", "url": "/f/i.zip", "hashes": {}}
+        ]
+    }"#;
+
+    fn tags() -> Vec<Tag> {
+        vec![
+            Tag::new("cp39", "cp39", "manylinux1_x86_64"),
+            Tag::new("py3", "none", "any"),
+        ]
+    }
+
+    fn urls(page: &ProjectPage, spec: &str, pre: Option<bool>) -> Vec<String> {
+        page.candidates(&SpecifierSet::try_from(spec).unwrap(), pre, &tags())
+            .iter()
+            .map(|c| c.file().url().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_page() {
+        let page = ProjectPage::from_json(JSON_PAGE).unwrap();
+        assert_eq!(page.name(), Some("example-project"));
+        assert_eq!(page.files().len(), 10);
+        assert_eq!(page.files()[0].hashes(), &[("sha256".to_owned(), "aa".to_owned())]);
+        assert_eq!(page.files()[2].requires_python(), Some(">=3.9"));
+        assert!(page.files()[5].is_yanked());
+        assert_eq!(page.files()[5].yanked_reason(), Some("broken \u{e9}"));
+        assert!(!page.files()[6].is_yanked());
+
+        // Versions first, then wheel tags, then build tags, then sdists
+        assert_eq!(
+            urls(&page, "", None),
+            vec!["/f/b.whl", "/f/d.whl", "/f/c.whl", "/f/a.whl", "/f/example_project-1.0.tar.gz"],
+        );
+        assert_eq!(
+            urls(&page, ">=1.0", Some(true))[0],
+            "/f/f.whl",
+        );
+        // Falls back on the pre-release
+        assert_eq!(urls(&page, ">1.2", None), vec!["/f/f.whl"]);
+        assert!(urls(&page, ">1.2", Some(false)).is_empty());
+        // Yanked is only used if pinned
+        assert!(urls(&page, ">1.1,<2", None).is_empty());
+        assert_eq!(urls(&page, "==1.2", None), vec!["/f/e.whl"]);
+
+        let best = page.best_candidate(&SpecifierSet::default(), None, &tags()).unwrap();
+        assert_eq!(best.version().to_string(), "1.1");
+
+        assert_eq!(
+            ProjectPage::from_json(r#"{"meta": {"api-version": "2.0"}, "name": "a", "files": []}"#),
+            Err(InvalidPage::UnsupportedApiVersion("2.0".to_owned())),
+        );
+        assert_eq!(
+            ProjectPage::from_json(r#"{"meta": {"api-version": "1.0"}, "files": []}"#),
+            Err(InvalidPage::MissingField("name")),
+        );
+        assert_eq!(
+            ProjectPage::from_json(r#"{"meta": {"api-version": "1.0"}, "name": "a", "files": [}"#),
+            Err(InvalidPage::InvalidJson),
+        );
+    }
+
+    #[test]
+    fn test_html_page() {
+        let page = ProjectPage::from_html(
+            "<!DOCTYPE html>\n<html><body>\n\
+             <h1>Links for example</h1>\n\
+             <a href=\"/f/example-1.0.tar.gz#sha256=abcd\">example-1.0.tar.gz</a><br/>\n\
+             <A HREF='/f/example-1.1-py3-none-any.whl' data-requires-python=\"&gt;=3.6\">\n  example-1.1-py3-none-any.whl</a>\n\
+             <a href=\"/f/example-1.2.tar.gz\" data-yanked>example-1.2.tar.gz</a>\n\
+             <a href=\"/f/example-1.3.tar.gz\" data-yanked=\"bad &amp; wrong\">example-1.3.tar.gz</a>\n\
+             <abbr>not a link</abbr>\n\
+             </body></html>",
+        );
+        assert_eq!(page.name(), None);
+        let files = page.files();
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0].url(), "/f/example-1.0.tar.gz");
+        assert_eq!(files[0].hashes(), &[("sha256".to_owned(), "abcd".to_owned())]);
+        assert_eq!(files[1].filename(), "example-1.1-py3-none-any.whl");
+        assert_eq!(files[1].requires_python(), Some(">=3.6"));
+        assert!(files[2].is_yanked());
+        assert_eq!(files[2].yanked_reason(), None);
+        assert_eq!(files[3].yanked_reason(), Some("bad & wrong"));
+
+        assert_eq!(
+            urls(&page, "", None),
+            vec!["/f/example-1.1-py3-none-any.whl", "/f/example-1.0.tar.gz"],
+        );
+        assert_eq!(urls(&page, "===1.3", None), vec!["/f/example-1.3.tar.gz"]);
+    }
+}
@@ -0,0 +1,271 @@
+//! Ordering of Python versions that predate PEP-440.
+//!
+//! Before PEP-440, setuptools' `parse_version()` accepted any string, and
+//! ordered them using a set of heuristics. PyPI still hosts a lot of releases
+//! with such versions (`2004d`, `1.0-SNAPSHOT`, `0.9beta-r2`), so they need to
+//! be sorted the same way.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use super::PythonVersion;
+
+/// A version following setuptools' legacy rules.
+///
+/// Any string is a valid legacy version. They are compared the way
+/// `pkg_resources.parse_version()` did before PEP-440.
+#[derive(Clone, Debug)]
+pub struct LegacyVersion {
+    version: String,
+    parts: Vec<String>,
+}
+
+impl LegacyVersion {
+    /// The comparison key, as computed by setuptools.
+    pub fn parts(&self) -> &[String] {
+        &self.parts
+    }
+}
+
+/// Split a version into runs of digits, runs of letters, dots, dashes, and
+/// runs of anything else.
+fn split_components(version: &str) -> Vec<&str> {
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    enum Class {
+        Digit,
+        Letter,
+        Dot,
+        Dash,
+        Other,
+    }
+
+    fn classify(c: char) -> Class {
+        match c {
+            '0' ..= '9' => Class::Digit,
+            'a' ..= 'z' => Class::Letter,
+            '.' => Class::Dot,
+            '-' => Class::Dash,
+            _ => Class::Other,
+        }
+    }
+
+    let mut components = Vec::new();
+    let mut start = 0;
+    let mut previous = None;
+    for (idx, c) in version.char_indices() {
+        let class = classify(c);
+        // Dots and dashes are always a component on their own
+        let split = match previous {
+            None => false,
+            Some(Class::Dot) | Some(Class::Dash) => true,
+            Some(p) => p != class,
+        };
+        if split {
+            components.push(&version[start .. idx]);
+            start = idx;
+        }
+        previous = Some(class);
+    }
+    if start < version.len() {
+        components.push(&version[start ..]);
+    }
+    components
+}
+
+fn legacy_parts(version: &str) -> Vec<String> {
+    let lower = version.to_lowercase();
+    let mut parts: Vec<String> = Vec::new();
+    let components = split_components(&lower)
+        .into_iter()
+        .filter_map(|component| match component {
+            "pre" | "preview" | "rc" => Some("*c".to_owned()),
+            "-" => Some("*final-".to_owned()),
+            "dev" => Some("*@".to_owned()),
+            "." => None,
+            c if c.as_bytes()[0].is_ascii_digit() => Some(format!("{:0>8}", c)),
+            c => Some(format!("*{}", c)),
+        })
+        .chain(Some("*final".to_owned()));
+    for part in components {
+        if part.starts_with('*') {
+            // Remove '-' before a pre-release tag
+            if part.as_str() < "*final" {
+                while parts.last().is_some_and(|p| p == "*final-") {
+                    parts.pop();
+                }
+            }
+            // Remove trailing zeros from each series of numeric parts
+            while parts.last().is_some_and(|p| p == "00000000") {
+                parts.pop();
+            }
+        }
+        parts.push(part);
+    }
+    parts
+}
+
+impl From<String> for LegacyVersion {
+    fn from(version: String) -> LegacyVersion {
+        let parts = legacy_parts(&version);
+        LegacyVersion { version, parts }
+    }
+}
+
+impl fmt::Display for LegacyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.version)
+    }
+}
+
+impl PartialEq<LegacyVersion> for LegacyVersion {
+    fn eq(&self, other: &LegacyVersion) -> bool {
+        self.parts == other.parts
+    }
+}
+
+impl Eq for LegacyVersion {}
+
+impl PartialOrd<LegacyVersion> for LegacyVersion {
+    fn partial_cmp(&self, other: &LegacyVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LegacyVersion {
+    fn cmp(&self, other: &LegacyVersion) -> Ordering {
+        self.parts.cmp(&other.parts)
+    }
+}
+
+/// Either a PEP-440 version, or a legacy version.
+///
+/// PEP-440 is tried first, falling back on the legacy rules. All the legacy
+/// versions sort before all the PEP-440 versions, as is done by pip.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnyVersion {
+    Pep440(PythonVersion),
+    Legacy(LegacyVersion),
+}
+
+impl AnyVersion {
+    /// Whether this version could not be parsed as PEP-440.
+    pub fn is_legacy(&self) -> bool {
+        match *self {
+            AnyVersion::Pep440(_) => false,
+            AnyVersion::Legacy(_) => true,
+        }
+    }
+}
+
+impl From<String> for AnyVersion {
+    fn from(version: String) -> AnyVersion {
+        match PythonVersion::try_from(version.clone()) {
+            Ok(v) => AnyVersion::Pep440(v),
+            Err(_) => AnyVersion::Legacy(LegacyVersion::from(version)),
+        }
+    }
+}
+
+impl fmt::Display for AnyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AnyVersion::Pep440(ref v) => write!(f, "{}", v),
+            AnyVersion::Legacy(ref v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl PartialOrd<AnyVersion> for AnyVersion {
+    fn partial_cmp(&self, other: &AnyVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AnyVersion {
+    fn cmp(&self, other: &AnyVersion) -> Ordering {
+        match (self, other) {
+            (AnyVersion::Pep440(a), AnyVersion::Pep440(b)) => a.cmp(b),
+            (AnyVersion::Legacy(a), AnyVersion::Legacy(b)) => a.cmp(b),
+            (AnyVersion::Legacy(_), AnyVersion::Pep440(_)) => Ordering::Less,
+            (AnyVersion::Pep440(_), AnyVersion::Legacy(_)) => Ordering::Greater,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyVersion, LegacyVersion};
+
+    fn legacy(s: &str) -> LegacyVersion {
+        LegacyVersion::from(s.to_owned())
+    }
+
+    #[test]
+    fn test_legacy_ordering() {
+        for &(a, b) in &[
+            ("2.1", "2.1.1"),
+            ("2a1", "2b0"),
+            ("2a1", "2.1"),
+            ("2.3a1", "2.3"),
+            ("2.1-1", "2.1-2"),
+            ("2.1-1", "2.1.1"),
+            ("2.1", "2.1pl4"),
+            ("2.1a0-20040501", "2.1"),
+            ("1.1", "02.1"),
+            ("3.2", "3.2.pl0"),
+            ("3.2-1", "3.2pl1"),
+            ("3.2pl1", "3.2pl1-1"),
+            ("0.4", "4.0"),
+            ("0.0.4", "0.4.0"),
+            ("0pl1", "0.4pl1"),
+            ("2.1.0-rc1", "2.1.0"),
+            ("2.1dev", "2.1a0"),
+            ("1.0", "1.0-SNAPSHOT"),
+            ("2004c", "2004d"),
+        ] {
+            assert!(legacy(a) < legacy(b), "{} < {}", a, b);
+        }
+
+        let torture = [
+            "0.77.0-1",
+            "0.77.1-1",
+            "0.77.2-1",
+            "0.79.9999+0.80.0pre2-2",
+            "0.79.9999+0.80.0pre2-3",
+            "0.79.9999+0.80.0pre4-1",
+            "0.80.1-1",
+            "0.80.1-2",
+            "0.80.1-3",
+        ];
+        for pair in torture.windows(2) {
+            assert!(legacy(pair[0]) < legacy(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+
+        for &(a, b) in &[
+            ("0.4", "0.4.0"),
+            ("0.4.0.0", "0.4.0"),
+            ("0.4.0-0", "0.4-0"),
+            ("0post1", "0.0post1"),
+            ("0pre1", "0.0c1"),
+            ("0.0.0preview1", "0c1"),
+            ("0.0c1", "0-rc1"),
+            ("1.2a1", "1.2.a.1"),
+            ("1.2.a", "1.2a"),
+        ] {
+            assert_eq!(legacy(a), legacy(b), "{} == {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_any_version() {
+        let pep440 = AnyVersion::from("1.0".to_owned());
+        let legacy = AnyVersion::from("1.0-SNAPSHOT".to_owned());
+        assert!(!pep440.is_legacy());
+        assert!(legacy.is_legacy());
+        assert!(legacy < pep440);
+        assert!(AnyVersion::from("2004d".to_owned()) < AnyVersion::from("0.1".to_owned()));
+        assert_eq!(AnyVersion::from("1.0-ALPHA".to_owned()).to_string(), "1.0a0");
+        assert_eq!(legacy.to_string(), "1.0-SNAPSHOT");
+    }
+}
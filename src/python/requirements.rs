@@ -0,0 +1,831 @@
+//! Parse requirements, and pip's requirements files.
+//!
+//! A requirement follows [PEP-508](https://www.python.org/dev/peps/pep-0508/):
+//! `name[extras] specifiers ; marker`, or `name[extras] @ url ; marker`.
+//! Environment markers are kept as strings, they are not evaluated.
+//!
+//! Requirements files are what `pip install -r` reads. On top of
+//! requirements, they can contain editable requirements (`-e`), URLs, paths,
+//! includes of other requirements (`-r`) or constraints (`-c`) files, and
+//! options such as `--index-url`. Options are recorded, not acted upon.
+
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::filename::normalize_name;
+use super::specifier::{InvalidSpecifier, SpecifierSet};
+
+/// Error for the requirement parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidRequirement {
+    /// The project name is missing or contains invalid characters.
+    InvalidName,
+    /// The list of extras is not closed or contains invalid names.
+    InvalidExtras,
+    /// The version specifiers are invalid.
+    InvalidSpecifier(InvalidSpecifier),
+    /// The URL after `@` is missing.
+    MissingUrl,
+    /// There is nothing after the `;`.
+    EmptyMarker,
+}
+
+impl From<InvalidSpecifier> for InvalidRequirement {
+    fn from(error: InvalidSpecifier) -> InvalidRequirement {
+        InvalidRequirement::InvalidSpecifier(error)
+    }
+}
+
+/// A requirement on a Python project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Requirement {
+    name: Option<String>,
+    extras: Vec<String>,
+    specifier: SpecifierSet,
+    url: Option<String>,
+    marker: Option<String>,
+}
+
+impl Requirement {
+    /// The project name, as written.
+    ///
+    /// This can only be missing for a URL or a path that doesn't indicate
+    /// the project name with an `#egg=` fragment.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The normalized project name.
+    pub fn normalized_name(&self) -> Option<String> {
+        self.name.as_ref().map(|n| normalize_name(n))
+    }
+
+    pub fn extras(&self) -> &[String] {
+        &self.extras
+    }
+
+    pub fn specifier(&self) -> &SpecifierSet {
+        &self.specifier
+    }
+
+    /// The URL or path to install from, if any.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// The environment marker, as written.
+    pub fn marker(&self) -> Option<&str> {
+        self.marker.as_deref()
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() &&
+        name.chars().all(is_name_char) &&
+        name.starts_with(|c: char| c.is_ascii_alphanumeric()) &&
+        name.ends_with(|c: char| c.is_ascii_alphanumeric())
+}
+
+/// Read `[extra1, extra2]` from the start of the string, if present.
+fn parse_extras(
+    string: &str,
+) -> Result<(Vec<String>, &str), InvalidRequirement> {
+    let string = string.trim_start();
+    let inner = match string.strip_prefix('[') {
+        Some(inner) => inner,
+        None => return Ok((Vec::new(), string)),
+    };
+    let end = inner.find(']').ok_or(InvalidRequirement::InvalidExtras)?;
+    let mut extras = Vec::new();
+    if !inner[.. end].trim().is_empty() {
+        for extra in inner[.. end].split(',') {
+            let extra = extra.trim();
+            if !is_valid_name(extra) {
+                return Err(InvalidRequirement::InvalidExtras);
+            }
+            extras.push(extra.to_owned());
+        }
+    }
+    Ok((extras, &inner[end + 1 ..]))
+}
+
+fn parse_marker(marker: &str) -> Result<String, InvalidRequirement> {
+    let marker = marker.trim();
+    if marker.is_empty() {
+        Err(InvalidRequirement::EmptyMarker)
+    } else {
+        Ok(marker.to_owned())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Requirement {
+    type Error = InvalidRequirement;
+
+    fn try_from(string: &'a str) -> Result<Requirement, InvalidRequirement> {
+        let string = string.trim();
+        let name_end = string.find(|c| !is_name_char(c))
+            .unwrap_or(string.len());
+        let name = &string[.. name_end];
+        if !is_valid_name(name) {
+            return Err(InvalidRequirement::InvalidName);
+        }
+        let (extras, rest) = parse_extras(&string[name_end ..])?;
+        let rest = rest.trim_start();
+
+        if let Some(url) = rest.strip_prefix('@') {
+            // The marker has to be separated from the URL by whitespace
+            let url = url.trim_start();
+            let url_end = url.find(char::is_whitespace).unwrap_or(url.len());
+            if url_end == 0 {
+                return Err(InvalidRequirement::MissingUrl);
+            }
+            let after = url[url_end ..].trim_start();
+            let marker = match after.strip_prefix(';') {
+                Some(marker) => Some(parse_marker(marker)?),
+                None if after.is_empty() => None,
+                None => return Err(InvalidRequirement::MissingUrl),
+            };
+            return Ok(Requirement {
+                name: Some(name.to_owned()),
+                extras,
+                specifier: SpecifierSet::default(),
+                url: Some(url[.. url_end].to_owned()),
+                marker,
+            });
+        }
+
+        let (specifier, marker) = match rest.find(';') {
+            Some(semicolon) => {
+                let marker = parse_marker(&rest[semicolon + 1 ..])?;
+                (&rest[.. semicolon], Some(marker))
+            }
+            None => (rest, None),
+        };
+        let mut specifier = specifier.trim();
+        if specifier.starts_with('(') && specifier.ends_with(')') {
+            specifier = &specifier[1 .. specifier.len() - 1];
+        }
+        Ok(Requirement {
+            name: Some(name.to_owned()),
+            extras,
+            specifier: SpecifierSet::try_from(specifier)?,
+            url: None,
+            marker,
+        })
+    }
+}
+
+/// Where a line of a requirements file came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    path: PathBuf,
+    line: usize,
+}
+
+impl Location {
+    /// The requirements file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The line number, starting at 1.
+    ///
+    /// For lines continued with a backslash, this is the first line.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+/// An option, either for the whole file (`--index-url`) or for a single
+/// requirement (`--global-option`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileOption {
+    name: String,
+    value: Option<String>,
+    location: Location,
+}
+
+impl FileOption {
+    /// The long name of the option, e.g. `--index-url` (even if `-i` was
+    /// used).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+}
+
+/// A requirement read from a requirements file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequirementLine {
+    requirement: Requirement,
+    editable: bool,
+    constraint: bool,
+    hashes: Vec<String>,
+    options: Vec<FileOption>,
+    location: Location,
+}
+
+impl RequirementLine {
+    pub fn requirement(&self) -> &Requirement {
+        &self.requirement
+    }
+
+    /// Whether this was installed in editable mode (`-e`).
+    pub fn is_editable(&self) -> bool {
+        self.editable
+    }
+
+    /// Whether this came from a constraints file (`-c`).
+    pub fn is_constraint(&self) -> bool {
+        self.constraint
+    }
+
+    /// The hashes given with `--hash`, e.g. `sha256:abcd...`.
+    pub fn hashes(&self) -> &[String] {
+        &self.hashes
+    }
+
+    /// Other options given on that line, such as `--global-option`.
+    pub fn options(&self) -> &[FileOption] {
+        &self.options
+    }
+
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+}
+
+/// The content of a requirements file and the files it includes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequirementsFile {
+    requirements: Vec<RequirementLine>,
+    options: Vec<FileOption>,
+}
+
+impl RequirementsFile {
+    /// The requirements, in the order they were read.
+    pub fn requirements(&self) -> &[RequirementLine] {
+        &self.requirements
+    }
+
+    /// The options applying to the whole file, such as `--index-url`.
+    ///
+    /// Includes of remote files (`-r https://...`) can't be followed, and are
+    /// also recorded here.
+    pub fn options(&self) -> &[FileOption] {
+        &self.options
+    }
+}
+
+/// Error for a line of a requirements file.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidLine {
+    /// The option is not supported in requirements files.
+    UnknownOption(String),
+    /// The option needs a value.
+    MissingValue(String),
+    /// The option can't be used with a requirement on the same line.
+    UnexpectedOption(String),
+    /// The requirement is invalid.
+    InvalidRequirement(InvalidRequirement),
+    /// The file includes itself, directly or indirectly.
+    RecursiveInclude,
+}
+
+impl From<InvalidRequirement> for InvalidLine {
+    fn from(error: InvalidRequirement) -> InvalidLine {
+        InvalidLine::InvalidRequirement(error)
+    }
+}
+
+/// Error for the requirements file parser.
+#[derive(Debug)]
+pub enum RequirementsError {
+    /// A file couldn't be read.
+    Io(PathBuf, io::Error),
+    /// A line is invalid.
+    InvalidLine(Location, InvalidLine),
+}
+
+/// Whether an option is a global one, or can be given for a requirement.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Global,
+    Requirement,
+}
+
+/// The options supported in requirements files: long name, short name, takes
+/// a value, scope.
+const OPTIONS: &[(&str, Option<char>, bool, Scope)] = &[
+    ("--index-url", Some('i'), true, Scope::Global),
+    ("--extra-index-url", None, true, Scope::Global),
+    ("--no-index", None, false, Scope::Global),
+    ("--constraint", Some('c'), true, Scope::Global),
+    ("--requirement", Some('r'), true, Scope::Global),
+    ("--editable", Some('e'), true, Scope::Global),
+    ("--find-links", Some('f'), true, Scope::Global),
+    ("--no-binary", None, true, Scope::Global),
+    ("--only-binary", None, true, Scope::Global),
+    ("--prefer-binary", None, false, Scope::Global),
+    ("--require-hashes", None, false, Scope::Global),
+    ("--pre", None, false, Scope::Global),
+    ("--trusted-host", None, true, Scope::Global),
+    ("--use-feature", None, true, Scope::Global),
+    ("--global-option", None, true, Scope::Requirement),
+    ("--config-settings", None, true, Scope::Requirement),
+    ("--hash", None, true, Scope::Requirement),
+];
+
+/// Split a string into words, shell-style (handling quotes and backslashes).
+fn shell_split(string: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                in_word = true;
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+            }
+            (Some(_), c) => word.push(c),
+            (None, '"') | (None, '\'') => {
+                in_word = true;
+                quote = Some(c);
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// An option read from a line: long name, value, scope.
+type ParsedOption = (&'static str, Option<String>, Scope);
+
+fn parse_options(string: &str) -> Result<Vec<ParsedOption>, InvalidLine> {
+    let mut options = Vec::new();
+    let mut words = shell_split(string).into_iter();
+    while let Some(word) = words.next() {
+        // Find the option, and the value if it is attached
+        let (option, attached) = if word.starts_with("--") {
+            let (name, value) = match word.find('=') {
+                Some(eq) => (&word[.. eq], Some(word[eq + 1 ..].to_owned())),
+                None => (&word[..], None),
+            };
+            let option = OPTIONS.iter().find(|o| o.0 == name);
+            (option, value)
+        } else if word.starts_with('-') && word.len() >= 2 {
+            let short = word[1 ..].chars().next().unwrap();
+            let option = OPTIONS.iter().find(|o| o.1 == Some(short));
+            let value = &word[1 + short.len_utf8() ..];
+            (option, if value.is_empty() { None } else { Some(value.to_owned()) })
+        } else {
+            (None, None)
+        };
+        let &(name, _, takes_value, scope) = match option {
+            Some(option) => option,
+            None => return Err(InvalidLine::UnknownOption(word)),
+        };
+
+        let value = if takes_value {
+            match attached.or_else(|| words.next()) {
+                Some(value) => Some(value),
+                None => return Err(InvalidLine::MissingValue(name.to_owned())),
+            }
+        } else if attached.is_some() {
+            return Err(InvalidLine::UnknownOption(word));
+        } else {
+            None
+        };
+        options.push((name, value, scope));
+    }
+    Ok(options)
+}
+
+/// Whether this looks like a URL or path, rather than a PEP-508 requirement.
+fn is_url_or_path(string: &str) -> bool {
+    let first = string.split_whitespace().next().unwrap_or("");
+    if let Some(scheme) = first.find("://") {
+        // `name @ url` is a requirement
+        return !first[.. scheme].contains('@');
+    }
+    first.starts_with("file:") ||
+        first.starts_with('.') ||
+        first.contains('/') ||
+        first.contains('\\') ||
+        [".whl", ".zip", ".tar.gz", ".tar.bz2", ".tgz"]
+            .iter()
+            .any(|ext| first.ends_with(ext))
+}
+
+/// Build a requirement from a URL or path, optionally followed by a marker.
+fn url_requirement(string: &str) -> Result<Requirement, InvalidRequirement> {
+    // As pip does, the marker must be separated by "; " for a URL
+    let separator = if string.contains("://") { "; " } else { ";" };
+    let (url, marker) = match string.find(separator) {
+        Some(idx) => {
+            let marker = parse_marker(&string[idx + 1 ..])?;
+            (string[.. idx].trim(), Some(marker))
+        }
+        None => (string.trim(), None),
+    };
+
+    // Extras can follow a path, e.g. `.[dev]`
+    let (url, extras) = match url.rfind('[') {
+        Some(bracket) if url.ends_with(']') && bracket > 0 => {
+            let (extras, _) = parse_extras(&url[bracket ..])?;
+            (&url[.. bracket], extras)
+        }
+        _ => (url, Vec::new()),
+    };
+
+    // The project name can be given in the fragment, e.g. `#egg=name`
+    let name = url.find('#')
+        .and_then(|hash| {
+            url[hash + 1 ..].split('&')
+                .find_map(|param| param.strip_prefix("egg="))
+        })
+        .map(|egg| egg.to_owned());
+    if let Some(ref name) = name {
+        if !is_valid_name(name) {
+            return Err(InvalidRequirement::InvalidName);
+        }
+    }
+
+    Ok(Requirement {
+        name,
+        extras,
+        specifier: SpecifierSet::default(),
+        url: Some(url.to_owned()),
+        marker,
+    })
+}
+
+/// Join continued lines and remove comments.
+///
+/// Returns the logical lines with the number of their first physical line.
+fn logical_lines(contents: &str) -> Vec<(usize, String)> {
+    fn strip_comment(line: &str) -> &str {
+        let mut previous = None;
+        for (idx, c) in line.char_indices() {
+            if c == '#' && previous.is_none_or(char::is_whitespace) {
+                return &line[.. idx];
+            }
+            previous = Some(c);
+        }
+        line
+    }
+
+    let mut lines = Vec::new();
+    let mut continued: Option<(usize, String)> = None;
+    for (idx, line) in contents.lines().enumerate() {
+        let line = strip_comment(line);
+        let (number, mut logical) = continued.take()
+            .unwrap_or_else(|| (idx + 1, String::new()));
+        match line.strip_suffix('\\') {
+            Some(start) => {
+                logical.push_str(start);
+                continued = Some((number, logical));
+            }
+            None => {
+                logical.push_str(line);
+                lines.push((number, logical));
+            }
+        }
+    }
+    if let Some(last) = continued {
+        lines.push(last);
+    }
+    lines.retain(|(_, line)| !line.trim().is_empty());
+    lines
+}
+
+struct FileParser {
+    result: RequirementsFile,
+    /// The files being read, to detect recursive includes.
+    stack: Vec<PathBuf>,
+}
+
+impl FileParser {
+    fn read(
+        &mut self,
+        path: &Path,
+        constraint: bool,
+    ) -> Result<(), RequirementsError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| RequirementsError::Io(path.to_owned(), e))?;
+        self.stack.push(fs::canonicalize(path).unwrap_or_else(|_| path.to_owned()));
+        for (number, line) in logical_lines(&contents) {
+            let location = Location { path: path.to_owned(), line: number };
+            self.line(&line, location.clone(), constraint)
+                .map_err(|e| match e {
+                    LineResult::Invalid(e) => {
+                        RequirementsError::InvalidLine(location, e)
+                    }
+                    LineResult::Error(e) => e,
+                })?;
+        }
+        self.stack.pop();
+        Ok(())
+    }
+
+    fn line(
+        &mut self,
+        line: &str,
+        location: Location,
+        constraint: bool,
+    ) -> Result<(), LineResult> {
+        // Everything until the first word starting with a dash is the
+        // requirement, the rest is options
+        let line = line.trim();
+        let split = line
+            .char_indices()
+            .find(|&(i, c)| c == '-' && line[.. i].chars().next_back().is_none_or(char::is_whitespace))
+            .map_or(line.len(), |(i, _)| i);
+        let (requirement, options) = line.split_at(split);
+        let requirement = requirement.trim();
+        let options = parse_options(options)?;
+
+        if !requirement.is_empty() {
+            let parsed = if is_url_or_path(requirement) {
+                url_requirement(requirement)
+            } else {
+                Requirement::try_from(requirement)
+            };
+            let mut line = RequirementLine {
+                requirement: parsed.map_err(InvalidLine::from)?,
+                editable: false,
+                constraint,
+                hashes: Vec::new(),
+                options: Vec::new(),
+                location: location.clone(),
+            };
+            for (name, value, scope) in options {
+                if scope != Scope::Requirement {
+                    return Err(InvalidLine::UnexpectedOption(name.to_owned()).into());
+                } else if name == "--hash" {
+                    line.hashes.extend(value);
+                } else {
+                    line.options.push(FileOption {
+                        name: name.to_owned(),
+                        value,
+                        location: location.clone(),
+                    });
+                }
+            }
+            self.result.requirements.push(line);
+            return Ok(());
+        }
+
+        for (name, value, _) in options {
+            match (name, value) {
+                ("--editable", Some(value)) => {
+                    let requirement = url_requirement(&value)
+                        .map_err(InvalidLine::from)?;
+                    self.result.requirements.push(RequirementLine {
+                        requirement,
+                        editable: true,
+                        constraint,
+                        hashes: Vec::new(),
+                        options: Vec::new(),
+                        location: location.clone(),
+                    });
+                }
+                ("--requirement", Some(ref value)) |
+                ("--constraint", Some(ref value))
+                    if !value.contains("://") =>
+                {
+                    let base = location.path.parent()
+                        .unwrap_or_else(|| Path::new(""));
+                    let included = base.join(value);
+                    let canonical = fs::canonicalize(&included)
+                        .unwrap_or_else(|_| included.clone());
+                    if self.stack.contains(&canonical) {
+                        return Err(InvalidLine::RecursiveInclude.into());
+                    }
+                    self.read(&included, name == "--constraint")
+                        .map_err(LineResult::Error)?;
+                }
+                (name, value) => {
+                    self.result.options.push(FileOption {
+                        name: name.to_owned(),
+                        value,
+                        location: location.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors from a line, which might come from an included file.
+enum LineResult {
+    Invalid(InvalidLine),
+    Error(RequirementsError),
+}
+
+impl From<InvalidLine> for LineResult {
+    fn from(error: InvalidLine) -> LineResult {
+        LineResult::Invalid(error)
+    }
+}
+
+/// Read a requirements file, and the files it includes.
+///
+/// Included files (`-r` and `-c`) are found relative to the file that
+/// includes them.
+pub fn requirements_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<RequirementsFile, RequirementsError> {
+    let mut parser = FileParser {
+        result: RequirementsFile::default(),
+        stack: Vec::new(),
+    };
+    parser.read(path.as_ref(), false)?;
+    Ok(parser.result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::{
+        InvalidLine, InvalidRequirement, Requirement, RequirementsError,
+        requirements_file,
+    };
+
+    #[test]
+    fn test_requirement() {
+        let req = Requirement::try_from("Foo.Bar[security, tests] >=2.8.1, ==2.8.* ; python_version < '2.7'").unwrap();
+        assert_eq!(req.name(), Some("Foo.Bar"));
+        assert_eq!(req.normalized_name(), Some("foo-bar".to_owned()));
+        assert_eq!(req.extras(), &["security".to_owned(), "tests".to_owned()]);
+        assert_eq!(req.specifier().to_string(), ">=2.8.1,==2.8.*");
+        assert_eq!(req.marker(), Some("python_version < '2.7'"));
+        assert_eq!(req.url(), None);
+
+        let req = Requirement::try_from("name (>=1.0)").unwrap();
+        assert_eq!(req.specifier().to_string(), ">=1.0");
+
+        let req = Requirement::try_from("pip @ https://github.com/pypa/pip/archive/1.3.1.zip ; python_version >= '3'").unwrap();
+        assert_eq!(req.name(), Some("pip"));
+        assert_eq!(req.url(), Some("https://github.com/pypa/pip/archive/1.3.1.zip"));
+        assert_eq!(req.marker(), Some("python_version >= '3'"));
+
+        assert_eq!(
+            Requirement::try_from("-foo"),
+            Err(InvalidRequirement::InvalidName),
+        );
+        assert_eq!(
+            Requirement::try_from("foo[bar"),
+            Err(InvalidRequirement::InvalidExtras),
+        );
+        assert_eq!(
+            Requirement::try_from("foo;"),
+            Err(InvalidRequirement::EmptyMarker),
+        );
+        assert!(Requirement::try_from("foo >= 1.0 extra").is_err());
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("verlib-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_requirements_file() {
+        let dir = temp_dir("requirements");
+        fs::write(
+            dir.join("requirements.txt"),
+            "# Main requirements\n\
+             --index-url https://pypi.example.org/simple\n\
+             -r\tsub/base.txt\n\
+             requests[security]>=2.0 \\\n    \
+             --hash=sha256:0123 \\\n    \
+             --hash sha256:4567\n\
+             \n\
+             -e git+https://github.com/org/proj.git#egg=proj  # editable\n\
+             ./local/path[dev]\n\
+             https://example.org/pkg-1.0.tar.gz; sys_platform == 'linux'\n\
+             --constraint sub/constraints.txt\n",
+        ).unwrap();
+        fs::write(dir.join("sub/base.txt"), "six==1.16.0\t--hash=sha256:89ab\n-i\thttps://other.org/simple\n")
+            .unwrap();
+        fs::write(dir.join("sub/constraints.txt"), "urllib3<2\n").unwrap();
+
+        let file = requirements_file(dir.join("requirements.txt")).unwrap();
+        let reqs = file.requirements();
+        assert_eq!(reqs.len(), 6);
+
+        assert_eq!(reqs[0].requirement().name(), Some("six"));
+        assert_eq!(reqs[0].location().path(), dir.join("sub/base.txt"));
+        assert_eq!(reqs[0].location().line(), 1);
+        assert_eq!(reqs[0].hashes(), &["sha256:89ab".to_owned()]);
+
+        assert_eq!(reqs[1].requirement().name(), Some("requests"));
+        assert_eq!(reqs[1].requirement().specifier().to_string(), ">=2.0");
+        assert_eq!(reqs[1].hashes(), &["sha256:0123".to_owned(), "sha256:4567".to_owned()]);
+        assert_eq!(reqs[1].location().line(), 4);
+
+        assert!(reqs[2].is_editable());
+        assert_eq!(reqs[2].requirement().name(), Some("proj"));
+        assert_eq!(reqs[2].requirement().url(), Some("git+https://github.com/org/proj.git#egg=proj"));
+        assert_eq!(reqs[2].location().line(), 8);
+
+        assert_eq!(reqs[3].requirement().name(), None);
+        assert_eq!(reqs[3].requirement().url(), Some("./local/path"));
+        assert_eq!(reqs[3].requirement().extras(), &["dev".to_owned()]);
+
+        assert_eq!(reqs[4].requirement().url(), Some("https://example.org/pkg-1.0.tar.gz"));
+        assert_eq!(reqs[4].requirement().marker(), Some("sys_platform == 'linux'"));
+
+        assert_eq!(reqs[5].requirement().name(), Some("urllib3"));
+        assert!(reqs[5].is_constraint());
+        assert!(!reqs[1].is_constraint());
+
+        let options = file.options();
+        assert_eq!(options.len(), 2);
+        assert_eq!(options[0].name(), "--index-url");
+        assert_eq!(options[0].value(), Some("https://pypi.example.org/simple"));
+        assert_eq!(options[0].location().line(), 2);
+        assert_eq!(options[1].name(), "--index-url");
+        assert_eq!(options[1].location().path(), dir.join("sub/base.txt"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_requirements_file_errors() {
+        let dir = temp_dir("requirements-errors");
+        fs::write(dir.join("a.txt"), "six\n-r sub/b.txt\n").unwrap();
+        fs::write(dir.join("sub/b.txt"), "-r ../a.txt\n").unwrap();
+        match requirements_file(dir.join("a.txt")) {
+            Err(RequirementsError::InvalidLine(location, InvalidLine::RecursiveInclude)) => {
+                assert_eq!(location.path(), dir.join("sub/b.txt"));
+                assert_eq!(location.line(), 1);
+            }
+            r => panic!("{:?}", r),
+        }
+
+        fs::write(dir.join("c.txt"), "six\n\n--frobnicate\n").unwrap();
+        match requirements_file(dir.join("c.txt")) {
+            Err(RequirementsError::InvalidLine(location, InvalidLine::UnknownOption(o))) => {
+                assert_eq!(location.line(), 3);
+                assert_eq!(o, "--frobnicate");
+            }
+            r => panic!("{:?}", r),
+        }
+
+        fs::write(dir.join("d.txt"), "six --index-url https://example.org\n").unwrap();
+        match requirements_file(dir.join("d.txt")) {
+            Err(RequirementsError::InvalidLine(_, InvalidLine::UnexpectedOption(o))) => {
+                assert_eq!(o, "--index-url");
+            }
+            r => panic!("{:?}", r),
+        }
+
+        match requirements_file(dir.join("missing.txt")) {
+            Err(RequirementsError::Io(path, _)) => {
+                assert_eq!(path, dir.join("missing.txt"));
+            }
+            r => panic!("{:?}", r),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
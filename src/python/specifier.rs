@@ -0,0 +1,471 @@
+//! Version specifiers, as used in requirements (`>=1.2,<2`).
+//!
+//! See [PEP-440](https://www.python.org/dev/peps/pep-0440/#version-specifiers)
+//! for the meaning of each operator. Pre-releases are excluded by default,
+//! unless a specifier explicitly mentions one, or nothing else matches.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+use super::PythonVersion;
+
+/// Comparison operator of a specifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `~=`, compatible release.
+    Compatible,
+    /// `==`, version matching, possibly with a `.*` suffix.
+    Equal,
+    /// `!=`, version exclusion, possibly with a `.*` suffix.
+    NotEqual,
+    /// `<=`
+    LessEqual,
+    /// `>=`
+    GreaterEqual,
+    /// `<`, which excludes pre-releases of the given version.
+    Less,
+    /// `>`, which excludes post-releases of the given version.
+    Greater,
+    /// `===`, arbitrary string equality.
+    ArbitraryEqual,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match *self {
+            Operator::Compatible => "~=",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::LessEqual => "<=",
+            Operator::GreaterEqual => ">=",
+            Operator::Less => "<",
+            Operator::Greater => ">",
+            Operator::ArbitraryEqual => "===",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// Error for the specifier parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidSpecifier {
+    /// The operator is missing or unknown.
+    InvalidOperator,
+    /// The version is not a valid PEP-440 version.
+    InvalidVersion(InvalidVersion),
+    /// The version can't be used with this operator (for example `>=1.*`,
+    /// or `~=1` which needs at least two fields).
+    InvalidVersionForOperator,
+}
+
+impl From<InvalidVersion> for InvalidSpecifier {
+    fn from(error: InvalidVersion) -> InvalidSpecifier {
+        InvalidSpecifier::InvalidVersion(error)
+    }
+}
+
+/// A single version specifier, such as `>=1.2`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Specifier {
+    operator: Operator,
+    /// The version as written.
+    string: String,
+    /// The parsed version, unless the operator is `===`.
+    version: Option<PythonVersion>,
+    /// Whether this is a prefix match (`==1.2.*`).
+    wildcard: bool,
+}
+
+/// Compare epoch and release segment only.
+fn cmp_base(a: &PythonVersion, b: &PythonVersion) -> Ordering {
+    let release = |v: &PythonVersion| {
+        let mut release = v.release.len();
+        while release > 0 && v.release[release - 1] == 0 {
+            release -= 1;
+        }
+        (v.epoch, v.release[.. release].to_vec())
+    };
+    release(a).cmp(&release(b))
+}
+
+/// Check that the version starts with the given epoch and release.
+fn prefix_match(version: &PythonVersion, epoch: u64, prefix: &[u64]) -> bool {
+    version.epoch == epoch &&
+        prefix.iter().enumerate().all(|(i, &num)| {
+            version.release.get(i).cloned().unwrap_or(0) == num
+        })
+}
+
+/// Drop the local version label.
+fn public(version: &PythonVersion) -> PythonVersion {
+    PythonVersion { local: Vec::new(), ..version.clone() }
+}
+
+impl Specifier {
+    pub fn operator(&self) -> Operator {
+        self.operator
+    }
+
+    /// The version, or `None` for the arbitrary equality operator `===`.
+    pub fn version(&self) -> Option<&PythonVersion> {
+        self.version.as_ref()
+    }
+
+    /// Whether this specifier ends with `.*`.
+    pub fn is_wildcard(&self) -> bool {
+        self.wildcard
+    }
+
+    /// Whether this specifier explicitly mentions a pre-release, which means
+    /// pre-releases should be accepted.
+    pub fn mentions_prerelease(&self) -> bool {
+        match self.operator {
+            Operator::Compatible | Operator::Equal | Operator::LessEqual |
+            Operator::GreaterEqual => {
+                self.version.as_ref().unwrap().is_prerelease()
+            }
+            Operator::ArbitraryEqual => {
+                PythonVersion::try_from(self.string.clone())
+                    .map(|v| v.is_prerelease())
+                    .unwrap_or(false)
+            }
+            Operator::NotEqual | Operator::Less | Operator::Greater => false,
+        }
+    }
+
+    /// Check whether a version matches this specifier.
+    ///
+    /// This doesn't take the pre-release policy into account, see
+    /// [`SpecifierSet::contains()`](struct.SpecifierSet.html#method.contains).
+    pub fn contains(&self, candidate: &PythonVersion) -> bool {
+        let spec = match self.version {
+            Some(ref v) => v,
+            None => {
+                return candidate.to_string()
+                    .eq_ignore_ascii_case(&self.string);
+            }
+        };
+        match self.operator {
+            Operator::Compatible => {
+                public(candidate) >= *spec &&
+                    prefix_match(
+                        candidate,
+                        spec.epoch,
+                        &spec.release[.. spec.release.len() - 1],
+                    )
+            }
+            Operator::Equal | Operator::NotEqual => {
+                let equal = if self.wildcard {
+                    prefix_match(candidate, spec.epoch, &spec.release)
+                } else if spec.local.is_empty() {
+                    public(candidate) == *spec
+                } else {
+                    candidate == spec
+                };
+                equal == (self.operator == Operator::Equal)
+            }
+            Operator::LessEqual => public(candidate) <= *spec,
+            Operator::GreaterEqual => public(candidate) >= *spec,
+            Operator::Less => {
+                // `<1.0` doesn't match `1.0rc1`
+                candidate < spec && !(
+                    !spec.is_prerelease() && candidate.is_prerelease() &&
+                    cmp_base(candidate, spec) == Ordering::Equal
+                )
+            }
+            Operator::Greater => {
+                // `>1.0` doesn't match `1.0.post1` or `1.0+local`
+                candidate > spec && !(
+                    (spec.post.is_none() && candidate.post.is_some() ||
+                        !candidate.local.is_empty()) &&
+                    cmp_base(candidate, spec) == Ordering::Equal
+                )
+            }
+            Operator::ArbitraryEqual => unreachable!(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Specifier {
+    type Error = InvalidSpecifier;
+
+    fn try_from(string: &'a str) -> Result<Specifier, InvalidSpecifier> {
+        let string = string.trim();
+        let operators = [
+            ("===", Operator::ArbitraryEqual),
+            ("~=", Operator::Compatible),
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessEqual),
+            (">=", Operator::GreaterEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ];
+        let (op_str, operator) = operators.iter()
+            .find(|&&(op, _)| string.starts_with(op))
+            .cloned()
+            .ok_or(InvalidSpecifier::InvalidOperator)?;
+        let version_str = string[op_str.len() ..].trim_start();
+        if version_str.is_empty() {
+            return Err(InvalidVersion::EmptyField.into());
+        }
+
+        if operator == Operator::ArbitraryEqual {
+            if version_str.contains(char::is_whitespace) {
+                return Err(InvalidVersion::InvalidCharacter.into());
+            }
+            return Ok(Specifier {
+                operator,
+                string: version_str.to_owned(),
+                version: None,
+                wildcard: false,
+            });
+        }
+
+        let (wildcard, to_parse) = match version_str.strip_suffix(".*") {
+            Some(prefix) => (true, prefix),
+            None => (false, version_str),
+        };
+        let version = PythonVersion::try_from(to_parse.to_owned())?;
+
+        let has_suffix =
+            version.pre.is_some() || version.post.is_some() ||
+            version.dev.is_some();
+        let valid = match operator {
+            Operator::Equal | Operator::NotEqual => {
+                !wildcard || !has_suffix && version.local.is_empty()
+            }
+            Operator::Compatible => {
+                !wildcard && version.local.is_empty() &&
+                    version.release.len() >= 2
+            }
+            _ => !wildcard && version.local.is_empty(),
+        };
+        if !valid {
+            return Err(InvalidSpecifier::InvalidVersionForOperator);
+        }
+
+        Ok(Specifier {
+            operator,
+            string: version_str.to_owned(),
+            version: Some(version),
+            wildcard,
+        })
+    }
+}
+
+impl fmt::Display for Specifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.operator, self.string)
+    }
+}
+
+/// A set of comma-separated specifiers, all of which have to match.
+///
+/// An empty set matches every version.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpecifierSet(Vec<Specifier>);
+
+impl SpecifierSet {
+    pub fn specifiers(&self) -> &[Specifier] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether pre-releases are accepted, because a specifier mentions one.
+    pub fn allows_prereleases(&self) -> bool {
+        self.0.iter().any(Specifier::mentions_prerelease)
+    }
+
+    /// Check whether a version matches, using the default pre-release policy.
+    pub fn contains(&self, version: &PythonVersion) -> bool {
+        self.contains_with_prereleases(version, self.allows_prereleases())
+    }
+
+    /// Check whether a version matches, accepting pre-releases or not.
+    pub fn contains_with_prereleases(
+        &self,
+        version: &PythonVersion,
+        prereleases: bool,
+    ) -> bool {
+        if version.is_prerelease() && !prereleases {
+            return false;
+        }
+        self.0.iter().all(|s| s.contains(version))
+    }
+
+    /// Select the matching versions.
+    ///
+    /// If `prereleases` is `None`, the default policy is used: pre-releases
+    /// are only returned if a specifier mentions one, or if no final release
+    /// matches.
+    pub fn filter<'a, I>(
+        &self,
+        versions: I,
+        prereleases: Option<bool>,
+    ) -> Vec<&'a PythonVersion>
+    where I: IntoIterator<Item=&'a PythonVersion>
+    {
+        self.filter_by(versions, |v| v, prereleases)
+    }
+
+    /// Select the items with a matching version, like `filter()`.
+    pub(crate) fn filter_by<T, I, F>(
+        &self,
+        items: I,
+        version: F,
+        prereleases: Option<bool>,
+    ) -> Vec<T>
+    where I: IntoIterator<Item=T>, F: Fn(&T) -> &PythonVersion
+    {
+        let allow = prereleases.unwrap_or_else(|| self.allows_prereleases());
+        let mut finals = Vec::new();
+        let mut pres = Vec::new();
+        for item in items {
+            let v = version(&item);
+            if !self.0.iter().all(|s| s.contains(v)) {
+                continue;
+            }
+            if v.is_prerelease() && !allow {
+                pres.push(item);
+            } else {
+                finals.push(item);
+            }
+        }
+        if finals.is_empty() && prereleases.is_none() {
+            pres
+        } else {
+            finals
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SpecifierSet {
+    type Error = InvalidSpecifier;
+
+    fn try_from(string: &'a str) -> Result<SpecifierSet, InvalidSpecifier> {
+        let mut specifiers = Vec::new();
+        if !string.trim().is_empty() {
+            for spec in string.split(',') {
+                specifiers.push(Specifier::try_from(spec)?);
+            }
+        }
+        Ok(SpecifierSet(specifiers))
+    }
+}
+
+impl fmt::Display for SpecifierSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, spec) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", spec)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::super::PythonVersion;
+    use super::{InvalidSpecifier, Specifier, SpecifierSet};
+
+    fn version(s: &str) -> PythonVersion {
+        PythonVersion::try_from(s.to_owned()).unwrap()
+    }
+
+    fn spec(s: &str) -> Specifier {
+        Specifier::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn test_specifier() {
+        for &(s, v, expected) in &[
+            ("==2.0", "2.0", true),
+            ("==2.0", "2.0.0", true),
+            ("==2.0", "2.0+deadbeef", true),
+            ("==2.0+deadbeef", "2.0", false),
+            ("==2.0", "2.1", false),
+            ("==2.*", "2.1.3", true),
+            ("==2.1.*", "2.1", true),
+            ("==2.1.*", "2.1rc1", true),
+            ("==2.1.*", "2.2", false),
+            ("==1!2.*", "2.0", false),
+            ("!=2.0", "2.1", true),
+            ("!=2.0.*", "2.0.1", false),
+            ("~=2.2", "2.3", true),
+            ("~=2.2", "3.0", false),
+            ("~=2.2.0", "2.2.9", true),
+            ("~=2.2.0", "2.3", false),
+            ("~=2.2.post3", "2.3", true),
+            ("~=2.2.post3", "2.2.post2", false),
+            ("<=2.0", "2.0+local", true),
+            (">=2.0", "2.0.dev1", false),
+            ("<2.0", "2.0rc1", false),
+            ("<2.0", "1.9", true),
+            ("<2.0rc2", "2.0rc1", true),
+            (">2.0", "2.0.post1", false),
+            (">2.0", "2.0+local", false),
+            (">2.0", "2.0.1", true),
+            (">2.0.post1", "2.0.post2", true),
+            ("===2.0", "2.0", true),
+            ("===2.0", "2.0.0", false),
+        ] {
+            assert_eq!(spec(s).contains(&version(v)), expected, "{} {}", s, v);
+        }
+
+        assert_eq!(
+            Specifier::try_from("~=1"),
+            Err(InvalidSpecifier::InvalidVersionForOperator),
+        );
+        assert_eq!(
+            Specifier::try_from(">=1.*"),
+            Err(InvalidSpecifier::InvalidVersionForOperator),
+        );
+        assert_eq!(
+            Specifier::try_from("==1.0a1.*"),
+            Err(InvalidSpecifier::InvalidVersionForOperator),
+        );
+        assert_eq!(
+            Specifier::try_from("1.0"),
+            Err(InvalidSpecifier::InvalidOperator),
+        );
+        assert_eq!(spec(" >= 1.0 ").to_string(), ">=1.0");
+    }
+
+    #[test]
+    fn test_specifier_set() {
+        let set = SpecifierSet::try_from(">=1.0, !=1.3.*, <2").unwrap();
+        assert_eq!(set.to_string(), ">=1.0,!=1.3.*,<2");
+        assert!(set.contains(&version("1.2")));
+        assert!(!set.contains(&version("1.3.1")));
+        assert!(!set.contains(&version("1.5rc1")));
+        assert!(set.contains_with_prereleases(&version("1.5rc1"), true));
+        assert!(!set.contains(&version("2.0")));
+
+        assert!(SpecifierSet::try_from(">=1.0rc1").unwrap().allows_prereleases());
+        assert!(!SpecifierSet::try_from("<1.0rc1").unwrap().allows_prereleases());
+
+        let versions = vec![version("1.0"), version("1.1rc1"), version("2.0b1")];
+        let filtered = |s: &str, pre| {
+            SpecifierSet::try_from(s).unwrap()
+                .filter(&versions, pre)
+                .into_iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(filtered("", None), vec!["1.0"]);
+        assert_eq!(filtered(">=1.0", Some(true)), vec!["1.0", "1.1rc1", "2.0b1"]);
+        // Falls back to pre-releases if nothing else matches
+        assert_eq!(filtered(">1.0", None), vec!["1.1rc1", "2.0b1"]);
+        assert!(filtered(">1.0", Some(false)).is_empty());
+    }
+}
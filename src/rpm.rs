@@ -0,0 +1,434 @@
+//! Implement RPM's versioning scheme.
+//!
+//! RPM versions are written `[epoch:]version[-release]`, where the version is
+//! the one of the packaged software and the release is the one of the
+//! packaging itself. Both are compared using
+//! [`rpmvercmp`](fn.rpmvercmp.html), which splits them into alphabetic and
+//! numeric segments, ignoring every other character:
+//!
+//! * separators are all equal, so `1.0`, `1_0` and `1+0` are the same;
+//! * a numeric segment is newer than an alphabetic one;
+//! * `~` sorts before everything, even the end of the string (`1.0~rc1` is
+//!   older than `1.0`);
+//! * `^` sorts after the end of the string but before any other segment
+//!   (`1.0^git1` is newer than `1.0` but older than `1.0.1`).
+//!
+//! Dependencies (`Requires:`), including rich dependencies like
+//! `(foo >= 1.0 with foo < 2.0)`, can be parsed and checked against a set of
+//! installed packages.
+//!
+//! Package identifiers (`name-[epoch:]version-release.arch`) and package file
+//! names can be split into their parts.
+
+mod dependency;
+mod nevra;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+
+pub use self::dependency::{
+    Dependency, InvalidDependency, Operator, SimpleDependency,
+};
+pub use self::nevra::{DistTag, InvalidNevra, Nevra};
+
+fn strip_zeros(number: &[u8]) -> &[u8] {
+    let zeros = number.iter().take_while(|&&c| c == b'0').count();
+    &number[zeros ..]
+}
+
+/// Compare two version (or release) strings the way RPM does.
+pub fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a == b {
+        return Ordering::Equal;
+    }
+    let skip = |s: &[u8], mut i: usize| {
+        while i < s.len()
+            && !s[i].is_ascii_alphanumeric() && s[i] != b'~' && s[i] != b'^'
+        {
+            i += 1;
+        }
+        i
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        i = skip(a, i);
+        j = skip(b, j);
+        let (one, two) = (a.get(i).cloned(), b.get(j).cloned());
+
+        // The tilde sorts before everything else
+        if one == Some(b'~') || two == Some(b'~') {
+            if one != Some(b'~') {
+                return Ordering::Greater;
+            }
+            if two != Some(b'~') {
+                return Ordering::Less;
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // The caret sorts after the end of the string, before anything else
+        if one == Some(b'^') || two == Some(b'^') {
+            match (one, two) {
+                (None, _) => return Ordering::Less,
+                (_, None) => return Ordering::Greater,
+                (Some(b'^'), Some(b'^')) => {}
+                (_, Some(b'^')) => return Ordering::Greater,
+                _ => return Ordering::Less,
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // If either string is over, the other one wins
+        let one = match (one, two) {
+            (Some(one), Some(_)) => one,
+            _ => break,
+        };
+
+        // Grab a segment of the same kind from both strings
+        let numeric = one.is_ascii_digit();
+        let kind = |c: &u8| if numeric {
+            c.is_ascii_digit()
+        } else {
+            c.is_ascii_alphabetic()
+        };
+        let end1 = i + a[i ..].iter().take_while(|c| kind(c)).count();
+        let end2 = j + b[j ..].iter().take_while(|c| kind(c)).count();
+        if end2 == j {
+            // Numeric segments are newer than alphabetic ones
+            return if numeric { Ordering::Greater } else { Ordering::Less };
+        }
+
+        let ordering = if numeric {
+            let one = strip_zeros(&a[i .. end1]);
+            let two = strip_zeros(&b[j .. end2]);
+            one.len().cmp(&two.len()).then_with(|| one.cmp(two))
+        } else {
+            a[i .. end1].cmp(&b[j .. end2])
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        i = end1;
+        j = end2;
+    }
+
+    // Whichever has characters left is newer
+    (i < a.len()).cmp(&(j < b.len()))
+}
+
+/// An RPM version, `[epoch:]version[-release]`.
+///
+/// Versions without an epoch compare like epoch `0`.
+#[derive(Clone, Debug)]
+pub struct RpmVersion {
+    epoch: Option<u64>,
+    version: String,
+    release: Option<String>,
+}
+
+/// Check the characters of a version or release field.
+fn check_field(field: &str) -> Result<(), InvalidVersion> {
+    if field.is_empty() {
+        return Err(InvalidVersion::EmptyField);
+    }
+    let valid = |c: u8| {
+        c.is_ascii_alphanumeric() || b"._+~^".contains(&c)
+    };
+    if !field.bytes().all(valid) {
+        return Err(InvalidVersion::InvalidCharacter);
+    }
+    Ok(())
+}
+
+impl RpmVersion {
+    /// Parse a version of the form `[epoch:]version[-release]`.
+    pub fn parse(string: &str) -> Result<RpmVersion, InvalidVersion> {
+        let (epoch, rest) = match string.find(':') {
+            Some(colon) => {
+                let epoch = &string[.. colon];
+                if epoch.is_empty() {
+                    return Err(InvalidVersion::EmptyField);
+                }
+                if !epoch.bytes().all(|c| c.is_ascii_digit()) {
+                    return Err(InvalidVersion::InvalidCharacter);
+                }
+                let epoch = epoch.parse()
+                    .map_err(|_| InvalidVersion::NumberTooLarge)?;
+                (Some(epoch), &string[colon + 1 ..])
+            }
+            None => (None, string),
+        };
+        let (version, release) = match rest.rfind('-') {
+            Some(hyphen) => (&rest[.. hyphen], Some(&rest[hyphen + 1 ..])),
+            None => (rest, None),
+        };
+        check_field(version)?;
+        if let Some(release) = release {
+            check_field(release)?;
+        }
+        Ok(RpmVersion {
+            epoch,
+            version: version.to_owned(),
+            release: release.map(|r| r.to_owned()),
+        })
+    }
+
+    /// The epoch, `0` if not specified.
+    pub fn epoch(&self) -> u64 {
+        self.epoch.unwrap_or(0)
+    }
+
+    /// The version of the packaged software.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The release, e.g. version of the packaging itself.
+    pub fn release(&self) -> Option<&str> {
+        self.release.as_deref()
+    }
+
+    /// Compare epoch, version and release the way RPM does in dependencies.
+    ///
+    /// If either side has no release, only the epoch and version are
+    /// compared, so that `foo >= 1.0` is satisfied by `1.0-3`, and `foo = 1.0`
+    /// by any release of `1.0`. This is not a total order: use `cmp()` to sort
+    /// versions.
+    pub fn cmp_evr(&self, other: &RpmVersion) -> Ordering {
+        let ordering = self.epoch().cmp(&other.epoch())
+            .then_with(|| rpmvercmp(&self.version, &other.version));
+        match (self.release(), other.release()) {
+            (Some(a), Some(b)) => ordering.then_with(|| rpmvercmp(a, b)),
+            _ => ordering,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for RpmVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<RpmVersion, InvalidVersion> {
+        RpmVersion::parse(string)
+    }
+}
+
+impl fmt::Display for RpmVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(epoch) = self.epoch {
+            write!(f, "{}:", epoch)?;
+        }
+        write!(f, "{}", self.version)?;
+        if let Some(ref release) = self.release {
+            write!(f, "-{}", release)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<RpmVersion> for RpmVersion {
+    fn eq(&self, other: &RpmVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RpmVersion {}
+
+impl PartialOrd<RpmVersion> for RpmVersion {
+    fn partial_cmp(&self, other: &RpmVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RpmVersion {
+    /// Total order, where a missing release sorts before any release.
+    fn cmp(&self, other: &RpmVersion) -> Ordering {
+        self.epoch().cmp(&other.epoch())
+            .then_with(|| rpmvercmp(&self.version, &other.version))
+            .then_with(|| rpmvercmp(
+                self.release().unwrap_or(""),
+                other.release().unwrap_or(""),
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::InvalidVersion;
+    use super::{RpmVersion, rpmvercmp};
+
+    fn parse(s: &str) -> RpmVersion {
+        RpmVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_rpmvercmp() {
+        // From rpm's tests/rpmvercmp.at
+        let fixtures = [
+            ("1.0", "1.0", 0),
+            ("1.0", "2.0", -1),
+            ("2.0", "1.0", 1),
+            ("2.0.1", "2.0.1", 0),
+            ("2.0", "2.0.1", -1),
+            ("2.0.1", "2.0", 1),
+            ("2.0.1a", "2.0.1a", 0),
+            ("2.0.1a", "2.0.1", 1),
+            ("2.0.1", "2.0.1a", -1),
+            ("5.5p1", "5.5p1", 0),
+            ("5.5p1", "5.5p2", -1),
+            ("5.5p2", "5.5p1", 1),
+            ("5.5p10", "5.5p10", 0),
+            ("5.5p1", "5.5p10", -1),
+            ("5.5p10", "5.5p1", 1),
+            ("10xyz", "10.1xyz", -1),
+            ("10.1xyz", "10xyz", 1),
+            ("xyz10", "xyz10", 0),
+            ("xyz10", "xyz10.1", -1),
+            ("xyz10.1", "xyz10", 1),
+            ("xyz.4", "xyz.4", 0),
+            ("xyz.4", "8", -1),
+            ("8", "xyz.4", 1),
+            ("xyz.4", "2", -1),
+            ("2", "xyz.4", 1),
+            ("5.5p2", "5.6p1", -1),
+            ("5.6p1", "5.5p2", 1),
+            ("5.6p1", "6.5p1", -1),
+            ("6.5p1", "5.6p1", 1),
+            ("6.0.rc1", "6.0", 1),
+            ("6.0", "6.0.rc1", -1),
+            ("10b2", "10a1", 1),
+            ("10a2", "10b2", -1),
+            ("1.0aa", "1.0aa", 0),
+            ("1.0a", "1.0aa", -1),
+            ("1.0aa", "1.0a", 1),
+            ("10.0001", "10.0001", 0),
+            ("10.0001", "10.1", 0),
+            ("10.1", "10.0001", 0),
+            ("10.0001", "10.0039", -1),
+            ("10.0039", "10.0001", 1),
+            ("4.999.9", "5.0", -1),
+            ("5.0", "4.999.9", 1),
+            ("20101121", "20101121", 0),
+            ("20101121", "20101122", -1),
+            ("20101122", "20101121", 1),
+            ("2_0", "2_0", 0),
+            ("2.0", "2_0", 0),
+            ("2_0", "2.0", 0),
+            ("a", "a", 0),
+            ("a+", "a+", 0),
+            ("a+", "a_", 0),
+            ("a_", "a+", 0),
+            ("+a", "+a", 0),
+            ("+a", "_a", 0),
+            ("_a", "+a", 0),
+            ("+_", "+_", 0),
+            ("_+", "+_", 0),
+            ("_+", "_", 0),
+            ("+", "_", 0),
+            ("1.0~rc1", "1.0~rc1", 0),
+            ("1.0~rc1", "1.0", -1),
+            ("1.0", "1.0~rc1", 1),
+            ("1.0~rc1", "1.0~rc2", -1),
+            ("1.0~rc2", "1.0~rc1", 1),
+            ("1.0~rc1~git123", "1.0~rc1~git123", 0),
+            ("1.0~rc1~git123", "1.0~rc1", -1),
+            ("1.0~rc1", "1.0~rc1~git123", 1),
+            ("1.0^", "1.0^", 0),
+            ("1.0^", "1.0", 1),
+            ("1.0", "1.0^", -1),
+            ("1.0^git1", "1.0^git1", 0),
+            ("1.0^git1", "1.0", 1),
+            ("1.0", "1.0^git1", -1),
+            ("1.0^git1", "1.0^git2", -1),
+            ("1.0^git2", "1.0^git1", 1),
+            ("1.0^git1", "1.01", -1),
+            ("1.01", "1.0^git1", 1),
+            ("1.0^20160101", "1.0^20160101", 0),
+            ("1.0^20160101", "1.0.1", -1),
+            ("1.0.1", "1.0^20160101", 1),
+            ("1.0^20160101^git1", "1.0^20160101^git1", 0),
+            ("1.0^20160102", "1.0^20160101^git1", 1),
+            ("1.0^20160101^git1", "1.0^20160102", -1),
+            ("1.0~rc1^git1", "1.0~rc1^git1", 0),
+            ("1.0~rc1^git1", "1.0~rc1", 1),
+            ("1.0~rc1", "1.0~rc1^git1", -1),
+            ("1.0^git1~pre", "1.0^git1~pre", 0),
+            ("1.0^git1", "1.0^git1~pre", 1),
+            ("1.0^git1~pre", "1.0^git1", -1),
+        ];
+        for &(a, b, expected) in &fixtures {
+            assert_eq!(rpmvercmp(a, b), expected.cmp(&0), "{} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let version = parse("2:1.2.3~rc1-4.fc40");
+        assert_eq!(version.epoch(), 2);
+        assert_eq!(version.version(), "1.2.3~rc1");
+        assert_eq!(version.release(), Some("4.fc40"));
+        assert_eq!(version.to_string(), "2:1.2.3~rc1-4.fc40");
+
+        let version = parse("1.0");
+        assert_eq!(version.epoch(), 0);
+        assert_eq!(version.release(), None);
+        assert_eq!(version.to_string(), "1.0");
+        assert_eq!(parse("0:1.0-1").to_string(), "0:1.0-1");
+
+        for &(invalid, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            (":1.0", InvalidVersion::EmptyField),
+            ("1.0-", InvalidVersion::EmptyField),
+            ("-1", InvalidVersion::EmptyField),
+            ("a:1.0", InvalidVersion::InvalidCharacter),
+            ("1.0 -1", InvalidVersion::InvalidCharacter),
+            ("1:2:3", InvalidVersion::InvalidCharacter),
+            ("99999999999999999999:1", InvalidVersion::NumberTooLarge),
+        ] {
+            assert_eq!(&RpmVersion::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let versions = [
+            "1.0~rc1-1",
+            "1.0-1",
+            "1.0-1.fc40",
+            "1.0-2",
+            "1.0^git1-1",
+            "1.0.1-1",
+            "1:0.5-1",
+        ];
+        for pair in versions.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(parse("0:1.0-1"), parse("1.0-1"));
+        assert_eq!(parse("1.01-1"), parse("1.1-1"));
+        assert!(parse("1.0") < parse("1.0-1"));
+    }
+
+    #[test]
+    fn test_cmp_evr() {
+        let fixtures = [
+            ("1.0", "1.0-5", Ordering::Equal),
+            ("1.0-5", "1.0", Ordering::Equal),
+            ("1.0-5", "1.0-6", Ordering::Less),
+            ("1.0", "1.1-1", Ordering::Less),
+            ("1:1.0", "2.0-1", Ordering::Greater),
+            ("0:1.0", "1.0-1", Ordering::Equal),
+        ];
+        for &(a, b, expected) in &fixtures {
+            assert_eq!(parse(a).cmp_evr(&parse(b)), expected, "{} {}", a, b);
+        }
+    }
+}
@@ -0,0 +1,555 @@
+//! RPM dependencies, e.g. the contents of `Requires:`.
+//!
+//! Simple dependencies are a name with an optional version constraint, like
+//! `foo >= 1:2.3-4`. Rich (boolean) dependencies combine them between
+//! parentheses, like `(foo >= 1.0 with foo < 2.0)` or
+//! `(feature if pkgC else pkgD)`.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+use super::RpmVersion;
+
+/// Error for the dependency parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidDependency {
+    /// There is no dependency (or an operand is missing).
+    Empty,
+    /// The package name is invalid, e.g. has unbalanced parentheses.
+    InvalidName,
+    /// Unknown comparison operator.
+    InvalidOperator,
+    /// The version in the constraint is invalid.
+    InvalidVersion(InvalidVersion),
+    /// Unknown boolean operator, or different operators mixed without
+    /// parentheses.
+    InvalidBooleanOperator,
+    /// Missing closing parenthesis.
+    UnbalancedParentheses,
+    /// Characters after the end of the dependency.
+    TrailingCharacters,
+}
+
+impl From<InvalidVersion> for InvalidDependency {
+    fn from(error: InvalidVersion) -> InvalidDependency {
+        InvalidDependency::InvalidVersion(error)
+    }
+}
+
+/// A comparison operator in a version constraint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Equal => "=",
+            Operator::GreaterEqual => ">=",
+            Operator::Greater => ">",
+        })
+    }
+}
+
+/// The spellings of the operators accepted by RPM.
+const OPERATORS: &[(&str, Operator)] = &[
+    ("<", Operator::Less),
+    ("<=", Operator::LessEqual),
+    ("=<", Operator::LessEqual),
+    ("=", Operator::Equal),
+    ("==", Operator::Equal),
+    (">=", Operator::GreaterEqual),
+    ("=>", Operator::GreaterEqual),
+    (">", Operator::Greater),
+];
+
+/// A name with an optional version constraint, e.g. `foo >= 1:2.3-4`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimpleDependency {
+    name: String,
+    constraint: Option<(Operator, RpmVersion)>,
+}
+
+impl SimpleDependency {
+    /// The name of the package (or capability), e.g. `perl(Foo::Bar)`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The version constraint, if any.
+    pub fn constraint(&self) -> Option<(Operator, &RpmVersion)> {
+        self.constraint.as_ref().map(|&(op, ref version)| (op, version))
+    }
+
+    /// Whether a package satisfies this dependency.
+    ///
+    /// Versions are compared with
+    /// [`RpmVersion::cmp_evr`](struct.RpmVersion.html#method.cmp_evr), so a
+    /// constraint without a release accepts any release.
+    pub fn matches(&self, name: &str, version: &RpmVersion) -> bool {
+        if name != self.name {
+            return false;
+        }
+        let (op, required) = match self.constraint() {
+            Some(constraint) => constraint,
+            None => return true,
+        };
+        let ordering = version.cmp_evr(required);
+        match op {
+            Operator::Less => ordering.is_lt(),
+            Operator::LessEqual => ordering.is_le(),
+            Operator::Equal => ordering.is_eq(),
+            Operator::GreaterEqual => ordering.is_ge(),
+            Operator::Greater => ordering.is_gt(),
+        }
+    }
+}
+
+impl fmt::Display for SimpleDependency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some((op, ref version)) = self.constraint {
+            write!(f, " {} {}", op, version)?;
+        }
+        Ok(())
+    }
+}
+
+/// A dependency, either simple or rich.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Dependency {
+    /// A name with an optional version constraint.
+    Simple(SimpleDependency),
+    /// All of the dependencies must be satisfied.
+    And(Vec<Dependency>),
+    /// Any of the dependencies must be satisfied.
+    Or(Vec<Dependency>),
+    /// `(then if condition [else otherwise])`.
+    If {
+        then: Box<Dependency>,
+        condition: Box<Dependency>,
+        otherwise: Option<Box<Dependency>>,
+    },
+    /// `(then unless condition [else otherwise])`.
+    Unless {
+        then: Box<Dependency>,
+        condition: Box<Dependency>,
+        otherwise: Option<Box<Dependency>>,
+    },
+    /// A single package must satisfy all of the dependencies.
+    With(Vec<Dependency>),
+    /// A single package must satisfy the first dependency but not the
+    /// second.
+    Without(Box<Dependency>, Box<Dependency>),
+}
+
+/// Simple cursor over a dependency string.
+struct Parser<'a> {
+    string: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.string.as_bytes().get(self.pos).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Read characters until one of the stop characters (or whitespace).
+    fn token(&mut self, stop: &[u8]) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_ascii_whitespace() && !stop.contains(&c)) {
+            self.pos += 1;
+        }
+        &self.string[start .. self.pos]
+    }
+
+    /// Read a name, which may contain balanced parentheses (`perl(Foo)`).
+    fn name(&mut self) -> Result<&'a str, InvalidDependency> {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            match c {
+                b'(' => depth += 1,
+                b')' if depth == 0 => break,
+                b')' => depth -= 1,
+                b'<' | b'>' | b'=' | b',' if depth == 0 => break,
+                _ if c.is_ascii_whitespace() => break,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        if depth != 0 {
+            return Err(InvalidDependency::InvalidName);
+        }
+        Ok(&self.string[start .. self.pos])
+    }
+
+    fn simple(&mut self) -> Result<SimpleDependency, InvalidDependency> {
+        let name = self.name()?;
+        if name.is_empty() {
+            return Err(InvalidDependency::Empty);
+        }
+        let end = self.pos;
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.peek().is_some_and(|c| b"<>=".contains(&c)) {
+            self.pos += 1;
+        }
+        let op = &self.string[start .. self.pos];
+        if op.is_empty() {
+            self.pos = end;
+            return Ok(SimpleDependency { name: name.to_owned(), constraint: None });
+        }
+        let op = OPERATORS.iter()
+            .find(|&&(spelling, _)| spelling == op)
+            .map(|&(_, op)| op)
+            .ok_or(InvalidDependency::InvalidOperator)?;
+        self.skip_whitespace();
+        let version = self.token(b",()");
+        if version.is_empty() {
+            return Err(InvalidDependency::Empty);
+        }
+        Ok(SimpleDependency {
+            name: name.to_owned(),
+            constraint: Some((op, RpmVersion::parse(version)?)),
+        })
+    }
+
+    fn dependency(&mut self) -> Result<Dependency, InvalidDependency> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'(') => self.rich(),
+            Some(_) => self.simple().map(Dependency::Simple),
+            None => Err(InvalidDependency::Empty),
+        }
+    }
+
+    fn keyword(&mut self) -> Result<&'a str, InvalidDependency> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(InvalidDependency::UnbalancedParentheses),
+            Some(b')') => {
+                self.pos += 1;
+                Ok(")")
+            }
+            Some(_) => Ok(self.token(b"()")),
+        }
+    }
+
+    fn rich(&mut self) -> Result<Dependency, InvalidDependency> {
+        self.pos += 1; // '('
+        let first = self.dependency()?;
+        let keyword = self.keyword()?;
+        let dependency = match keyword {
+            ")" => return Ok(first),
+            "and" | "or" | "with" => {
+                let mut operands = vec![first, self.dependency()?];
+                loop {
+                    match self.keyword()? {
+                        ")" => break,
+                        k if k == keyword => operands.push(self.dependency()?),
+                        _ => return Err(InvalidDependency::InvalidBooleanOperator),
+                    }
+                }
+                return Ok(match keyword {
+                    "and" => Dependency::And(operands),
+                    "or" => Dependency::Or(operands),
+                    _ => Dependency::With(operands),
+                });
+            }
+            "if" | "unless" => {
+                let condition = Box::new(self.dependency()?);
+                let otherwise = match self.keyword()? {
+                    ")" => return Ok(conditional(keyword, first, condition, None)),
+                    "else" => Box::new(self.dependency()?),
+                    _ => return Err(InvalidDependency::InvalidBooleanOperator),
+                };
+                conditional(keyword, first, condition, Some(otherwise))
+            }
+            "without" => {
+                Dependency::Without(Box::new(first), Box::new(self.dependency()?))
+            }
+            _ => return Err(InvalidDependency::InvalidBooleanOperator),
+        };
+        match self.keyword()? {
+            ")" => Ok(dependency),
+            _ => Err(InvalidDependency::InvalidBooleanOperator),
+        }
+    }
+}
+
+fn conditional(
+    keyword: &str,
+    then: Dependency,
+    condition: Box<Dependency>,
+    otherwise: Option<Box<Dependency>>,
+) -> Dependency {
+    let then = Box::new(then);
+    if keyword == "if" {
+        Dependency::If { then, condition, otherwise }
+    } else {
+        Dependency::Unless { then, condition, otherwise }
+    }
+}
+
+impl Dependency {
+    /// Parse a single dependency, simple or rich.
+    pub fn parse(string: &str) -> Result<Dependency, InvalidDependency> {
+        let mut parser = Parser { string, pos: 0 };
+        let dependency = parser.dependency()?;
+        parser.skip_whitespace();
+        if parser.pos != string.len() {
+            return Err(InvalidDependency::TrailingCharacters);
+        }
+        Ok(dependency)
+    }
+
+    /// Parse the value of a `Requires:` tag, which can list multiple
+    /// dependencies separated by commas or whitespace.
+    pub fn parse_list(string: &str) -> Result<Vec<Dependency>, InvalidDependency> {
+        let mut parser = Parser { string, pos: 0 };
+        let mut dependencies = Vec::new();
+        loop {
+            parser.skip_whitespace();
+            if parser.peek().is_none() {
+                break;
+            }
+            dependencies.push(parser.dependency()?);
+            parser.skip_whitespace();
+            match parser.peek() {
+                Some(b',') => parser.pos += 1,
+                Some(b')') => return Err(InvalidDependency::TrailingCharacters),
+                _ => {}
+            }
+        }
+        Ok(dependencies)
+    }
+
+    /// Whether a single package satisfies this dependency.
+    ///
+    /// This is how the operands of `with` and `without` are evaluated.
+    pub fn matches(&self, name: &str, version: &RpmVersion) -> bool {
+        match *self {
+            Dependency::Simple(ref simple) => simple.matches(name, version),
+            _ => self.evaluate(&|dependency| dependency.matches(name, version)),
+        }
+    }
+
+    /// Whether a set of installed packages satisfies this dependency.
+    ///
+    /// Only package names are known, so capabilities that are provided
+    /// under another name (e.g. `perl(Foo)` or files) are not found.
+    pub fn is_satisfied_by<N: AsRef<str>>(
+        &self,
+        installed: &[(N, RpmVersion)],
+    ) -> bool {
+        match *self {
+            Dependency::Simple(_) | Dependency::With(_) | Dependency::Without(..) => {
+                installed.iter().any(|(name, version)| {
+                    self.matches(name.as_ref(), version)
+                })
+            }
+            _ => self.evaluate(&|dependency| dependency.is_satisfied_by(installed)),
+        }
+    }
+
+    /// Evaluate the boolean operator, using `test` for the operands.
+    fn evaluate(&self, test: &dyn Fn(&Dependency) -> bool) -> bool {
+        match *self {
+            Dependency::Simple(_) => unreachable!("no operands"),
+            Dependency::And(ref operands) | Dependency::With(ref operands) => {
+                operands.iter().all(test)
+            }
+            Dependency::Or(ref operands) => operands.iter().any(test),
+            Dependency::If { ref then, ref condition, ref otherwise } => {
+                if test(condition) {
+                    test(then)
+                } else {
+                    otherwise.as_ref().is_none_or(|o| test(o))
+                }
+            }
+            Dependency::Unless { ref then, ref condition, ref otherwise } => {
+                if test(condition) {
+                    otherwise.as_ref().is_none_or(|o| test(o))
+                } else {
+                    test(then)
+                }
+            }
+            Dependency::Without(ref first, ref second) => {
+                test(first) && !test(second)
+            }
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Dependency {
+    type Error = InvalidDependency;
+
+    fn try_from(string: &'a str) -> Result<Dependency, InvalidDependency> {
+        Dependency::parse(string)
+    }
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter, operands: &[Dependency], keyword| {
+            write!(f, "(")?;
+            for (i, operand) in operands.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", keyword)?;
+                }
+                write!(f, "{}", operand)?;
+            }
+            write!(f, ")")
+        };
+        let conditional = |
+            f: &mut fmt::Formatter,
+            then: &Dependency,
+            keyword,
+            condition: &Dependency,
+            otherwise: &Option<Box<Dependency>>,
+        | {
+            write!(f, "({} {} {}", then, keyword, condition)?;
+            if let Some(ref otherwise) = *otherwise {
+                write!(f, " else {}", otherwise)?;
+            }
+            write!(f, ")")
+        };
+        match *self {
+            Dependency::Simple(ref simple) => write!(f, "{}", simple),
+            Dependency::And(ref operands) => join(f, operands, "and"),
+            Dependency::Or(ref operands) => join(f, operands, "or"),
+            Dependency::With(ref operands) => join(f, operands, "with"),
+            Dependency::If { ref then, ref condition, ref otherwise } => {
+                conditional(f, then, "if", condition, otherwise)
+            }
+            Dependency::Unless { ref then, ref condition, ref otherwise } => {
+                conditional(f, then, "unless", condition, otherwise)
+            }
+            Dependency::Without(ref first, ref second) => {
+                write!(f, "({} without {})", first, second)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use crate::rpm::RpmVersion;
+    use super::{Dependency, InvalidDependency, Operator};
+
+    fn installed(packages: &[(&str, &str)]) -> Vec<(String, RpmVersion)> {
+        packages.iter()
+            .map(|&(name, version)| {
+                (name.to_owned(), RpmVersion::parse(version).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let dependency = Dependency::parse("foo >= 1:2.3-4").unwrap();
+        match dependency {
+            Dependency::Simple(ref simple) => {
+                assert_eq!(simple.name(), "foo");
+                let (op, version) = simple.constraint().unwrap();
+                assert_eq!(op, Operator::GreaterEqual);
+                assert_eq!(version.to_string(), "1:2.3-4");
+            }
+            _ => panic!(),
+        }
+
+        for &(input, normalized) in &[
+            ("foo", "foo"),
+            ("foo>=1.0", "foo >= 1.0"),
+            ("foo => 1.0", "foo >= 1.0"),
+            ("foo == 1.0", "foo = 1.0"),
+            ("perl(Foo::Bar) < 2", "perl(Foo::Bar) < 2"),
+            ("pkgconfig(glib-2.0)", "pkgconfig(glib-2.0)"),
+            ("/usr/bin/sh", "/usr/bin/sh"),
+            ("(foo >= 1.0 with foo < 2.0)", "(foo >= 1.0 with foo < 2.0)"),
+            ("(pkgA or pkgB)", "(pkgA or pkgB)"),
+            ("(pkgA or pkgB or pkgC)", "(pkgA or pkgB or pkgC)"),
+            ("(feature if pkgC else pkgD)", "(feature if pkgC else pkgD)"),
+            ("(feature unless pkgC)", "(feature unless pkgC)"),
+            ("(foo without foo = 1.5)", "(foo without foo = 1.5)"),
+            ("((a and b) or (c if d))", "((a and b) or (c if d))"),
+            ("( foo )", "foo"),
+            ("(perl(Foo) and bar>1)", "(perl(Foo) and bar > 1)"),
+        ] {
+            assert_eq!(Dependency::parse(input).unwrap().to_string(), normalized, "{}", input);
+        }
+
+        for &(input, ref error) in &[
+            ("", InvalidDependency::Empty),
+            ("foo >=", InvalidDependency::Empty),
+            ("(foo and)", InvalidDependency::Empty),
+            ("foo <> 1", InvalidDependency::InvalidOperator),
+            ("foo >= 1.0-", InvalidDependency::InvalidVersion(InvalidVersion::EmptyField)),
+            ("perl(Foo", InvalidDependency::InvalidName),
+            ("(a and b or c)", InvalidDependency::InvalidBooleanOperator),
+            ("(a xor b)", InvalidDependency::InvalidBooleanOperator),
+            ("(a without b without c)", InvalidDependency::InvalidBooleanOperator),
+            ("(a and b", InvalidDependency::UnbalancedParentheses),
+            ("a b", InvalidDependency::TrailingCharacters),
+            ("(a) b", InvalidDependency::TrailingCharacters),
+        ] {
+            assert_eq!(&Dependency::parse(input).unwrap_err(), error, "{}", input);
+        }
+
+        let list = Dependency::parse_list("foo >= 1.0, bar (a or b) baz").unwrap();
+        let list: Vec<String> = list.iter().map(|d| d.to_string()).collect();
+        assert_eq!(list, ["foo >= 1.0", "bar", "(a or b)", "baz"]);
+    }
+
+    #[test]
+    fn test_satisfied() {
+        let system = installed(&[
+            ("foo", "1:2.3-4.fc40"),
+            ("bar", "1.5-1"),
+            ("pkgC", "1.0-1"),
+        ]);
+        let satisfied = |dependency: &str| {
+            Dependency::parse(dependency).unwrap().is_satisfied_by(&system)
+        };
+
+        assert!(satisfied("foo"));
+        assert!(satisfied("foo >= 1:2.3-4"));
+        assert!(satisfied("foo = 1:2.3"));
+        assert!(!satisfied("foo = 2.3"));
+        assert!(!satisfied("foo > 1:2.3-4.fc40"));
+        assert!(satisfied("foo > 2.3"));
+        assert!(!satisfied("missing"));
+
+        assert!(satisfied("(bar >= 1.0 with bar < 2.0)"));
+        assert!(!satisfied("(bar >= 2.0 with bar < 3.0)"));
+        assert!(!satisfied("(foo with bar)"));
+        assert!(satisfied("(foo and bar)"));
+        assert!(!satisfied("(foo and missing)"));
+        assert!(satisfied("(missing or bar)"));
+        assert!(satisfied("(bar if pkgC else missing)"));
+        assert!(!satisfied("(missing if pkgC else bar)"));
+        assert!(satisfied("(missing if pkgD)"));
+        assert!(satisfied("(bar if pkgD else foo)"));
+        assert!(satisfied("(missing unless pkgC)"));
+        assert!(!satisfied("(missing unless pkgD)"));
+        assert!(satisfied("(bar without bar < 1.0)"));
+        assert!(!satisfied("(bar without bar = 1.5)"));
+        assert!(satisfied("((missing or foo) and (bar with bar > 1))"));
+    }
+}
@@ -0,0 +1,295 @@
+//! Package identifiers, `name-[epoch:]version-release.arch`, and file names.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+use super::RpmVersion;
+
+/// Error for the NEVRA parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidNevra {
+    /// Missing the name, version, release, or architecture.
+    MissingField,
+    /// The name contains invalid characters.
+    InvalidName,
+    /// The epoch, version or release is invalid.
+    InvalidVersion(InvalidVersion),
+    /// The architecture contains invalid characters.
+    InvalidArch,
+    /// The file name doesn't end with `.rpm`.
+    NotRpm,
+}
+
+impl From<InvalidVersion> for InvalidNevra {
+    fn from(error: InvalidVersion) -> InvalidNevra {
+        InvalidNevra::InvalidVersion(error)
+    }
+}
+
+/// A package identifier, `name-[epoch:]version-release.arch`.
+///
+/// Packages are ordered by name, then version (as RPM compares them), then
+/// architecture.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Nevra {
+    name: String,
+    evr: RpmVersion,
+    arch: String,
+}
+
+/// Prefixes of distribution tags, as set in `%{?dist}` by each distribution.
+const DISTRIBUTIONS: &[&str] = &[
+    "el", "fc", "amzn", "suse", "sle", "lp", "mga", "mdv", "ph", "cm", "azl",
+    "an", "oe",
+];
+
+/// The distribution tag of a release, e.g. `el9_3` in `362.8.1.el9_3`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DistTag<'a> {
+    tag: &'a str,
+    distribution: &'a str,
+    major: u64,
+}
+
+impl<'a> DistTag<'a> {
+    /// Find the distribution tag in a release field.
+    ///
+    /// This is the last dot-separated component made of a known distribution
+    /// prefix then a number, optionally followed by `_` and a minor number,
+    /// such as `fc40`, `el9` or `el9_3`. The first component, the release
+    /// number of the package itself, is never a tag, and neither are
+    /// snapshot or pre-release markers such as `git20230101` or `rc1`.
+    pub fn from_release(release: &'a str) -> Option<DistTag<'a>> {
+        release.split('.')
+            .skip(1)
+            // Module builds look like `module+el8.5.0+14+abc`
+            .flat_map(|component| component.split('+'))
+            .filter_map(DistTag::parse_tag)
+            .last()
+    }
+
+    fn parse_tag(tag: &'a str) -> Option<DistTag<'a>> {
+        let letters = tag.bytes().take_while(|c| c.is_ascii_alphabetic()).count();
+        let (distribution, number) = tag.split_at(letters);
+        let (major, minor) = match number.find('_') {
+            Some(underscore) => (&number[.. underscore], Some(&number[underscore + 1 ..])),
+            None => (number, None),
+        };
+        let is_number = |s: &str| !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit());
+        if !DISTRIBUTIONS.contains(&distribution) || !is_number(major) || !minor.is_none_or(is_number) {
+            return None;
+        }
+        Some(DistTag { tag, distribution, major: major.parse().ok()? })
+    }
+
+    /// The whole tag, e.g. `el9_3`.
+    pub fn tag(&self) -> &'a str {
+        self.tag
+    }
+
+    /// The distribution, e.g. `el` or `fc`.
+    pub fn distribution(&self) -> &'a str {
+        self.distribution
+    }
+
+    /// The major release of the distribution, e.g. `9` for `el9_3`.
+    pub fn major(&self) -> u64 {
+        self.major
+    }
+}
+
+impl Nevra {
+    /// Parse a string of the form `name-[epoch:]version-release.arch`.
+    pub fn parse(string: &str) -> Result<Nevra, InvalidNevra> {
+        let dot = string.rfind('.').ok_or(InvalidNevra::MissingField)?;
+        let (rest, arch) = (&string[.. dot], &string[dot + 1 ..]);
+        if arch.is_empty() {
+            return Err(InvalidNevra::MissingField);
+        }
+        if !arch.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_') {
+            return Err(InvalidNevra::InvalidArch);
+        }
+
+        // The version and release can't contain hyphens, the name can
+        let release = rest.rfind('-').ok_or(InvalidNevra::MissingField)?;
+        let version = rest[.. release].rfind('-').ok_or(InvalidNevra::MissingField)?;
+        let name = &rest[.. version];
+        if name.is_empty() {
+            return Err(InvalidNevra::MissingField);
+        }
+        if name.bytes().any(|c| c.is_ascii_whitespace() || c == b':' || c == b'/') {
+            return Err(InvalidNevra::InvalidName);
+        }
+        let evr = RpmVersion::parse(&rest[version + 1 ..])?;
+
+        Ok(Nevra {
+            name: name.to_owned(),
+            evr,
+            arch: arch.to_owned(),
+        })
+    }
+
+    /// Parse a package file name, e.g. `bash-5.2.26-3.fc40.x86_64.rpm`.
+    ///
+    /// Leading directories are ignored. Source packages have the `src` (or
+    /// `nosrc`) architecture.
+    pub fn from_filename(filename: &str) -> Result<Nevra, InvalidNevra> {
+        let filename = match filename.rfind('/') {
+            Some(slash) => &filename[slash + 1 ..],
+            None => filename,
+        };
+        let stem = filename.strip_suffix(".rpm").ok_or(InvalidNevra::NotRpm)?;
+        Nevra::parse(stem)
+    }
+
+    /// The name of the package.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The epoch, version and release.
+    pub fn evr(&self) -> &RpmVersion {
+        &self.evr
+    }
+
+    /// The architecture, e.g. `x86_64`, `noarch` or `src`.
+    pub fn arch(&self) -> &str {
+        &self.arch
+    }
+
+    /// Whether this is a source package.
+    pub fn is_source(&self) -> bool {
+        self.arch == "src" || self.arch == "nosrc"
+    }
+
+    /// The distribution tag from the release, e.g. `fc40`.
+    pub fn dist_tag(&self) -> Option<DistTag<'_>> {
+        self.evr.release().and_then(DistTag::from_release)
+    }
+
+    /// The name of the package file, which doesn't include the epoch.
+    pub fn filename(&self) -> String {
+        format!(
+            "{}-{}-{}.{}.rpm",
+            self.name,
+            self.evr.version(),
+            self.evr.release().unwrap_or_default(),
+            self.arch,
+        )
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Nevra {
+    type Error = InvalidNevra;
+
+    fn try_from(string: &'a str) -> Result<Nevra, InvalidNevra> {
+        Nevra::parse(string)
+    }
+}
+
+impl fmt::Display for Nevra {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}.{}", self.name, self.evr, self.arch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::{DistTag, InvalidNevra, Nevra};
+
+    #[test]
+    fn test_parse() {
+        let nevra = Nevra::parse("kernel-core-0:5.14.0-362.8.1.el9_3.x86_64").unwrap();
+        assert_eq!(nevra.name(), "kernel-core");
+        assert_eq!(nevra.evr().epoch(), 0);
+        assert_eq!(nevra.evr().version(), "5.14.0");
+        assert_eq!(nevra.evr().release(), Some("362.8.1.el9_3"));
+        assert_eq!(nevra.arch(), "x86_64");
+        assert!(!nevra.is_source());
+        assert_eq!(nevra.to_string(), "kernel-core-0:5.14.0-362.8.1.el9_3.x86_64");
+        assert_eq!(nevra.filename(), "kernel-core-5.14.0-362.8.1.el9_3.x86_64.rpm");
+
+        let nevra = Nevra::parse("python3-dnf-4.14.0-1.fc40.noarch").unwrap();
+        assert_eq!(nevra.name(), "python3-dnf");
+        assert_eq!(nevra.evr().to_string(), "4.14.0-1.fc40");
+        assert_eq!(nevra.arch(), "noarch");
+
+        for &(invalid, ref error) in &[
+            ("bash", InvalidNevra::MissingField),
+            ("bash-5.2.x86_64", InvalidNevra::MissingField),
+            ("-5.2-1.x86_64", InvalidNevra::MissingField),
+            ("bash-5.2-1.", InvalidNevra::MissingField),
+            ("bash-5.2-1.x86-64", InvalidNevra::InvalidArch),
+            ("bash-5.2-1.fc40.x86 64", InvalidNevra::InvalidArch),
+            ("my bash-5.2-1.x86_64", InvalidNevra::InvalidName),
+            ("bash-a:5.2-1.x86_64", InvalidNevra::InvalidVersion(InvalidVersion::InvalidCharacter)),
+        ] {
+            assert_eq!(&Nevra::parse(invalid).unwrap_err(), error, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_filename() {
+        let nevra = Nevra::from_filename(
+            "/var/cache/glibc-langpack-en-2.34-83.el9_3.7.x86_64.rpm",
+        ).unwrap();
+        assert_eq!(nevra.name(), "glibc-langpack-en");
+        assert_eq!(nevra.evr().to_string(), "2.34-83.el9_3.7");
+        assert_eq!(nevra.arch(), "x86_64");
+
+        let nevra = Nevra::from_filename("bash-5.2.26-3.fc40.src.rpm").unwrap();
+        assert_eq!(nevra.name(), "bash");
+        assert!(nevra.is_source());
+        assert_eq!(nevra.filename(), "bash-5.2.26-3.fc40.src.rpm");
+
+        assert_eq!(
+            Nevra::from_filename("bash-5.2.26-3.fc40.src.deb").unwrap_err(),
+            InvalidNevra::NotRpm,
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let packages = [
+            "bash-5.2-1.fc40.x86_64",
+            "bash-5.2-2.fc40.aarch64",
+            "bash-5.2-2.fc40.x86_64",
+            "bash-5.10-1.fc40.x86_64",
+            "bash-1:4.0-1.fc40.x86_64",
+            "bash-completion-2.11-1.fc40.noarch",
+        ];
+        for pair in packages.windows(2) {
+            let (a, b) = (Nevra::parse(pair[0]).unwrap(), Nevra::parse(pair[1]).unwrap());
+            assert!(a < b, "{} < {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_dist_tag() {
+        for &(release, expected) in &[
+            ("362.8.1.el9_3", Some(("el9_3", "el", 9))),
+            ("1.el9_3.1", Some(("el9_3", "el", 9))),
+            ("5.fc40", Some(("fc40", "fc", 40))),
+            ("0.1.git20230101.fc40", Some(("fc40", "fc", 40))),
+            ("1.amzn2023.0.2", Some(("amzn2023", "amzn", 2023))),
+            ("1.module+el8.5.0+14+abc", Some(("el8", "el", 8))),
+            ("1", None),
+            ("fc40", None),
+            ("1.git", None),
+            ("0.1.git20230101", None),
+            ("1.rc1", None),
+            ("2.beta3_1", None),
+            ("1.lp155", Some(("lp155", "lp", 155))),
+            ("1.mga9", Some(("mga9", "mga", 9))),
+            ("1.fc40.rc1", Some(("fc40", "fc", 40))),
+        ] {
+            let tag = DistTag::from_release(release);
+            let tag = tag.map(|t| (t.tag(), t.distribution(), t.major()));
+            assert_eq!(tag, expected, "{}", release);
+        }
+        let nevra = Nevra::parse("bash-5.2.26-3.fc40.x86_64").unwrap();
+        assert_eq!(nevra.dist_tag().unwrap().major(), 40);
+    }
+}
@@ -0,0 +1,393 @@
+//! Implement RubyGems' versioning scheme, as implemented by `Gem::Version`.
+//!
+//! Versions are split into segments of digits or letters, on `.` and on
+//! transitions between digits and letters, so `1.0.rc1` is `[1, 0, rc, 1]`.
+//! A `-` is read as `.pre.`, so `1.0-rc1` is `1.0.pre.rc1`. A version with
+//! a letter is a pre-release.
+//!
+//! Segments are compared in order, numbers numerically and strings byte by
+//! byte, and strings are less than numbers. Missing segments count as `0`,
+//! and trailing zeros are ignored before the first string and at the end, so
+//! `1.0.a < 1 == 1.0 < 1.0.1`.
+//!
+//! Requirements (`~> 1.2, != 1.2.5`) and the gems locked by a
+//! `Gemfile.lock` can be parsed too.
+
+mod lockfile;
+mod requirement;
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{InvalidVersion, Version};
+
+pub use self::lockfile::{GemfileLock, InvalidLockfile, InvalidSpec, LockedDependency, LockedGem};
+pub use self::requirement::{GemRequirement, InvalidRequirement, Operator};
+
+/// A segment of a version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    /// A number, without leading zeros.
+    Number(String),
+    String(String),
+}
+
+impl Segment {
+    fn is_zero(&self) -> bool {
+        *self == Segment::Number(String::new())
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Segment::Number(ref n) if n.is_empty() => f.write_str("0"),
+            Segment::Number(ref n) | Segment::String(ref n) => f.write_str(n),
+        }
+    }
+}
+
+impl PartialOrd for Segment {
+    fn partial_cmp(&self, other: &Segment) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Segment {
+    fn cmp(&self, other: &Segment) -> Ordering {
+        match (self, other) {
+            (Segment::Number(a), Segment::Number(b)) => {
+                a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+            }
+            (Segment::Number(_), Segment::String(_)) => Ordering::Greater,
+            (Segment::String(_), Segment::Number(_)) => Ordering::Less,
+            (Segment::String(a), Segment::String(b)) => a.cmp(b),
+        }
+    }
+}
+
+/// A RubyGems version, e.g. `1.2.3` or `2.0.0.rc1`.
+#[derive(Clone, Debug)]
+pub struct GemVersion {
+    string: String,
+    segments: Vec<Segment>,
+}
+
+fn split_segments(string: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut start = None;
+    for (i, c) in string.char_indices().chain(Some((string.len(), '.'))) {
+        if let Some(s) = start {
+            let previous = string[s ..].chars().next().unwrap();
+            if c.is_ascii_alphanumeric() && c.is_ascii_digit() == previous.is_ascii_digit() {
+                continue;
+            }
+            let part = &string[s .. i];
+            segments.push(if previous.is_ascii_digit() {
+                Segment::Number(part.trim_start_matches('0').to_owned())
+            } else {
+                Segment::String(part.to_owned())
+            });
+            start = None;
+        }
+        if c.is_ascii_alphanumeric() {
+            start = Some(i);
+        }
+    }
+    segments
+}
+
+impl GemVersion {
+    /// Parse a version, e.g. `1.2.3`, `1.0.rc1` or `1.0-rc1`.
+    pub fn parse(string: &str) -> Result<GemVersion, InvalidVersion> {
+        if string.is_empty() {
+            return Err(InvalidVersion::EmptyField);
+        }
+        // Hyphens are allowed after the first one
+        let (release, pre) = match string.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (string, None),
+        };
+        if !release.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(InvalidVersion::InvalidCharacter);
+        }
+        let fields = release.split('.').chain(pre.into_iter().flat_map(|p| p.split('.')));
+        for field in fields {
+            if field.is_empty() {
+                return Err(InvalidVersion::EmptyField);
+            }
+            if !field.chars().all(|c| c.is_ascii_alphanumeric() || (pre.is_some() && c == '-')) {
+                return Err(InvalidVersion::InvalidCharacter);
+            }
+        }
+        Ok(GemVersion {
+            string: string.to_owned(),
+            segments: split_segments(&string.replace('-', ".pre.")),
+        })
+    }
+
+    /// Whether the version contains a letter, e.g. `1.0.a`.
+    pub fn is_prerelease(&self) -> bool {
+        self.segments.iter().any(|s| matches!(*s, Segment::String(_)))
+    }
+
+    /// The version without its pre-release segments, e.g. `1.0` for
+    /// `1.0.rc1`.
+    pub fn release(&self) -> GemVersion {
+        let numbers = self.segments.iter().take_while(|s| matches!(**s, Segment::Number(_)));
+        from_segments(numbers.cloned().collect())
+    }
+
+    /// The upper bound of `~>` requirements: drop the pre-release segments
+    /// and the last number, then increment the new last number, e.g. `1.3`
+    /// for `1.2.5` and `2` for `1.2`.
+    pub fn bump(&self) -> GemVersion {
+        let mut numbers: Vec<Segment> = self.segments.iter()
+            .take_while(|s| matches!(**s, Segment::Number(_)))
+            .cloned()
+            .collect();
+        if numbers.len() > 1 {
+            numbers.pop();
+        }
+        let last = numbers.pop().map_or(String::new(), |n| n.to_string());
+        numbers.push(Segment::Number(increment(&last)));
+        from_segments(numbers)
+    }
+
+    /// The segments used for comparisons, without the trailing zeros of the
+    /// numbers before the first string and of the whole version.
+    fn canonical_segments(&self) -> Vec<&Segment> {
+        let first_string = self.segments.iter()
+            .position(|s| matches!(*s, Segment::String(_)))
+            .unwrap_or(self.segments.len());
+        let (numbers, rest) = self.segments.split_at(first_string);
+        let strip = |segments: &'_ [Segment]| {
+            let end = segments.iter().rposition(|s| !s.is_zero()).map_or(0, |i| i + 1);
+            segments.len() - end
+        };
+        let numbers = &numbers[.. numbers.len() - strip(numbers)];
+        let rest = &rest[.. rest.len() - strip(rest)];
+        numbers.iter().chain(rest).collect()
+    }
+}
+
+/// Increment a decimal number.
+fn increment(digits: &str) -> String {
+    let mut digits: Vec<u8> = digits.bytes().collect();
+    for digit in digits.iter_mut().rev() {
+        if *digit == b'9' {
+            *digit = b'0';
+        } else {
+            *digit += 1;
+            return String::from_utf8(digits).unwrap();
+        }
+    }
+    digits.insert(0, b'1');
+    String::from_utf8(digits).unwrap()
+}
+
+fn from_segments(segments: Vec<Segment>) -> GemVersion {
+    let segments = if segments.is_empty() { vec![Segment::Number(String::new())] } else { segments };
+    let strings: Vec<String> = segments.iter().map(|s| s.to_string()).collect();
+    GemVersion { string: strings.join("."), segments }
+}
+
+impl<'a> TryFrom<&'a str> for GemVersion {
+    type Error = InvalidVersion;
+
+    fn try_from(string: &'a str) -> Result<GemVersion, InvalidVersion> {
+        GemVersion::parse(string)
+    }
+}
+
+impl fmt::Display for GemVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.string)
+    }
+}
+
+impl PartialEq<GemVersion> for GemVersion {
+    fn eq(&self, other: &GemVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for GemVersion {}
+
+impl PartialOrd<GemVersion> for GemVersion {
+    fn partial_cmp(&self, other: &GemVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GemVersion {
+    fn cmp(&self, other: &GemVersion) -> Ordering {
+        let zero = Segment::Number(String::new());
+        let a = self.canonical_segments();
+        let b = other.canonical_segments();
+        for i in 0 .. a.len().max(b.len()) {
+            let x = a.get(i).cloned().unwrap_or(&zero);
+            let y = b.get(i).cloned().unwrap_or(&zero);
+            match x.cmp(y) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl<'a> TryFrom<&'a GemVersion> for Version {
+    type Error = InvalidVersion;
+
+    /// Encode the version so that `Version`'s order matches RubyGems'.
+    ///
+    /// Each segment is written as a non-digit part and a number: `.N` for
+    /// numbers, and the string followed by `0` for strings. As strings are
+    /// less than missing segments, each segment is prefixed with `~` when
+    /// the first string or non-zero number from there is a string, e.g.
+    /// `1.0.a` becomes `0.1~a0`.
+    ///
+    /// Strings must only contain lowercase ASCII letters.
+    fn try_from(gem: &'a GemVersion) -> Result<Version, InvalidVersion> {
+        let segments = gem.canonical_segments();
+        let mut encoded = Vec::with_capacity(segments.len() + 1);
+        let mut below_missing = false;
+        for segment in segments.iter().rev() {
+            let part = match **segment {
+                Segment::Number(ref n) => {
+                    if !n.is_empty() {
+                        below_missing = false;
+                    }
+                    format!(".{}", segment)
+                }
+                Segment::String(ref s) => {
+                    if !s.bytes().all(|c| c.is_ascii_lowercase()) {
+                        return Err(InvalidVersion::InvalidCharacter);
+                    }
+                    below_missing = true;
+                    format!("{}0", s)
+                }
+            };
+            let marker = if below_missing { "~" } else { "" };
+            encoded.push(format!("{}{}", marker, part));
+        }
+        encoded.push("0".to_owned());
+        encoded.reverse();
+        Ok(Version(encoded.concat()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{InvalidVersion, Version};
+    use super::GemVersion;
+
+    fn parse(s: &str) -> GemVersion {
+        GemVersion::parse(s).unwrap()
+    }
+
+    /// Versions in increasing order, with equal ones grouped.
+    const VERSIONS: &[&[&str]] = &[
+        &["0.a"],
+        &["0", "0.0", "0.0.0"],
+        &["0.9"],
+        &["1.0.a", "1.0.a.0", "1.a"],
+        &["1.0.a.1"],
+        &["1.0.a.2"],
+        &["1.0.a10"],
+        &["1.0.b1"],
+        &["1.0-a"],
+        &["1.0.pre.rc1", "1.0-rc1"],
+        &["1.0.rc1"],
+        &["1", "1.0", "1.0.0"],
+        &["1.0.0.1"],
+        &["1.0.1"],
+        &["1.2"],
+        &["1.10"],
+        &["1.010.1"],
+        &["2.0.0.pre"],
+        &["2"],
+    ];
+
+    #[test]
+    fn test_parse() {
+        for &(version, ref error) in &[
+            ("", InvalidVersion::EmptyField),
+            ("1.", InvalidVersion::EmptyField),
+            ("1..2", InvalidVersion::EmptyField),
+            ("1.0-", InvalidVersion::EmptyField),
+            ("a.1", InvalidVersion::InvalidCharacter),
+            ("1.0 ", InvalidVersion::InvalidCharacter),
+            ("1_0", InvalidVersion::InvalidCharacter),
+        ] {
+            assert_eq!(&GemVersion::parse(version).unwrap_err(), error, "{}", version);
+        }
+        assert_eq!(parse("1.0-rc-1").to_string(), "1.0-rc-1");
+        assert!(parse("1.0.a").is_prerelease());
+        assert!(parse("1.0-1").is_prerelease());
+        assert!(!parse("1.0.0").is_prerelease());
+    }
+
+    #[test]
+    fn test_ordering() {
+        for (i, equal) in VERSIONS.iter().enumerate() {
+            for &a in *equal {
+                for &b in *equal {
+                    assert_eq!(parse(a), parse(b), "{} == {}", a, b);
+                }
+                for &b in VERSIONS[i + 1 ..].iter().flat_map(|v| v.iter()) {
+                    assert!(parse(a) < parse(b), "{} < {}", a, b);
+                }
+            }
+        }
+        // Byte order, so uppercase letters come first
+        assert!(parse("1.0.RC1") < parse("1.0.beta"));
+    }
+
+    #[test]
+    fn test_release_bump() {
+        for &(version, release, bump) in &[
+            ("1.2.3", "1.2.3", "1.3"),
+            ("1.2", "1.2", "2"),
+            ("1", "1", "2"),
+            ("1.9.a", "1.9", "2"),
+            ("1.2.3.rc1", "1.2.3", "1.3"),
+            ("1.09.1", "1.9.1", "1.10"),
+            ("1.0-rc1", "1.0", "2"),
+        ] {
+            assert_eq!(parse(version).release().to_string(), release, "{}", version);
+            assert_eq!(parse(version).bump().to_string(), bump, "{}", version);
+        }
+    }
+
+    #[test]
+    fn test_to_version() {
+        for &(gem, expected) in &[
+            ("1.2.3", "0.1.2.3"),
+            ("1.0.0", "0.1"),
+            ("1.0.a", "0.1~a0"),
+            ("1.a.0.b", "0.1~a0~.0~b0"),
+            ("1.a.0.1", "0.1~a0.0.1"),
+        ] {
+            assert_eq!(&*Version::try_from(&parse(gem)).unwrap(), expected, "{}", gem);
+        }
+        assert_eq!(
+            Version::try_from(&parse("1.0.RC1")).unwrap_err(),
+            InvalidVersion::InvalidCharacter,
+        );
+
+        let mut versions: Vec<&str> = VERSIONS.iter().flat_map(|v| v.iter()).cloned().collect();
+        versions.extend(&["1.a.0.b", "1.a.0.1", "1.a.b", "1.ab", "1.a.1.b", "1.0.0.a.0.0"]);
+        for &a in &versions {
+            for &b in &versions {
+                let (a, b) = (parse(a), parse(b));
+                let (x, y) = (Version::try_from(&a).unwrap(), Version::try_from(&b).unwrap());
+                assert_eq!(x.cmp(&y), a.cmp(&b), "{} ({}) {} ({})", a, x, b, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Read the gems locked by a `Gemfile.lock`.
+//!
+//! The `specs:` of the `GEM`, `GIT`, `PATH` and `PLUGIN SOURCE` sections are
+//! read, other sections such as `PLATFORMS` or `DEPENDENCIES` are skipped:
+//!
+//! ```text
+//! GEM
+//!   remote: https://rubygems.org/
+//!   specs:
+//!     nokogiri (1.15.4-x86_64-linux)
+//!       racc (~> 1.4)
+//! ```
+
+use std::convert::TryFrom;
+
+use crate::InvalidVersion;
+use super::{GemRequirement, GemVersion, InvalidRequirement};
+
+/// Sections that list gems.
+const SOURCES: &[&str] = &["GEM", "GIT", "PATH", "PLUGIN SOURCE"];
+
+/// Error for a line of a `Gemfile.lock`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidSpec {
+    /// The line is not a gem or a dependency, or is not indented as one.
+    InvalidSyntax,
+    /// The version of a gem is invalid.
+    InvalidVersion(InvalidVersion),
+    /// The requirement of a dependency is invalid.
+    InvalidRequirement(InvalidRequirement),
+}
+
+impl From<InvalidVersion> for InvalidSpec {
+    fn from(error: InvalidVersion) -> InvalidSpec {
+        InvalidSpec::InvalidVersion(error)
+    }
+}
+
+impl From<InvalidRequirement> for InvalidSpec {
+    fn from(error: InvalidRequirement) -> InvalidSpec {
+        InvalidSpec::InvalidRequirement(error)
+    }
+}
+
+/// Error for the `Gemfile.lock` parser.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidLockfile {
+    line: usize,
+    error: InvalidSpec,
+}
+
+impl InvalidLockfile {
+    /// The line number of the error, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The error on that line.
+    pub fn error(&self) -> &InvalidSpec {
+        &self.error
+    }
+}
+
+/// A dependency of a locked gem, e.g. `racc (~> 1.4)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockedDependency {
+    name: String,
+    requirement: GemRequirement,
+}
+
+impl LockedDependency {
+    /// The name of the required gem.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The requirement, `>= 0` if none is given.
+    pub fn requirement(&self) -> &GemRequirement {
+        &self.requirement
+    }
+}
+
+/// A gem locked at a version, e.g. `nokogiri (1.15.4-x86_64-linux)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockedGem {
+    name: String,
+    version: GemVersion,
+    platform: Option<String>,
+    source: String,
+    remote: Option<String>,
+    dependencies: Vec<LockedDependency>,
+}
+
+impl LockedGem {
+    /// The name of the gem, e.g. `rails`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The locked version, e.g. `7.1.2`.
+    pub fn version(&self) -> &GemVersion {
+        &self.version
+    }
+
+    /// The platform of a binary gem, e.g. `x86_64-linux`.
+    pub fn platform(&self) -> Option<&str> {
+        self.platform.as_deref()
+    }
+
+    /// The section the gem is listed in, e.g. `GEM` or `GIT`.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The `remote:` of the section, e.g. `https://rubygems.org/`.
+    pub fn remote(&self) -> Option<&str> {
+        self.remote.as_deref()
+    }
+
+    /// The runtime dependencies of the gem, in order.
+    pub fn dependencies(&self) -> &[LockedDependency] {
+        &self.dependencies
+    }
+}
+
+/// The gems locked by a `Gemfile.lock`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GemfileLock {
+    gems: Vec<LockedGem>,
+}
+
+/// Split `name (parenthesized)` into its parts.
+fn split_entry(entry: &str) -> Result<(&str, Option<&str>), InvalidSpec> {
+    let (name, rest) = match entry.split_once(' ') {
+        Some((name, rest)) => (name, Some(rest)),
+        None => (entry, None),
+    };
+    if name.is_empty() || name.contains(['(', ')']) {
+        return Err(InvalidSpec::InvalidSyntax);
+    }
+    match rest {
+        None => Ok((name, None)),
+        Some(rest) => match rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+            Some(inner) => Ok((name, Some(inner))),
+            None => Err(InvalidSpec::InvalidSyntax),
+        },
+    }
+}
+
+impl GemfileLock {
+    /// Parse the contents of a `Gemfile.lock`.
+    pub fn parse(contents: &str) -> Result<GemfileLock, InvalidLockfile> {
+        let mut gems: Vec<LockedGem> = Vec::new();
+        let mut section = "";
+        let mut remote = None;
+        let mut in_specs = false;
+        // Whether a gem of the current specs was read
+        let mut has_gem = false;
+        for (index, line) in contents.lines().enumerate() {
+            let error = |error| InvalidLockfile { line: index + 1, error };
+            let trimmed = line.trim_start_matches(' ');
+            if trimmed.is_empty() {
+                continue;
+            }
+            let indent = line.len() - trimmed.len();
+            if indent == 0 {
+                section = trimmed.trim_end();
+                remote = None;
+                in_specs = false;
+                has_gem = false;
+                continue;
+            }
+            if !SOURCES.contains(&section) {
+                continue;
+            }
+            match indent {
+                2 => {
+                    in_specs = trimmed == "specs:";
+                    has_gem = false;
+                    if let Some(value) = trimmed.strip_prefix("remote: ") {
+                        remote = Some(value.to_owned());
+                    }
+                }
+                4 if in_specs => {
+                    let (name, version) = split_entry(trimmed).map_err(error)?;
+                    let version = version.ok_or(InvalidSpec::InvalidSyntax).map_err(error)?;
+                    // Bundler splits the platform at the first hyphen
+                    let (version, platform) = match version.split_once('-') {
+                        Some((version, platform)) => (version, Some(platform.to_owned())),
+                        None => (version, None),
+                    };
+                    gems.push(LockedGem {
+                        name: name.to_owned(),
+                        version: GemVersion::parse(version).map_err(|e| error(e.into()))?,
+                        platform,
+                        source: section.to_owned(),
+                        remote: remote.clone(),
+                        dependencies: Vec::new(),
+                    });
+                    has_gem = true;
+                }
+                6 if in_specs && has_gem => {
+                    let (name, requirement) = split_entry(trimmed).map_err(error)?;
+                    let requirement = match requirement {
+                        Some(r) => GemRequirement::parse(r).map_err(|e| error(e.into()))?,
+                        None => GemRequirement::default(),
+                    };
+                    gems.last_mut().unwrap().dependencies.push(LockedDependency {
+                        name: name.to_owned(),
+                        requirement,
+                    });
+                }
+                _ if in_specs => return Err(error(InvalidSpec::InvalidSyntax)),
+                _ => {}
+            }
+        }
+        Ok(GemfileLock { gems })
+    }
+
+    /// The locked gems, in order.
+    pub fn gems(&self) -> &[LockedGem] {
+        &self.gems
+    }
+
+    /// Find the locked versions of a gem, one per platform.
+    pub fn find<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a LockedGem> + 'a {
+        self.gems.iter().filter(move |g| g.name == name)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for GemfileLock {
+    type Error = InvalidLockfile;
+
+    fn try_from(contents: &'a str) -> Result<GemfileLock, InvalidLockfile> {
+        GemfileLock::parse(contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::{GemfileLock, InvalidSpec};
+
+    const LOCKFILE: &str = "\
+GIT
+  remote: https://github.com/rails/rails.git
+  revision: 0123456789abcdef0123456789abcdef01234567
+  branch: main
+  specs:
+    rails (7.2.0.alpha)
+      actionpack (= 7.2.0.alpha)
+
+GEM
+  remote: https://rubygems.org/
+  specs:
+    actionpack (7.2.0.alpha)
+    nokogiri (1.15.4-x86_64-linux)
+      racc (~> 1.4)
+    nokogiri (1.15.4-arm64-darwin)
+      racc (~> 1.4)
+    racc (1.7.1)
+    rake (13.0.6)
+      json
+
+PLATFORMS
+  arm64-darwin
+  x86_64-linux
+
+DEPENDENCIES
+  nokogiri (>= 1.15, < 2)
+  rails!
+
+BUNDLED WITH
+   2.4.19
+";
+
+    #[test]
+    fn test_parse() {
+        let lockfile = GemfileLock::parse(LOCKFILE).unwrap();
+        let gems: Vec<String> = lockfile.gems().iter()
+            .map(|g| format!("{} {} {:?}", g.name(), g.version(), g.platform()))
+            .collect();
+        assert_eq!(
+            gems,
+            vec![
+                "rails 7.2.0.alpha None",
+                "actionpack 7.2.0.alpha None",
+                "nokogiri 1.15.4 Some(\"x86_64-linux\")",
+                "nokogiri 1.15.4 Some(\"arm64-darwin\")",
+                "racc 1.7.1 None",
+                "rake 13.0.6 None",
+            ],
+        );
+
+        let rails = &lockfile.gems()[0];
+        assert_eq!(rails.source(), "GIT");
+        assert_eq!(rails.remote(), Some("https://github.com/rails/rails.git"));
+        assert!(rails.version().is_prerelease());
+        assert_eq!(rails.dependencies()[0].name(), "actionpack");
+        assert_eq!(rails.dependencies()[0].requirement().to_string(), "= 7.2.0.alpha");
+
+        let nokogiri: Vec<_> = lockfile.find("nokogiri").collect();
+        assert_eq!(nokogiri.len(), 2);
+        assert_eq!(nokogiri[0].source(), "GEM");
+        assert_eq!(nokogiri[0].remote(), Some("https://rubygems.org/"));
+        assert_eq!(nokogiri[0].dependencies()[0].requirement().to_string(), "~> 1.4");
+
+        let rake = lockfile.find("rake").next().unwrap();
+        assert_eq!(rake.dependencies()[0].requirement().to_string(), ">= 0");
+        assert_eq!(lockfile.find("rails!").count(), 0);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for &(contents, line, ref error) in &[
+            ("GEM\n  specs:\n    rake\n", 3, InvalidSpec::InvalidSyntax),
+            ("GEM\n  specs:\n    rake 13.0\n", 3, InvalidSpec::InvalidSyntax),
+            ("GEM\n  specs:\n      json\n", 3, InvalidSpec::InvalidSyntax),
+            ("GEM\n  specs:\n     rake (13.0)\n", 3, InvalidSpec::InvalidSyntax),
+            ("GEM\n  specs:\n    rake (13..0)\n", 3, InvalidSpec::InvalidVersion(InvalidVersion::EmptyField)),
+        ] {
+            let invalid = GemfileLock::parse(contents).unwrap_err();
+            assert_eq!(invalid.line(), line, "{}", contents);
+            assert_eq!(invalid.error(), error, "{}", contents);
+        }
+        // Other sections are not read
+        assert!(GemfileLock::parse("DEPENDENCIES\n  rake (>= 1, !\n").is_ok());
+    }
+}
@@ -0,0 +1,211 @@
+//! Requirements, e.g. `~> 1.2, != 1.2.5`, as implemented by
+//! `Gem::Requirement`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::InvalidVersion;
+use super::GemVersion;
+
+/// Error for the requirement parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidRequirement {
+    /// The operator is not known, e.g. `=>`.
+    InvalidOperator,
+    /// A version is invalid or missing.
+    InvalidVersion(InvalidVersion),
+}
+
+impl From<InvalidVersion> for InvalidRequirement {
+    fn from(error: InvalidVersion) -> InvalidRequirement {
+        InvalidRequirement::InvalidVersion(error)
+    }
+}
+
+/// A version operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `=`
+    Equal,
+    /// `!=`
+    NotEqual,
+    /// `>`
+    Greater,
+    /// `<`
+    Less,
+    /// `>=`
+    GreaterEqual,
+    /// `<=`
+    LessEqual,
+    /// `~>`: greater or equal, and less than the `bump` of the operand, so
+    /// `~> 1.2` means `>= 1.2, < 2` and `~> 1.2.0` means `>= 1.2.0, < 1.3`.
+    Pessimistic,
+}
+
+const OPERATORS: &[(&str, Operator)] = &[
+    ("=", Operator::Equal),
+    ("!=", Operator::NotEqual),
+    (">", Operator::Greater),
+    ("<", Operator::Less),
+    (">=", Operator::GreaterEqual),
+    ("<=", Operator::LessEqual),
+    ("~>", Operator::Pessimistic),
+];
+
+impl Operator {
+    /// Whether `version` satisfies the operator with the given operand.
+    pub fn matches(self, version: &GemVersion, operand: &GemVersion) -> bool {
+        let ordering = version.cmp(operand);
+        match self {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::NotEqual => ordering != Ordering::Equal,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::Less => ordering == Ordering::Less,
+            Operator::GreaterEqual => ordering != Ordering::Less,
+            Operator::LessEqual => ordering != Ordering::Greater,
+            Operator::Pessimistic => {
+                ordering != Ordering::Less && version.release() < operand.bump()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(OPERATORS.iter().find(|&&(_, op)| op == *self).unwrap().0)
+    }
+}
+
+/// A list of constraints that a version must all satisfy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GemRequirement {
+    constraints: Vec<(Operator, GemVersion)>,
+}
+
+impl GemRequirement {
+    /// Parse comma-separated constraints, e.g. `>= 1.0, < 2`.
+    ///
+    /// The operator defaults to `=`.
+    pub fn parse(string: &str) -> Result<GemRequirement, InvalidRequirement> {
+        let mut constraints = Vec::new();
+        for constraint in string.split(',') {
+            let constraint = constraint.trim();
+            let end = constraint
+                .find(|c: char| !"=!<>~".contains(c))
+                .unwrap_or(constraint.len());
+            let operator = match &constraint[.. end] {
+                "" => Operator::Equal,
+                op => match OPERATORS.iter().find(|&&(o, _)| o == op) {
+                    Some(&(_, operator)) => operator,
+                    None => return Err(InvalidRequirement::InvalidOperator),
+                },
+            };
+            let version = GemVersion::parse(constraint[end ..].trim_start())?;
+            constraints.push((operator, version));
+        }
+        Ok(GemRequirement { constraints })
+    }
+
+    /// The constraints, in order.
+    pub fn constraints(&self) -> &[(Operator, GemVersion)] {
+        &self.constraints
+    }
+
+    /// Whether a version satisfies all the constraints.
+    pub fn contains(&self, version: &GemVersion) -> bool {
+        self.constraints.iter().all(|&(op, ref operand)| op.matches(version, operand))
+    }
+
+    /// Whether one of the operands is a pre-release.
+    pub fn is_prerelease(&self) -> bool {
+        self.constraints.iter().any(|(_, version)| version.is_prerelease())
+    }
+}
+
+impl Default for GemRequirement {
+    /// The requirement used when none is given, `>= 0`.
+    fn default() -> GemRequirement {
+        GemRequirement {
+            constraints: vec![(Operator::GreaterEqual, GemVersion::parse("0").unwrap())],
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for GemRequirement {
+    type Error = InvalidRequirement;
+
+    fn try_from(string: &'a str) -> Result<GemRequirement, InvalidRequirement> {
+        GemRequirement::parse(string)
+    }
+}
+
+impl fmt::Display for GemRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &(op, ref version)) in self.constraints.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{} {}", op, version)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvalidVersion;
+    use super::super::GemVersion;
+    use super::{GemRequirement, InvalidRequirement};
+
+    fn check(requirement: &str, matching: &[&str], not_matching: &[&str]) {
+        let parsed = GemRequirement::parse(requirement).unwrap();
+        for &version in matching {
+            assert!(parsed.contains(&GemVersion::parse(version).unwrap()), "{} {}", requirement, version);
+        }
+        for &version in not_matching {
+            assert!(!parsed.contains(&GemVersion::parse(version).unwrap()), "{} {}", requirement, version);
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        check("1.0", &["1", "1.0.0"], &["1.0.1", "1.0.a"]);
+        check("= 1.0", &["1"], &["1.1"]);
+        check("!= 1.0", &["1.1", "1.0.a"], &["1.0.0"]);
+        check(">1.0", &["1.0.1"], &["1.0", "1.0.a"]);
+        check("< 1.0", &["0.9", "1.0.a"], &["1.0"]);
+        check(">= 1.0", &["1.0", "2"], &["0.9", "1.0.a"]);
+        check("<= 1.0", &["1.0", "0.1"], &["1.0.1"]);
+        check("~> 1.2", &["1.2", "1.9.9", "1.3.a"], &["1.1", "2", "2.0.a", "1.2.a"]);
+        check("~> 1.2.0", &["1.2.0", "1.2.9"], &["1.3", "1.3.a", "1.1.9"]);
+        check("~> 1", &["1", "1.9"], &["2", "0.9"]);
+        check("~> 1.0.a", &["1.0.a", "1.0.b", "1.0", "1.9"], &["2", "0.9"]);
+        check(">= 1.0, < 2, != 1.5", &["1.0", "1.9"], &["2", "1.5", "0.1"]);
+        check("~> 1.2, >= 1.2.5", &["1.2.5", "1.9"], &["1.2.4", "2"]);
+        assert!(GemRequirement::default().contains(&GemVersion::parse("0.0.1").unwrap()));
+        assert!(!GemRequirement::default().contains(&GemVersion::parse("0.a").unwrap()));
+    }
+
+    #[test]
+    fn test_parse() {
+        let requirement = GemRequirement::parse("~>1.2,  != 1.2.5").unwrap();
+        assert_eq!(requirement.to_string(), "~> 1.2, != 1.2.5");
+        assert_eq!(requirement.constraints().len(), 2);
+        assert!(!requirement.is_prerelease());
+        assert!(GemRequirement::parse("< 2.0.rc1").unwrap().is_prerelease());
+        assert_eq!(GemRequirement::parse("1").unwrap().to_string(), "= 1");
+        assert_eq!(GemRequirement::default().to_string(), ">= 0");
+
+        for &(requirement, ref error) in &[
+            ("", InvalidRequirement::InvalidVersion(InvalidVersion::EmptyField)),
+            (">= 1,", InvalidRequirement::InvalidVersion(InvalidVersion::EmptyField)),
+            ("=> 1", InvalidRequirement::InvalidOperator),
+            ("~ 1", InvalidRequirement::InvalidOperator),
+            ("== 1", InvalidRequirement::InvalidOperator),
+            (">= a", InvalidRequirement::InvalidVersion(InvalidVersion::InvalidCharacter)),
+        ] {
+            assert_eq!(&GemRequirement::parse(requirement).unwrap_err(), error, "{}", requirement);
+        }
+    }
+}
@@ -23,19 +23,424 @@
 //! indicates a pre-release always (there is no way to specify a post-release
 //! or build number).
 
+mod compat;
+pub mod npm;
+mod req;
+
+use std::cmp::Ordering;
 use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use crate::Version;
 use crate::utils::NumChecker;
 
+pub use self::compat::{Change, classify, is_compatible};
+pub use self::req::{Comparator, InvalidVersionReq, Op, VersionReq};
+
+/// A pre-release identifier.
+///
+/// Numeric identifiers sort before alphanumeric ones.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Identifier {
+    Numeric(u64),
+    AlphaNumeric(String),
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Identifier::Numeric(n) => write!(f, "{}", n),
+            Identifier::AlphaNumeric(ref s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Which part of a version to increment, see
+/// [`SemverVersion::bump()`](struct.SemverVersion.html#method.bump).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Level {
+    /// `1.2.3` to `2.0.0`, or `2.0.0-rc.1` to `2.0.0`.
+    Major,
+    /// `1.2.3` to `1.3.0`, or `1.3.0-rc.1` to `1.3.0`.
+    Minor,
+    /// `1.2.3` to `1.2.4`, or `1.2.4-rc.1` to `1.2.4`.
+    Patch,
+    /// `1.2.3` to `2.0.0-0`.
+    PreMajor,
+    /// `1.2.3` to `1.3.0-0`.
+    PreMinor,
+    /// `1.2.3` to `1.2.4-0`.
+    PrePatch,
+    /// `1.2.3` to `1.2.4-0`, or `1.2.4-0` to `1.2.4-1`.
+    PreRelease,
+}
+
 /// "Semantic version" as per semver.org.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct SemverVersion(String);
+///
+/// Comparison follows semver's precedence rules, which ignore the build
+/// metadata: `1.0.0+a` and `1.0.0+b` are equal. Use
+/// [`cmp_build()`](#method.cmp_build) for a total order.
+#[derive(Clone, Debug)]
+pub struct SemverVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: Vec<Identifier>,
+    build: Vec<String>,
+}
+
+/// Error for the semver parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidSemver {
+    /// The version contains invalid characters.
+    InvalidCharacter,
+    /// A numeric field or identifier has a leading zero.
+    LeadingZero,
+    /// Empty field or identifier (for example, two consecutive dots).
+    EmptyField,
+    /// The version doesn't have exactly three numeric fields.
+    WrongFieldCount,
+    /// A number doesn't fit in 64 bits.
+    NumberTooLarge,
+}
+
+/// Parse a number without leading zeros.
+fn parse_numeric(field: &str) -> Result<u64, InvalidSemver> {
+    if field.is_empty() {
+        return Err(InvalidSemver::EmptyField);
+    }
+    if !field.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(InvalidSemver::InvalidCharacter);
+    }
+    if field.len() > 1 && field.starts_with('0') {
+        return Err(InvalidSemver::LeadingZero);
+    }
+    field.parse().map_err(|_| InvalidSemver::NumberTooLarge)
+}
+
+/// Check the characters of a pre-release or build identifier.
+fn check_identifier(identifier: &str) -> Result<(), InvalidSemver> {
+    if identifier.is_empty() {
+        Err(InvalidSemver::EmptyField)
+    } else if
+        !identifier.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-')
+    {
+        Err(InvalidSemver::InvalidCharacter)
+    } else {
+        Ok(())
+    }
+}
+
+/// Parse a single pre-release identifier.
+fn parse_identifier(identifier: &str) -> Result<Identifier, InvalidSemver> {
+    check_identifier(identifier)?;
+    if identifier.bytes().all(|c| c.is_ascii_digit()) {
+        Ok(Identifier::Numeric(parse_numeric(identifier)?))
+    } else {
+        Ok(Identifier::AlphaNumeric(identifier.to_owned()))
+    }
+}
+
+/// Parse the pre-release part of a version, after the hyphen.
+fn parse_pre(pre: &str) -> Result<Vec<Identifier>, InvalidSemver> {
+    pre.split('.').map(parse_identifier).collect()
+}
+
+fn increment(number: u64) -> Result<u64, InvalidSemver> {
+    number.checked_add(1).ok_or(InvalidSemver::NumberTooLarge)
+}
+
+/// Compare pre-release identifiers, a release (no identifiers) sorting last.
+fn compare_pre(a: &[Identifier], b: &[Identifier]) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.cmp(b),
+    }
+}
+
+impl SemverVersion {
+    /// Parse a version following the semver 2.0.0 grammar.
+    pub fn parse(version: &str) -> Result<SemverVersion, InvalidSemver> {
+        let (version, build) = match version.find('+') {
+            Some(plus) => (&version[.. plus], Some(&version[plus + 1 ..])),
+            None => (version, None),
+        };
+        let (core, pre) = match version.find('-') {
+            Some(hyphen) => (&version[.. hyphen], Some(&version[hyphen + 1 ..])),
+            None => (version, None),
+        };
+
+        let fields = core.split('.')
+            .map(parse_numeric)
+            .collect::<Result<Vec<u64>, InvalidSemver>>()?;
+        if fields.len() != 3 {
+            return Err(InvalidSemver::WrongFieldCount);
+        }
+
+        let pre_identifiers = match pre {
+            Some(pre) => parse_pre(pre)?,
+            None => Vec::new(),
+        };
+
+        let mut build_identifiers = Vec::new();
+        if let Some(build) = build {
+            for identifier in build.split('.') {
+                check_identifier(identifier)?;
+                build_identifiers.push(identifier.to_owned());
+            }
+        }
+
+        Ok(SemverVersion {
+            major: fields[0],
+            minor: fields[1],
+            patch: fields[2],
+            pre: pre_identifiers,
+            build: build_identifiers,
+        })
+    }
+
+    pub fn major(&self) -> u64 {
+        self.major
+    }
+
+    pub fn minor(&self) -> u64 {
+        self.minor
+    }
+
+    pub fn patch(&self) -> u64 {
+        self.patch
+    }
+
+    /// The pre-release identifiers, e.g. `[rc, 1]` for `1.0.0-rc.1`.
+    pub fn pre(&self) -> &[Identifier] {
+        &self.pre
+    }
+
+    /// The build metadata identifiers, e.g. `[build, 5]` for `1.0.0+build.5`.
+    pub fn build(&self) -> &[String] {
+        &self.build
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Increment the version, like `npm version`.
+    ///
+    /// The optional pre-release identifier is used by the `Pre*` levels: for
+    /// example, `PreRelease` with `beta` turns `1.2.3` into `1.2.4-beta.0`,
+    /// and `1.2.4-beta.1` into `1.2.4-beta.2`. The build metadata is dropped.
+    pub fn bump(
+        &self, level: Level, preid: Option<&str>,
+    ) -> Result<SemverVersion, InvalidSemver> {
+        let preid = match preid {
+            Some(preid) => Some(parse_identifier(preid)?),
+            None => None,
+        };
+        let mut version = self.clone();
+        version.build.clear();
+        match level {
+            Level::Major => {
+                // The release of a major pre-release is just the release
+                if version.minor != 0 || version.patch != 0 || version.pre.is_empty() {
+                    version.major = increment(version.major)?;
+                }
+                version.minor = 0;
+                version.patch = 0;
+                version.pre.clear();
+            }
+            Level::Minor => {
+                if version.patch != 0 || version.pre.is_empty() {
+                    version.minor = increment(version.minor)?;
+                }
+                version.patch = 0;
+                version.pre.clear();
+            }
+            Level::Patch => version.bump_patch()?,
+            Level::PreMajor => {
+                version.major = increment(version.major)?;
+                version.minor = 0;
+                version.patch = 0;
+                version.pre.clear();
+                version.bump_pre(preid)?;
+            }
+            Level::PreMinor => {
+                version.minor = increment(version.minor)?;
+                version.patch = 0;
+                version.pre.clear();
+                version.bump_pre(preid)?;
+            }
+            Level::PrePatch => {
+                version.pre.clear();
+                version.bump_patch()?;
+                version.bump_pre(preid)?;
+            }
+            Level::PreRelease => {
+                if version.pre.is_empty() {
+                    version.bump_patch()?;
+                }
+                version.bump_pre(preid)?;
+            }
+        }
+        Ok(version)
+    }
+
+    fn bump_patch(&mut self) -> Result<(), InvalidSemver> {
+        if self.pre.is_empty() {
+            self.patch = increment(self.patch)?;
+        }
+        self.pre.clear();
+        Ok(())
+    }
+
+    /// Increment the last numeric pre-release identifier, or add one.
+    fn bump_pre(&mut self, preid: Option<Identifier>) -> Result<(), InvalidSemver> {
+        let last_numeric = self.pre.iter_mut().rev().find_map(|identifier| {
+            match *identifier {
+                Identifier::Numeric(ref mut n) => Some(n),
+                Identifier::AlphaNumeric(_) => None,
+            }
+        });
+        match last_numeric {
+            Some(n) => *n = increment(*n)?,
+            None => self.pre.push(Identifier::Numeric(0)),
+        }
+
+        // 1.2.0-beta.1 bumps to 1.2.0-beta.2, but 1.2.0-beta.foo or
+        // 1.2.0-alpha.1 bump to 1.2.0-beta.0
+        if let Some(preid) = preid {
+            let same = self.pre[0] == preid
+                && matches!(self.pre.get(1), Some(&Identifier::Numeric(_)));
+            if !same {
+                self.pre = vec![preid, Identifier::Numeric(0)];
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode a `Version` created from a `SemverVersion`.
+    ///
+    /// This is the exact inverse of the `From<SemverVersion>` conversion. To
+    /// convert a version from another source, such as a Debian version, use
+    /// [`ToSemver`](trait.ToSemver.html) instead.
+    pub fn from_version(version: &Version) -> Result<SemverVersion, InvalidSemver> {
+        let core_end = version.0.find(['~', '+'])
+            .unwrap_or(version.0.len());
+        let mut semver = version.0[.. core_end].to_owned();
+        if !semver.bytes().all(|c| c.is_ascii_digit() || c == b'.') {
+            return Err(InvalidSemver::InvalidCharacter);
+        }
+        let mut rest = &version.0[core_end ..];
+        if let Some(pre) = rest.strip_prefix('~') {
+            let (identifiers, after) = decode_identifiers(pre)?;
+            if identifiers.is_empty() {
+                return Err(InvalidSemver::EmptyField);
+            }
+            semver.push('-');
+            semver.push_str(&identifiers.join("."));
+            rest = after;
+        }
+        if let Some(build) = rest.strip_prefix('+') {
+            let (identifiers, after) = decode_identifiers(build)?;
+            if identifiers.is_empty() {
+                return Err(InvalidSemver::EmptyField);
+            }
+            semver.push('+');
+            semver.push_str(&identifiers.join("."));
+            rest = after;
+        }
+        if !rest.is_empty() {
+            return Err(InvalidSemver::InvalidCharacter);
+        }
+        SemverVersion::parse(&semver)
+    }
+
+    /// Compare versions, using the build metadata to break ties.
+    ///
+    /// Build identifiers are compared one by one: numeric ones numerically,
+    /// and before alphanumeric ones, which are compared in ASCII order. If
+    /// all are equal, fewer identifiers sort first.
+    pub fn cmp_build(&self, other: &SemverVersion) -> Ordering {
+        fn numeric(identifier: &str) -> bool {
+            identifier.bytes().all(|c| c.is_ascii_digit())
+        }
+
+        self.cmp(other).then_with(|| {
+            for (a, b) in self.build.iter().zip(other.build.iter()) {
+                let o = match (numeric(a), numeric(b)) {
+                    (true, true) => {
+                        let a_digits = a.trim_start_matches('0');
+                        let b_digits = b.trim_start_matches('0');
+                        a_digits.len().cmp(&b_digits.len())
+                            .then_with(|| a_digits.cmp(b_digits))
+                            .then_with(|| a.len().cmp(&b.len()))
+                    }
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    (false, false) => a.cmp(b),
+                };
+                if o != Ordering::Equal {
+                    return o;
+                }
+            }
+            self.build.len().cmp(&other.build.len())
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SemverVersion {
+    type Error = InvalidSemver;
+
+    fn try_from(version: &'a str) -> Result<SemverVersion, InvalidSemver> {
+        SemverVersion::parse(version)
+    }
+}
 
 impl fmt::Display for SemverVersion {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        for (i, identifier) in self.pre.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { '-' } else { '.' }, identifier)?;
+        }
+        for (i, identifier) in self.build.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { '+' } else { '.' }, identifier)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<SemverVersion> for SemverVersion {
+    fn eq(&self, other: &SemverVersion) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SemverVersion {}
+
+impl Hash for SemverVersion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Build metadata is ignored, like for equality
+        self.major.hash(state);
+        self.minor.hash(state);
+        self.patch.hash(state);
+        self.pre.hash(state);
+    }
+}
+
+impl PartialOrd<SemverVersion> for SemverVersion {
+    fn partial_cmp(&self, other: &SemverVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemverVersion {
+    fn cmp(&self, other: &SemverVersion) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| compare_pre(&self.pre, &other.pre))
     }
 }
 
@@ -47,33 +452,134 @@ pub enum ToSemverError {
     TooManyFields,
     LeadingZero,
     InvalidCharacter,
+    NumberTooLarge,
+}
+
+/// Information that could not be kept exactly by a lossy conversion.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SemverLoss {
+    /// The epoch was removed.
+    Epoch(String),
+    /// Leading zeros were removed from numeric fields.
+    LeadingZero,
+    /// Separators were replaced by dots, or removed.
+    Separator,
+    /// Fields after the third, and what follows them, were moved to the
+    /// build metadata, which is ignored for ordering.
+    ExtraFields,
+    /// A post-release was encoded as a pre-release, of the next patch
+    /// version unless it follows a pre-release.
+    PostRelease,
+    /// A number didn't fit in 64 bits and was replaced by the largest one.
+    NumberTooLarge,
 }
 
 pub trait ToSemver {
     /// Convert if the version is a valid semver.
     fn to_semver(&self) -> Result<SemverVersion, ToSemverError>;
 
-    /// Convert as best as we can.
-    fn to_semver_lossy(&self) -> SemverVersion;
+    /// Convert as best as we can, also returning what was lost.
+    fn to_semver_lossy(&self) -> (SemverVersion, Vec<SemverLoss>);
+}
+
+/// Split into semver identifiers: on separators, and between letters and
+/// digits.
+fn identifiers(string: &str, lost: &mut Vec<SemverLoss>) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut separator = false;
+    for c in string.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if c != '.' {
+                separator = true;
+            }
+            if !current.is_empty() {
+                result.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if let Some(last) = current.chars().last() {
+            if last.is_ascii_digit() != c.is_ascii_digit() {
+                result.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        result.push(current);
+    }
+    if separator {
+        lost.push(SemverLoss::Separator);
+    }
+    for id in &mut result {
+        if id.starts_with('0') && id.len() > 1 && id.bytes().all(|c| c.is_ascii_digit()) {
+            *id = strip_zeros(id, lost);
+        }
+    }
+    result
+}
+
+/// Remove leading zeros from a number.
+fn strip_zeros(number: &str, lost: &mut Vec<SemverLoss>) -> String {
+    let stripped = number.trim_start_matches('0');
+    if stripped.len() == number.len() || number == "0" {
+        number.to_owned()
+    } else {
+        if !lost.contains(&SemverLoss::LeadingZero) {
+            lost.push(SemverLoss::LeadingZero);
+        }
+        if stripped.is_empty() { "0".to_owned() } else { stripped.to_owned() }
+    }
+}
+
+/// Parse a numeric field, saturating if it doesn't fit.
+fn number(digits: &str, lost: &mut Vec<SemverLoss>) -> u64 {
+    match strip_zeros(digits, lost).parse() {
+        Ok(n) => n,
+        Err(_) => {
+            if !lost.contains(&SemverLoss::NumberTooLarge) {
+                lost.push(SemverLoss::NumberTooLarge);
+            }
+            u64::MAX
+        }
+    }
+}
+
+/// Turn the output of `identifiers()` into pre-release identifiers.
+fn pre_identifiers(ids: Vec<String>, lost: &mut Vec<SemverLoss>) -> Vec<Identifier> {
+    ids.into_iter()
+        .map(|id| if id.bytes().all(|c| c.is_ascii_digit()) {
+            Identifier::Numeric(number(&id, lost))
+        } else {
+            Identifier::AlphaNumeric(id)
+        })
+        .collect()
 }
 
 impl ToSemver for Version {
     /// Convert to a semantic version.
     ///
     /// This will work if the version has maximum two fields, and no
-    /// post-release or epoch segment.
+    /// post-release or epoch segment, or if it was encoded from a semantic
+    /// version by `From<SemverVersion>`.
     ///
     /// Examples:
     ///
     /// * `1.2` -> `1.2.0`
     /// * `1.2.4~rc1` -> `1.2.4-rc.1`
+    /// * `1.0.0~.rc~~-1+.build~~-5` -> `1.0.0-rc.1+build.5`
     fn to_semver(&self) -> Result<SemverVersion, ToSemverError> {
+        // The encoding always has a `-` or `.` after the tilde, or a `+`,
+        // which are rejected below
+        if let Ok(semver) = SemverVersion::from_version(self) {
+            return Ok(semver);
+        }
         let mut field = 0;
         let mut num_check = NumChecker::new();
         let mut version = Vec::new();
         let mut read_epoch = false;
         for c in self.0.bytes() {
-            if b'0' <= c && c <= b'9' {
+            if c.is_ascii_digit() {
                 if num_check == NumChecker::NotNum {
                     version.push(b'.');
                     num_check.reset();
@@ -117,7 +623,7 @@ impl ToSemver for Version {
                 }
             } else if c == b'-' && num_check != NumChecker::Start {
                 return Err(ToSemverError::HasPost);
-            } else if b'a' <= c && c <= b'z' {
+            } else if c.is_ascii_lowercase() {
                 if field < 3 {
                     // Alphabetical characters only allowed in pre-release part
                     return Err(ToSemverError::InvalidCharacter);
@@ -140,7 +646,11 @@ impl ToSemver for Version {
         for _ in field .. 2 {
             version.extend(b".0");
         }
-        Ok(SemverVersion(String::from_utf8(version).unwrap()))
+        SemverVersion::parse(std::str::from_utf8(&version).unwrap())
+            .map_err(|e| match e {
+                InvalidSemver::NumberTooLarge => ToSemverError::NumberTooLarge,
+                _ => ToSemverError::InvalidCharacter,
+            })
     }
 
     /// Convert to a semantic version, removing incompatible information
@@ -153,11 +663,128 @@ impl ToSemver for Version {
     ///
     /// * `1.2` -> `1.2.0`
     /// * `1.2.3.1` -> `1.2.3+patch.1`
+    /// * `1.2.3.1~rc1` -> `1.2.3+patch.1.rc.1`
     /// * `1.2.4~rc1` -> `1.2.4-rc.1`
     /// * `2:1.2.2` -> `1.2.2`
-    fn to_semver_lossy(&self) -> SemverVersion {
-        unimplemented!() // TODO: Version to SemverVersion lossy
-            // Put post-release info in local version, etc
+    /// * `1.2.3-2` -> `1.2.4-0.post.2`
+    /// * `1.2.4~rc1-2` -> `1.2.4-rc.1.post.2`
+    ///
+    /// Post-releases (Debian revisions, or anything following the version
+    /// that is not a tilde) become pre-releases of the next patch version, so
+    /// that they still sort after the version they are based on. Additional
+    /// fields, and what follows them, are stored in build metadata, so they
+    /// are not taken into account for ordering: `1.2.3.1` compares equal to
+    /// `1.2.3`, and below `1.2.3-1`. Otherwise, order is kept, except with
+    /// pre-releases of the next patch version starting with `0`, and between
+    /// post-releases whose separators or letters differ, such as `1.0a` and
+    /// `1.0-1`.
+    fn to_semver_lossy(&self) -> (SemverVersion, Vec<SemverLoss>) {
+        if let Ok(semver) = SemverVersion::from_version(self) {
+            return (semver, Vec::new());
+        }
+        let mut lost = Vec::new();
+        let mut version = &self.0[..];
+
+        // Drop the epoch
+        if let Some(colon) = version.find(':') {
+            let epoch = &version[.. colon];
+            if !epoch.is_empty() && epoch.bytes().all(|c| c.is_ascii_digit()) {
+                if epoch.bytes().any(|c| c != b'0') {
+                    lost.push(SemverLoss::Epoch(epoch.to_owned()));
+                }
+                version = &version[colon + 1 ..];
+            }
+        }
+
+        // Split the Debian revision
+        let (upstream, revision) = match version.rfind('-') {
+            Some(hyphen) => (&version[.. hyphen], Some(&version[hyphen + 1 ..])),
+            None => (version, None),
+        };
+
+        // Read the numeric fields
+        let mut fields = Vec::new();
+        let mut rest = upstream;
+        loop {
+            let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                break;
+            }
+            fields.push(number(&rest[.. digits], &mut lost));
+            rest = &rest[digits ..];
+            if rest.starts_with('.') && rest[1 ..].starts_with(|c: char| c.is_ascii_digit()) {
+                rest = &rest[1 ..];
+            } else {
+                break;
+            }
+        }
+        let mut extra = Vec::new();
+        if fields.len() > 3 {
+            lost.push(SemverLoss::ExtraFields);
+            extra.extend(fields.drain(3 ..).map(|n| n.to_string()));
+        }
+        while fields.len() < 3 {
+            fields.push(0);
+        }
+
+        // What follows is either a pre-release (after a tilde) or a
+        // post-release
+        let (pre, mut post) = match rest.strip_prefix('~') {
+            Some(pre) => (identifiers(pre, &mut lost), Vec::new()),
+            None => (Vec::new(), identifiers(rest, &mut lost)),
+        };
+        if let Some(revision) = revision {
+            post.extend(identifiers(revision, &mut lost));
+        }
+
+        let mut next_patch = |lost: &mut Vec<SemverLoss>| {
+            fields[2] = match fields[2].checked_add(1) {
+                Some(patch) => patch,
+                None => {
+                    if !lost.contains(&SemverLoss::NumberTooLarge) {
+                        lost.push(SemverLoss::NumberTooLarge);
+                    }
+                    u64::MAX
+                }
+            };
+        };
+        let mut build = Vec::new();
+        let pre = if !extra.is_empty() {
+            // Keep the order of the three first fields only, rather than
+            // sorting a pre-release of `1.2.3.1` before `1.2.3`
+            build.push("patch".to_owned());
+            build.extend(extra);
+            build.extend(pre);
+            if !post.is_empty() {
+                build.push("post".to_owned());
+                build.extend(post);
+            }
+            Vec::new()
+        } else if post.is_empty() {
+            pre
+        } else if !pre.is_empty() {
+            lost.push(SemverLoss::PostRelease);
+            let mut pre = pre;
+            pre.push("post".to_owned());
+            pre.extend(post);
+            pre
+        } else {
+            // Use the lowest pre-release of the next version
+            lost.push(SemverLoss::PostRelease);
+            next_patch(&mut lost);
+            let mut pre = vec!["0".to_owned(), "post".to_owned()];
+            pre.extend(post);
+            pre
+        };
+
+        let semver = SemverVersion {
+            major: fields[0],
+            minor: fields[1],
+            patch: fields[2],
+            pre: pre_identifiers(pre, &mut lost),
+            build,
+        };
+        (semver, lost)
     }
 }
 
@@ -169,22 +796,370 @@ impl<'a> TryFrom<&'a Version> for SemverVersion {
     }
 }
 
+/// Append the encoding of identifiers to a `Version` string.
+///
+/// Numeric identifiers are written as `-` followed by the number, and
+/// alphanumeric identifiers as `.` followed by the identifier's characters and
+/// terminated by `~~`. Lowercase letters are kept, other characters are escaped
+/// with a tilde: `-` as `~a`, digits as `~b` to `~k`, and uppercase letters as
+/// `~l` followed by the lowercase letter.
+fn encode_identifiers<'a, I>(version: &mut String, identifiers: I)
+where I: Iterator<Item = &'a str> {
+    for identifier in identifiers {
+        if identifier.bytes().all(|c| c.is_ascii_digit()) {
+            version.push('-');
+            version.push_str(identifier);
+            continue;
+        }
+        version.push('.');
+        for c in identifier.chars() {
+            match c {
+                'a' ..= 'z' => version.push(c),
+                '-' => version.push_str("~a"),
+                '0' ..= '9' => {
+                    version.push('~');
+                    version.push((c as u8 - b'0' + b'b') as char);
+                }
+                _ => {
+                    version.push_str("~l");
+                    version.push(c.to_ascii_lowercase());
+                }
+            }
+        }
+        version.push_str("~~");
+    }
+}
+
+/// Read identifiers written by `encode_identifiers()`, returning them and the
+/// rest of the string.
+fn decode_identifiers(mut rest: &str) -> Result<(Vec<String>, &str), InvalidSemver> {
+    let mut identifiers = Vec::new();
+    loop {
+        if let Some(number) = rest.strip_prefix('-') {
+            let digits = number.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                return Err(InvalidSemver::EmptyField);
+            }
+            identifiers.push(number[.. digits].to_owned());
+            rest = &number[digits ..];
+        } else if let Some(encoded) = rest.strip_prefix('.') {
+            let mut identifier = String::new();
+            let mut chars = encoded.char_indices();
+            loop {
+                match chars.next() {
+                    Some((_, c @ 'a' ..= 'z')) => identifier.push(c),
+                    Some((_, '~')) => match chars.next() {
+                        Some((i, '~')) => {
+                            rest = &encoded[i + 1 ..];
+                            break;
+                        }
+                        Some((_, 'a')) => identifier.push('-'),
+                        Some((_, c @ 'b' ..= 'k')) => {
+                            identifier.push((c as u8 - b'b' + b'0') as char);
+                        }
+                        Some((_, 'l')) => match chars.next() {
+                            Some((_, c @ 'a' ..= 'z')) => {
+                                identifier.push(c.to_ascii_uppercase());
+                            }
+                            _ => return Err(InvalidSemver::InvalidCharacter),
+                        },
+                        _ => return Err(InvalidSemver::InvalidCharacter),
+                    },
+                    _ => return Err(InvalidSemver::InvalidCharacter),
+                }
+            }
+            if identifier.is_empty() {
+                return Err(InvalidSemver::EmptyField);
+            }
+            identifiers.push(identifier);
+        } else {
+            return Ok((identifiers, rest));
+        }
+    }
+}
+
+/// Encode a semantic version as a `Version`, keeping its precedence.
+///
+/// The result is the core version, followed by `~` and the pre-release
+/// identifiers, followed by `+` and the build metadata identifiers. For
+/// example `1.0.0-rc.1+build.5` becomes `1.0.0~.rc~~-1+.build~~-5`.
+///
+/// Comparing the results with the Debian algorithm gives the same order as
+/// semver's precedence:
+///
+/// * The tilde puts pre-releases before the release.
+/// * Identifier encodings start with `-` (numeric) or `.` (alphanumeric), and
+///   these sort in that order, after the end of the string. So numeric
+///   identifiers sort before alphanumeric ones, and a longer list of
+///   identifiers sorts after its prefix.
+/// * Numeric identifiers are compared as numbers.
+/// * The characters of alphanumeric identifiers map to codes that sort in
+///   ASCII order, and none of them is a prefix of another. The `~~`
+///   terminator sorts before all of them, so a shorter identifier sorts
+///   before a longer one it is the prefix of.
+///
+/// The build metadata is introduced by `+`, which sorts after the end of the
+/// string but before `-` and `.`. So a version with build metadata sorts after
+/// the same version without it, but before any version that has more
+/// pre-release identifiers, or that is otherwise greater. Build metadata
+/// identifiers are encoded like pre-release ones, so the versions are sorted
+/// like [`SemverVersion::cmp_build()`], except that numeric identifiers
+/// differing only by leading zeros are equal.
+///
+/// Use [`SemverVersion::from_version()`] to get the original version back.
 impl From<SemverVersion> for Version {
     fn from(semver: SemverVersion) -> Version {
-        Version(semver.0.replace("-", "~"))
+        let mut version = format!(
+            "{}.{}.{}",
+            semver.major, semver.minor, semver.patch,
+        );
+        if !semver.pre.is_empty() {
+            version.push('~');
+            let pre = semver.pre.iter()
+                .map(|identifier| identifier.to_string())
+                .collect::<Vec<_>>();
+            encode_identifiers(&mut version, pre.iter().map(|s| &s[..]));
+        }
+        if !semver.build.is_empty() {
+            version.push('+');
+            encode_identifiers(&mut version, semver.build.iter().map(|s| &s[..]));
+        }
+        Version(version)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::Version;
-    use super::{SemverVersion, ToSemver, ToSemverError};
+    use std::cmp::Ordering;
+    use std::convert::TryFrom;
+
+    use super::{
+        Identifier, InvalidSemver, SemverLoss, SemverVersion, ToSemver,
+        ToSemverError,
+    };
+
+    #[test]
+    fn test_parse() {
+        let v = SemverVersion::parse("1.2.3-rc.1.x-y+build.007").unwrap();
+        assert_eq!((v.major(), v.minor(), v.patch()), (1, 2, 3));
+        assert_eq!(
+            v.pre(),
+            &[
+                Identifier::AlphaNumeric("rc".into()),
+                Identifier::Numeric(1),
+                Identifier::AlphaNumeric("x-y".into()),
+            ],
+        );
+        assert_eq!(v.build(), &["build".to_owned(), "007".to_owned()]);
+        assert!(v.is_prerelease());
+        assert_eq!(v.to_string(), "1.2.3-rc.1.x-y+build.007");
+
+        assert_eq!(SemverVersion::parse("1.2"), Err(InvalidSemver::WrongFieldCount));
+        assert_eq!(SemverVersion::parse("1.2.3.4"), Err(InvalidSemver::WrongFieldCount));
+        assert_eq!(SemverVersion::parse("1.02.3"), Err(InvalidSemver::LeadingZero));
+        assert_eq!(SemverVersion::parse("1.2.3-01"), Err(InvalidSemver::LeadingZero));
+        assert!(SemverVersion::parse("1.2.3-0a").is_ok());
+        assert!(SemverVersion::parse("1.2.3+01").is_ok());
+        assert_eq!(SemverVersion::parse("1.2.3-"), Err(InvalidSemver::EmptyField));
+        assert_eq!(SemverVersion::parse("1.2.3-a..b"), Err(InvalidSemver::EmptyField));
+        assert_eq!(SemverVersion::parse("1.2.3+"), Err(InvalidSemver::EmptyField));
+        assert_eq!(SemverVersion::parse("1..3"), Err(InvalidSemver::EmptyField));
+        assert_eq!(SemverVersion::parse("1.2.3-a_b"), Err(InvalidSemver::InvalidCharacter));
+        assert_eq!(SemverVersion::parse("v1.2.3"), Err(InvalidSemver::InvalidCharacter));
+        assert_eq!(
+            SemverVersion::parse("1.2.18446744073709551616"),
+            Err(InvalidSemver::NumberTooLarge),
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let versions = [
+            "1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta",
+            "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0", "1.0.1",
+            "1.1.0", "2.0.0",
+        ];
+        let versions = versions.iter()
+            .map(|v| SemverVersion::parse(v).unwrap())
+            .collect::<Vec<_>>();
+        for (i, a) in versions.iter().enumerate() {
+            for (j, b) in versions.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{} {}", a, b);
+            }
+        }
+
+        let a = SemverVersion::parse("1.0.0+a").unwrap();
+        let b = SemverVersion::parse("1.0.0+b").unwrap();
+        let nine = SemverVersion::parse("1.0.0+9").unwrap();
+        let ten = SemverVersion::parse("1.0.0+10").unwrap();
+        let none = SemverVersion::parse("1.0.0").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.cmp_build(&b), Ordering::Less);
+        assert_eq!(nine.cmp_build(&ten), Ordering::Less);
+        assert_eq!(ten.cmp_build(&a), Ordering::Less);
+        assert_eq!(none.cmp_build(&nine), Ordering::Less);
+    }
+
+    #[test]
+    fn test_bump() {
+        use super::Level::*;
+
+        // From node-semver's test/fixtures/increments.js
+        let fixtures = [
+            ("1.2.3", Major, None, "2.0.0"),
+            ("1.2.3", Minor, None, "1.3.0"),
+            ("1.2.3", Patch, None, "1.2.4"),
+            ("1.2.3-tag", Major, None, "2.0.0"),
+            ("1.2.0-0", Patch, None, "1.2.0"),
+            ("1.2.3-4", Major, None, "2.0.0"),
+            ("1.2.3-4", Minor, None, "1.3.0"),
+            ("1.2.3-4", Patch, None, "1.2.3"),
+            ("1.2.3-alpha.0.beta", Major, None, "2.0.0"),
+            ("1.2.3-alpha.0.beta", Patch, None, "1.2.3"),
+            ("1.2.4", PreRelease, None, "1.2.5-0"),
+            ("1.2.3-0", PreRelease, None, "1.2.3-1"),
+            ("1.2.3-alpha.0", PreRelease, None, "1.2.3-alpha.1"),
+            ("1.2.3-alpha.0.beta", PreRelease, None, "1.2.3-alpha.1.beta"),
+            ("1.2.3-alpha.10.0.beta", PreRelease, None, "1.2.3-alpha.10.1.beta"),
+            ("1.2.3-alpha.10.beta.0", PreRelease, None, "1.2.3-alpha.10.beta.1"),
+            ("1.2.3-alpha.9.beta", PreRelease, None, "1.2.3-alpha.10.beta"),
+            ("1.2.3-alpha.beta", PreRelease, None, "1.2.3-alpha.beta.0"),
+            ("1.2.0", PrePatch, None, "1.2.1-0"),
+            ("1.2.0-1", PrePatch, None, "1.2.1-0"),
+            ("1.2.0", PreMinor, None, "1.3.0-0"),
+            ("1.2.3-1", PreMinor, None, "1.3.0-0"),
+            ("1.2.0", PreMajor, None, "2.0.0-0"),
+            ("1.2.3-1", PreMajor, None, "2.0.0-0"),
+            ("1.2.0-1", Minor, None, "1.2.0"),
+            ("1.0.0-1", Major, None, "1.0.0"),
+            ("1.2.3", Major, Some("dev"), "2.0.0"),
+            ("1.2.3-4", Patch, Some("dev"), "1.2.3"),
+            ("1.2.4", PreRelease, Some("dev"), "1.2.5-dev.0"),
+            ("1.2.3-0", PreRelease, Some("dev"), "1.2.3-dev.0"),
+            ("1.2.3-alpha.0", PreRelease, Some("dev"), "1.2.3-dev.0"),
+            ("1.2.3-alpha.0", PreRelease, Some("alpha"), "1.2.3-alpha.1"),
+            ("1.2.3-alpha.0.beta", PreRelease, Some("alpha"), "1.2.3-alpha.1.beta"),
+            ("1.2.3-alpha.10.beta.2", PreRelease, Some("alpha"), "1.2.3-alpha.10.beta.3"),
+            ("1.2.3-alpha.9.beta", PreRelease, Some("dev"), "1.2.3-dev.0"),
+            ("1.2.0", PrePatch, Some("dev"), "1.2.1-dev.0"),
+            ("1.2.0-1", PrePatch, Some("dev"), "1.2.1-dev.0"),
+            ("1.2.3-1", PreMinor, Some("dev"), "1.3.0-dev.0"),
+            ("1.2.3-1", PreMajor, Some("dev"), "2.0.0-dev.0"),
+            ("1.2.3-dev.bar", PreRelease, Some("dev"), "1.2.3-dev.0"),
+            ("1.2.4-beta.1", PreRelease, Some("beta"), "1.2.4-beta.2"),
+            ("1.2.3+build.5", Patch, None, "1.2.4"),
+        ];
+        for &(version, level, preid, expected) in &fixtures {
+            let bumped = SemverVersion::parse(version).unwrap().bump(level, preid);
+            assert_eq!(
+                bumped.map(|v| v.to_string()), Ok(expected.into()),
+                "{} {:?} {:?}", version, level, preid,
+            );
+        }
+
+        let version = SemverVersion::parse("1.2.3").unwrap();
+        assert_eq!(version.bump(PreRelease, Some("")), Err(InvalidSemver::EmptyField));
+        assert_eq!(version.bump(PreRelease, Some("a.b")), Err(InvalidSemver::InvalidCharacter));
+        let version = SemverVersion::parse("18446744073709551615.0.0").unwrap();
+        assert_eq!(version.bump(Major, None), Err(InvalidSemver::NumberTooLarge));
+    }
+
+    #[test]
+    fn test_version_round_trip() {
+        // Valid versions from semver.org
+        let versions = [
+            "0.0.4", "1.2.3", "10.20.30", "1.1.2-prerelease+meta", "1.1.2+meta",
+            "1.1.2+meta-valid", "1.0.0-alpha", "1.0.0-beta", "1.0.0-alpha.beta",
+            "1.0.0-alpha.beta.1", "1.0.0-alpha.1", "1.0.0-alpha0.valid",
+            "1.0.0-alpha.0valid",
+            "1.0.0-alpha-a.b-c-somethinglong+build.1-aef.1-its-okay",
+            "1.0.0-rc.1+build.1", "2.0.0-rc.1+build.123", "1.2.3-beta",
+            "10.2.3-DEV-SNAPSHOT", "1.2.3-SNAPSHOT-123", "1.0.0",
+            "2.0.0", "1.1.7", "2.0.0+build.1848", "2.0.1-alpha.1227",
+            "1.0.0-alpha+beta", "1.2.3----RC-SNAPSHOT.12.9.1--.12+788",
+            "1.2.3----R-S.12.9.1--.12+meta", "1.2.3----RC-SNAPSHOT.12.9.1--.12",
+            "1.0.0+0.build.1-rc.10000aaa-kk-0.1",
+            "99999999999999999.999999999999999999.99999999999999999",
+            "1.0.0-0A.is.legal",
+        ];
+        for version in &versions {
+            let semver = SemverVersion::parse(version).unwrap();
+            let encoded = Version::from(semver.clone());
+            let decoded = SemverVersion::from_version(&encoded).unwrap();
+            assert_eq!(decoded.to_string(), *version);
+            assert_eq!(encoded.to_semver().map(|v| v.to_string()), Ok(version.to_string()));
+            assert_eq!(encoded.to_semver_lossy(), (semver.clone(), vec![]));
+            assert_eq!(SemverVersion::try_from(&encoded), Ok(semver));
+        }
+
+        assert_eq!(
+            &*Version::from(SemverVersion::parse("1.0.0-alpha-1+build.5").unwrap()),
+            "1.0.0~.alpha~a~c~~+.build~~-5",
+        );
+        assert_eq!(
+            &*Version::from(SemverVersion::parse("1.0.0-rc.1").unwrap()),
+            "1.0.0~.rc~~-1",
+        );
+
+        // Build metadata sorts right after the version it is attached to
+        let encoded = |v: &str| Version::from(SemverVersion::parse(v).unwrap());
+        assert!(encoded("1.0.0") < encoded("1.0.0+build.5"));
+        assert!(encoded("1.0.0+build.5") < encoded("1.0.1"));
+        assert!(encoded("1.0.0-rc.1") < encoded("1.0.0-rc.1+build"));
+        assert!(encoded("1.0.0-rc.1+build") < encoded("1.0.0"));
+
+        for invalid in &[
+            "1.0.0~", "1.0.0~.rc", "1.0.0~.~~", "1.0.0~.rc~~x", "1.0.0+", "1.0.0~-",
+            "1.0.0-1", "1.0.0-rc.1",
+        ] {
+            assert!(SemverVersion::from_version(&Version((*invalid).into())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_version_order() {
+        let identifiers = [
+            "0", "1", "2", "10", "4294967296", "a", "b", "ab", "abc", "a-",
+            "-", "-a", "A", "Ab", "0a", "a0", "a10", "a9", "rc", "rc1", "z",
+        ];
+        let mut lists = vec![vec![]];
+        for a in &identifiers {
+            lists.push(vec![*a]);
+            for b in &identifiers {
+                lists.push(vec![*a, *b]);
+            }
+        }
+        let mut versions = Vec::new();
+        for core in &["1.0.0", "1.0.1", "1.10.0"] {
+            for pre in &lists {
+                for build in &["", "+0", "+a", "+a.0", "+b"] {
+                    let mut version = core.to_string();
+                    if !pre.is_empty() {
+                        version.push('-');
+                        version.push_str(&pre.join("."));
+                    }
+                    version.push_str(build);
+                    versions.push(SemverVersion::parse(&version).unwrap());
+                }
+            }
+        }
+        // The Debian order is total, so checking consecutive versions is
+        // enough
+        versions.sort_by(SemverVersion::cmp_build);
+        for pair in versions.windows(2) {
+            assert_eq!(pair[0].cmp_build(&pair[1]), Ordering::Less);
+            let a = Version::from(pair[0].clone());
+            let b = Version::from(pair[1].clone());
+            assert!(a < b, "{} {}", pair[0], pair[1]);
+        }
+    }
 
     #[test]
     fn test_to_semver() {
         assert_eq!(
             Version("1.2.3".into()).to_semver(),
-            Ok(SemverVersion("1.2.3".into())),
+            Ok(SemverVersion::parse("1.2.3").unwrap()),
         );
         assert_eq!(
             Version("1.2.3.4".into()).to_semver(),
@@ -192,15 +1167,15 @@ mod tests {
         );
         assert_eq!(
             Version("1.2".into()).to_semver(),
-            Ok(SemverVersion("1.2.0".into())),
+            Ok(SemverVersion::parse("1.2.0").unwrap()),
         );
         assert_eq!(
             Version("8".into()).to_semver(),
-            Ok(SemverVersion("8.0.0".into())),
+            Ok(SemverVersion::parse("8.0.0").unwrap()),
         );
         assert_eq!(
             Version("0:1.2.3".into()).to_semver(),
-            Ok(SemverVersion("1.2.3".into())),
+            Ok(SemverVersion::parse("1.2.3").unwrap()),
         );
         assert_eq!(
             Version("1:1.2.3".into()).to_semver(),
@@ -232,15 +1207,73 @@ mod tests {
         );
         assert_eq!(
             Version("1.2~rc1".into()).to_semver(),
-            Ok(SemverVersion("1.2.0-rc.1".into())),
+            Ok(SemverVersion::parse("1.2.0-rc.1").unwrap()),
         );
         assert_eq!(
             Version("1.2~0ubuntu3".into()).to_semver(),
-            Ok(SemverVersion("1.2.0-0.ubuntu.3".into())),
+            Ok(SemverVersion::parse("1.2.0-0.ubuntu.3").unwrap()),
         );
         assert_eq!(
             Version("1.-2".into()).to_semver(),
             Err(ToSemverError::InvalidCharacter),
         );
     }
+
+    fn lossy(version: &str) -> (String, Vec<SemverLoss>) {
+        let (semver, lost) = Version(version.into()).to_semver_lossy();
+        (semver.to_string(), lost)
+    }
+
+    #[test]
+    fn test_to_semver_lossy() {
+        assert_eq!(lossy("1.2"), ("1.2.0".into(), vec![]));
+        assert_eq!(lossy("1.2.3.1"), ("1.2.3+patch.1".into(), vec![SemverLoss::ExtraFields]));
+        assert_eq!(lossy("1.2.3.4~rc1"), ("1.2.3+patch.4.rc.1".into(), vec![SemverLoss::ExtraFields]));
+        assert_eq!(lossy("1.2.4~rc1"), ("1.2.4-rc.1".into(), vec![]));
+        assert_eq!(lossy("2:1.2.2"), ("1.2.2".into(), vec![SemverLoss::Epoch("2".into())]));
+        assert_eq!(lossy("0:1.2.2"), ("1.2.2".into(), vec![]));
+        assert_eq!(lossy("1.2.3-2"), ("1.2.4-0.post.2".into(), vec![SemverLoss::PostRelease]));
+        assert_eq!(
+            lossy("1.2.9+dfsg-0ubuntu3"),
+            ("1.2.10-0.post.dfsg.0.ubuntu.3".into(), vec![SemverLoss::Separator, SemverLoss::PostRelease]),
+        );
+        assert_eq!(lossy("1.2.4~rc1-2"), ("1.2.4-rc.1.post.2".into(), vec![SemverLoss::PostRelease]));
+        assert_eq!(lossy("01.2.003"), ("1.2.3".into(), vec![SemverLoss::LeadingZero]));
+        assert_eq!(lossy("1.2.3.4.5-1"), ("1.2.3+patch.4.5.post.1".into(), vec![SemverLoss::ExtraFields]));
+        assert_eq!(lossy("99999999999999999999999"), ("18446744073709551615.0.0".into(), vec![SemverLoss::NumberTooLarge]));
+        assert_eq!(
+            lossy("1.2.18446744073709551615-1"),
+            ("1.2.18446744073709551615-0.post.1".into(), vec![SemverLoss::PostRelease, SemverLoss::NumberTooLarge]),
+        );
+        assert_eq!(lossy("1.2.999"), ("1.2.999".into(), vec![]));
+        assert_eq!(lossy("1.2.999a"), ("1.2.1000-0.post.a".into(), vec![SemverLoss::PostRelease]));
+
+        // Order is kept, without extra fields or epochs
+        let versions = [
+            "0.9", "1.0~rc1", "1.0~rc1-1", "1.0~rc2", "1.0", "1.00", "1.0-1",
+            "1.0-2", "1.0.1~beta", "1.0.1~beta2", "1.0.1", "1.0.1-1",
+            "1.0.2", "1.01.2", "1.2.3~rc1-1", "1.2.4~rc1", "1.2.4", "1.10",
+        ];
+        for a in &versions {
+            for b in &versions {
+                let (semver_a, _) = Version((*a).into()).to_semver_lossy();
+                let (semver_b, _) = Version((*b).into()).to_semver_lossy();
+                assert_eq!(
+                    semver_a.cmp(&semver_b),
+                    Version((*a).into()).cmp(&Version((*b).into())),
+                    "{} {}", a, b,
+                );
+            }
+        }
+        // Extra fields, and what follows them, are ignored
+        for version in &["1.2.3.1", "1.2.3.1~rc1", "1.2.3.1-1", "1.2.3.1.5"] {
+            let (semver, _) = Version((*version).into()).to_semver_lossy();
+            assert_eq!(semver, SemverVersion::parse("1.2.3").unwrap(), "{}", version);
+        }
+
+        // Never fails
+        for version in &["", ":", "-", "~", "1:", "a:b", "1.2.3-", "..", "~~a", "+", "1..2", "abc"] {
+            lossy(version);
+        }
+    }
 }
@@ -0,0 +1,116 @@
+//! Compatibility between versions, following Cargo's rules.
+//!
+//! Semver says that anything may change before 1.0.0. Cargo refines this: for
+//! `0.y.z` versions, `y` is treated as the major version and `z` as the minor
+//! version, and for `0.0.z` versions, every change is breaking.
+
+use super::{SemverVersion, VersionReq};
+
+/// The kind of change between two versions, from the least to the most
+/// disruptive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Change {
+    /// Only the pre-release (or the build metadata) changed.
+    PreRelease,
+    /// Compatible bug fixes (a patch bump).
+    Fix,
+    /// Compatible new features (a minor bump, or a patch bump for `0.y.z`).
+    Feature,
+    /// Incompatible changes, including downgrades.
+    Breaking,
+}
+
+/// Whether Cargo would accept the candidate for a dependency on a version.
+///
+/// This is the same as matching `^version`: only the left-most non-zero field
+/// has to be equal, and pre-releases are only accepted if `version` is a
+/// pre-release of the same `major.minor.patch`.
+pub fn is_compatible(version: &SemverVersion, candidate: &SemverVersion) -> bool {
+    VersionReq::caret(version).matches(candidate)
+}
+
+/// Classify the change between two versions.
+///
+/// Returns `None` if the versions have the same precedence. Pre-releases are
+/// classified like the release they lead to, e.g. going from `1.2.3` to
+/// `1.3.0-rc.1` is a feature change.
+pub fn classify(old: &SemverVersion, new: &SemverVersion) -> Option<Change> {
+    if old == new {
+        return None;
+    }
+    if new < old {
+        return Some(Change::Breaking);
+    }
+    if (old.major(), old.minor(), old.patch())
+        == (new.major(), new.minor(), new.patch())
+    {
+        return Some(Change::PreRelease);
+    }
+
+    let release = |version: &SemverVersion| {
+        let mut release = version.clone();
+        release.pre.clear();
+        release.build.clear();
+        release
+    };
+    if !is_compatible(&release(old), &release(new)) {
+        Some(Change::Breaking)
+    } else if old.minor() != new.minor() || old.major() == 0 {
+        Some(Change::Feature)
+    } else {
+        Some(Change::Fix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semver::SemverVersion;
+    use super::{Change, classify, is_compatible};
+
+    fn v(version: &str) -> SemverVersion {
+        SemverVersion::parse(version).unwrap()
+    }
+
+    #[test]
+    fn test_classify() {
+        let fixtures = [
+            ("1.2.3", "2.0.0", Some(Change::Breaking)),
+            ("1.2.3", "1.3.0", Some(Change::Feature)),
+            ("1.2.3", "1.2.4", Some(Change::Fix)),
+            ("1.2.3", "1.2.2", Some(Change::Breaking)),
+            ("1.2.3", "1.1.9", Some(Change::Breaking)),
+            ("1.2.3", "1.3.0-rc.1", Some(Change::Feature)),
+            ("1.2.3", "2.0.0-rc.1", Some(Change::Breaking)),
+            ("1.3.0-rc.1", "1.3.0-rc.2", Some(Change::PreRelease)),
+            ("1.3.0-rc.2", "1.3.0", Some(Change::PreRelease)),
+            ("1.3.0", "1.3.0-rc.1", Some(Change::Breaking)),
+            ("1.3.0-rc.2", "1.3.0-rc.1", Some(Change::Breaking)),
+            ("1.2.3", "1.2.3+build", None),
+            ("0.2.3", "0.3.0", Some(Change::Breaking)),
+            ("0.2.3", "0.2.4", Some(Change::Feature)),
+            ("0.0.3", "0.0.4", Some(Change::Breaking)),
+            ("0.9.0", "1.0.0", Some(Change::Breaking)),
+        ];
+        for &(old, new, expected) in &fixtures {
+            assert_eq!(classify(&v(old), &v(new)), expected, "{} {}", old, new);
+        }
+        assert!(Change::Fix < Change::Breaking);
+    }
+
+    #[test]
+    fn test_is_compatible() {
+        assert!(is_compatible(&v("1.2.3"), &v("1.2.3")));
+        assert!(is_compatible(&v("1.2.3"), &v("1.9.0")));
+        assert!(!is_compatible(&v("1.2.3"), &v("2.0.0")));
+        assert!(!is_compatible(&v("1.2.3"), &v("1.2.2")));
+        assert!(!is_compatible(&v("1.2.3"), &v("1.3.0-rc.1")));
+        assert!(is_compatible(&v("0.2.3"), &v("0.2.9")));
+        assert!(!is_compatible(&v("0.2.3"), &v("0.3.0")));
+        assert!(is_compatible(&v("0.0.3"), &v("0.0.3")));
+        assert!(!is_compatible(&v("0.0.3"), &v("0.0.4")));
+        assert!(is_compatible(&v("1.2.3-rc.1"), &v("1.2.3-rc.2")));
+        assert!(is_compatible(&v("1.2.3-rc.1"), &v("1.4.0")));
+        assert!(!is_compatible(&v("1.2.3-rc.1"), &v("1.2.3-beta")));
+        assert!(!is_compatible(&v("1.2.3-rc.1"), &v("1.2.4-rc.1")));
+    }
+}
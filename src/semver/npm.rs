@@ -0,0 +1,980 @@
+//! npm's version ranges, as implemented by node-semver (`^1.2 || >=3`).
+//!
+//! See [node-semver's documentation](https://github.com/npm/node-semver#ranges)
+//! for the syntax. Like node-semver, ranges are desugared into sets of
+//! primitive comparators (`<`, `<=`, `>`, `>=`, `=`), and a version satisfies
+//! a range if it matches all the comparators of one of its sets. A version
+//! with a pre-release only satisfies a set if one of its comparators mentions
+//! a pre-release of the same `major.minor.patch`, unless the
+//! `include_prerelease` option is set.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use super::{Identifier, SemverVersion};
+
+/// Parsing options, like node-semver's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// Accept malformed versions (such as `1.2.3beta`, `01.2.3` or
+    /// `=v1.2.3`), and ignore invalid comparators.
+    pub loose: bool,
+    /// Let pre-releases satisfy any comparator, not only those mentioning a
+    /// pre-release of the same version.
+    pub include_prerelease: bool,
+}
+
+/// Error for the range parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidRange {
+    /// A comparator or a version couldn't be parsed.
+    InvalidComparator,
+    /// A number doesn't fit in 64 bits.
+    NumberTooLarge,
+    /// In loose mode, every comparator of every set was invalid.
+    Empty,
+}
+
+/// Operator of a primitive comparator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `=`, or no operator.
+    Equal,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEqual,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match *self {
+            Operator::Equal => "=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// A primitive comparator, for example `>=1.2.0`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Comparator {
+    operator: Operator,
+    version: SemverVersion,
+}
+
+impl Comparator {
+    pub fn operator(&self) -> Operator {
+        self.operator
+    }
+
+    pub fn version(&self) -> &SemverVersion {
+        &self.version
+    }
+
+    /// Whether the version matches this comparator.
+    ///
+    /// This doesn't apply the pre-release rule, see
+    /// [`Range::satisfies()`](struct.Range.html#method.satisfies).
+    pub fn matches(&self, version: &SemverVersion) -> bool {
+        match self.operator {
+            Operator::Equal => *version == self.version,
+            Operator::Less => *version < self.version,
+            Operator::LessEqual => *version <= self.version,
+            Operator::Greater => *version > self.version,
+            Operator::GreaterEqual => *version >= self.version,
+        }
+    }
+
+    /// Whether this is `<0.0.0-0`, that nothing matches.
+    fn is_null(&self) -> bool {
+        self.operator == Operator::Less
+            && self.version == version(0, 0, 0, zero())
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Like node-semver, leave out the equal sign
+        if self.operator == Operator::Equal {
+            write!(f, "{}", self.version)
+        } else {
+            write!(f, "{}{}", self.operator, self.version)
+        }
+    }
+}
+
+fn version(
+    major: u64, minor: u64, patch: u64, pre: Vec<Identifier>,
+) -> SemverVersion {
+    SemverVersion { major, minor, patch, pre, build: Vec::new() }
+}
+
+/// The pre-release `0`, the lowest there is.
+fn zero() -> Vec<Identifier> {
+    vec![Identifier::Numeric(0)]
+}
+
+fn increment(number: u64) -> Result<u64, InvalidRange> {
+    number.checked_add(1).ok_or(InvalidRange::NumberTooLarge)
+}
+
+fn comparator(operator: Operator, version: SemverVersion) -> Comparator {
+    Comparator { operator, version }
+}
+
+/// A version where any field may be missing, or a wildcard (`x`, `X`, `*`).
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<Identifier>,
+}
+
+impl Partial {
+    fn parse(string: &str, loose: bool) -> Result<Partial, InvalidRange> {
+        fn number(digits: &str, loose: bool) -> Result<u64, InvalidRange> {
+            if !loose && digits.len() > 1 && digits.starts_with('0') {
+                return Err(InvalidRange::InvalidComparator);
+            }
+            digits.parse().map_err(|_| InvalidRange::NumberTooLarge)
+        }
+
+        let mut rest = string;
+        let mut fields = Vec::new();
+        loop {
+            if let Some(r) = rest.strip_prefix(['x', 'X', '*']) {
+                fields.push(None);
+                rest = r;
+            } else {
+                let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+                if digits == 0 {
+                    return Err(InvalidRange::InvalidComparator);
+                }
+                fields.push(Some(number(&rest[.. digits], loose)?));
+                rest = &rest[digits ..];
+            }
+            match rest.strip_prefix('.') {
+                Some(r) if fields.len() < 3 => rest = r,
+                _ => break,
+            }
+        }
+
+        // Pre-release and build metadata are only allowed after three fields,
+        // and loose mode doesn't require the hyphen
+        let mut pre = Vec::new();
+        if fields.len() == 3 {
+            let pre_string = match rest.strip_prefix('-') {
+                Some(r) => Some(r),
+                None if loose && rest.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                    Some(rest)
+                }
+                None => None,
+            };
+            if let Some(pre_string) = pre_string {
+                let end = pre_string.find('+').unwrap_or(pre_string.len());
+                for identifier in pre_string[.. end].split('.') {
+                    if identifier.is_empty()
+                        || !identifier.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-')
+                    {
+                        return Err(InvalidRange::InvalidComparator);
+                    }
+                    if identifier.bytes().all(|c| c.is_ascii_digit()) {
+                        pre.push(Identifier::Numeric(number(identifier, loose)?));
+                    } else {
+                        pre.push(Identifier::AlphaNumeric(identifier.to_owned()));
+                    }
+                }
+                rest = &pre_string[end ..];
+            }
+            if let Some(build) = rest.strip_prefix('+') {
+                if build.split('.').any(|identifier| {
+                    identifier.is_empty()
+                        || !identifier.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-')
+                }) {
+                    return Err(InvalidRange::InvalidComparator);
+                }
+                rest = "";
+            }
+        }
+        if !rest.is_empty() {
+            return Err(InvalidRange::InvalidComparator);
+        }
+
+        Ok(Partial {
+            major: fields[0],
+            minor: fields.get(1).cloned().unwrap_or(None),
+            patch: fields.get(2).cloned().unwrap_or(None),
+            pre,
+        })
+    }
+
+    /// Whether any field is missing (the pre-release doesn't count).
+    fn has_wildcard(&self) -> bool {
+        self.patch.is_none() || self.minor.is_none() || self.major.is_none()
+    }
+
+    /// Turn into a version, missing fields being 0.
+    fn version(&self) -> SemverVersion {
+        version(
+            self.major.unwrap_or(0),
+            self.minor.unwrap_or(0),
+            self.patch.unwrap_or(0),
+            self.pre.clone(),
+        )
+    }
+}
+
+/// Parse a version, in strict or loose mode.
+///
+/// Strict mode allows a leading `v`, loose mode also allows leading `=`
+/// signs, leading zeros, and a pre-release without a hyphen.
+pub fn parse_version(string: &str, loose: bool) -> Result<SemverVersion, InvalidRange> {
+    let string = string.trim();
+    let rest = if loose {
+        string.trim_start_matches(|c: char| c == 'v' || c == '=' || c.is_whitespace())
+    } else {
+        string.strip_prefix('v').unwrap_or(string)
+    };
+    let partial = Partial::parse(rest, loose)?;
+    if partial.has_wildcard() {
+        return Err(InvalidRange::InvalidComparator);
+    }
+    let mut version = partial.version();
+    if let Some(plus) = rest.find('+') {
+        version.build = rest[plus + 1 ..].split('.').map(str::to_owned).collect();
+    }
+    Ok(version)
+}
+
+/// Desugar `~1.2.3`, where the tilde has been removed.
+fn tilde(partial: Partial) -> Result<Vec<Comparator>, InvalidRange> {
+    let (major, minor, patch) = match (partial.major, partial.minor, partial.patch) {
+        (None, _, _) => return Ok(Vec::new()),
+        (Some(major), None, _) => {
+            return Ok(vec![
+                comparator(Operator::GreaterEqual, version(major, 0, 0, Vec::new())),
+                comparator(Operator::Less, version(increment(major)?, 0, 0, zero())),
+            ]);
+        }
+        (Some(major), Some(minor), None) => (major, minor, 0),
+        (Some(major), Some(minor), Some(patch)) => (major, minor, patch),
+    };
+    Ok(vec![
+        comparator(Operator::GreaterEqual, version(major, minor, patch, partial.pre)),
+        comparator(Operator::Less, version(major, increment(minor)?, 0, zero())),
+    ])
+}
+
+/// Desugar `^1.2.3`, where the caret has been removed.
+fn caret(partial: Partial, options: Options) -> Result<Vec<Comparator>, InvalidRange> {
+    let z = if options.include_prerelease { zero() } else { Vec::new() };
+    let major = match partial.major {
+        Some(major) => major,
+        None => return Ok(Vec::new()),
+    };
+    let (from, to) = match (partial.minor, partial.patch) {
+        (None, _) => (
+            version(major, 0, 0, z),
+            version(increment(major)?, 0, 0, zero()),
+        ),
+        (Some(minor), None) => (
+            version(major, minor, 0, z),
+            if major == 0 {
+                version(major, increment(minor)?, 0, zero())
+            } else {
+                version(increment(major)?, 0, 0, zero())
+            },
+        ),
+        (Some(minor), Some(patch)) => (
+            version(major, minor, patch, partial.pre),
+            if major != 0 {
+                version(increment(major)?, 0, 0, zero())
+            } else if minor != 0 {
+                version(major, increment(minor)?, 0, zero())
+            } else {
+                version(major, minor, increment(patch)?, zero())
+            },
+        ),
+    };
+    Ok(vec![
+        comparator(Operator::GreaterEqual, from),
+        comparator(Operator::Less, to),
+    ])
+}
+
+/// Desugar a comparison with a partial version, such as `>=1.2` or `1.x`.
+fn x_range(
+    operator: Option<Operator>, partial: Partial, options: Options,
+) -> Result<Vec<Comparator>, InvalidRange> {
+    let z = if options.include_prerelease { zero() } else { Vec::new() };
+    let major = match partial.major {
+        Some(major) => major,
+        None => {
+            return Ok(match operator {
+                // Nothing is allowed
+                Some(Operator::Less) | Some(Operator::Greater) => {
+                    vec![comparator(Operator::Less, version(0, 0, 0, zero()))]
+                }
+                // Nothing is forbidden
+                _ => Vec::new(),
+            });
+        }
+    };
+    match (operator, partial.minor) {
+        (None, None) | (Some(Operator::Equal), None) => Ok(vec![
+            comparator(Operator::GreaterEqual, version(major, 0, 0, z)),
+            comparator(Operator::Less, version(increment(major)?, 0, 0, zero())),
+        ]),
+        (None, Some(minor)) | (Some(Operator::Equal), Some(minor)) => Ok(vec![
+            comparator(Operator::GreaterEqual, version(major, minor, 0, z)),
+            comparator(Operator::Less, version(major, increment(minor)?, 0, zero())),
+        ]),
+        // >1 is >=2.0.0, >1.2 is >=1.3.0
+        (Some(Operator::Greater), None) => Ok(vec![comparator(
+            Operator::GreaterEqual, version(increment(major)?, 0, 0, z),
+        )]),
+        (Some(Operator::Greater), Some(minor)) => Ok(vec![comparator(
+            Operator::GreaterEqual, version(major, increment(minor)?, 0, z),
+        )]),
+        // <=1 is <2.0.0-0, <=1.2 is <1.3.0-0
+        (Some(Operator::LessEqual), None) => Ok(vec![comparator(
+            Operator::Less, version(increment(major)?, 0, 0, zero()),
+        )]),
+        (Some(Operator::LessEqual), Some(minor)) => Ok(vec![comparator(
+            Operator::Less, version(major, increment(minor)?, 0, zero()),
+        )]),
+        (Some(Operator::Less), minor) => Ok(vec![comparator(
+            Operator::Less, version(major, minor.unwrap_or(0), 0, zero()),
+        )]),
+        (Some(Operator::GreaterEqual), minor) => Ok(vec![comparator(
+            Operator::GreaterEqual, version(major, minor.unwrap_or(0), 0, z),
+        )]),
+    }
+}
+
+/// Desugar a hyphen range `1.2 - 3.4`.
+fn hyphen(
+    from: Partial, to: Partial, options: Options,
+) -> Result<Vec<Comparator>, InvalidRange> {
+    let z = if options.include_prerelease { zero() } else { Vec::new() };
+    let mut comparators = Vec::new();
+    if let Some(major) = from.major {
+        let from = match (from.minor, from.patch) {
+            (Some(_), Some(_)) if !from.pre.is_empty() => from.version(),
+            (minor, patch) => {
+                version(major, minor.unwrap_or(0), patch.unwrap_or(0), z)
+            }
+        };
+        comparators.push(comparator(Operator::GreaterEqual, from));
+    }
+    if let Some(major) = to.major {
+        comparators.push(match (to.minor, to.patch) {
+            (None, _) => comparator(
+                Operator::Less, version(increment(major)?, 0, 0, zero()),
+            ),
+            (Some(minor), None) => comparator(
+                Operator::Less, version(major, increment(minor)?, 0, zero()),
+            ),
+            (Some(_), Some(_)) if !to.pre.is_empty() => {
+                comparator(Operator::LessEqual, to.version())
+            }
+            (Some(minor), Some(patch)) if options.include_prerelease => comparator(
+                Operator::Less, version(major, minor, increment(patch)?, zero()),
+            ),
+            (Some(_), Some(_)) => comparator(Operator::LessEqual, to.version()),
+        });
+    }
+    Ok(comparators)
+}
+
+/// Parse and desugar a single comparator, which may expand to zero (any
+/// version) or more primitive comparators.
+fn parse_comparator(
+    string: &str, options: Options,
+) -> Result<Vec<Comparator>, InvalidRange> {
+    let prefix_end = |s: &str| s.len() - s.trim_start_matches(['v', '=']).len();
+
+    if let Some(rest) = string.strip_prefix('~') {
+        let rest = rest.strip_prefix('>').unwrap_or(rest);
+        return tilde(Partial::parse(&rest[prefix_end(rest) ..], options.loose)?);
+    }
+    if let Some(rest) = string.strip_prefix('^') {
+        return caret(Partial::parse(&rest[prefix_end(rest) ..], options.loose)?, options);
+    }
+
+    let operators = [
+        (">=", Operator::GreaterEqual), ("<=", Operator::LessEqual),
+        (">", Operator::Greater), ("<", Operator::Less), ("=", Operator::Equal),
+    ];
+    let (operator, rest) = operators.iter()
+        .find(|&&(prefix, _)| string.starts_with(prefix))
+        .map(|&(prefix, operator)| (Some(operator), &string[prefix.len() ..]))
+        .unwrap_or((None, string));
+    let prefix = &rest[.. prefix_end(rest)];
+    let partial = Partial::parse(&rest[prefix.len() ..], options.loose)?;
+    if partial.has_wildcard() {
+        return x_range(operator, partial, options);
+    }
+    // A full version can only be prefixed by "v", unless in loose mode
+    if !options.loose && !prefix.is_empty() && prefix != "v" {
+        return Err(InvalidRange::InvalidComparator);
+    }
+    let operator = operator.unwrap_or(Operator::Equal);
+    Ok(vec![comparator(operator, partial.version())])
+}
+
+/// Parse one of the `||`-separated sets of a range.
+///
+/// Returns `None` if, in loose mode, all the comparators were invalid. An
+/// empty set matches any version.
+fn parse_set(
+    string: &str, options: Options,
+) -> Result<Option<Vec<Comparator>>, InvalidRange> {
+    let words = string.split_whitespace().collect::<Vec<_>>();
+
+    // Hyphen range
+    if words.len() == 3 && words[1] == "-" {
+        let partial = |s: &str| {
+            let s = s.trim_start_matches(['v', '=']);
+            Partial::parse(s, options.loose)
+        };
+        if let (Ok(from), Ok(to)) = (partial(words[0]), partial(words[2])) {
+            return hyphen(from, to, options).map(Some);
+        }
+    }
+
+    // Join operators with the following version, e.g. ">= 1.2"
+    let mut tokens = Vec::new();
+    let mut pending: Option<&str> = None;
+    for word in words {
+        if let Some(operator) = pending.take() {
+            tokens.push(format!("{}{}", operator, word));
+        } else if let "<" | ">" | "<=" | ">=" | "=" | "~" | "~>" | "^" = word {
+            pending = Some(word);
+        } else {
+            tokens.push(word.to_owned());
+        }
+    }
+    if let Some(operator) = pending {
+        tokens.push(operator.to_owned());
+    }
+    if tokens.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+
+    let mut any = false;
+    let mut comparators: Vec<Comparator> = Vec::new();
+    for token in tokens {
+        let expanded = match parse_comparator(&token, options) {
+            Ok(expanded) => expanded,
+            Err(_) if options.loose => continue,
+            Err(e) => return Err(e),
+        };
+        if expanded.is_empty() {
+            any = true;
+        }
+        for c in expanded {
+            // >=0.0.0 is the same as *
+            let lowest = if options.include_prerelease { zero() } else { Vec::new() };
+            if c.operator == Operator::GreaterEqual && c.version == version(0, 0, 0, lowest) {
+                any = true;
+                continue;
+            }
+            if c.is_null() {
+                return Ok(Some(vec![c]));
+            }
+            if !comparators.contains(&c) {
+                comparators.push(c);
+            }
+        }
+    }
+    if comparators.is_empty() && !any {
+        Ok(None)
+    } else {
+        Ok(Some(comparators))
+    }
+}
+
+/// Test a version against a set of comparators, applying the pre-release
+/// rule.
+fn test_set(set: &[Comparator], version: &SemverVersion, options: Options) -> bool {
+    if !set.iter().all(|c| c.matches(version)) {
+        return false;
+    }
+    if version.is_prerelease() && !options.include_prerelease {
+        return set.iter().any(|c| {
+            c.version.is_prerelease()
+                && c.version.major == version.major
+                && c.version.minor == version.minor
+                && c.version.patch == version.patch
+        });
+    }
+    true
+}
+
+/// Whether some version can match both comparators (`None` standing for the
+/// empty set, that matches any version).
+fn comparators_intersect(
+    a: Option<&Comparator>, b: Option<&Comparator>, options: Options,
+) -> bool {
+    use self::Operator::*;
+
+    let (a, b) = match (a, b) {
+        (None, _) => return true,
+        (Some(a), b) if a.operator == Equal => {
+            let set = b.into_iter().cloned().collect::<Vec<_>>();
+            return test_set(&set, &a.version, options);
+        }
+        (Some(_), None) => return true,
+        (Some(a), Some(b)) if b.operator == Equal => {
+            return test_set(std::slice::from_ref(a), &b.version, options);
+        }
+        (Some(a), Some(b)) => (a, b),
+    };
+
+    // Nothing can possibly be lower
+    let nothing_lower = |c: &Comparator| if options.include_prerelease {
+        c.is_null()
+    } else {
+        c.operator == Less
+            && c.version.major == 0 && c.version.minor == 0 && c.version.patch == 0
+    };
+    if nothing_lower(a) || nothing_lower(b) {
+        return false;
+    }
+
+    let greater = |c: &Comparator| c.operator == Greater || c.operator == GreaterEqual;
+    let less = |c: &Comparator| c.operator == Less || c.operator == LessEqual;
+    let inclusive = |c: &Comparator| c.operator == GreaterEqual || c.operator == LessEqual;
+    (greater(a) && greater(b))
+        || (less(a) && less(b))
+        || (a.version == b.version && inclusive(a) && inclusive(b))
+        || (a.version < b.version && greater(a) && less(b))
+        || (a.version > b.version && less(a) && greater(b))
+}
+
+/// The comparators of a set, with `None` for the empty set.
+fn set_comparators(set: &[Comparator]) -> Vec<Option<&Comparator>> {
+    if set.is_empty() {
+        vec![None]
+    } else {
+        set.iter().map(Some).collect()
+    }
+}
+
+/// Whether all the comparators of a set can match at the same time.
+fn is_satisfiable(set: &[Comparator], options: Options) -> bool {
+    let mut remaining = set_comparators(set);
+    while let Some(test) = remaining.pop() {
+        if !remaining.iter().all(|&other| comparators_intersect(test, other, options)) {
+            return false;
+        }
+    }
+    true
+}
+
+/// An npm version range, for example `^1.2 || >=3`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Range {
+    set: Vec<Vec<Comparator>>,
+    options: Options,
+}
+
+impl Range {
+    /// Parse a range using node-semver's grammar.
+    pub fn parse(string: &str, options: Options) -> Result<Range, InvalidRange> {
+        let mut set = Vec::new();
+        for comparators in string.split("||") {
+            if let Some(comparators) = parse_set(comparators, options)? {
+                set.push(comparators);
+            }
+        }
+        if set.is_empty() {
+            return Err(InvalidRange::Empty);
+        }
+
+        // Throw out sets that match nothing (but keep one), and if a set
+        // matches everything, the range is just that
+        if set.len() > 1 {
+            let first = set[0].clone();
+            set.retain(|comparators| !comparators.first().is_some_and(Comparator::is_null));
+            if set.is_empty() {
+                set.push(first);
+            } else if let Some(any) = set.iter().position(Vec::is_empty) {
+                set = vec![set.swap_remove(any)];
+            }
+        }
+        Ok(Range { set, options })
+    }
+
+    /// The sets of primitive comparators; an empty set matches any version.
+    pub fn set(&self) -> &[Vec<Comparator>] {
+        &self.set
+    }
+
+    pub fn options(&self) -> Options {
+        self.options
+    }
+
+    /// Whether the version satisfies this range.
+    pub fn satisfies(&self, version: &SemverVersion) -> bool {
+        self.set.iter().any(|set| test_set(set, version, self.options))
+    }
+
+    /// Find the highest version satisfying this range.
+    pub fn max_satisfying<'a, I>(&self, versions: I) -> Option<&'a SemverVersion>
+    where I: IntoIterator<Item = &'a SemverVersion> {
+        let mut max: Option<&'a SemverVersion> = None;
+        for version in versions {
+            if self.satisfies(version) && max.is_none_or(|max| max < version) {
+                max = Some(version);
+            }
+        }
+        max
+    }
+
+    /// Find the lowest version that can satisfy this range.
+    pub fn min_version(&self) -> Option<SemverVersion> {
+        for lowest in &[Vec::new(), zero()] {
+            let lowest = version(0, 0, 0, lowest.clone());
+            if self.satisfies(&lowest) {
+                return Some(lowest);
+            }
+        }
+
+        let mut min: Option<SemverVersion> = None;
+        for set in &self.set {
+            let mut set_min: Option<SemverVersion> = None;
+            for c in set {
+                let mut candidate = c.version.clone();
+                match c.operator {
+                    Operator::Greater => {
+                        if candidate.pre.is_empty() {
+                            candidate.patch = match candidate.patch.checked_add(1) {
+                                Some(patch) => patch,
+                                None => continue,
+                            };
+                        } else {
+                            candidate.pre.push(Identifier::Numeric(0));
+                        }
+                    }
+                    Operator::Equal | Operator::GreaterEqual => {}
+                    Operator::Less | Operator::LessEqual => continue,
+                }
+                if set_min.as_ref().is_none_or(|set_min| candidate > *set_min) {
+                    set_min = Some(candidate);
+                }
+            }
+            if let Some(set_min) = set_min {
+                if min.as_ref().is_none_or(|min| *min > set_min) {
+                    min = Some(set_min);
+                }
+            }
+        }
+        min.filter(|min| self.satisfies(min))
+    }
+
+    /// Whether some version can satisfy both ranges.
+    ///
+    /// This uses the options of this range.
+    pub fn intersects(&self, other: &Range) -> bool {
+        let options = self.options;
+        self.set.iter().any(|a| {
+            is_satisfiable(a, options) && other.set.iter().any(|b| {
+                is_satisfiable(b, options)
+                    && set_comparators(a).iter().all(|&ca| {
+                        set_comparators(b).iter().all(|&cb| {
+                            comparators_intersect(ca, cb, options)
+                        })
+                    })
+            })
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Range {
+    type Error = InvalidRange;
+
+    fn try_from(string: &'a str) -> Result<Range, InvalidRange> {
+        Range::parse(string, Options::default())
+    }
+}
+
+impl fmt::Display for Range {
+    /// Write the desugared range, e.g. `>=1.2.0 <2.0.0-0` for `^1.2`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, set) in self.set.iter().enumerate() {
+            if i > 0 {
+                write!(f, "||")?;
+            }
+            if set.is_empty() {
+                write!(f, "*")?;
+            }
+            for (j, comparator) in set.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", comparator)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::{InvalidRange, Options, Range, parse_version};
+
+    const LOOSE: Options = Options { loose: true, include_prerelease: false };
+    const PRE: Options = Options { loose: false, include_prerelease: true };
+
+    fn satisfies(range: &str, version: &str, options: Options) -> bool {
+        let range = Range::parse(range, options).unwrap();
+        range.satisfies(&parse_version(version, options.loose).unwrap())
+    }
+
+    #[test]
+    fn test_parse() {
+        // From node-semver's test/fixtures/range-parse.js
+        let fixtures: &[(&str, Option<&str>, Options)] = &[
+            ("1.0.0 - 2.0.0", Some(">=1.0.0 <=2.0.0"), Options::default()),
+            ("1.0.0 - 2.0.0", Some(">=1.0.0-0 <2.0.1-0"), PRE),
+            ("1 - 2", Some(">=1.0.0 <3.0.0-0"), Options::default()),
+            ("1 - 2", Some(">=1.0.0-0 <3.0.0-0"), PRE),
+            ("1.0 - 2.0", Some(">=1.0.0 <2.1.0-0"), Options::default()),
+            ("1.0.0", Some("1.0.0"), Options::default()),
+            (">=*", Some("*"), Options::default()),
+            ("", Some("*"), Options::default()),
+            ("*", Some("*"), Options::default()),
+            (">=1.0.0", Some(">=1.0.0"), Options::default()),
+            (">1.0.0", Some(">1.0.0"), Options::default()),
+            ("<=2.0.0", Some("<=2.0.0"), Options::default()),
+            ("1", Some(">=1.0.0 <2.0.0-0"), Options::default()),
+            (">= 1.0.0", Some(">=1.0.0"), Options::default()),
+            ("<\t2.0.0", Some("<2.0.0"), Options::default()),
+            ("0.1.20 || 1.2.4", Some("0.1.20||1.2.4"), Options::default()),
+            (">=0.2.3 || <0.0.1", Some(">=0.2.3||<0.0.1"), Options::default()),
+            ("||", Some("*"), Options::default()),
+            ("2.x.x", Some(">=2.0.0 <3.0.0-0"), Options::default()),
+            ("1.2.x || 2.x", Some(">=1.2.0 <1.3.0-0||>=2.0.0 <3.0.0-0"), Options::default()),
+            ("x", Some("*"), Options::default()),
+            ("2.*.*", Some(">=2.0.0 <3.0.0-0"), Options::default()),
+            ("2.3", Some(">=2.3.0 <2.4.0-0"), Options::default()),
+            ("~2.4", Some(">=2.4.0 <2.5.0-0"), Options::default()),
+            ("~>3.2.1", Some(">=3.2.1 <3.3.0-0"), Options::default()),
+            ("~> 1", Some(">=1.0.0 <2.0.0-0"), Options::default()),
+            ("~ 1.0", Some(">=1.0.0 <1.1.0-0"), Options::default()),
+            ("^0", Some("<1.0.0-0"), Options::default()),
+            ("^ 1", Some(">=1.0.0 <2.0.0-0"), Options::default()),
+            ("^0.1", Some(">=0.1.0 <0.2.0-0"), Options::default()),
+            ("^1.2", Some(">=1.2.0 <2.0.0-0"), Options::default()),
+            ("^0.0.1", Some(">=0.0.1 <0.0.2-0"), Options::default()),
+            ("^0.0.1-beta", Some(">=0.0.1-beta <0.0.2-0"), Options::default()),
+            ("^0.1.2", Some(">=0.1.2 <0.2.0-0"), Options::default()),
+            ("^1.2.3-beta.4", Some(">=1.2.3-beta.4 <2.0.0-0"), Options::default()),
+            ("<1", Some("<1.0.0-0"), Options::default()),
+            (">=1", Some(">=1.0.0"), Options::default()),
+            ("< 1.2", Some("<1.2.0-0"), Options::default()),
+            (">01.02.03", Some(">1.2.3"), LOOSE),
+            (">01.02.03", None, Options::default()),
+            ("~1.2.3beta", Some(">=1.2.3-beta <1.3.0-0"), LOOSE),
+            ("~1.2.3beta", None, Options::default()),
+            ("^ 1.2 ^ 1", Some(">=1.2.0 <2.0.0-0 >=1.0.0"), Options::default()),
+            ("1.2 - 3.4.5", Some(">=1.2.0 <=3.4.5"), Options::default()),
+            ("1.2.3 - 3.4", Some(">=1.2.3 <3.5.0-0"), Options::default()),
+            (">1", Some(">=2.0.0"), Options::default()),
+            (">1.2", Some(">=1.3.0"), Options::default()),
+            (">X", Some("<0.0.0-0"), Options::default()),
+            ("<X", Some("<0.0.0-0"), Options::default()),
+            ("<x <* || >* 2.x", Some("<0.0.0-0"), Options::default()),
+            (">x 2.x || * || <x", Some("*"), Options::default()),
+            ("<=0.7.x", Some("<0.8.0-0"), Options::default()),
+            (">=09090", None, Options::default()),
+            (">=09090", Some(">=9090.0.0"), LOOSE),
+            (">=09090-0", None, PRE),
+            ("=v1.2.3", Some("1.2.3"), Options::default()),
+            ("v=1.2.3", None, Options::default()),
+            ("v=1.2.3", Some("1.2.3"), LOOSE),
+            ("  =1.2.3 ", Some("1.2.3"), Options::default()),
+            ("1.2.3 foo", None, Options::default()),
+            ("1.2.3 foo", Some("1.2.3"), LOOSE),
+        ];
+        for &(range, expected, options) in fixtures {
+            let parsed = Range::parse(range, options).map(|r| r.to_string());
+            match expected {
+                Some(expected) => assert_eq!(parsed, Ok(expected.into()), "{:?}", range),
+                None => assert!(parsed.is_err(), "{:?} parsed as {:?}", range, parsed),
+            }
+        }
+
+        assert_eq!(Range::parse("foo || bar", LOOSE), Err(InvalidRange::Empty));
+        assert_eq!(Range::try_from("1.2.99999999999999999999"), Err(InvalidRange::NumberTooLarge));
+        assert_eq!(Range::try_from("~1.18446744073709551615"), Err(InvalidRange::NumberTooLarge));
+    }
+
+    #[test]
+    fn test_satisfies() {
+        // From node-semver's test/fixtures/range-include.js
+        let include: &[(&str, &str, Options)] = &[
+            ("1.0.0 - 2.0.0", "1.2.3", Options::default()),
+            ("^1.2.3+build", "1.3.0", Options::default()),
+            ("1.2.3pre+asdf - 2.4.3-pre+asdf", "1.2.3", LOOSE),
+            ("1.2.3-pre+asdf - 2.4.3-pre+asdf", "1.2.3-pre.2", Options::default()),
+            ("1.2.3-pre+asdf - 2.4.3-pre+asdf", "2.4.3-alpha", Options::default()),
+            ("*", "v1.2.3", LOOSE),
+            ("<=2.0.0", "1.9999.9999", Options::default()),
+            (">=0.2.3 || <0.0.1", "0.0.0", Options::default()),
+            ("||", "1.3.4", Options::default()),
+            ("1.2.* || 2.*", "2.1.3", Options::default()),
+            ("~x", "0.0.9", Options::default()),
+            ("~ 1.0.3", "1.0.12", Options::default()),
+            ("~ 1.0.3alpha", "1.0.12", LOOSE),
+            ("~v0.5.4-pre", "0.5.5", Options::default()),
+            ("<=0.7.x", "0.7.2", Options::default()),
+            ("~1.2.1 =1.2.3", "1.2.3", Options::default()),
+            ("^1.2 ^1", "1.4.2", Options::default()),
+            ("^1.2.3-alpha", "1.2.3-pre", Options::default()),
+            ("^0.0.1-alpha", "0.0.1", Options::default()),
+            ("x - 1.x", "0.9.7", Options::default()),
+            ("1.x - x", "1.9.7", Options::default()),
+            ("2.x", "2.0.0-pre.0", PRE),
+            ("*", "1.0.0-rc1", PRE),
+            ("^1.0.0", "1.1.0-rc1", PRE),
+            ("1 - 2", "2.0.0-pre", PRE),
+            ("1.0 - 2", "1.0.0-pre", PRE),
+            ("<=0.7.x", "0.7.0-asdf", PRE),
+            (">=1.0.0 <=1.1.0", "1.1.0-pre", PRE),
+        ];
+        for &(range, version, options) in include {
+            assert!(satisfies(range, version, options), "{} {}", range, version);
+        }
+
+        // From node-semver's test/fixtures/range-exclude.js
+        let exclude: &[(&str, &str, Options)] = &[
+            ("1.0.0 - 2.0.0", "2.2.3", Options::default()),
+            ("1.2.3+asdf - 2.4.3+asdf", "1.2.3-pre.2", Options::default()),
+            ("^1.2.3+build", "1.2.0", Options::default()),
+            ("^1.2.3", "1.2.3-pre", Options::default()),
+            (">1.2", "1.3.0-beta", Options::default()),
+            ("<=1.2.3", "1.2.3-beta", Options::default()),
+            ("=0.7.x", "0.7.0-asdf", Options::default()),
+            ("1", "1.0.0beta", LOOSE),
+            ("<1", "1.0.0beta", LOOSE),
+            (">=0.1.97", "v0.1.93", LOOSE),
+            ("1.2.x || 2.x", "1.1.3", Options::default()),
+            ("~0.0.1", "0.1.0-alpha", Options::default()),
+            ("~>3.2.1", "3.2.0", Options::default()),
+            ("~v0.5.4-beta", "0.5.4-alpha", Options::default()),
+            ("<0.7.x", "0.7.2", Options::default()),
+            (">1.2", "1.2.8", Options::default()),
+            ("^0.0.1", "0.0.2", Options::default()),
+            ("*", "v1.2.3-foo", LOOSE),
+            ("^1.0.0", "2.0.0-rc1", PRE),
+            ("1 - 2", "3.0.0-pre", PRE),
+            ("1 - 2", "2.0.0-pre", Options::default()),
+            ("1.1.x", "1.1.0-a", Options::default()),
+            ("1.x", "2.0.0-a", PRE),
+            (">=1.0.0 <1.1.0", "1.1.0-pre", Options::default()),
+            ("== 1.0.0 || foo", "2.0.0", LOOSE),
+        ];
+        for &(range, version, options) in exclude {
+            assert!(!satisfies(range, version, options), "{} {}", range, version);
+        }
+    }
+
+    #[test]
+    fn test_min_version() {
+        // From node-semver's test/fixtures/min-version.js
+        let fixtures = [
+            ("*", Some("0.0.0")),
+            (">=2 || *", Some("0.0.0")),
+            ("1.0", Some("1.0.0")),
+            ("1.*.x", Some("1.0.0")),
+            ("=1.0.0", Some("1.0.0")),
+            ("~1.1.1-beta", Some("1.1.1-beta")),
+            ("^1.1.1 || >=2", Some("1.1.1")),
+            ("^2.16.2 ^2.16", Some("2.16.2")),
+            ("1.1 - 1.8.0", Some("1.1.0")),
+            ("<2", Some("0.0.0")),
+            ("<0.0.0-beta", Some("0.0.0-0")),
+            ("<0.0.1-beta", Some("0.0.0")),
+            ("<0.0.0-beta >0.0.0-alpha", Some("0.0.0-alpha.0")),
+            (">=2.2.2 <2 || >=1.1.1 <2", Some("1.1.1")),
+            (">1.0.0", Some("1.0.1")),
+            (">1.0.0-0", Some("1.0.0-0.0")),
+            (">2 || >1.0.0-beta", Some("1.0.0-beta.0")),
+            (">4 <3", None),
+        ];
+        for &(range, expected) in &fixtures {
+            let min = Range::try_from(range).unwrap().min_version();
+            assert_eq!(min.map(|v| v.to_string()).as_deref(), expected, "{}", range);
+        }
+    }
+
+    #[test]
+    fn test_max_satisfying() {
+        let check = |versions: &[&str], range: &str, expected: &str, options: Options| {
+            let versions = versions.iter()
+                .map(|v| parse_version(v, options.loose).unwrap())
+                .collect::<Vec<_>>();
+            let range = Range::parse(range, options).unwrap();
+            let max = range.max_satisfying(&versions).map(|v| v.to_string());
+            assert_eq!(max.as_deref(), Some(expected));
+        };
+        check(&["1.2.3", "1.2.4"], "1.2", "1.2.4", Options::default());
+        check(&["1.2.4", "1.2.3"], "1.2", "1.2.4", Options::default());
+        check(&["1.2.3", "1.2.4", "1.2.5", "1.2.6"], "~1.2.3", "1.2.6", Options::default());
+        check(
+            &["1.1.0", "1.2.0", "1.2.1", "1.3.0", "2.0.0b1", "2.0.0b2", "2.0.0b3", "2.0.0", "2.1.0"],
+            "~2.0.0", "2.0.0", LOOSE,
+        );
+        let range = Range::try_from("^3").unwrap();
+        assert_eq!(range.max_satisfying(&[parse_version("2.0.0", false).unwrap()]), None);
+    }
+
+    #[test]
+    fn test_intersects() {
+        // From node-semver's test/fixtures/range-intersection.js
+        let fixtures = [
+            ("1.3.0 || <1.0.0 >2.0.0", "1.3.0 || <1.0.0 >2.0.0", true),
+            ("<1.0.0 >2.0.0", ">0.0.0", false),
+            (">0.0.0", "<1.0.0 >2.0.0", false),
+            ("<1.0.0 >2.0.0", ">1.4.0 <1.6.0 || 2.0.0", false),
+            (">1.0.0 <=2.0.0", "2.0.0", true),
+            ("<1.0.0 >=2.0.0", "2.1.0", false),
+            ("1.5.x", "<1.5.0", false),
+            ("<1.5.0", "1.5.x", false),
+            ("1.0.0 - 2.0.0", "2.0.0 - 3.0.0", true),
+            ("1.0.0 - 2.0.0", "2.0.1 - 3.0.0", false),
+            ("1.x", "1.2.3", true),
+            ("*", "*", true),
+            ("^1.0.0", "<1.0.0", false),
+            (">=1.0.0", ">=2.0.0 || <0.5.0", true),
+        ];
+        for &(a, b, expected) in &fixtures {
+            let a = Range::try_from(a).unwrap();
+            let b = Range::try_from(b).unwrap();
+            assert_eq!(a.intersects(&b), expected, "{} {}", a, b);
+            assert_eq!(b.intersects(&a), expected, "{} {}", b, a);
+        }
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version(" v1.2.3 ", false).unwrap().to_string(), "1.2.3");
+        assert_eq!(parse_version("=v01.2.3beta+b", true).unwrap().to_string(), "1.2.3-beta+b");
+        assert!(parse_version("=1.2.3", false).is_err());
+        assert!(parse_version("1.2.3beta", false).is_err());
+        assert!(parse_version("1.2", true).is_err());
+    }
+}
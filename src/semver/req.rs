@@ -0,0 +1,529 @@
+//! Version requirements, as used by Cargo (`^1.2`, `>=1.2, <1.5`).
+//!
+//! See [the Cargo book](https://doc.rust-lang.org/cargo/reference/specifying-dependencies.html)
+//! for the meaning of each operator. A version with a pre-release only
+//! matches if one of the comparators mentions a pre-release of the same
+//! `major.minor.patch`.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use super::{
+    Identifier, InvalidSemver, SemverVersion, compare_pre, parse_numeric,
+    parse_pre,
+};
+
+/// Operator of a comparator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// `=`, the given fields must be equal.
+    Exact,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEq,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEq,
+    /// `~`, only the patch version may be greater (or the minor version, if
+    /// only the major version is given).
+    Tilde,
+    /// `^`, or no operator: the left-most non-zero field must be equal.
+    Caret,
+    /// `1.*` or `1.2.*`, the given fields must be equal.
+    Wildcard,
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match *self {
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Tilde => "~",
+            Op::Caret => "^",
+            Op::Wildcard => "",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// Error for the requirement parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidVersionReq {
+    /// The requirement or one of its comparators is empty.
+    Empty,
+    /// The version of a comparator is invalid.
+    InvalidVersion(InvalidSemver),
+    /// A pre-release was given on a version without a patch number.
+    UnexpectedPreRelease,
+    /// A wildcard was followed by a number, or used with an operator.
+    InvalidWildcard,
+}
+
+impl From<InvalidSemver> for InvalidVersionReq {
+    fn from(error: InvalidSemver) -> InvalidVersionReq {
+        InvalidVersionReq::InvalidVersion(error)
+    }
+}
+
+/// A single comparison, for example `>=1.2`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Comparator {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<Identifier>,
+}
+
+impl Comparator {
+    pub fn op(&self) -> Op {
+        self.op
+    }
+
+    pub fn major(&self) -> u64 {
+        self.major
+    }
+
+    pub fn minor(&self) -> Option<u64> {
+        self.minor
+    }
+
+    pub fn patch(&self) -> Option<u64> {
+        self.patch
+    }
+
+    pub fn pre(&self) -> &[Identifier] {
+        &self.pre
+    }
+
+    /// Whether the version satisfies this comparator.
+    ///
+    /// This doesn't apply the pre-release rule, see
+    /// [`VersionReq::matches()`](struct.VersionReq.html#method.matches).
+    pub fn matches(&self, version: &SemverVersion) -> bool {
+        match self.op {
+            Op::Exact | Op::Wildcard => self.matches_exact(version),
+            Op::Greater => self.matches_greater(version),
+            Op::GreaterEq => {
+                self.matches_exact(version) || self.matches_greater(version)
+            }
+            Op::Less => self.matches_less(version),
+            Op::LessEq => {
+                self.matches_exact(version) || self.matches_less(version)
+            }
+            Op::Tilde => self.matches_tilde(version),
+            Op::Caret => self.matches_caret(version),
+        }
+    }
+
+    /// Whether a pre-release of this version may match.
+    fn allows_pre(&self, version: &SemverVersion) -> bool {
+        self.major == version.major()
+            && self.minor == Some(version.minor())
+            && self.patch == Some(version.patch())
+            && !self.pre.is_empty()
+    }
+
+    fn matches_exact(&self, version: &SemverVersion) -> bool {
+        if version.major() != self.major {
+            return false;
+        }
+        if let Some(minor) = self.minor {
+            if version.minor() != minor {
+                return false;
+            }
+        }
+        if let Some(patch) = self.patch {
+            if version.patch() != patch {
+                return false;
+            }
+        }
+        version.pre() == &self.pre[..]
+    }
+
+    fn matches_greater(&self, version: &SemverVersion) -> bool {
+        if version.major() != self.major {
+            return version.major() > self.major;
+        }
+        match self.minor {
+            None => return false,
+            Some(minor) if version.minor() != minor => {
+                return version.minor() > minor;
+            }
+            Some(_) => {}
+        }
+        match self.patch {
+            None => return false,
+            Some(patch) if version.patch() != patch => {
+                return version.patch() > patch;
+            }
+            Some(_) => {}
+        }
+        compare_pre(version.pre(), &self.pre) == Ordering::Greater
+    }
+
+    fn matches_less(&self, version: &SemverVersion) -> bool {
+        if version.major() != self.major {
+            return version.major() < self.major;
+        }
+        match self.minor {
+            None => return false,
+            Some(minor) if version.minor() != minor => {
+                return version.minor() < minor;
+            }
+            Some(_) => {}
+        }
+        match self.patch {
+            None => return false,
+            Some(patch) if version.patch() != patch => {
+                return version.patch() < patch;
+            }
+            Some(_) => {}
+        }
+        compare_pre(version.pre(), &self.pre) == Ordering::Less
+    }
+
+    fn matches_tilde(&self, version: &SemverVersion) -> bool {
+        if version.major() != self.major {
+            return false;
+        }
+        if let Some(minor) = self.minor {
+            if version.minor() != minor {
+                return false;
+            }
+        }
+        if let Some(patch) = self.patch {
+            if version.patch() != patch {
+                return version.patch() > patch;
+            }
+        }
+        compare_pre(version.pre(), &self.pre) != Ordering::Less
+    }
+
+    fn matches_caret(&self, version: &SemverVersion) -> bool {
+        if version.major() != self.major {
+            return false;
+        }
+        let minor = match self.minor {
+            Some(minor) => minor,
+            None => return true,
+        };
+        let patch = match self.patch {
+            Some(patch) => patch,
+            None if self.major > 0 => return version.minor() >= minor,
+            None => return version.minor() == minor,
+        };
+        if self.major > 0 {
+            if version.minor() != minor {
+                return version.minor() > minor;
+            } else if version.patch() != patch {
+                return version.patch() > patch;
+            }
+        } else if minor > 0 {
+            if version.minor() != minor {
+                return false;
+            } else if version.patch() != patch {
+                return version.patch() > patch;
+            }
+        } else if version.minor() != minor || version.patch() != patch {
+            return false;
+        }
+        compare_pre(version.pre(), &self.pre) != Ordering::Less
+    }
+}
+
+/// A field of a partial version: a number or a wildcard.
+fn parse_field(field: &str) -> Result<Option<u64>, InvalidVersionReq> {
+    match field {
+        "*" | "x" | "X" => Ok(None),
+        _ => Ok(Some(parse_numeric(field)?)),
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Comparator {
+    type Error = InvalidVersionReq;
+
+    fn try_from(string: &'a str) -> Result<Comparator, InvalidVersionReq> {
+        let string = string.trim();
+        let ops = [
+            (">=", Op::GreaterEq), ("<=", Op::LessEq), (">", Op::Greater),
+            ("<", Op::Less), ("=", Op::Exact), ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ];
+        let (op, version) = ops.iter()
+            .find(|&&(prefix, _)| string.starts_with(prefix))
+            .map(|&(prefix, op)| (Some(op), string[prefix.len() ..].trim_start()))
+            .unwrap_or((None, string));
+        if version.is_empty() {
+            return Err(InvalidVersionReq::Empty);
+        }
+        if version.contains('+') {
+            return Err(InvalidSemver::InvalidCharacter.into());
+        }
+
+        let (version, pre) = match version.find('-') {
+            Some(hyphen) => (&version[.. hyphen], Some(&version[hyphen + 1 ..])),
+            None => (version, None),
+        };
+        let fields = version.split('.')
+            .map(parse_field)
+            .collect::<Result<Vec<_>, _>>()?;
+        if fields.len() > 3 {
+            return Err(InvalidSemver::WrongFieldCount.into());
+        }
+        let major = match fields[0] {
+            Some(major) => major,
+            // "*" alone is handled by VersionReq::parse()
+            None => return Err(InvalidVersionReq::InvalidWildcard),
+        };
+        let minor = fields.get(1).cloned().unwrap_or(None);
+        let patch = fields.get(2).cloned().unwrap_or(None);
+        let wildcard = fields.len() > 1 && fields.iter().any(Option::is_none);
+        if wildcard {
+            // Only trailing wildcards, without operator (but "=")
+            let numbers = fields.iter().take_while(|f| f.is_some()).count();
+            if fields[numbers ..].iter().any(Option::is_some) {
+                return Err(InvalidVersionReq::InvalidWildcard);
+            }
+            if op.is_some() && op != Some(Op::Exact) {
+                return Err(InvalidVersionReq::InvalidWildcard);
+            }
+        }
+
+        let pre = match pre {
+            Some(_) if patch.is_none() => {
+                return Err(InvalidVersionReq::UnexpectedPreRelease);
+            }
+            Some(pre) => parse_pre(pre)?,
+            None => Vec::new(),
+        };
+
+        let op = if wildcard {
+            Op::Wildcard
+        } else {
+            op.unwrap_or(Op::Caret)
+        };
+        Ok(Comparator { op, major, minor, patch, pre })
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.op, self.major)?;
+        match self.minor {
+            Some(minor) => write!(f, ".{}", minor)?,
+            None if self.op == Op::Wildcard => return write!(f, ".*"),
+            None => return Ok(()),
+        }
+        match self.patch {
+            Some(patch) => write!(f, ".{}", patch)?,
+            None if self.op == Op::Wildcard => return write!(f, ".*"),
+            None => return Ok(()),
+        }
+        for (i, identifier) in self.pre.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { '-' } else { '.' }, identifier)?;
+        }
+        Ok(())
+    }
+}
+
+/// A version requirement: comma-separated comparators that must all match.
+///
+/// `*` is the requirement with no comparators, that matches every version but
+/// pre-releases.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VersionReq(Vec<Comparator>);
+
+impl VersionReq {
+    /// Parse a requirement using Cargo's syntax.
+    pub fn parse(string: &str) -> Result<VersionReq, InvalidVersionReq> {
+        let string = string.trim();
+        if string.is_empty() {
+            return Err(InvalidVersionReq::Empty);
+        }
+        if let "*" | "x" | "X" = string {
+            return Ok(VersionReq(Vec::new()));
+        }
+        string.split(',')
+            .map(Comparator::try_from)
+            .collect::<Result<_, _>>()
+            .map(VersionReq)
+    }
+
+    /// The requirement Cargo uses for a dependency on this version,
+    /// `^major.minor.patch[-pre]`.
+    pub(super) fn caret(version: &SemverVersion) -> VersionReq {
+        VersionReq(vec![Comparator {
+            op: Op::Caret,
+            major: version.major(),
+            minor: Some(version.minor()),
+            patch: Some(version.patch()),
+            pre: version.pre().to_vec(),
+        }])
+    }
+
+    pub fn comparators(&self) -> &[Comparator] {
+        &self.0
+    }
+
+    /// Whether the version satisfies this requirement.
+    pub fn matches(&self, version: &SemverVersion) -> bool {
+        self.0.iter().all(|c| c.matches(version))
+            && (
+                !version.is_prerelease()
+                || self.0.iter().any(|c| c.allows_pre(version))
+            )
+    }
+
+    /// Find the highest version satisfying this requirement.
+    pub fn highest_match<'a, I>(&self, candidates: I) -> Option<&'a SemverVersion>
+    where I: IntoIterator<Item = &'a SemverVersion> {
+        candidates.into_iter()
+            .filter(|v| self.matches(v))
+            .max_by(|a, b| a.cmp_build(b))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for VersionReq {
+    type Error = InvalidVersionReq;
+
+    fn try_from(string: &'a str) -> Result<VersionReq, InvalidVersionReq> {
+        VersionReq::parse(string)
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "*");
+        }
+        for (i, comparator) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", comparator)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semver::{InvalidSemver, SemverVersion};
+    use super::{InvalidVersionReq, Op, VersionReq};
+
+    fn req(string: &str) -> VersionReq {
+        VersionReq::parse(string).unwrap()
+    }
+
+    fn check(req: &VersionReq, matching: &[&str], not_matching: &[&str]) {
+        for version in matching {
+            let version = SemverVersion::parse(version).unwrap();
+            assert!(req.matches(&version), "{} should match {}", req, version);
+        }
+        for version in not_matching {
+            let version = SemverVersion::parse(version).unwrap();
+            assert!(!req.matches(&version), "{} shouldn't match {}", req, version);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(req("1.2").to_string(), "^1.2");
+        assert_eq!(req(">= 1.2.3, < 2").to_string(), ">=1.2.3, <2");
+        assert_eq!(req("~1").to_string(), "~1");
+        assert_eq!(req("1.*").to_string(), "1.*");
+        assert_eq!(req("1.2.x").to_string(), "1.2.*");
+        assert_eq!(req("=1.2.*").comparators()[0].op(), Op::Wildcard);
+        assert_eq!(req("=1.2.3-rc.1").to_string(), "=1.2.3-rc.1");
+        assert_eq!(req("*").to_string(), "*");
+        assert!(req("*").comparators().is_empty());
+
+        assert_eq!(VersionReq::parse(""), Err(InvalidVersionReq::Empty));
+        assert_eq!(VersionReq::parse(">="), Err(InvalidVersionReq::Empty));
+        assert_eq!(VersionReq::parse("1.2,"), Err(InvalidVersionReq::Empty));
+        assert_eq!(VersionReq::parse("1.*.3"), Err(InvalidVersionReq::InvalidWildcard));
+        assert_eq!(VersionReq::parse(">=1.*"), Err(InvalidVersionReq::InvalidWildcard));
+        assert_eq!(VersionReq::parse("*, 1.2"), Err(InvalidVersionReq::InvalidWildcard));
+        assert_eq!(VersionReq::parse("1.2-rc.1"), Err(InvalidVersionReq::UnexpectedPreRelease));
+        assert_eq!(
+            VersionReq::parse("01.2"),
+            Err(InvalidVersionReq::InvalidVersion(InvalidSemver::LeadingZero)),
+        );
+        assert_eq!(
+            VersionReq::parse("1.2.3.4"),
+            Err(InvalidVersionReq::InvalidVersion(InvalidSemver::WrongFieldCount)),
+        );
+        assert_eq!(
+            VersionReq::parse("1.2.3+build"),
+            Err(InvalidVersionReq::InvalidVersion(InvalidSemver::InvalidCharacter)),
+        );
+        assert_eq!(
+            VersionReq::parse("!=1.2"),
+            Err(InvalidVersionReq::InvalidVersion(InvalidSemver::InvalidCharacter)),
+        );
+    }
+
+    #[test]
+    fn test_caret() {
+        check(&req("^1.2.3"), &["1.2.3", "1.8.1", "1.2.3+build"], &["1.2.2", "2.0.0", "1.2.4-rc.1"]);
+        check(&req("1.2"), &["1.2.0", "1.9.9"], &["1.1.9", "2.0.0"]);
+        check(&req("^1"), &["1.0.0", "1.9.9"], &["0.9.9", "2.0.0"]);
+        check(&req("^0.2.3"), &["0.2.3", "0.2.9"], &["0.2.2", "0.3.0"]);
+        check(&req("^0.2"), &["0.2.0", "0.2.9"], &["0.1.9", "0.3.0"]);
+        check(&req("^0.0.3"), &["0.0.3"], &["0.0.2", "0.0.4"]);
+        check(&req("^0.0"), &["0.0.0", "0.0.9"], &["0.1.0"]);
+        check(&req("^0"), &["0.0.0", "0.9.9"], &["1.0.0"]);
+    }
+
+    #[test]
+    fn test_tilde() {
+        check(&req("~1.2.3"), &["1.2.3", "1.2.9"], &["1.2.2", "1.3.0"]);
+        check(&req("~1.2"), &["1.2.0", "1.2.9"], &["1.1.9", "1.3.0"]);
+        check(&req("~1"), &["1.0.0", "1.9.9"], &["0.9.9", "2.0.0"]);
+        check(&req("~1.2.3-beta"), &["1.2.3-beta", "1.2.3-beta.2", "1.2.3", "1.2.4"], &["1.2.3-alpha", "1.2.4-beta"]);
+    }
+
+    #[test]
+    fn test_comparison() {
+        check(&req(">1.2.3"), &["1.2.4", "2.0.0"], &["1.2.3", "1.2.0"]);
+        check(&req(">1.2"), &["1.3.0"], &["1.2.9", "1.2.0"]);
+        check(&req(">=1.2"), &["1.2.0", "1.3.0"], &["1.1.9"]);
+        check(&req("<1.2.3"), &["1.2.2", "0.1.0"], &["1.2.3", "1.2.3-rc.1"]);
+        check(&req("<1.2"), &["1.1.9"], &["1.2.0"]);
+        check(&req("<=1.2"), &["1.1.9", "1.2.0", "1.2.9"], &["1.3.0"]);
+        check(&req("=1.2.3"), &["1.2.3", "1.2.3+build"], &["1.2.4", "1.2.3-rc.1"]);
+        check(&req("=1.2"), &["1.2.0", "1.2.9"], &["1.3.0"]);
+        check(&req(">=1.2.3, <1.5"), &["1.2.3", "1.4.9"], &["1.2.2", "1.5.0"]);
+        check(&req("1.*"), &["1.0.0", "1.9.9"], &["2.0.0"]);
+        check(&req("1.2.*"), &["1.2.0", "1.2.9"], &["1.3.0"]);
+        check(&req("*"), &["0.0.0", "99.0.0"], &["1.0.0-rc.1"]);
+    }
+
+    #[test]
+    fn test_prerelease() {
+        // Pre-releases only match if a comparator has one for the same version
+        check(&req(">=1.2.3-rc.1"), &["1.2.3-rc.1", "1.2.3-rc.2", "1.2.3", "1.3.0"], &["1.2.4-rc.1", "2.0.0-alpha"]);
+        check(&req(">=1.2.3-rc.1, <2"), &["1.2.3-rc.5"], &["1.9.0-rc.1"]);
+        check(&req("^1.2.3-alpha"), &["1.2.3-alpha", "1.2.3-beta", "1.5.0"], &["1.2.3-0", "1.3.0-alpha"]);
+        check(&req("<2.0.0"), &["1.9.9"], &["2.0.0-alpha"]);
+        check(&req("=1.2.3-rc.1"), &["1.2.3-rc.1"], &["1.2.3-rc.2", "1.2.3"]);
+    }
+
+    #[test]
+    fn test_highest_match() {
+        let versions = ["1.0.0", "1.2.0", "1.5.0-rc.1", "1.4.2", "2.0.0"].iter()
+            .map(|v| SemverVersion::parse(v).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(req("^1").highest_match(&versions).unwrap().to_string(), "1.4.2");
+        assert_eq!(req(">=1.5.0-rc.1").highest_match(&versions).unwrap().to_string(), "2.0.0");
+        assert_eq!(req(">=1.5.0-rc.1, <2").highest_match(&versions).unwrap().to_string(), "1.5.0-rc.1");
+        assert_eq!(req("^3").highest_match(&versions), None);
+    }
+}
@@ -29,21 +29,21 @@ impl NumChecker {
             NumChecker::Start|NumChecker::NotNum => {
                 if c == b'0' {
                     NumChecker::Zero
-                } else if b'0' <= c && c <= b'9' {
+                } else if c.is_ascii_digit() {
                     NumChecker::OtherNum
                 } else {
                     NumChecker::NotNum
                 }
             }
             NumChecker::Zero => {
-                if b'0' <= c && c <= b'9' {
+                if c.is_ascii_digit() {
                     return false;
                 } else {
                     NumChecker::NotNum
                 }
             }
             NumChecker::OtherNum => {
-                if b'0' <= c && c <= b'9' {
+                if c.is_ascii_digit() {
                     NumChecker::OtherNum
                 } else {
                     NumChecker::NotNum